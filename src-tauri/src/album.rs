@@ -0,0 +1,303 @@
+use std::fs;
+use std::path::Path;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::hidden;
+use crate::i18n::t;
+use crate::image::{self, ImageInfo};
+use crate::index::LibraryIndex;
+use crate::lock;
+use crate::privacy;
+use crate::sensitive;
+use crate::slideshow::AlbumSlideshowSettings;
+
+/// アルバムの概要
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AlbumSummary {
+    /// アルバム名
+    pub name: String,
+    /// 画像数
+    pub count: usize,
+}
+
+/// アルバムの保存先（インデックスと同じSQLiteファイル）
+pub struct AlbumStore {
+    conn: Connection,
+}
+
+impl AlbumStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("album.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS albums (
+                name TEXT PRIMARY KEY
+            );
+            CREATE TABLE IF NOT EXISTS album_images (
+                album TEXT NOT NULL,
+                path TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                PRIMARY KEY (album, path)
+            );"
+        ).map_err(|e| t!("album.init_failed", e))?;
+
+        // アルバムごとのスライドショーの設定（JSON、未設定ならNULL）は後から追加した列
+        let has_slideshow = self.conn.prepare("SELECT slideshow FROM albums LIMIT 0").is_ok();
+        if !has_slideshow {
+            self.conn.execute_batch("ALTER TABLE albums ADD COLUMN slideshow TEXT")
+                .map_err(|e| t!("album.init_failed", e))?;
+        }
+        Ok(())
+    }
+
+    /// アルバムを作成する（既にあれば何もしない）
+    pub fn create(&self, name: &str) -> Result<(), String> {
+        if name.trim().is_empty() {
+            return Err(t!("album.empty_name"));
+        }
+        self.conn.execute("INSERT OR IGNORE INTO albums (name) VALUES (?1)", params![name])
+            .map_err(|e| t!("album.create_failed", name, e))?;
+        Ok(())
+    }
+
+    /// アルバムを削除する（画像ファイル自体は削除しない）
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        let tx = self.conn.transaction()
+            .map_err(|e| t!("album.delete_failed", e))?;
+        tx.execute("DELETE FROM album_images WHERE album = ?1", params![name])
+            .map_err(|e| t!("album.delete_name_failed", name, e))?;
+        tx.execute("DELETE FROM albums WHERE name = ?1", params![name])
+            .map_err(|e| t!("album.delete_name_failed", name, e))?;
+        tx.commit().map_err(|e| t!("album.delete_failed", e))
+    }
+
+    /// アルバムが存在するか
+    pub fn exists(&self, name: &str) -> Result<bool, String> {
+        self.conn.query_row("SELECT COUNT(*) FROM albums WHERE name = ?1", params![name], |row| row.get::<_, i64>(0))
+            .map(|count| count > 0)
+            .map_err(|e| t!("album.read_failed", e))
+    }
+
+    /// 画像を末尾に追加する（追加済みの画像は無視する）
+    pub fn add_images(&mut self, name: &str, paths: &[String]) -> Result<(), String> {
+        if !self.exists(name)? {
+            return Err(t!("album.not_found", name));
+        }
+
+        let tx = self.conn.transaction()
+            .map_err(|e| t!("album.update_failed", e))?;
+        let next: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM album_images WHERE album = ?1",
+            params![name],
+            |row| row.get(0),
+        ).map_err(|e| t!("album.update_failed", e))?;
+
+        for (offset, path) in paths.iter().enumerate() {
+            tx.execute(
+                "INSERT OR IGNORE INTO album_images (album, path, position) VALUES (?1, ?2, ?3)",
+                params![name, path, next + offset as i64],
+            ).map_err(|e| t!("album.update_path_failed", path, e))?;
+        }
+        tx.commit().map_err(|e| t!("album.update_failed", e))
+    }
+
+    /// 画像をアルバムから外す
+    pub fn remove_images(&mut self, name: &str, paths: &[String]) -> Result<(), String> {
+        let tx = self.conn.transaction()
+            .map_err(|e| t!("album.update_failed", e))?;
+        for path in paths {
+            tx.execute("DELETE FROM album_images WHERE album = ?1 AND path = ?2", params![name, path])
+                .map_err(|e| t!("album.update_path_failed", path, e))?;
+        }
+        tx.commit().map_err(|e| t!("album.update_failed", e))
+    }
+
+    /// アルバムの一覧を名前順で取得する
+    pub fn list(&self) -> Result<Vec<AlbumSummary>, String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT a.name, COUNT(i.path) FROM albums a
+             LEFT JOIN album_images i ON i.album = a.name
+             GROUP BY a.name ORDER BY a.name"
+        ).map_err(|e| t!("album.read_failed", e))?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(AlbumSummary { name: row.get(0)?, count: row.get::<_, i64>(1)? as usize })
+        }).map_err(|e| t!("album.read_failed", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| t!("album.read_failed", e))
+    }
+
+    /// アルバム内の画像パスを追加順で取得する
+    pub fn paths(&self, name: &str) -> Result<Vec<String>, String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path FROM album_images WHERE album = ?1 ORDER BY position"
+        ).map_err(|e| t!("album.read_failed", e))?;
+
+        let rows = stmt.query_map(params![name], |row| row.get(0))
+            .map_err(|e| t!("album.read_failed", e))?;
+
+        rows.collect::<Result<Vec<String>, _>>()
+            .map_err(|e| t!("album.read_failed", e))
+    }
+
+    /// スライドショーの設定を保存する（Noneで削除する）
+    pub fn set_slideshow_settings(&self, name: &str, settings: Option<&AlbumSlideshowSettings>) -> Result<(), String> {
+        if !self.exists(name)? {
+            return Err(t!("album.not_found", name));
+        }
+        let json = settings.map(serde_json::to_string).transpose()
+            .map_err(|e| t!("album.slideshow_serialize_failed", e))?;
+        self.conn.execute("UPDATE albums SET slideshow = ?2 WHERE name = ?1", params![name, json])
+            .map(|_| ())
+            .map_err(|e| t!("album.update_path_failed", name, e))
+    }
+
+    /// 保存したスライドショーの設定を取得する（未設定・読み込めない場合はNone）
+    pub fn slideshow_settings(&self, name: &str) -> Result<Option<AlbumSlideshowSettings>, String> {
+        let json: Option<String> = self.conn.query_row(
+            "SELECT slideshow FROM albums WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        ).optional().map_err(|e| t!("album.read_failed", e))?.flatten();
+        Ok(json.and_then(|json| match serde_json::from_str(&json) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                tracing::warn!("スライドショーの設定を解析できませんでした: {} - {}", name, e);
+                None
+            },
+        }))
+    }
+
+    /// アルバム内の画像情報を追加順で取得する（見つからないファイルは除く）
+    pub fn images(&self, name: &str) -> Result<Vec<ImageInfo>, String> {
+        if !self.exists(name)? {
+            return Err(t!("album.not_found", name));
+        }
+
+        Ok(self.paths(name)?
+            .iter()
+            .filter_map(|path| image::image_info(Path::new(path)).ok())
+            .collect())
+    }
+}
+
+/// アルバムの一覧を取得する
+#[tauri::command]
+pub async fn list_albums(app_handle: AppHandle) -> Result<Vec<AlbumSummary>, String> {
+    AlbumStore::open(&app_handle)?.list()
+}
+
+/// アルバムを作成する
+#[tauri::command]
+pub async fn create_album(app_handle: AppHandle, name: String) -> Result<(), String> {
+    let result = AlbumStore::open(&app_handle).and_then(|store| store.create(&name));
+    audit::complete(&app_handle, "create_album", &result);
+    result
+}
+
+/// アルバムを削除する
+#[tauri::command]
+pub async fn delete_album(app_handle: AppHandle, name: String) -> Result<(), String> {
+    let result = AlbumStore::open(&app_handle).and_then(|mut store| store.delete(&name));
+    audit::complete(&app_handle, "delete_album", &result);
+    result
+}
+
+/// アルバムに画像を追加する
+#[tauri::command]
+pub async fn add_to_album(app_handle: AppHandle, name: String, paths: Vec<String>) -> Result<(), String> {
+    let result = AlbumStore::open(&app_handle).and_then(|mut store| store.add_images(&name, &paths));
+    audit::complete(&app_handle, "add_to_album", &result);
+    result
+}
+
+/// アルバムから画像を外す
+#[tauri::command]
+pub async fn remove_from_album(app_handle: AppHandle, name: String, paths: Vec<String>) -> Result<(), String> {
+    let result = AlbumStore::open(&app_handle).and_then(|mut store| store.remove_images(&name, &paths));
+    audit::complete(&app_handle, "remove_from_album", &result);
+    result
+}
+
+/// アルバム内の画像一覧を取得する
+#[tauri::command]
+pub async fn get_album_images(app_handle: AppHandle, name: String) -> Result<Vec<ImageInfo>, String> {
+    lock::ensure_album_unlocked(&app_handle, &name)?;
+    let images = AlbumStore::open(&app_handle)?.images(&name)?;
+    Ok(sensitive::mark(&app_handle, hidden::filter_images(&app_handle, privacy::filter_images(&app_handle, images))))
+}
+
+/// アルバムのスライドショーの設定（間隔・切り替え効果・順序・音楽）を保存する（`settings`を省略すると削除する）
+#[tauri::command]
+pub async fn set_album_slideshow_settings(
+    app_handle: AppHandle,
+    name: String,
+    settings: Option<AlbumSlideshowSettings>,
+) -> Result<(), String> {
+    let result = AlbumStore::open(&app_handle).and_then(|store| store.set_slideshow_settings(&name, settings.as_ref()));
+    audit::complete(&app_handle, "set_album_slideshow_settings", &result);
+    result
+}
+
+/// アルバムに保存したスライドショーの設定を取得する（未設定ならNone）
+#[tauri::command]
+pub async fn get_album_slideshow_settings(app_handle: AppHandle, name: String) -> Result<Option<AlbumSlideshowSettings>, String> {
+    AlbumStore::open(&app_handle)?.slideshow_settings(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_album_order_and_counts() {
+        let path = std::env::temp_dir().join(format!("poir-album-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut store = AlbumStore::open_at(&path).unwrap();
+
+        store.create("旅行").unwrap();
+        store.create("空").unwrap();
+        store.add_images("旅行", &["/b.jpg".to_string(), "/a.jpg".to_string()]).unwrap();
+        store.add_images("旅行", &["/a.jpg".to_string(), "/c.jpg".to_string()]).unwrap();
+        store.remove_images("旅行", &["/b.jpg".to_string()]).unwrap();
+
+        assert_eq!(store.paths("旅行").unwrap(), vec!["/a.jpg", "/c.jpg"]);
+        assert_eq!(store.list().unwrap(), vec![
+            AlbumSummary { name: "旅行".to_string(), count: 2 },
+            AlbumSummary { name: "空".to_string(), count: 0 },
+        ]);
+        assert!(store.add_images("なし", &["/a.jpg".to_string()]).is_err());
+
+        let settings: AlbumSlideshowSettings = serde_json::from_value(serde_json::json!({ "interval_ms": 8000, "transition": "fade" })).unwrap();
+        assert_eq!(store.slideshow_settings("旅行").unwrap(), None);
+        store.set_slideshow_settings("旅行", Some(&settings)).unwrap();
+        assert_eq!(store.slideshow_settings("旅行").unwrap(), Some(settings));
+        store.set_slideshow_settings("旅行", None).unwrap();
+        assert_eq!(store.slideshow_settings("旅行").unwrap(), None);
+        assert!(store.set_slideshow_settings("なし", None).is_err());
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,175 @@
+use ::image::{GenericImageView, RgbImage};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::path_guard;
+use crate::preview;
+use crate::settings::AppSettings;
+
+/// 解析前に縮小する最大の大きさ（ヒストグラムの形は縮小してもほぼ変わらない）
+const ANALYSIS_MAX_SIZE: u32 = 1024;
+
+/// 各チャンネルのヒストグラム（256段階）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Histogram {
+    pub red: Vec<u32>,
+    pub green: Vec<u32>,
+    pub blue: Vec<u32>,
+    /// 輝度（Rec.709の係数で求める）
+    pub luminance: Vec<u32>,
+    /// 集計した画素数
+    pub pixel_count: u64,
+    /// 縮小した画像で集計したか
+    pub downscaled: bool,
+}
+
+/// 鮮鋭度マップの既定の大きさ（長辺のセル数）
+const DEFAULT_MAP_SIZE: u32 = 64;
+
+/// 鮮鋭度の解析結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SharpnessMap {
+    /// マップの横・縦のセル数
+    pub width: u32,
+    pub height: u32,
+    /// セルごとのエッジの強さ（0.0〜1.0、行優先）
+    pub values: Vec<f32>,
+    /// 画像全体の鮮鋭度（ラプラシアンの分散、大きいほどくっきりしている）
+    pub score: f64,
+}
+
+/// 解析用に画像を読み込み、大きければ縮小する
+fn load_for_analysis(app_handle: &AppHandle, path: &str) -> Result<(RgbImage, bool), String> {
+    let path = path_guard::guard(app_handle, path)?;
+    let limits = AppSettings::load(app_handle).unwrap_or_default().decode_limits;
+    let image = preview::open_image(app_handle, &path, &limits)?;
+    let (width, height) = image.dimensions();
+    if width.max(height) > ANALYSIS_MAX_SIZE {
+        Ok((image.thumbnail(ANALYSIS_MAX_SIZE, ANALYSIS_MAX_SIZE).to_rgb8(), true))
+    } else {
+        Ok((image.to_rgb8(), false))
+    }
+}
+
+/// 画素の輝度（0〜255）
+pub(crate) fn luminance(r: u8, g: u8, b: u8) -> u8 {
+    (0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64).round().min(255.0) as u8
+}
+
+fn histogram(image: &RgbImage, downscaled: bool) -> Histogram {
+    let mut result = Histogram {
+        red: vec![0; 256],
+        green: vec![0; 256],
+        blue: vec![0; 256],
+        luminance: vec![0; 256],
+        pixel_count: image.width() as u64 * image.height() as u64,
+        downscaled,
+    };
+    for pixel in image.pixels() {
+        let [r, g, b] = pixel.0;
+        result.red[r as usize] += 1;
+        result.green[g as usize] += 1;
+        result.blue[b as usize] += 1;
+        result.luminance[luminance(r, g, b) as usize] += 1;
+    }
+    result
+}
+
+/// 各画素のラプラシアン（4近傍）の絶対値を求める（端の画素は0）
+fn laplacian(image: &RgbImage) -> Vec<f64> {
+    let (width, height) = image.dimensions();
+    let gray: Vec<f64> = image.pixels().map(|pixel| luminance(pixel[0], pixel[1], pixel[2]) as f64).collect();
+    let at = |x: u32, y: u32| gray[(y * width + x) as usize];
+
+    let mut result = vec![0.0; gray.len()];
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let value = at(x - 1, y) + at(x + 1, y) + at(x, y - 1) + at(x, y + 1) - 4.0 * at(x, y);
+            result[(y * width + x) as usize] = value;
+        }
+    }
+    result
+}
+
+fn sharpness_map(image: &RgbImage, map_size: u32) -> SharpnessMap {
+    let (width, height) = image.dimensions();
+    let edges = laplacian(image);
+
+    let count = edges.len().max(1) as f64;
+    let mean = edges.iter().sum::<f64>() / count;
+    let score = edges.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / count;
+
+    // 縦横比を保ったままセルに分け、セル内のエッジの強さを平均する
+    let scale = map_size.max(1) as f64 / width.max(height).max(1) as f64;
+    let map_width = ((width as f64 * scale).round() as u32).clamp(1, width.max(1));
+    let map_height = ((height as f64 * scale).round() as u32).clamp(1, height.max(1));
+    let mut sums = vec![0.0; (map_width * map_height) as usize];
+    let mut counts = vec![0u32; sums.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let cell = ((y * map_height / height) * map_width + x * map_width / width) as usize;
+            sums[cell] += edges[(y * width + x) as usize].abs();
+            counts[cell] += 1;
+        }
+    }
+    let averages: Vec<f64> = sums.iter().zip(&counts).map(|(sum, count)| sum / (*count).max(1) as f64).collect();
+    let max = averages.iter().cloned().fold(0.0, f64::max);
+
+    SharpnessMap {
+        width: map_width,
+        height: map_height,
+        values: averages.iter().map(|value| if max > 0.0 { (value / max) as f32 } else { 0.0 }).collect(),
+        score,
+    }
+}
+
+/// 画像のRGB・輝度のヒストグラムを取得する
+#[tauri::command]
+pub async fn get_histogram(app_handle: AppHandle, path: String) -> Result<Histogram, String> {
+    let (image, downscaled) = load_for_analysis(&app_handle, &path)?;
+    Ok(histogram(&image, downscaled))
+}
+
+/// 画像のエッジの強さのマップと全体の鮮鋭度を取得する（連写から最もくっきりした1枚を選ぶため）
+///
+/// 鮮鋭度は縮小後の画像で求めるため、同じ大きさの画像どうしで比較する
+#[tauri::command]
+pub async fn get_sharpness_map(app_handle: AppHandle, path: String, map_size: Option<u32>) -> Result<SharpnessMap, String> {
+    let (image, _) = load_for_analysis(&app_handle, &path)?;
+    Ok(sharpness_map(&image, map_size.unwrap_or(DEFAULT_MAP_SIZE)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_counts_each_channel() {
+        let mut image = RgbImage::new(2, 2);
+        image.put_pixel(0, 0, ::image::Rgb([255, 0, 0]));
+        image.put_pixel(1, 0, ::image::Rgb([255, 255, 255]));
+
+        let result = histogram(&image, false);
+        assert_eq!(result.pixel_count, 4);
+        assert_eq!(result.red[255], 2);
+        assert_eq!(result.red[0], 2);
+        assert_eq!(result.green[0], 3);
+        assert_eq!(result.luminance[255], 1);
+        assert_eq!(result.luminance[0], 2);
+        assert_eq!(result.luminance[54], 1);
+    }
+
+    #[test]
+    fn test_sharp_image_scores_higher_than_flat() {
+        let flat = RgbImage::from_pixel(32, 16, ::image::Rgb([128, 128, 128]));
+        let checker = RgbImage::from_fn(32, 16, |x, y| {
+            if (x + y) % 2 == 0 { ::image::Rgb([255, 255, 255]) } else { ::image::Rgb([0, 0, 0]) }
+        });
+
+        let flat_map = sharpness_map(&flat, 8);
+        let checker_map = sharpness_map(&checker, 8);
+        assert_eq!((checker_map.width, checker_map.height), (8, 4));
+        assert_eq!(flat_map.score, 0.0);
+        assert!(checker_map.score > flat_map.score);
+        assert!(checker_map.values.iter().all(|value| (0.0..=1.0).contains(value)));
+    }
+}
@@ -0,0 +1,243 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use ::image::{AnimationDecoder, Delay, Frame, Frames, ImageFormat, ImageReader};
+use ::image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
+use ::image::codecs::webp::WebPDecoder;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::i18n::t;
+use crate::path_guard;
+use crate::preview;
+use crate::safe_write::{self, WriteOptions};
+use crate::settings::{AppSettings, DecodeLimits};
+
+/// 重複とみなす前のフレームとの差（チャンネルごとの差の平均、0〜255）の上限
+const DUPLICATE_TOLERANCE: f64 = 0.5;
+
+/// アニメーションの解析結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AnimationAnalysis {
+    pub path: String,
+    /// `gif`または`webp`
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    /// フレーム数
+    pub frames: usize,
+    /// 直前のフレームと同じ内容のフレーム数（表示時間を延ばせば省ける）
+    pub duplicate_frames: usize,
+    /// 1周の長さ（ミリ秒）
+    pub duration_ms: f64,
+    /// フレーム数から求めたフレームレート
+    pub fps: f64,
+    /// 重複を除いた実質のフレームレート
+    pub effective_fps: f64,
+}
+
+/// 最適化の結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AnimationOptimizeResult {
+    pub path: String,
+    pub frames_before: usize,
+    pub frames_after: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// アニメーションのフレームを読み込む（各フレームは画面全体に合成済み）
+pub(crate) fn open_frames(path: &Path, limits: &DecodeLimits) -> Result<(Frames<'static>, &'static str), String> {
+    let (width, height) = ::image::image_dimensions(path)
+        .map_err(|e| t!("animation.read_failed", path.display(), e))?;
+    // 合成中は前後のフレームを保持するため、1フレーム分の上限で確認する
+    preview::check_budget(path, width, height, preview::RGBA8_BYTES_PER_PIXEL, limits.max_decode_bytes)?;
+
+    let format = ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| t!("animation.read_failed", path.display(), e))?
+        .format();
+    let file = File::open(path).map_err(|e| t!("animation.read_failed", path.display(), e))?;
+    let reader = BufReader::new(file);
+    let decoded = match format {
+        Some(ImageFormat::Gif) => GifDecoder::new(reader).map(|decoder| (decoder.into_frames(), "gif")),
+        Some(ImageFormat::WebP) => WebPDecoder::new(reader).map(|decoder| (decoder.into_frames(), "webp")),
+        _ => return Err(t!("animation.unsupported", path.display())),
+    };
+    decoded.map_err(|e| t!("animation.read_failed", path.display(), e))
+}
+
+pub(crate) fn delay_ms(frame: &Frame) -> f64 {
+    let (numer, denom) = frame.delay().numer_denom_ms();
+    numer as f64 / denom.max(1) as f64
+}
+
+/// 直前のフレームとほぼ同じ内容か
+fn is_duplicate(previous: &Frame, frame: &Frame) -> bool {
+    let (a, b) = (previous.buffer(), frame.buffer());
+    if a.dimensions() != b.dimensions() {
+        return false;
+    }
+    let total: u64 = a.as_raw().iter().zip(b.as_raw()).map(|(x, y)| x.abs_diff(*y) as u64).sum();
+    total as f64 / a.as_raw().len().max(1) as f64 <= DUPLICATE_TOLERANCE
+}
+
+/// 重複したフレームをまとめて1周分を流す（`emit`にはまとめたフレームと表示時間を渡す）
+///
+/// 全フレームを保持しないよう、直前の1フレームだけを残して順に処理する
+fn collapse<F>(frames: Frames, mut emit: F) -> Result<(usize, usize, f64, (u32, u32)), String>
+where
+    F: FnMut(Frame, f64) -> Result<(), String>,
+{
+    let mut count = 0;
+    let mut duplicates = 0;
+    let mut duration = 0.0;
+    let mut size = (0, 0);
+    let mut pending: Option<(Frame, f64)> = None;
+    for frame in frames {
+        let frame = frame.map_err(|e| t!("animation.decode_failed", e))?;
+        let delay = delay_ms(&frame);
+        count += 1;
+        duration += delay;
+        size = frame.buffer().dimensions();
+        match pending.as_mut() {
+            Some((previous, total)) if is_duplicate(previous, &frame) => {
+                duplicates += 1;
+                *total += delay;
+            },
+            _ => {
+                if let Some((previous, total)) = pending.replace((frame, delay)) {
+                    emit(previous, total)?;
+                }
+            },
+        }
+    }
+    if let Some((previous, total)) = pending {
+        emit(previous, total)?;
+    }
+    Ok((count, duplicates, duration, size))
+}
+
+fn analyze(path: &Path, limits: &DecodeLimits) -> Result<AnimationAnalysis, String> {
+    let (frames, format) = open_frames(path, limits)?;
+    let (count, duplicates, duration, (width, height)) = collapse(frames, |_, _| Ok(()))?;
+    let per_second = |frames: usize| if duration > 0.0 { frames as f64 * 1000.0 / duration } else { 0.0 };
+    Ok(AnimationAnalysis {
+        path: path.to_string_lossy().to_string(),
+        format: format.to_string(),
+        width,
+        height,
+        frames: count,
+        duplicate_frames: duplicates,
+        duration_ms: duration,
+        fps: per_second(count),
+        effective_fps: per_second(count - duplicates),
+    })
+}
+
+/// 重複したフレームを前のフレームの表示時間にまとめてGIFで書き出す
+fn optimize(source: &Path, dest: &Path, limits: &DecodeLimits) -> Result<AnimationOptimizeResult, String> {
+    let (frames, _) = open_frames(source, limits)?;
+    let mut count = 0;
+    let mut written = 0;
+    safe_write::write_with(dest, WriteOptions::default(), |temp| {
+        let file = File::create(temp).map_err(|e| t!("animation.save_failed", dest.display(), e))?;
+        let mut encoder = GifEncoder::new(BufWriter::new(file));
+        encoder.set_repeat(Repeat::Infinite).map_err(|e| t!("animation.save_failed", dest.display(), e))?;
+        (count, _, _, _) = collapse(frames, |frame, total| {
+            let delay = Delay::from_numer_denom_ms(total.round() as u32, 1);
+            written += 1;
+            encoder.encode_frame(Frame::from_parts(frame.into_buffer(), 0, 0, delay))
+                .map_err(|e| t!("animation.save_failed", dest.display(), e))
+        })?;
+        Ok(())
+    })?;
+
+    let size = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    Ok(AnimationOptimizeResult {
+        path: dest.to_string_lossy().to_string(),
+        frames_before: count,
+        frames_after: written,
+        bytes_before: size(source),
+        bytes_after: size(dest),
+    })
+}
+
+fn optimize_to(app_handle: &AppHandle, path: &str, dest: &str) -> Result<AnimationOptimizeResult, String> {
+    let source = path_guard::guard(app_handle, path)?;
+    let dest_path = PathBuf::from(dest);
+    let is_gif = dest_path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| ext.eq_ignore_ascii_case("gif"));
+    if !is_gif {
+        return Err(t!("animation.invalid_dest", dest));
+    }
+    if dest_path.parent().is_some_and(|parent| !parent.as_os_str().is_empty() && !parent.is_dir()) {
+        return Err(t!("path.not_directory", dest_path.display()));
+    }
+    // 別名保存のみとし、元ファイルは上書きしない
+    if path_guard::canonicalize(dest).is_ok_and(|existing| existing == source) {
+        return Err(t!("crop.overwrite_source", dest));
+    }
+
+    let limits = AppSettings::load(app_handle).unwrap_or_default().decode_limits;
+    let result = optimize(&source, &dest_path, &limits)?;
+    tracing::info!("アニメーションを最適化しました: {} -> {} ({}→{}フレーム)", path, dest, result.frames_before, result.frames_after);
+    Ok(result)
+}
+
+/// アニメーションGIF・WebPの重複フレームと実質のフレームレートを調べる
+#[tauri::command]
+pub async fn analyze_animation(app_handle: AppHandle, path: String) -> Result<AnimationAnalysis, String> {
+    let source = path_guard::guard(&app_handle, &path)?;
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().decode_limits;
+    analyze(&source, &limits)
+}
+
+/// 重複フレームを前のフレームの表示時間にまとめたアニメーションGIFを別名で保存する
+///
+/// 書き出しはGIFのみ対応する（WebPから変換すると256色に減色される）
+#[tauri::command]
+pub async fn optimize_animation(app_handle: AppHandle, path: String, dest: String) -> Result<AnimationOptimizeResult, String> {
+    let result = optimize_to(&app_handle, &path, &dest);
+    audit::complete(&app_handle, "optimize_animation", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::{Rgba, RgbaImage};
+
+    #[test]
+    fn test_duplicate_frames_are_merged() {
+        let dir = std::env::temp_dir().join(format!("poir-animation-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("source.gif");
+        let dest = dir.join("optimized.gif");
+
+        let red = RgbaImage::from_pixel(8, 8, Rgba([255, 0, 0, 255]));
+        let blue = RgbaImage::from_pixel(8, 8, Rgba([0, 0, 255, 255]));
+        {
+            let mut encoder = GifEncoder::new(File::create(&source).unwrap());
+            for image in [&red, &red, &blue, &blue, &blue, &red] {
+                let frame = Frame::from_parts(image.clone(), 0, 0, Delay::from_numer_denom_ms(100, 1));
+                encoder.encode_frame(frame).unwrap();
+            }
+        }
+
+        let limits = DecodeLimits::default();
+        let analysis = analyze(&source, &limits).unwrap();
+        assert_eq!((analysis.format.as_str(), analysis.width, analysis.height), ("gif", 8, 8));
+        assert_eq!((analysis.frames, analysis.duplicate_frames), (6, 3));
+        assert_eq!(analysis.duration_ms, 600.0);
+        assert_eq!((analysis.fps, analysis.effective_fps), (10.0, 5.0));
+
+        let result = optimize(&source, &dest, &limits).unwrap();
+        assert_eq!((result.frames_before, result.frames_after), (6, 3));
+        let optimized = analyze(&dest, &limits).unwrap();
+        assert_eq!((optimized.frames, optimized.duplicate_frames), (3, 0));
+        assert_eq!(optimized.duration_ms, 600.0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
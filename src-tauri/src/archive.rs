@@ -0,0 +1,332 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+use crate::i18n::t;
+use crate::image;
+use crate::jobs::{self, JobHandle};
+use crate::navigation;
+use crate::path_guard;
+use crate::preview;
+use crate::safe_write::{self, WriteOptions};
+use crate::settings::{AppSettings, ArchiveLimits};
+
+/// 書庫として扱う拡張子（書庫内の書庫は展開しない）
+const ARCHIVE_EXTENSIONS: [&str; 2] = ["zip", "cbz"];
+
+/// 書庫内の画像
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    /// 書庫内のパス
+    pub name: String,
+    /// 展開後のサイズ（書庫に記録された値）
+    pub size: u64,
+    pub compressed_size: u64,
+}
+
+/// 書庫への変換・書庫からの変換の結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArchiveConversion {
+    /// 作成した書庫、または展開先のフォルダのパス
+    pub path: String,
+    pub images: usize,
+}
+
+/// パスが書庫ファイルか
+pub fn is_archive_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ARCHIVE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn open(path: &Path) -> Result<ZipArchive<File>, String> {
+    let file = File::open(path).map_err(|e| t!("file.read_failed", path.display(), e))?;
+    ZipArchive::new(file).map_err(|e| t!("archive.open_failed", path.display(), e))
+}
+
+/// 書庫の中身を上限に照らして検証し、画像の一覧を返す
+///
+/// 展開前に書庫に記録されたサイズで件数・合計サイズ・圧縮率・フォルダの深さを確認する
+fn list_entries<R: Read + io::Seek>(archive: &mut ZipArchive<R>, limits: &ArchiveLimits) -> Result<Vec<ArchiveEntry>, String> {
+    if archive.len() > limits.max_entries {
+        return Err(t!("archive.too_many_entries", archive.len(), limits.max_entries));
+    }
+
+    let mut entries = Vec::new();
+    let mut total: u64 = 0;
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index).map_err(|e| t!("archive.entry_failed", e))?;
+        // `../`や絶対パスで展開先の外を指すものは書庫ごと拒否する
+        let Some(enclosed) = file.enclosed_name() else {
+            return Err(t!("archive.unsafe_entry", file.name()));
+        };
+        if enclosed.components().count() > limits.max_depth {
+            return Err(t!("archive.too_deep", file.name(), limits.max_depth));
+        }
+        if file.is_dir() {
+            continue;
+        }
+
+        total = total.saturating_add(file.size());
+        if total > limits.max_total_bytes {
+            return Err(t!("archive.too_large", limits.max_total_bytes));
+        }
+        if file.size() > limits.max_entry_bytes
+            || file.size() > file.compressed_size().max(1).saturating_mul(limits.max_ratio)
+        {
+            return Err(t!("archive.suspicious_entry", file.name()));
+        }
+
+        if is_archive_file(&enclosed) {
+            tracing::warn!("書庫内の書庫は展開しません: {}", file.name());
+        } else if image::is_image_file(&enclosed) {
+            entries.push(ArchiveEntry {
+                name: file.name().to_string(),
+                size: file.size(),
+                compressed_size: file.compressed_size(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// ディレクトリ内のファイルの合計サイズ
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().filter_map(|entry| entry.metadata().ok()).map(|m| m.len()).sum())
+        .unwrap_or(0)
+}
+
+/// 展開キャッシュが上限を超えないよう古いファイルから削除する
+fn trim_cache(dir: &Path, incoming: u64, max_bytes: u64) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut files: Vec<(PathBuf, u64, std::time::SystemTime)> = entries.flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((entry.path(), metadata.len(), metadata.modified().ok()?))
+        })
+        .collect();
+    files.sort_by_key(|(_, _, modified)| *modified);
+
+    let mut used = dir_size(dir);
+    for (path, size, _) in files {
+        if used.saturating_add(incoming) <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            used = used.saturating_sub(size);
+        }
+    }
+}
+
+/// 書庫内の画像1枚を展開キャッシュに取り出し、そのパスを返す（取り出し済みならそのまま返す）
+///
+/// 書庫に記録されたサイズを超えて展開されるものは途中で打ち切って削除する
+pub fn extract_entry(app_handle: &AppHandle, path: &Path, name: &str, limits: &ArchiveLimits) -> Result<PathBuf, String> {
+    let mut archive = open(path)?;
+    let entry = list_entries(&mut archive, limits)?
+        .into_iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| t!("archive.entry_not_found", name))?;
+
+    let extension = Path::new(name).extension().and_then(|ext| ext.to_str()).unwrap_or("img").to_lowercase();
+    let cache_dir = preview::get_cache_dir(app_handle, "archives");
+    let dest = cache_dir.join(format!("{}.{}", preview::cache_key(path, name), extension));
+    if dest.exists() {
+        return Ok(dest);
+    }
+
+    fs::create_dir_all(&cache_dir).map_err(|e| t!("common.dir_create_failed", cache_dir.display(), e))?;
+    trim_cache(&cache_dir, entry.size, limits.max_cache_bytes);
+
+    let file = archive.by_name(name).map_err(|e| t!("archive.entry_failed", e))?;
+    let mut out = File::create(&dest).map_err(|e| t!("archive.extract_failed", name, e))?;
+    let written = io::copy(&mut file.take(entry.size + 1), &mut out);
+    match written {
+        Ok(written) if written <= entry.size => Ok(dest),
+        Ok(_) => {
+            let _ = fs::remove_file(&dest);
+            Err(t!("archive.suspicious_entry", name))
+        },
+        Err(e) => {
+            let _ = fs::remove_file(&dest);
+            Err(t!("archive.extract_failed", name, e))
+        },
+    }
+}
+
+/// フォルダ内の画像を書庫内の名前（`/`区切りの相対パス）の順に並べる
+fn pack_entries(dir: &Path, max_depth: usize) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut entries: Vec<(String, PathBuf)> = image::list_folder_images(dir, max_depth)?
+        .into_iter()
+        .filter_map(|info| {
+            let path = PathBuf::from(&info.path);
+            let name = path.strip_prefix(dir).ok()?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some((name, path))
+        })
+        .collect();
+    // ページ番号の桁が揃っていなくても読む順に並べる
+    entries.sort_by(|(a, _), (b, _)| navigation::natural_cmp(a, b));
+    Ok(entries)
+}
+
+fn pack(job: &JobHandle, entries: &[(String, PathBuf)], dest: &Path) -> Result<ArchiveConversion, String> {
+    safe_write::write_with(dest, WriteOptions::default(), |temp| {
+        let file = File::create(temp).map_err(|e| t!("archive.write_failed", temp.display(), e))?;
+        let mut writer = ZipWriter::new(file);
+        // 画像は圧縮済みのため、再圧縮せずに格納する
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        for (index, (name, source)) in entries.iter().enumerate() {
+            if job.is_cancelled() {
+                return Err(t!("jobs.cancelled"));
+            }
+            job.progress(index, Some(name));
+            writer.start_file(name.as_str(), options).map_err(|e| t!("archive.write_failed", dest.display(), e))?;
+            let mut input = File::open(source).map_err(|e| t!("file.read_failed", source.display(), e))?;
+            io::copy(&mut input, &mut writer).map_err(|e| t!("archive.write_failed", dest.display(), e))?;
+        }
+        writer.finish().map_err(|e| t!("archive.write_failed", dest.display(), e))?;
+        Ok(())
+    })?;
+    job.progress(entries.len(), None);
+    tracing::info!("フォルダを書庫にまとめました: {} ({}枚)", dest.display(), entries.len());
+    Ok(ArchiveConversion { path: dest.to_string_lossy().to_string(), images: entries.len() })
+}
+
+fn unpack(job: &JobHandle, path: &Path, entries: &[ArchiveEntry], dest_dir: &Path) -> Result<ArchiveConversion, String> {
+    let mut archive = open(path)?;
+    for (index, entry) in entries.iter().enumerate() {
+        if job.is_cancelled() {
+            return Err(t!("jobs.cancelled"));
+        }
+        job.progress(index, Some(&entry.name));
+        let file = archive.by_name(&entry.name).map_err(|e| t!("archive.entry_failed", e))?;
+        let relative = file.enclosed_name().ok_or_else(|| t!("archive.unsafe_entry", entry.name))?;
+        let dest = dest_dir.join(relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+        }
+        let mut out = File::create(&dest).map_err(|e| t!("archive.extract_failed", entry.name, e))?;
+        // 書庫に記録されたサイズを超えて展開されるものは打ち切る
+        let written = io::copy(&mut file.take(entry.size + 1), &mut out).map_err(|e| t!("archive.extract_failed", entry.name, e))?;
+        if written > entry.size {
+            drop(out);
+            let _ = fs::remove_file(&dest);
+            return Err(t!("archive.suspicious_entry", entry.name));
+        }
+    }
+    job.progress(entries.len(), None);
+    tracing::info!("書庫を展開しました: {} -> {} ({}枚)", path.display(), dest_dir.display(), entries.len());
+    Ok(ArchiveConversion { path: dest_dir.to_string_lossy().to_string(), images: entries.len() })
+}
+
+/// フォルダ内の画像を、フォルダと同じ場所の同名の`.cbz`にまとめるジョブを開始し、ジョブIDを返す
+///
+/// 元のフォルダは変更しない。進捗と結果（`ArchiveConversion`）は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn pack_folder_to_cbz(app_handle: AppHandle, path: String) -> Result<String, String> {
+    let dir = path_guard::guard(&app_handle, &path)?;
+    if !dir.is_dir() {
+        return Err(t!("path.not_directory", path));
+    }
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().archive_limits;
+    let entries = pack_entries(&dir, limits.max_depth.saturating_sub(1))?;
+    if entries.is_empty() {
+        return Err(t!("archive.no_images", path));
+    }
+    let dest = dir.with_extension("cbz");
+    if dest.exists() {
+        return Err(t!("archive.already_exists", dest.display()));
+    }
+    jobs::spawn(&app_handle, "pack_cbz", entries.len(), move |job| pack(job, &entries, &dest))
+}
+
+/// 書庫内の画像をフォルダに展開するジョブを開始し、ジョブIDを返す
+///
+/// 展開前に書庫を上限に照らして検証し、画像以外のエントリは展開しない。
+/// 既存のファイルを上書きしないよう、空のフォルダか新しいフォルダにのみ展開する。
+/// 元の書庫は変更しない。進捗と結果（`ArchiveConversion`）は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn unpack_archive(app_handle: AppHandle, path: String, dest: String) -> Result<String, String> {
+    let source = path_guard::guard(&app_handle, &path)?;
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().archive_limits;
+    let entries = list_entries(&mut open(&source)?, &limits)?;
+    if entries.is_empty() {
+        return Err(t!("archive.no_images", path));
+    }
+    let dest_dir = PathBuf::from(&dest);
+    if fs::read_dir(&dest_dir).is_ok_and(|mut existing| existing.next().is_some()) {
+        return Err(t!("archive.dest_not_empty", dest));
+    }
+    fs::create_dir_all(&dest_dir).map_err(|e| t!("common.dir_create_failed", dest_dir.display(), e))?;
+    jobs::spawn(&app_handle, "unpack_archive", entries.len(), move |job| unpack(job, &source, &entries, &dest_dir))
+}
+
+/// 書庫内の画像の一覧を取得する（上限を超える書庫はエラー）
+#[tauri::command]
+pub async fn list_archive_images(app_handle: AppHandle, path: String) -> Result<Vec<ArchiveEntry>, String> {
+    let path = path_guard::guard(&app_handle, &path)?;
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().archive_limits;
+    list_entries(&mut open(&path)?, &limits)
+}
+
+/// 書庫内の画像を展開キャッシュに取り出し、そのパスを返す
+#[tauri::command]
+pub async fn extract_archive_image(app_handle: AppHandle, path: String, entry: String) -> Result<String, String> {
+    let path = path_guard::guard(&app_handle, &path)?;
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().archive_limits;
+    extract_entry(&app_handle, &path, &entry, &limits).map(|dest| dest.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    fn build_zip(files: &[(&str, &[u8])]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            writer.start_file(*name, SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated)).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_limits_reject_bombs_and_traversal() {
+        let limits = ArchiveLimits::default();
+        let entries = list_entries(&mut build_zip(&[("01.jpg", b"a"), ("notes.txt", b"b"), ("inner.cbz", b"c")]), &limits).unwrap();
+        assert_eq!(entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), vec!["01.jpg"]);
+
+        assert!(list_entries(&mut build_zip(&[("../evil.jpg", b"a")]), &limits).is_err());
+        let few = ArchiveLimits { max_entries: 1, ..Default::default() };
+        assert!(list_entries(&mut build_zip(&[("01.jpg", b"a"), ("02.jpg", b"b")]), &few).is_err());
+
+        // 高圧縮率のエントリ（ゼロ埋め）
+        let zeros = vec![0u8; 4 * 1024 * 1024];
+        assert!(list_entries(&mut build_zip(&[("bomb.png", &zeros)]), &limits).is_err());
+    }
+
+    #[test]
+    fn test_pack_entries_use_relative_names() {
+        let dir = std::env::temp_dir().join(format!("poir-pack-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("ch2")).unwrap();
+        fs::write(dir.join("02.jpg"), b"a").unwrap();
+        fs::write(dir.join("01.jpg"), b"b").unwrap();
+        fs::write(dir.join("ch2/01.png"), b"c").unwrap();
+        fs::write(dir.join("notes.txt"), b"d").unwrap();
+
+        let names: Vec<String> = pack_entries(&dir, 1).unwrap().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["01.jpg", "02.jpg", "ch2/01.png"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
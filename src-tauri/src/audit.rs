@@ -0,0 +1,247 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, TryLockError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::{AppHandle, Manager, Runtime, State};
+use crate::i18n::t;
+use crate::privacy;
+
+/// 保持する履歴の最大件数（古いものから捨てる）
+const MAX_RECORDS: usize = 500;
+
+/// パスを伏せた場合の置き換え文字列
+const REDACTED: &str = "<redacted>";
+
+/// コマンドの実行結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum CommandOutcome {
+    Ok,
+    Error { message: String },
+}
+
+/// コマンド呼び出し1件分の記録
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommandRecord {
+    /// 連番
+    pub id: u64,
+    /// 呼び出し日時（Unix時間・ミリ秒）
+    pub timestamp: u64,
+    /// コマンド名
+    pub command: String,
+    /// 引数（パスを伏せる設定ではパスらしき文字列を置き換える）
+    pub params: Value,
+    /// 結果（記録対象外のコマンドや実行中はNone）
+    pub outcome: Option<CommandOutcome>,
+}
+
+/// コマンド呼び出しの履歴を保持するステート
+#[derive(Default)]
+pub struct AuditLog {
+    records: Mutex<VecDeque<CommandRecord>>,
+    next_id: AtomicU64,
+    redact_paths: AtomicBool,
+}
+
+/// パスらしき文字列かどうか
+fn looks_like_path(value: &str) -> bool {
+    value.contains('/') || value.contains('\\')
+}
+
+/// 引数内のパスらしき文字列を再帰的に伏せる
+fn redact(value: Value) -> Value {
+    match value {
+        Value::String(s) if looks_like_path(&s) => Value::String(REDACTED.to_string()),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact).collect()),
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, redact(v))).collect()),
+        other => other,
+    }
+}
+
+/// 常に伏せる引数名（PIN・パスワード等）
+const SECRET_KEYS: [&str; 4] = ["pin", "password", "secret", "token"];
+
+/// 秘密の値を持つ引数を再帰的に伏せる
+fn mask_secrets(value: Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().map(mask_secrets).collect()),
+        Value::Object(map) => Value::Object(map.into_iter()
+            .map(|(k, v)| {
+                let secret = SECRET_KEYS.iter().any(|key| k.to_lowercase().contains(key));
+                let v = if secret && !v.is_null() { Value::String(REDACTED.to_string()) } else { mask_secrets(v) };
+                (k, v)
+            })
+            .collect()),
+        other => other,
+    }
+}
+
+/// 引数に隠しているフォルダ内のパスが含まれるか
+fn mentions_hidden(value: &Value, hidden: &[String]) -> bool {
+    match value {
+        Value::String(s) => privacy::is_hidden(s, hidden),
+        Value::Array(items) => items.iter().any(|item| mentions_hidden(item, hidden)),
+        Value::Object(map) => map.values().any(|item| mentions_hidden(item, hidden)),
+        _ => false,
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl AuditLog {
+    /// 呼び出しを記録する
+    pub fn push(&self, command: &str, params: Value) {
+        let params = mask_secrets(params);
+        let params = if self.redact_paths.load(Ordering::Relaxed) { redact(params) } else { params };
+        let record = CommandRecord {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            timestamp: now_millis(),
+            command: command.to_string(),
+            params,
+            outcome: None,
+        };
+
+        if let Ok(mut records) = self.records.lock() {
+            if records.len() >= MAX_RECORDS {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+    }
+
+    /// 指定コマンドの直近の未完了の記録に結果を設定する
+    pub fn complete<T>(&self, command: &str, result: &Result<T, String>) {
+        let outcome = match result {
+            Ok(_) => CommandOutcome::Ok,
+            Err(e) => CommandOutcome::Error { message: e.clone() },
+        };
+
+        if let Ok(mut records) = self.records.lock() {
+            if let Some(record) = records.iter_mut()
+                .rev()
+                .find(|record| record.command == command && record.outcome.is_none())
+            {
+                record.outcome = Some(outcome);
+            }
+        }
+    }
+
+    /// ロックを待たずに直近の記録を取得する（パニック中に同じスレッドがロックを持っている場合は空）
+    fn try_snapshot(&self, limit: usize) -> Vec<CommandRecord> {
+        let records = match self.records.try_lock() {
+            Ok(records) => records,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return Vec::new(),
+        };
+        let start = records.len().saturating_sub(limit);
+        records.iter().skip(start).cloned().collect()
+    }
+
+    fn snapshot(&self, limit: usize) -> Result<Vec<CommandRecord>, String> {
+        let records = self.records.lock()
+            .map_err(|e| t!("common.lock_failed", t!("audit.name"), e))?;
+        let start = records.len().saturating_sub(limit);
+        Ok(records.iter().skip(start).cloned().collect())
+    }
+}
+
+/// invokeハンドラから呼ばれ、コマンド名と引数を記録する
+pub fn record_invoke<R: Runtime>(invoke: &Invoke<R>) {
+    let Some(log) = invoke.message.state_ref().try_get::<AuditLog>() else { return };
+    let params = match invoke.message.payload() {
+        InvokeBody::Json(value) => value.clone(),
+        InvokeBody::Raw(bytes) => Value::String(format!("<{} bytes>", bytes.len())),
+    };
+    log.push(invoke.message.command(), params);
+}
+
+/// 設定やファイルを変更するコマンドの結果を記録する（ステート未登録時は何もしない）
+pub fn complete<T>(app_handle: &AppHandle, command: &str, result: &Result<T, String>) {
+    if let Some(log) = app_handle.try_state::<AuditLog>() {
+        log.complete(command, result);
+    }
+}
+
+/// クラッシュレポートに含める直近の呼び出しを古い順に取得する
+///
+/// パニック中にも呼ばれるためロックは待たない。プライバシーモード中は引数のパスを伏せる
+pub fn recent_for_report(app_handle: &AppHandle, limit: usize) -> Vec<CommandRecord> {
+    let Some(log) = app_handle.try_state::<AuditLog>() else { return Vec::new() };
+    let records = log.try_snapshot(limit);
+    if !privacy::is_enabled(app_handle) {
+        return records;
+    }
+    records.into_iter()
+        .map(|record| CommandRecord { params: redact(record.params), ..record })
+        .collect()
+}
+
+/// コマンドの呼び出し履歴を古い順に取得する
+#[tauri::command]
+///
+/// プライバシーモード中は、隠しているフォルダを引数に含む記録を除く
+pub fn get_command_history(
+    app_handle: AppHandle,
+    state: State<'_, AuditLog>,
+    limit: Option<usize>,
+) -> Result<Vec<CommandRecord>, String> {
+    let hidden = privacy::hidden_folders(&app_handle);
+    let mut records = state.snapshot(MAX_RECORDS)?;
+    if !hidden.is_empty() {
+        records.retain(|record| !mentions_hidden(&record.params, &hidden));
+    }
+    let start = records.len().saturating_sub(limit.unwrap_or(MAX_RECORDS));
+    Ok(records.split_off(start))
+}
+
+/// 履歴に記録する引数のパスを伏せるかどうかを設定する
+#[tauri::command]
+pub fn set_command_history_redaction(state: State<'_, AuditLog>, enabled: bool) {
+    state.redact_paths.store(enabled, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_ring_buffer_and_outcome() {
+        let log = AuditLog::default();
+        for i in 0..MAX_RECORDS + 5 {
+            log.push("greet", json!({ "name": i }));
+        }
+        log.push("add_resource_path", json!({ "path": "/photos" }));
+        log.complete::<()>("add_resource_path", &Err("パスが存在しません".to_string()));
+
+        let records = log.snapshot(MAX_RECORDS).unwrap();
+        assert_eq!(records.len(), MAX_RECORDS);
+        assert_eq!(records[0].params, json!({ "name": 6 }));
+        assert_eq!(
+            records.last().unwrap().outcome,
+            Some(CommandOutcome::Error { message: "パスが存在しません".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_redact_paths() {
+        let log = AuditLog::default();
+        log.redact_paths.store(true, Ordering::Relaxed);
+        log.push("import_metadata", json!({ "path": "C:\\photos\\tags.csv", "format": "csv" }));
+
+        let records = log.snapshot(1).unwrap();
+        assert_eq!(records[0].params, json!({ "path": REDACTED, "format": "csv" }));
+
+        log.redact_paths.store(false, Ordering::Relaxed);
+        log.push("set_privacy_mode", json!({ "enabled": false, "pin": "1234" }));
+        assert_eq!(log.snapshot(1).unwrap()[0].params, json!({ "enabled": false, "pin": REDACTED }));
+    }
+}
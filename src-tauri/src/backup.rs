@@ -0,0 +1,250 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{params, Connection};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::ZipArchive;
+use crate::audit;
+use crate::config::{self, ResourceConfig};
+use crate::i18n::t;
+use crate::index::LibraryIndex;
+use crate::path_guard;
+use crate::settings::AppSettings;
+
+/// バックアップの形式（互換性のない変更をしたら上げる）
+const BACKUP_FORMAT: u32 = 1;
+
+/// 書庫内のバックアップの説明
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// バックアップするアプリデータ（設定フォルダ・アプリ設定・インデックス）
+///
+/// タグ・評価・アルバムはインデックスと同じファイルに保存されている
+const BACKUP_FILES: [&str; 3] = ["resources.json", "settings.json", "index.db"];
+
+/// バックアップ内のインデックス
+const INDEX_FILE: &str = "index.db";
+
+/// バックアップの説明
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BackupManifest {
+    pub format: u32,
+    pub app_version: String,
+    /// 作成日時（Unix時間）
+    pub created_at: u64,
+    pub files: Vec<String>,
+}
+
+/// バックアップ・復元の結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BackupResult {
+    /// 書庫のパス
+    pub path: String,
+    pub manifest: BackupManifest,
+}
+
+/// 一時ファイルのパス（同じフォルダに作り、最後に置き換える）
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// インデックスを読み込む（VACUUM INTOで書き込み途中の状態を含まない複製を作ってから読む）
+fn read_index(data_dir: &Path) -> Result<Vec<u8>, String> {
+    let copy = temp_path(&data_dir.join("index-backup.db"));
+    let _ = fs::remove_file(&copy);
+    let content = Connection::open(data_dir.join(INDEX_FILE))
+        .and_then(|conn| conn.execute("VACUUM INTO ?1", params![copy.to_string_lossy()]))
+        .map_err(|e| t!("backup.index_copy_failed", e))
+        .and_then(|_| fs::read(&copy).map_err(|e| t!("file.read_failed", copy.display(), e)));
+    let _ = fs::remove_file(&copy);
+    content
+}
+
+/// 説明とファイルを書庫に書き込む
+fn write_entries(file: File, data_dir: &Path, manifest: &BackupManifest, dest: &Path) -> Result<(), String> {
+    let write_err = |e: &dyn std::fmt::Display| t!("backup.write_failed", dest.display(), e);
+    let mut writer = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let json = serde_json::to_vec_pretty(manifest).map_err(|e| write_err(&e))?;
+    writer.start_file(MANIFEST_NAME, options).map_err(|e| write_err(&e))?;
+    writer.write_all(&json).map_err(|e| write_err(&e))?;
+    for name in &manifest.files {
+        let source = data_dir.join(name);
+        let content = if name == INDEX_FILE {
+            read_index(data_dir)?
+        } else {
+            fs::read(&source).map_err(|e| t!("file.read_failed", source.display(), e))?
+        };
+        writer.start_file(name.as_str(), options).map_err(|e| write_err(&e))?;
+        writer.write_all(&content).map_err(|e| write_err(&e))?;
+    }
+    writer.finish().map(|_| ()).map_err(|e| write_err(&e))
+}
+
+/// アプリデータのファイルを書庫にまとめる（一時ファイルに書き出してから置き換える）
+fn write_backup(data_dir: &Path, dest: &Path, created_at: u64) -> Result<BackupManifest, String> {
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at,
+        files: BACKUP_FILES.iter()
+            .filter(|name| data_dir.join(name).is_file())
+            .map(|name| name.to_string())
+            .collect(),
+    };
+
+    let temp = temp_path(dest);
+    let result = File::create(&temp)
+        .map_err(|e| t!("backup.write_failed", dest.display(), e))
+        .and_then(|file| write_entries(file, data_dir, &manifest, dest))
+        .and_then(|_| fs::rename(&temp, dest).map_err(|e| t!("backup.write_failed", dest.display(), e)));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result.map(|_| manifest)
+}
+
+/// 書庫からエントリを読み出す
+fn read_entry<R: Read + io::Seek>(archive: &mut ZipArchive<R>, name: &str, limit: u64) -> Result<Vec<u8>, String> {
+    let entry = archive.by_name(name).map_err(|_| t!("archive.entry_not_found", name))?;
+    if entry.size() > limit {
+        return Err(t!("archive.suspicious_entry", name));
+    }
+    let mut content = Vec::with_capacity(entry.size() as usize);
+    entry.take(limit).read_to_end(&mut content).map_err(|e| t!("archive.extract_failed", name, e))?;
+    Ok(content)
+}
+
+/// 書庫の内容を検証してからアプリデータを置き換える
+///
+/// すべてのファイルを一時ファイルに書き出して検証し、問題がなければまとめて置き換える
+fn restore_backup<R: Read + io::Seek>(archive: &mut ZipArchive<R>, data_dir: &Path, limit: u64) -> Result<BackupManifest, String> {
+    let manifest: BackupManifest = serde_json::from_slice(&read_entry(archive, MANIFEST_NAME, limit)?)
+        .map_err(|e| t!("backup.invalid_manifest", e))?;
+    if manifest.format != BACKUP_FORMAT {
+        return Err(t!("backup.unsupported_format", manifest.format));
+    }
+    if let Some(unknown) = manifest.files.iter().find(|name| !BACKUP_FILES.contains(&name.as_str())) {
+        return Err(t!("backup.unknown_file", unknown));
+    }
+
+    fs::create_dir_all(data_dir).map_err(|e| t!("common.dir_create_failed", data_dir.display(), e))?;
+    let mut staged = Vec::new();
+    let result = manifest.files.iter()
+        .try_for_each(|name| stage(archive, data_dir, name, limit, &mut staged))
+        .and_then(|_| staged.iter().try_for_each(|(temp, target)| {
+            fs::rename(temp, target).map_err(|e| t!("backup.write_failed", target.display(), e))
+        }));
+    for (temp, _) in &staged {
+        let _ = fs::remove_file(temp);
+    }
+    result.map(|_| manifest)
+}
+
+/// エントリを一時ファイルに書き出して、読み込めるか検証する
+fn stage<R: Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    data_dir: &Path,
+    name: &str,
+    limit: u64,
+    staged: &mut Vec<(PathBuf, PathBuf)>,
+) -> Result<(), String> {
+    let content = read_entry(archive, name, limit)?;
+    let target = data_dir.join(name);
+    let temp = temp_path(&target);
+    fs::write(&temp, &content).map_err(|e| t!("backup.write_failed", temp.display(), e))?;
+    staged.push((temp.clone(), target));
+
+    let valid = match name {
+        "resources.json" => serde_json::from_slice::<ResourceConfig>(&content).map(|_| ()).map_err(|e| e.to_string()),
+        "settings.json" => serde_json::from_slice::<AppSettings>(&content).map(|_| ()).map_err(|e| e.to_string()),
+        _ => LibraryIndex::open_at(&temp)
+            .and_then(|index| index.integrity_check())
+            .and_then(|problems| if problems.is_empty() { Ok(()) } else { Err(problems.join(", ")) }),
+    };
+    valid.map_err(|e| t!("backup.invalid_file", name, e))
+}
+
+/// 保存先を確認してからバックアップを作成する
+fn backup(app_handle: &AppHandle, dest_zip: &str) -> Result<BackupResult, String> {
+    let dest = PathBuf::from(dest_zip);
+    if !dest.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| ext.eq_ignore_ascii_case("zip")) {
+        return Err(t!("backup.invalid_dest", dest_zip));
+    }
+    if dest.parent().is_some_and(|parent| !parent.as_os_str().is_empty() && !parent.is_dir()) {
+        return Err(t!("path.not_directory", dest.display()));
+    }
+    let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+    let manifest = write_backup(&config::app_data_dir(app_handle), &dest, created_at)?;
+    tracing::info!("アプリデータをバックアップしました: {}", dest.display());
+    Ok(BackupResult { path: dest.to_string_lossy().to_string(), manifest })
+}
+
+/// 設定フォルダ・アプリ設定・インデックス（タグ・評価・アルバムを含む）を1つの書庫にまとめる（別のPCへの移行用）
+#[tauri::command]
+pub async fn backup_app_data(app_handle: AppHandle, dest_zip: String) -> Result<BackupResult, String> {
+    let result = backup(&app_handle, &dest_zip);
+    audit::complete(&app_handle, "backup_app_data", &result);
+    result
+}
+
+/// バックアップの書庫からアプリデータを復元する（現在の設定・インデックスは置き換えられる）
+#[tauri::command]
+pub async fn restore_app_data(app_handle: AppHandle, src_zip: String) -> Result<BackupResult, String> {
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().archive_limits;
+    let result = path_guard::guard(&app_handle, &src_zip)
+        .and_then(|source| File::open(source).map_err(|e| t!("file.read_failed", src_zip, e)))
+        .and_then(|file| ZipArchive::new(file).map_err(|e| t!("archive.open_failed", src_zip, e)))
+        .and_then(|mut archive| restore_backup(&mut archive, &config::app_data_dir(&app_handle), limits.max_total_bytes))
+        .map(|manifest| BackupResult { path: src_zip.clone(), manifest });
+    if result.is_ok() {
+        tracing::info!("アプリデータを復元しました: {}", src_zip);
+    }
+    audit::complete(&app_handle, "restore_app_data", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_and_restore_round_trip() {
+        let root = std::env::temp_dir().join(format!("poir-backup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (source, target) = (root.join("source"), root.join("target"));
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("resources.json"), r#"{"id":"default","name":"写真","filters":{"include":["/photos"],"exclude":[]}}"#).unwrap();
+        LibraryIndex::open_at(&source.join("index.db")).unwrap();
+
+        let dest = root.join("backup.zip");
+        let manifest = write_backup(&source, &dest, 100).unwrap();
+        assert_eq!(manifest.files, vec!["resources.json", "index.db"]);
+
+        let mut archive = ZipArchive::new(File::open(&dest).unwrap()).unwrap();
+        assert_eq!(restore_backup(&mut archive, &target, u64::MAX).unwrap(), manifest);
+        assert_eq!(fs::read(target.join("resources.json")).unwrap(), fs::read(source.join("resources.json")).unwrap());
+        assert!(LibraryIndex::open_at(&target.join("index.db")).unwrap().integrity_check().unwrap().is_empty());
+        assert!(!target.join("settings.json").exists());
+
+        // 壊れた設定を含む書庫では何も置き換えない
+        let broken = root.join("broken.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&broken).unwrap());
+        writer.start_file(MANIFEST_NAME, SimpleFileOptions::default()).unwrap();
+        writer.write_all(&serde_json::to_vec(&BackupManifest { files: vec!["resources.json".to_string()], ..manifest }).unwrap()).unwrap();
+        writer.start_file("resources.json", SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"not json").unwrap();
+        writer.finish().unwrap();
+        let mut archive = ZipArchive::new(File::open(&broken).unwrap()).unwrap();
+        assert!(restore_backup(&mut archive, &target, u64::MAX).is_err());
+        assert_eq!(fs::read(target.join("resources.json")).unwrap(), fs::read(source.join("resources.json")).unwrap());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
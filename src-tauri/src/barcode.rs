@@ -0,0 +1,129 @@
+use std::fs;
+use std::process::Command;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::i18n::t;
+use crate::path_guard;
+use crate::preview;
+use crate::settings::AppSettings;
+
+/// `zbarimg`が出力するコードの種類（行頭の`種類:`で新しいコードの始まりを見分ける）
+const SYMBOLOGIES: [&str; 18] = [
+    "QR-Code", "SQ-Code", "EAN-13", "EAN-8", "EAN-5", "EAN-2", "UPC-A", "UPC-E", "ISBN-10", "ISBN-13",
+    "I2/5", "DataBar", "DataBar-Exp", "Codabar", "CODE-39", "CODE-93", "CODE-128", "PDF417",
+];
+
+/// 読み取ったコード
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DetectedCode {
+    /// コードの種類（`QR-Code`、`EAN-13`など）
+    pub symbology: String,
+    /// コードの内容
+    pub payload: String,
+    /// Wi-Fiの接続情報のQRコードなら、その内容
+    pub wifi: Option<WifiCredentials>,
+}
+
+/// Wi-Fiの接続情報（`WIFI:T:WPA;S:名前;P:パスワード;;`形式）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WifiCredentials {
+    pub ssid: String,
+    pub password: Option<String>,
+    /// 暗号化の方式（`WPA`、`WEP`、`nopass`など）
+    pub security: Option<String>,
+    pub hidden: bool,
+}
+
+/// `zbarimg`の出力を分ける（内容に改行を含むコードは次の`種類:`の行までを1つとする）
+fn parse_output(output: &str) -> Vec<DetectedCode> {
+    let mut codes: Vec<(String, String)> = Vec::new();
+    for line in output.lines() {
+        let start = SYMBOLOGIES.iter().find_map(|symbology| {
+            line.strip_prefix(symbology).and_then(|rest| rest.strip_prefix(':')).map(|payload| (*symbology, payload))
+        });
+        match (start, codes.last_mut()) {
+            (Some((symbology, payload)), _) => codes.push((symbology.to_string(), payload.to_string())),
+            (None, Some((_, payload))) => {
+                payload.push('\n');
+                payload.push_str(line);
+            },
+            (None, None) => {},
+        }
+    }
+    codes.into_iter()
+        .map(|(symbology, payload)| DetectedCode { wifi: parse_wifi(&payload), symbology, payload })
+        .collect()
+}
+
+/// Wi-Fiの接続情報を読み取る（`\`でエスケープされた`;`・`:`・`,`・`\`を戻す）
+fn parse_wifi(payload: &str) -> Option<WifiCredentials> {
+    let body = payload.strip_prefix("WIFI:")?;
+    let mut fields: Vec<(String, String)> = Vec::new();
+    let mut current = String::new();
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            ';' => {
+                if let Some((key, value)) = current.split_once(':') {
+                    fields.push((key.to_string(), value.to_string()));
+                }
+                current.clear();
+            },
+            _ => current.push(c),
+        }
+    }
+    let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value.clone());
+    Some(WifiCredentials {
+        ssid: field("S")?,
+        password: field("P").filter(|password| !password.is_empty()),
+        security: field("T").filter(|security| !security.is_empty()),
+        hidden: field("H").is_some_and(|hidden| hidden.eq_ignore_ascii_case("true")),
+    })
+}
+
+/// 画像内のQRコード・バーコードを読み取る（チケットやWi-FiのQRコードのスクリーンショット向け）
+///
+/// 形式やEXIFの向きによらず読めるよう、正立させたPNGに変換してから`zbarimg`に渡す
+#[tauri::command]
+pub async fn detect_codes(app_handle: AppHandle, path: String) -> Result<Vec<DetectedCode>, String> {
+    let source = path_guard::guard(&app_handle, &path)?;
+    let settings = AppSettings::load(&app_handle).unwrap_or_default().barcode;
+    let (image, orientation) = preview::open_oriented(&app_handle, &source)?;
+    let input = preview::get_cache_dir(&app_handle, "barcode")
+        .join(format!("{}.png", preview::cache_key(&source, &format!("barcode-o{}", orientation.unwrap_or(1)))));
+    preview::save_png(&image, &input)?;
+
+    let zbarimg = settings.zbarimg_path.unwrap_or_else(|| "zbarimg".to_string());
+    let output = Command::new(&zbarimg).arg("--quiet").arg(&input).output();
+    let _ = fs::remove_file(&input);
+
+    match output {
+        Ok(output) if output.status.success() => Ok(parse_output(&String::from_utf8_lossy(&output.stdout))),
+        // コードが見つからないときは終了コード4を返す
+        Ok(output) if output.status.code() == Some(4) => Ok(Vec::new()),
+        Ok(output) => Err(t!("barcode.failed", path, String::from_utf8_lossy(&output.stderr).trim())),
+        Err(e) => Err(t!("barcode.unavailable", zbarimg, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output_and_wifi() {
+        let output = "QR-Code:WIFI:T:WPA;S:Cafe\\;Guest;P:p@ss\\:word;;\nEAN-13:4901234567894\nQR-Code:line one\nline two\n";
+        let codes = parse_output(output);
+        assert_eq!(codes.len(), 3);
+        assert_eq!(codes[0].wifi, Some(WifiCredentials {
+            ssid: "Cafe;Guest".to_string(),
+            password: Some("p@ss:word".to_string()),
+            security: Some("WPA".to_string()),
+            hidden: false,
+        }));
+        assert_eq!((codes[1].symbology.as_str(), codes[1].payload.as_str()), ("EAN-13", "4901234567894"));
+        assert_eq!(codes[2].payload, "line one\nline two");
+        assert!(codes[2].wifi.is_none());
+    }
+}
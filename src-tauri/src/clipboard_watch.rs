@@ -0,0 +1,148 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use ::image::{DynamicImage, RgbaImage};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+#[cfg(desktop)]
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use crate::config;
+use crate::event_bridge::{self, Delivery};
+use crate::exif_info;
+use crate::preview;
+use crate::settings::{AppSettings, ClipboardCaptureSettings};
+
+/// 無効な間に設定を確認し直す間隔
+const DISABLED_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 確認する間隔の下限（クリップボードの読み取りで負荷をかけすぎないよう）
+const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// `clipboard-captured`で通知する内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClipCaptured {
+    /// 保存した画像のパス
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 保存先のフォルダ
+fn clips_dir(app_handle: &AppHandle, settings: &ClipboardCaptureSettings) -> PathBuf {
+    settings.folder.as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config::app_data_dir(app_handle).join("clips"))
+}
+
+/// UNIX秒から`clip-20240101-123456`の形のファイル名を作る（UTC）
+fn timestamped_name(prefix: &str, timestamp: u64) -> String {
+    let (year, month, day) = exif_info::civil_from_days((timestamp / 86_400) as i64);
+    let seconds = timestamp % 86_400;
+    format!("{}-{:04}{:02}{:02}-{:02}{:02}{:02}", prefix, year, month, day, seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+/// 日時入りのPNGの保存先を、同じ秒に保存した画像と重ならないよう決める
+pub(crate) fn timestamped_path(dir: &Path, prefix: &str, timestamp: u64) -> PathBuf {
+    let name = timestamped_name(prefix, timestamp);
+    let mut path = dir.join(format!("{}.png", name));
+    let mut number = 1;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.png", name, number));
+        number += 1;
+    }
+    path
+}
+
+/// クリップボードの画像の内容を区別するためのハッシュ
+fn image_hash(width: u32, height: u32, rgba: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (width, height).hash(&mut hasher);
+    rgba.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// クリップボードの画像を読み取る（幅・高さ・RGBA）
+#[cfg(desktop)]
+fn read_image(app_handle: &AppHandle) -> Option<(u32, u32, Vec<u8>)> {
+    app_handle.clipboard().read_image().ok()
+        .map(|image| (image.width(), image.height(), image.rgba().to_vec()))
+}
+
+/// モバイルではクリップボードのプラグインを登録しないため読み取れない
+#[cfg(mobile)]
+fn read_image(_app_handle: &AppHandle) -> Option<(u32, u32, Vec<u8>)> {
+    None
+}
+
+/// クリップボードの画像を保存して通知する
+fn capture(app_handle: &AppHandle, settings: &ClipboardCaptureSettings, width: u32, height: u32, rgba: Vec<u8>) -> Result<(), String> {
+    let image = RgbaImage::from_raw(width, height, rgba)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| format!("クリップボードの画像の大きさが不正です: {}x{}", width, height))?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let dest = timestamped_path(&clips_dir(app_handle, settings), "clip", timestamp);
+    preview::save_png(&image, &dest)?;
+
+    tracing::info!("クリップボードの画像を保存しました: {}", dest.display());
+    let path = dest.to_string_lossy().to_string();
+    event_bridge::emit(app_handle, "clipboard-captured", Delivery::Batch, ClipCaptured { path, width, height });
+    Ok(())
+}
+
+/// クリップボードにコピーされた画像を監視し、設定のフォルダに保存する
+///
+/// 有効にした時点でクリップボードにある画像は保存せず、その後にコピーされた画像だけを保存する。
+/// モバイルでは監視しない
+pub fn start_monitor(app_handle: &AppHandle) {
+    if cfg!(mobile) {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        // 最後に見たクリップボードの画像（無効な間はNoneに戻す）
+        let mut last: Option<Option<u64>> = None;
+        loop {
+            let settings = AppSettings::load(&app_handle).unwrap_or_default().clipboard_capture;
+            if !settings.enabled {
+                last = None;
+                std::thread::sleep(DISABLED_CHECK_INTERVAL);
+                continue;
+            }
+
+            let current = read_image(&app_handle);
+            let hash = current.as_ref().map(|(width, height, rgba)| image_hash(*width, *height, rgba));
+            if last.is_some_and(|last| last != hash) {
+                if let Some((width, height, rgba)) = current {
+                    if let Err(e) = capture(&app_handle, &settings, width, height, rgba) {
+                        tracing::warn!("クリップボードの画像の保存に失敗しました: {}", e);
+                    }
+                }
+            }
+            last = Some(hash);
+            std::thread::sleep(Duration::from_millis(settings.interval_ms).max(MIN_INTERVAL));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamped_path() {
+        // 2024-02-29 12:34:56 UTC
+        assert_eq!(timestamped_name("clip", 1_709_210_096), "clip-20240229-123456");
+
+        let dir = std::env::temp_dir().join(format!("poir-clips-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = timestamped_path(&dir, "clip", 0);
+        assert_eq!(first, dir.join("clip-19700101-000000.png"));
+        std::fs::write(&first, b"").unwrap();
+        assert_eq!(timestamped_path(&dir, "clip", 0), dir.join("clip-19700101-000000-1.png"));
+
+        assert_ne!(image_hash(1, 1, &[0, 0, 0, 255]), image_hash(1, 1, &[255, 0, 0, 255]));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,190 @@
+use std::cmp::Ordering;
+use serde::{Serialize, Deserialize};
+use unicode_normalization::UnicodeNormalization;
+use crate::navigation;
+
+/// ファイル名を並べる際の文字の比べ方
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Collation {
+    /// 文字コード順（大文字小文字を区別せず、数字は数値として比べる）
+    #[default]
+    Natural,
+    /// 日本語を考慮した順（ひらがな・カタカナ・半角・全角を区別せず、英数字の後に五十音順）
+    Japanese,
+    /// かなをローマ字に読み替えて英字と混ぜて並べる（`さくら`と`sakura`が隣り合う）
+    Romaji,
+}
+
+/// 並べ替えに使うキー（比べる文字列と、同じ順になった場合に比べる元の名前）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollationKey {
+    folded: String,
+    original: String,
+}
+
+impl Ord for CollationKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        navigation::natural_cmp(&self.folded, &other.folded)
+            .then_with(|| navigation::natural_cmp(&self.original, &other.original))
+    }
+}
+
+impl PartialOrd for CollationKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// カタカナをひらがなにする
+fn katakana_to_hiragana(c: char) -> char {
+    match c {
+        '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// 小書きのかなを普通のかなにする
+fn small_to_large(c: char) -> char {
+    match c {
+        'ぁ' => 'あ', 'ぃ' => 'い', 'ぅ' => 'う', 'ぇ' => 'え', 'ぉ' => 'お',
+        'っ' => 'つ', 'ゃ' => 'や', 'ゅ' => 'ゆ', 'ょ' => 'よ', 'ゎ' => 'わ',
+        'ゕ' => 'か', 'ゖ' => 'け',
+        _ => c,
+    }
+}
+
+/// 濁点・半濁点・アクセント記号などの結合文字か
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}' | '\u{3099}' | '\u{309A}')
+}
+
+/// 全角英数字・半角カナを揃え、カタカナをひらがなにする
+fn fold_width_and_kana(name: &str) -> String {
+    name.nfkc().map(katakana_to_hiragana).collect()
+}
+
+/// 濁点・小書き・アクセントの違いを無視した文字列にする（違いは元の名前で比べる）
+fn strip_marks(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).map(small_to_large).collect()
+}
+
+/// ひらがな1文字のローマ字（ヘボン式）
+fn kana_romaji(c: char) -> Option<&'static str> {
+    Some(match c {
+        'あ' | 'ぁ' => "a", 'い' | 'ぃ' | 'ゐ' => "i", 'う' | 'ぅ' => "u", 'え' | 'ぇ' | 'ゑ' => "e", 'お' | 'ぉ' | 'を' => "o",
+        'か' | 'ゕ' => "ka", 'き' => "ki", 'く' => "ku", 'け' | 'ゖ' => "ke", 'こ' => "ko",
+        'が' => "ga", 'ぎ' => "gi", 'ぐ' => "gu", 'げ' => "ge", 'ご' => "go",
+        'さ' => "sa", 'し' => "shi", 'す' => "su", 'せ' => "se", 'そ' => "so",
+        'ざ' => "za", 'じ' | 'ぢ' => "ji", 'ず' | 'づ' => "zu", 'ぜ' => "ze", 'ぞ' => "zo",
+        'た' => "ta", 'ち' => "chi", 'つ' => "tsu", 'て' => "te", 'と' => "to",
+        'だ' => "da", 'で' => "de", 'ど' => "do",
+        'な' => "na", 'に' => "ni", 'ぬ' => "nu", 'ね' => "ne", 'の' => "no",
+        'は' => "ha", 'ひ' => "hi", 'ふ' => "fu", 'へ' => "he", 'ほ' => "ho",
+        'ば' => "ba", 'び' => "bi", 'ぶ' => "bu", 'べ' => "be", 'ぼ' => "bo",
+        'ぱ' => "pa", 'ぴ' => "pi", 'ぷ' => "pu", 'ぺ' => "pe", 'ぽ' => "po",
+        'ま' => "ma", 'み' => "mi", 'む' => "mu", 'め' => "me", 'も' => "mo",
+        'や' | 'ゃ' => "ya", 'ゆ' | 'ゅ' => "yu", 'よ' | 'ょ' => "yo",
+        'ら' => "ra", 'り' => "ri", 'る' => "ru", 'れ' => "re", 'ろ' => "ro",
+        'わ' | 'ゎ' => "wa", 'ん' => "n", 'ゔ' => "vu",
+        _ => return None,
+    })
+}
+
+/// ひらがなをローマ字にする（かな以外はそのまま残す）
+fn to_romaji(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut romaji = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        // 促音は次の子音を重ねる（`っち`は`tchi`）
+        if c == 'っ' {
+            if let Some(next) = chars.get(i).and_then(|next| kana_romaji(*next)) {
+                match next.chars().next() {
+                    Some('c') => romaji.push('t'),
+                    Some(consonant) if !"aiueon".contains(consonant) => romaji.push(consonant),
+                    _ => {},
+                }
+            }
+            continue;
+        }
+        // 長音は直前の母音を繰り返す
+        if c == 'ー' {
+            if let Some(vowel) = romaji.chars().last().filter(|last| "aiueo".contains(*last)) {
+                romaji.push(vowel);
+            }
+            continue;
+        }
+        let Some(syllable) = kana_romaji(c) else {
+            romaji.push(c);
+            continue;
+        };
+        // 拗音（`きゃ`は`kya`、`しゃ`は`sha`）
+        let contracted = chars.get(i).and_then(|next| match next {
+            'ゃ' => Some('a'),
+            'ゅ' => Some('u'),
+            'ょ' => Some('o'),
+            _ => None,
+        });
+        match contracted {
+            Some(vowel) if syllable.len() > 1 && syllable.ends_with('i') => {
+                let base = &syllable[..syllable.len() - 1];
+                romaji.push_str(base);
+                if !matches!(base, "sh" | "ch" | "j") {
+                    romaji.push('y');
+                }
+                romaji.push(vowel);
+                i += 1;
+            },
+            _ => romaji.push_str(syllable),
+        }
+    }
+    romaji
+}
+
+/// 名前から並べ替えのキーを作る
+pub fn sort_key(name: &str, collation: Collation) -> CollationKey {
+    let folded = match collation {
+        Collation::Natural => name.to_string(),
+        Collation::Japanese => strip_marks(&fold_width_and_kana(name)),
+        Collation::Romaji => strip_marks(&to_romaji(&fold_width_and_kana(name))),
+    };
+    CollationKey { folded, original: name.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str], collation: Collation) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        names.sort_by_cached_key(|name| sort_key(name, collation));
+        names
+    }
+
+    #[test]
+    fn test_japanese_collation_unifies_kana_and_width() {
+        let names = ["カメラ2.jpg", "ｶﾒﾗ10.jpg", "がっこう.jpg", "かめら1.jpg", "Ｂ.jpg", "a.jpg", "桜.jpg"];
+        assert_eq!(
+            sorted(&names, Collation::Japanese),
+            vec!["a.jpg", "Ｂ.jpg", "がっこう.jpg", "かめら1.jpg", "カメラ2.jpg", "ｶﾒﾗ10.jpg", "桜.jpg"],
+        );
+        // 文字コード順では全角・半角・カタカナが離れる
+        assert_eq!(sorted(&names, Collation::Natural)[0], "a.jpg");
+        assert_eq!(sorted(&names, Collation::Natural)[6], "ｶﾒﾗ10.jpg");
+    }
+
+    #[test]
+    fn test_romaji_collation_mixes_with_latin() {
+        assert_eq!(to_romaji("きょうと"), "kyouto");
+        assert_eq!(to_romaji("しゃしん"), "shashin");
+        assert_eq!(to_romaji("まっちゃ"), "matcha");
+        assert_eq!(to_romaji("がっこう"), "gakkou");
+        assert_eq!(to_romaji(&fold_width_and_kana("ラーメン")), "raamen");
+
+        let names = ["tokyo.jpg", "さくら.jpg", "sakura2.jpg", "osaka.jpg"];
+        assert_eq!(sorted(&names, Collation::Romaji), vec!["osaka.jpg", "さくら.jpg", "sakura2.jpg", "tokyo.jpg"]);
+    }
+}
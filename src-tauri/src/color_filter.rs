@@ -0,0 +1,148 @@
+use std::path::Path;
+use ::image::{DynamicImage, RgbImage};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::path_guard;
+use crate::preview;
+
+/// プレビューの長辺の既定の最大値
+const DEFAULT_MAX_SIZE: u32 = 2048;
+
+/// 色覚の見え方のシミュレーション（Machadoら2009年のモデル、程度は最大）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorFilter {
+    /// 1型（赤の錐体がない）
+    Protanopia,
+    /// 2型（緑の錐体がない）
+    Deuteranopia,
+    /// 3型（青の錐体がない）
+    Tritanopia,
+    /// 明暗のみ
+    Grayscale,
+}
+
+impl ColorFilter {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Protanopia => "protanopia",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Tritanopia => "tritanopia",
+            Self::Grayscale => "grayscale",
+        }
+    }
+
+    /// リニアRGBに掛ける行列
+    fn matrix(self) -> [[f64; 3]; 3] {
+        match self {
+            Self::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Self::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Self::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+            // Rec.709の輝度
+            Self::Grayscale => [[0.2126, 0.7152, 0.0722]; 3],
+        }
+    }
+}
+
+/// フィルターを適用したプレビュー
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FilteredPreview {
+    /// プレビューのパス（元ファイルは変更しない）
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// 適用したEXIFの向き（適用していなければNone）
+    pub applied_orientation: Option<u32>,
+    pub filter: ColorFilter,
+}
+
+fn to_linear(value: u8) -> f64 {
+    let v = value as f64 / 255.0;
+    if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+}
+
+fn to_srgb(value: f64) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let encoded = if v <= 0.0031308 { v * 12.92 } else { 1.055 * v.powf(1.0 / 2.4) - 0.055 };
+    (encoded * 255.0).round() as u8
+}
+
+/// 色をリニアRGBに戻してから行列を掛ける（ガンマのかかった値のままでは正しく混ざらないため）
+fn apply_filter(image: &mut RgbImage, filter: ColorFilter) {
+    let linear: Vec<f64> = (0..=255).map(to_linear).collect();
+    let matrix = filter.matrix();
+    for pixel in image.pixels_mut() {
+        let rgb = pixel.0.map(|value| linear[value as usize]);
+        pixel.0 = matrix.map(|row| to_srgb(row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]));
+    }
+}
+
+/// 色覚の見え方をシミュレーションしたプレビューを作成する（作品の配色の確認用、元ファイルは変更しない）
+#[tauri::command]
+pub async fn get_preview_with_filter(
+    app_handle: AppHandle,
+    path: String,
+    filter: ColorFilter,
+    max_size: Option<u32>,
+) -> Result<FilteredPreview, String> {
+    let max_size = max_size.unwrap_or(DEFAULT_MAX_SIZE);
+    let source = path_guard::guard(&app_handle, &path)?;
+    let (image, applied_orientation) = preview::open_oriented(&app_handle, &source)?;
+    let mut image = image.thumbnail(max_size, max_size).to_rgb8();
+    apply_filter(&mut image, filter);
+
+    let variant = format!("filter-{}-{}-o{}", filter.name(), max_size, applied_orientation.unwrap_or(1));
+    let dest = preview::get_cache_dir(&app_handle, "filtered")
+        .join(format!("{}.png", preview::cache_key(Path::new(&source), &variant)));
+    let (width, height) = image.dimensions();
+    preview::save_png(&DynamicImage::ImageRgb8(image), &dest)?;
+
+    Ok(FilteredPreview {
+        path: dest.to_string_lossy().to_string(),
+        width,
+        height,
+        applied_orientation,
+        filter,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_keep_neutrals_and_merge_red_green() {
+        let colors = [[255, 255, 255], [128, 128, 128], [220, 40, 40], [40, 160, 40]];
+        let image = RgbImage::from_fn(4, 1, |x, _| ::image::Rgb(colors[x as usize]));
+        let distance = |a: [u8; 3], b: [u8; 3]| a.iter().zip(b).map(|(x, y)| (*x as i32 - y as i32).abs()).sum::<i32>();
+
+        for filter in [ColorFilter::Protanopia, ColorFilter::Deuteranopia, ColorFilter::Tritanopia, ColorFilter::Grayscale] {
+            let mut filtered = image.clone();
+            apply_filter(&mut filtered, filter);
+            // 白と灰色はほぼそのまま
+            assert!(distance(filtered.get_pixel(0, 0).0, [255, 255, 255]) <= 3, "{:?}", filter);
+            assert!(distance(filtered.get_pixel(1, 0).0, [128, 128, 128]) <= 3, "{:?}", filter);
+        }
+
+        let mut protan = image.clone();
+        apply_filter(&mut protan, ColorFilter::Protanopia);
+        assert!(distance(protan.get_pixel(2, 0).0, protan.get_pixel(3, 0).0) < distance(colors[2], colors[3]));
+
+        let mut gray = image;
+        apply_filter(&mut gray, ColorFilter::Grayscale);
+        let [r, g, b] = gray.get_pixel(2, 0).0;
+        assert!(r == g && g == b);
+    }
+}
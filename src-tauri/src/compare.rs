@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use ::image::imageops::FilterType;
+use ::image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::exif_info;
+use crate::path_guard;
+use crate::preview;
+
+fn default_max_size() -> u32 {
+    1024
+}
+
+/// 比較の設定
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompareOptions {
+    /// プレビューの最大辺（ピクセル）
+    #[serde(default = "default_max_size")]
+    pub max_size: u32,
+    /// 差分のヒートマップを作成する
+    #[serde(default)]
+    pub heatmap: bool,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self { max_size: default_max_size(), heatmap: false }
+    }
+}
+
+/// 比較対象の1枚分の情報
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComparedImage {
+    /// ファイルのパス
+    pub path: String,
+    /// 元画像の幅
+    pub width: u32,
+    /// 元画像の高さ
+    pub height: u32,
+    /// ファイルサイズ（バイト）
+    pub size: u64,
+    /// 位置合わせ済みプレビューのパス（両画像で同じ大きさ）
+    pub preview: String,
+    /// プレビューに適用したEXIFの向き（適用していなければNone）
+    pub applied_orientation: Option<u32>,
+}
+
+/// EXIF項目の差分
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExifDiff {
+    /// 項目名
+    pub tag: String,
+    /// 画像Aの値
+    pub a: Option<String>,
+    /// 画像Bの値
+    pub b: Option<String>,
+}
+
+/// 2枚の画像の比較結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompareResult {
+    pub a: ComparedImage,
+    pub b: ComparedImage,
+    /// プレビューの幅
+    pub preview_width: u32,
+    /// プレビューの高さ
+    pub preview_height: u32,
+    /// 元画像の大きさが同じか
+    pub same_dimensions: bool,
+    /// ファイルサイズの差（B - A、バイト）
+    pub size_difference: i64,
+    /// 値の異なるEXIF項目
+    pub exif_diff: Vec<ExifDiff>,
+    /// プレビュー上の平均差分（0.0〜1.0、0は同一）
+    pub difference_score: f64,
+    /// 差分ヒートマップのパス（要求時のみ）
+    pub heatmap: Option<String>,
+}
+
+/// EXIFの主要項目を読み込む（EXIFのない画像は空）
+fn read_exif(path: &Path) -> BTreeMap<String, String> {
+    let Some(data) = exif_info::read(path) else { return BTreeMap::new() };
+
+    data.fields()
+        .filter(|field| field.ifd_num == exif::In::PRIMARY)
+        .map(|field| (field.tag.to_string(), field.display_value().with_unit(&data).to_string()))
+        .collect()
+}
+
+/// 値の異なるEXIF項目を列挙する
+fn diff_exif(a: &BTreeMap<String, String>, b: &BTreeMap<String, String>) -> Vec<ExifDiff> {
+    let mut tags: Vec<&String> = a.keys().chain(b.keys()).collect();
+    tags.sort();
+    tags.dedup();
+
+    tags.into_iter()
+        .filter(|tag| a.get(*tag) != b.get(*tag))
+        .map(|tag| ExifDiff {
+            tag: tag.clone(),
+            a: a.get(tag).cloned(),
+            b: b.get(tag).cloned(),
+        })
+        .collect()
+}
+
+/// 差分の大きさを黒→赤→黄→白の色に変換する
+fn heat_color(value: u8) -> Rgb<u8> {
+    let v = value as u32 * 3;
+    Rgb([v.min(255) as u8, v.saturating_sub(255).min(255) as u8, v.saturating_sub(510).min(255) as u8])
+}
+
+/// 同じ大きさの2枚の差分を計算し、平均差分とヒートマップを返す
+fn difference(a: &RgbImage, b: &RgbImage) -> (f64, RgbImage) {
+    let mut heatmap = RgbImage::new(a.width(), a.height());
+    let mut total: u64 = 0;
+
+    for (x, y, pa) in a.enumerate_pixels() {
+        let pb = b.get_pixel(x, y);
+        let diff = (0..3).map(|i| pa[i].abs_diff(pb[i]) as u32).max().unwrap_or(0) as u8;
+        total += diff as u64;
+        heatmap.put_pixel(x, y, heat_color(diff));
+    }
+
+    let pixels = (a.width() as u64 * a.height() as u64).max(1);
+    (total as f64 / pixels as f64 / 255.0, heatmap)
+}
+
+/// 最大辺に収まる大きさを求める（拡大はしない）
+fn fit_within(width: u32, height: u32, max_size: u32) -> (u32, u32) {
+    let max_size = max_size.max(1);
+    if width <= max_size && height <= max_size {
+        return (width.max(1), height.max(1));
+    }
+    let scale = max_size as f64 / width.max(height) as f64;
+    (((width as f64 * scale).round() as u32).max(1), ((height as f64 * scale).round() as u32).max(1))
+}
+
+/// 2枚の画像を比較し、位置合わせ済みプレビュー・サイズ/EXIFの差分・ヒートマップを返す
+///
+/// プレビューはAの縦横比に合わせ、Bは中央を基準に切り抜いて同じ大きさにする
+#[tauri::command]
+pub async fn compare_images(
+    app_handle: AppHandle,
+    path_a: String,
+    path_b: String,
+    options: Option<CompareOptions>,
+) -> Result<CompareResult, String> {
+    let options = options.unwrap_or_default();
+    let (pa, pb) = (path_guard::guard(&app_handle, &path_a)?, path_guard::guard(&app_handle, &path_b)?);
+    let (pa, pb) = (pa.as_path(), pb.as_path());
+    let (image_a, orientation_a) = preview::open_oriented(&app_handle, pa)?;
+    let (image_b, orientation_b) = preview::open_oriented(&app_handle, pb)?;
+
+    let (width, height) = fit_within(image_a.width(), image_a.height(), options.max_size);
+    let aligned_a = image_a.resize_exact(width, height, FilterType::Triangle);
+    let aligned_b = image_b.resize_to_fill(width, height, FilterType::Triangle);
+
+    let cache_dir = preview::get_cache_dir(&app_handle, "compare");
+    let variant = format!("aligned-{}x{}", width, height);
+    let preview_a = cache_dir.join(format!("{}.png", preview::cache_key(pa, &format!("{}-o{}", variant, orientation_a.unwrap_or(1)))));
+    let preview_b = cache_dir.join(format!("{}.png", preview::cache_key(pb, &format!("{}-o{}", variant, orientation_b.unwrap_or(1)))));
+    if !preview_a.exists() {
+        preview::save_png(&aligned_a, &preview_a)?;
+    }
+    if !preview_b.exists() {
+        preview::save_png(&aligned_b, &preview_b)?;
+    }
+
+    let (difference_score, heatmap_image) = difference(&aligned_a.to_rgb8(), &aligned_b.to_rgb8());
+    let heatmap = if options.heatmap {
+        let dest = cache_dir.join(format!(
+            "{}-{}.png",
+            preview::cache_key(pa, "heatmap"),
+            preview::cache_key(pb, &variant),
+        ));
+        preview::save_png(&DynamicImage::ImageRgb8(heatmap_image), &dest)?;
+        Some(dest.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    let size_a = pa.metadata().map(|m| m.len()).unwrap_or(0);
+    let size_b = pb.metadata().map(|m| m.len()).unwrap_or(0);
+
+    Ok(CompareResult {
+        same_dimensions: image_a.dimensions() == image_b.dimensions(),
+        size_difference: size_b as i64 - size_a as i64,
+        exif_diff: diff_exif(&read_exif(pa), &read_exif(pb)),
+        a: ComparedImage {
+            path: path_a.clone(),
+            width: image_a.width(),
+            height: image_a.height(),
+            size: size_a,
+            preview: preview_a.to_string_lossy().to_string(),
+            applied_orientation: orientation_a,
+        },
+        b: ComparedImage {
+            path: path_b.clone(),
+            width: image_b.width(),
+            height: image_b.height(),
+            size: size_b,
+            preview: preview_b.to_string_lossy().to_string(),
+            applied_orientation: orientation_b,
+        },
+        preview_width: width,
+        preview_height: height,
+        difference_score,
+        heatmap,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difference_and_exif_diff() {
+        let a = RgbImage::from_pixel(4, 4, Rgb([0, 0, 0]));
+        let mut b = a.clone();
+        b.put_pixel(0, 0, Rgb([255, 0, 0]));
+
+        let (score, heatmap) = difference(&a, &b);
+        assert!((score - 1.0 / 16.0).abs() < 1e-9);
+        assert_eq!(heatmap.get_pixel(0, 0), &Rgb([255, 255, 255]));
+        assert_eq!(heatmap.get_pixel(1, 1), &Rgb([0, 0, 0]));
+
+        let exif_a = BTreeMap::from([("Model".to_string(), "X100".to_string()), ("ISO".to_string(), "200".to_string())]);
+        let exif_b = BTreeMap::from([("Model".to_string(), "X100".to_string()), ("ISO".to_string(), "400".to_string())]);
+        assert_eq!(diff_exif(&exif_a, &exif_b), vec![ExifDiff {
+            tag: "ISO".to_string(),
+            a: Some("200".to_string()),
+            b: Some("400".to_string()),
+        }]);
+
+        assert_eq!(fit_within(4000, 3000, 1000), (1000, 750));
+        assert_eq!(fit_within(100, 50, 1000), (100, 50));
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use rusqlite::{params, Connection};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager};
+use crate::hidden;
+use crate::i18n::t;
+use crate::image::{self, ImageInfo};
+use crate::index::LibraryIndex;
+use crate::privacy;
+use crate::sensitive;
+use crate::settings::{AppSettings, ClassifierSettings};
+
+/// 検索結果の最大件数
+const MAX_SEARCH_RESULTS: usize = 500;
+
+/// モデルを指定しなかったときのファイル名（アプリデータの`models`内）
+const DEFAULT_MODEL: &str = "classifier.onnx";
+
+/// 分類中か（スキャンのたびに重ねて開始しないため）
+static INDEXING: AtomicBool = AtomicBool::new(false);
+
+/// 自動タグ付けの状態
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClassifierStatus {
+    /// このビルドで画像分類を使えるか（`ml`フィーチャー）
+    pub available: bool,
+    pub enabled: bool,
+    pub model_path: String,
+    pub model_found: bool,
+    pub labels_found: bool,
+    /// 分類済みの画像の数
+    pub classified: usize,
+    /// 分類を実行中か
+    pub running: bool,
+}
+
+/// 画像に付いた自動タグ
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Concept {
+    /// ラベル（ラベル一覧の1行、別名を含む）
+    pub label: String,
+    /// 確率（0.0〜1.0）
+    pub score: f32,
+}
+
+/// 自動タグの保存先（インデックスと同じSQLiteファイル）
+pub struct ConceptStore {
+    conn: Connection,
+}
+
+impl ConceptStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("concepts.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する（タグが付かなかった画像も分類済みとして記録する）
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS image_concepts (
+                path TEXT NOT NULL,
+                label TEXT NOT NULL,
+                score REAL NOT NULL,
+                PRIMARY KEY (path, label)
+            );
+            CREATE TABLE IF NOT EXISTS classified_images (
+                path TEXT PRIMARY KEY
+            );"
+        ).map_err(|e| t!("concepts.init_failed", e))
+    }
+
+    /// 画像の自動タグを置き換える
+    #[cfg_attr(not(feature = "ml"), allow(dead_code))]
+    pub fn set(&mut self, path: &str, concepts: &[Concept]) -> Result<(), String> {
+        let tx = self.conn.transaction()
+            .map_err(|e| t!("concepts.save_failed", e))?;
+        tx.execute("DELETE FROM image_concepts WHERE path = ?1", params![path])
+            .map_err(|e| t!("concepts.save_path_failed", path, e))?;
+        for concept in concepts {
+            tx.execute(
+                "INSERT OR REPLACE INTO image_concepts (path, label, score) VALUES (?1, ?2, ?3)",
+                params![path, concept.label, concept.score as f64],
+            ).map_err(|e| t!("concepts.save_path_failed", path, e))?;
+        }
+        tx.execute("INSERT OR IGNORE INTO classified_images (path) VALUES (?1)", params![path])
+            .map_err(|e| t!("concepts.save_path_failed", path, e))?;
+        tx.commit().map_err(|e| t!("concepts.save_failed", e))
+    }
+
+    /// 画像の自動タグを確率の高い順で取得する
+    pub fn get(&self, path: &str) -> Result<Vec<Concept>, String> {
+        let mut stmt = self.conn.prepare("SELECT label, score FROM image_concepts WHERE path = ?1 ORDER BY score DESC")
+            .map_err(|e| t!("concepts.read_failed", e))?;
+        let rows = stmt.query_map(params![path], |row| {
+            Ok(Concept { label: row.get(0)?, score: row.get::<_, f64>(1)? as f32 })
+        }).map_err(|e| t!("concepts.read_failed", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| t!("concepts.read_failed", e))
+    }
+
+    /// 分類済みの画像のパス
+    pub fn classified(&self) -> Result<HashSet<String>, String> {
+        let mut stmt = self.conn.prepare("SELECT path FROM classified_images")
+            .map_err(|e| t!("concepts.read_failed", e))?;
+        let rows = stmt.query_map([], |row| row.get(0))
+            .map_err(|e| t!("concepts.read_failed", e))?;
+        rows.collect::<Result<HashSet<String>, _>>()
+            .map_err(|e| t!("concepts.read_failed", e))
+    }
+
+    /// ラベルに`query`を含む画像のパスを確率の高い順で取得する（大文字・小文字は区別しない）
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<String>, String> {
+        let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let mut stmt = self.conn.prepare(
+            "SELECT path FROM image_concepts WHERE label LIKE ?1 ESCAPE '\\'
+             GROUP BY path ORDER BY MAX(score) DESC, path LIMIT ?2"
+        ).map_err(|e| t!("concepts.search_failed", e))?;
+        let rows = stmt.query_map(params![format!("%{}%", escaped), limit as i64], |row| row.get(0))
+            .map_err(|e| t!("concepts.search_failed", e))?;
+        rows.collect::<Result<Vec<String>, _>>()
+            .map_err(|e| t!("concepts.search_failed", e))
+    }
+}
+
+/// モデルの出力を確率に直す（合計が1でない出力はソフトマックスで直す）
+#[cfg_attr(not(feature = "ml"), allow(dead_code))]
+pub(crate) fn probabilities(scores: &[f32]) -> Vec<f32> {
+    let sum: f32 = scores.iter().sum();
+    if scores.iter().all(|s| (0.0..=1.0).contains(s)) && (sum - 1.0).abs() < 0.01 {
+        return scores.to_vec();
+    }
+    let max = scores.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let exp: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+    let total: f32 = exp.iter().sum();
+    exp.iter().map(|e| e / total).collect()
+}
+
+/// モデルの出力から、確率の高いラベルを選ぶ
+#[cfg_attr(not(feature = "ml"), allow(dead_code))]
+fn top_concepts(scores: &[f32], labels: &[String], min_score: f32, max_tags: usize) -> Vec<Concept> {
+    let mut ranked: Vec<(usize, f32)> = probabilities(scores).into_iter().enumerate().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.into_iter()
+        .filter(|(_, score)| *score >= min_score)
+        .filter_map(|(index, score)| labels.get(index).map(|label| Concept { label: label.clone(), score }))
+        .take(max_tags)
+        .collect()
+}
+
+/// モデルとラベル一覧のパス（省略するとアプリデータの`models/<default_name>`と、同じ場所の`.labels.txt`）
+pub(crate) fn model_paths(
+    app_handle: &AppHandle,
+    model_path: Option<&str>,
+    labels_path: Option<&str>,
+    default_name: &str,
+) -> (PathBuf, PathBuf) {
+    let model = model_path.map(PathBuf::from).unwrap_or_else(|| {
+        app_handle.path().app_data_dir().unwrap_or_default().join("models").join(default_name)
+    });
+    let labels = labels_path.map(PathBuf::from).unwrap_or_else(|| model.with_extension("labels.txt"));
+    (model, labels)
+}
+
+#[cfg(feature = "ml")]
+pub(crate) mod model {
+    use std::path::Path;
+    use ::image::{imageops::FilterType, DynamicImage};
+    use tract_onnx::prelude::*;
+    use crate::i18n::t;
+
+    /// ImageNetで学習したモデルの入力の正規化
+    const MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+    const STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+    pub fn load_labels(path: &Path) -> Result<Vec<String>, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| t!("file.read_failed", path.display(), e))?;
+        Ok(content.lines().map(|line| line.trim().to_string()).collect())
+    }
+
+    pub struct Classifier {
+        model: TypedRunnableModel<TypedModel>,
+        size: u32,
+    }
+
+    impl Classifier {
+        pub fn load(path: &Path, size: u32) -> Result<Self, String> {
+            let side = size as usize;
+            let model = tract_onnx::onnx()
+                .model_for_path(path)
+                .and_then(|model| model.with_input_fact(0, f32::fact([1, 3, side, side]).into()))
+                .and_then(|model| model.into_optimized())
+                .and_then(|model| model.into_runnable())
+                .map_err(|e| t!("concepts.model_failed", path.display(), e))?;
+            Ok(Self { model, size })
+        }
+
+        /// 画像を分類し、クラスごとの出力を返す
+        pub fn scores(&self, image: &DynamicImage) -> Result<Vec<f32>, String> {
+            let rgb = image.resize_exact(self.size, self.size, FilterType::Triangle).to_rgb8();
+            let side = self.size as usize;
+            let tensor: Tensor = tract_ndarray::Array4::from_shape_fn((1, 3, side, side), |(_, c, y, x)| {
+                (rgb.get_pixel(x as u32, y as u32)[c] as f32 / 255.0 - MEAN[c]) / STD[c]
+            }).into();
+            let outputs = self.model.run(tvec!(tensor.into())).map_err(|e| t!("concepts.classify_failed", e))?;
+            let view = outputs[0].to_array_view::<f32>().map_err(|e| t!("concepts.classify_failed", e))?;
+            Ok(view.iter().copied().collect())
+        }
+    }
+}
+
+/// インデックス内の未分類の画像を分類して自動タグを保存する
+#[cfg(feature = "ml")]
+fn index_pending(app_handle: &AppHandle, settings: &ClassifierSettings) -> Result<usize, String> {
+    let (model_path, labels_path) = model_paths(app_handle, settings.model_path.as_deref(), settings.labels_path.as_deref(), DEFAULT_MODEL);
+    if !model_path.is_file() {
+        return Err(t!("concepts.model_not_found", model_path.display()));
+    }
+    let labels = model::load_labels(&labels_path)?;
+    let classifier = model::Classifier::load(&model_path, settings.input_size)?;
+
+    let mut store = ConceptStore::open(app_handle)?;
+    let classified = store.classified()?;
+    let pending: Vec<ImageInfo> = LibraryIndex::open(app_handle)?
+        .all_images()?
+        .into_iter()
+        .filter(|image| !classified.contains(&image.path))
+        .collect();
+
+    let mut count = 0;
+    for image in pending {
+        let result = crate::preview::open_oriented(app_handle, Path::new(&image.path))
+            .and_then(|(decoded, _)| classifier.scores(&decoded));
+        match result {
+            Ok(scores) => {
+                store.set(&image.path, &top_concepts(&scores, &labels, settings.min_score, settings.max_tags))?;
+                count += 1;
+            },
+            Err(e) => tracing::warn!("画像を分類できませんでした: {} - {}", image.path, e),
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(not(feature = "ml"))]
+fn index_pending(_app_handle: &AppHandle, _settings: &ClassifierSettings) -> Result<usize, String> {
+    Err(t!("concepts.unavailable"))
+}
+
+/// 設定で有効なら、未分類の画像の分類をバックグラウンドで開始する（スキャン後に呼ぶ）
+pub fn index_in_background(app_handle: &AppHandle) {
+    let settings = AppSettings::load(app_handle).unwrap_or_default().classifier;
+    if !settings.enabled || INDEXING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let handle = app_handle.clone();
+    std::thread::spawn(move || {
+        match index_pending(&handle, &settings) {
+            Ok(count) => tracing::info!("画像を分類しました: {}枚", count),
+            Err(e) => tracing::warn!("画像の分類を行えませんでした: {}", e),
+        }
+        INDEXING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// 自動タグに`query`を含む画像の一覧を取得する（「dog」「beach」「document」など）
+#[tauri::command]
+pub async fn search_by_concept(app_handle: AppHandle, query: String) -> Result<Vec<ImageInfo>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let images = ConceptStore::open(&app_handle)?
+        .search(query.trim(), MAX_SEARCH_RESULTS)?
+        .iter()
+        .filter_map(|path| image::image_info(Path::new(path)).ok())
+        .collect();
+    Ok(sensitive::mark(&app_handle, hidden::filter_images(&app_handle, privacy::filter_images(&app_handle, images))))
+}
+
+/// 自動タグ付けの状態（モデルの有無・分類済みの数）を取得する
+#[tauri::command]
+pub async fn get_classifier_status(app_handle: AppHandle) -> Result<ClassifierStatus, String> {
+    let settings = AppSettings::load(&app_handle).unwrap_or_default().classifier;
+    let (model_path, labels_path) = model_paths(&app_handle, settings.model_path.as_deref(), settings.labels_path.as_deref(), DEFAULT_MODEL);
+    Ok(ClassifierStatus {
+        available: cfg!(feature = "ml"),
+        enabled: settings.enabled,
+        model_path: model_path.to_string_lossy().to_string(),
+        model_found: model_path.is_file(),
+        labels_found: labels_path.is_file(),
+        classified: ConceptStore::open(&app_handle)?.classified()?.len(),
+        running: INDEXING.load(Ordering::SeqCst),
+    })
+}
+
+/// 画像に付いた自動タグを取得する
+#[tauri::command]
+pub async fn get_image_concepts(app_handle: AppHandle, path: String) -> Result<Vec<Concept>, String> {
+    ConceptStore::open(&app_handle)?.get(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_concepts_and_search() {
+        let labels: Vec<String> = ["cat", "dog, puppy", "beach"].iter().map(|s| s.to_string()).collect();
+        let concepts = top_concepts(&[0.5, 3.0, 1.0], &labels, 0.1, 2);
+        assert_eq!(concepts.iter().map(|c| c.label.as_str()).collect::<Vec<_>>(), vec!["dog, puppy", "beach"]);
+        assert!((concepts.iter().map(|c| c.score).sum::<f32>() - 0.93).abs() < 0.01);
+
+        let path = std::env::temp_dir().join(format!("poir-concepts-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut store = ConceptStore::open_at(&path).unwrap();
+        store.set("/a.jpg", &concepts).unwrap();
+        store.set("/b.jpg", &[Concept { label: "dog, puppy".to_string(), score: 0.9 }]).unwrap();
+        store.set("/c.jpg", &[]).unwrap();
+
+        assert_eq!(store.search("Puppy", 10).unwrap(), vec!["/b.jpg", "/a.jpg"]);
+        assert_eq!(store.classified().unwrap().len(), 3);
+        assert_eq!(store.get("/a.jpg").unwrap().len(), 2);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -1,7 +1,30 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
+use crate::i18n::t;
+use crate::image;
+use crate::long_path;
+
+// 画像数の見積もりで調べるエントリ数の上限（大きなフォルダやネットワーク上で待たせないため）
+const ESTIMATE_MAX_ENTRIES: usize = 20_000;
+
+// 画像数の見積もりで調べる深さ（一覧のスキャンの既定値と同じ）
+const ESTIMATE_MAX_DEPTH: usize = 3;
+
+// アプリデータの保存先ディレクトリを取得
+pub fn app_data_dir(app_handle: &AppHandle) -> PathBuf {
+    app_handle.path().app_data_dir().unwrap_or_else(|_| {
+        // アプリディレクトリが取得できない場合は実行ファイルのディレクトリを使用
+        std::env::current_exe()
+            .unwrap_or_default()
+            .parent()
+            .unwrap_or(Path::new("."))
+            .to_path_buf()
+    })
+}
 
 // resources.jsonの内容を表す構造体
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -9,6 +32,27 @@ pub struct ResourceConfig {
     pub id: String,
     pub name: String,
     pub filters: Filters,
+    // includeのパスごとのオプション（既定値のフォルダは記録しない）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub folder_options: HashMap<String, FolderOptions>,
+}
+
+// 設定フォルダごとのオプション
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct FolderOptions {
+    // プライバシーモード中は一覧・検索・履歴から除外する
+    pub private: bool,
+    // 無効にしたフォルダは設定に残したままスキャンしない
+    pub enabled: bool,
+    // スキャンの優先度（大きいほど先にスキャンする）
+    pub priority: i32,
+}
+
+impl Default for FolderOptions {
+    fn default() -> Self {
+        Self { private: false, enabled: true, priority: 0 }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -17,6 +61,129 @@ pub struct Filters {
     pub exclude: Vec<String>,
 }
 
+// 設定フォルダごとの検証結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PathStatus {
+    pub path: String,
+    pub valid: bool,
+    // 無効な理由
+    pub reason: Option<String>,
+}
+
+// `config-status`で通知する設定の状態
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ConfigStatus {
+    // すべての設定フォルダが有効で、1つ以上設定されているか
+    pub valid: bool,
+    // 設定フォルダの数
+    pub total: usize,
+    pub valid_count: usize,
+    pub invalid_count: usize,
+    pub paths: Vec<PathStatus>,
+    // 設定全体が無効な理由（フォルダが1つもない等）
+    pub reason: Option<String>,
+}
+
+// フォルダを使えない理由
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PathIssue {
+    Empty,
+    NotFound,
+    NotDirectory,
+    PermissionDenied,
+    // ネットワーク上のフォルダに接続できない
+    NetworkOffline,
+}
+
+// フォルダ追加時の詳しい検証結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PathValidation {
+    pub path: String,
+    pub valid: bool,
+    pub issue: Option<PathIssue>,
+    // 画面に表示する説明
+    pub message: Option<String>,
+    // スキャンで見つかる画像のおおよその数（有効な場合のみ）
+    pub estimated_images: Option<usize>,
+    // 上限に達して数えるのを打ち切ったか（実際はこれより多い）
+    pub estimate_truncated: bool,
+}
+
+// まとめて追加したパスごとの結果
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AddPathOutcome {
+    Added,
+    // 既に設定されている（正規化形式の違いは同じパスとみなす）
+    AlreadyAdded,
+    Invalid,
+}
+
+// まとめて追加したパスごとの結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AddPathResult {
+    pub path: String,
+    pub outcome: AddPathOutcome,
+    // 追加できなかった理由
+    pub issue: Option<PathIssue>,
+    pub message: Option<String>,
+}
+
+// フォルダ選択ダイアログから追加したフォルダ
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PickedFolder {
+    pub path: String,
+    pub outcome: AddPathOutcome,
+    // スキャンで見つかった画像の数
+    pub images: usize,
+}
+
+// ネットワーク上のパス（UNCパス）か
+fn is_network_path(path: &str) -> bool {
+    let path = long_path::strip_extended(path);
+    path.starts_with(r"\\") || path.starts_with("//")
+}
+
+// ネットワークの切断・応答なしによるエラーか
+fn is_network_error(error: &io::Error) -> bool {
+    // Windowsのネットワーク関連のエラーコード（ERROR_BAD_NETPATH・ERROR_BAD_NET_NAMEなど）
+    const WINDOWS_NETWORK_ERRORS: [i32; 6] = [51, 53, 64, 67, 1222, 1231];
+    matches!(
+        error.kind(),
+        io::ErrorKind::HostUnreachable
+            | io::ErrorKind::NetworkUnreachable
+            | io::ErrorKind::NetworkDown
+            | io::ErrorKind::StaleNetworkFileHandle
+            | io::ErrorKind::TimedOut
+    ) || (cfg!(windows) && error.raw_os_error().is_some_and(|code| WINDOWS_NETWORK_ERRORS.contains(&code)))
+}
+
+// フォルダ内の画像のおおよその数を数える（調べたエントリ数が上限を超えたら打ち切る）
+fn estimate_images(dir: &Path) -> (usize, bool) {
+    let mut count = 0;
+    let mut visited = 0;
+    let mut pending = vec![(dir.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            visited += 1;
+            if visited > ESTIMATE_MAX_ENTRIES {
+                return (count, true);
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                if depth < ESTIMATE_MAX_DEPTH {
+                    pending.push((path, depth + 1));
+                }
+            } else if image::is_image_file(&path) {
+                count += 1;
+            }
+        }
+    }
+    (count, false)
+}
+
 impl Default for ResourceConfig {
     fn default() -> Self {
         Self {
@@ -26,6 +193,7 @@ impl Default for ResourceConfig {
                 include: Vec::new(),
                 exclude: Vec::new(),
             },
+            folder_options: HashMap::new(),
         }
     }
 }
@@ -33,16 +201,7 @@ impl Default for ResourceConfig {
 impl ResourceConfig {
     // 設定ファイルのパスを取得
     pub fn get_config_path(app_handle: &AppHandle) -> PathBuf {
-        let app_dir = app_handle.path().app_data_dir().unwrap_or_else(|_| {
-            // アプリディレクトリが取得できない場合は実行ファイルのディレクトリを使用
-            let exe_dir = std::env::current_exe()
-                .unwrap_or_default()
-                .parent()
-                .unwrap_or(Path::new("."))
-                .to_path_buf();
-            exe_dir
-        });
-        app_dir.join("resources.json")
+        app_data_dir(app_handle).join("resources.json")
     }
 
     // 設定ファイルの存在確認、なければデフォルト作成
@@ -53,20 +212,20 @@ impl ResourceConfig {
         if let Some(parent_dir) = config_path.parent() {
             if !parent_dir.exists() {
                 fs::create_dir_all(parent_dir)
-                    .map_err(|e| format!("ディレクトリの作成に失敗 ({}): {}", parent_dir.display(), e))?;
-                println!("アプリディレクトリを作成しました: {}", parent_dir.display());
+                    .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+                tracing::info!("アプリディレクトリを作成しました: {}", parent_dir.display());
             }
         }
         
         if !config_path.exists() {
             let default_config = Self::default();
             let config_json = serde_json::to_string_pretty(&default_config)
-                .map_err(|e| format!("デフォルト設定のシリアライズに失敗: {}", e))?;
+                .map_err(|e| t!("config.default_serialize_failed", e))?;
             
             fs::write(&config_path, config_json)
-                .map_err(|e| format!("設定ファイルの作成に失敗 ({}): {}", config_path.display(), e))?;
+                .map_err(|e| t!("config.create_failed", config_path.display(), e))?;
             
-            println!("デフォルト設定ファイルを作成しました: {}", config_path.display());
+            tracing::info!("デフォルト設定ファイルを作成しました: {}", config_path.display());
         }
         
         Ok(())
@@ -78,10 +237,10 @@ impl ResourceConfig {
         
         let config_path = Self::get_config_path(app_handle);
         let config_str = fs::read_to_string(&config_path)
-            .map_err(|e| format!("設定ファイルの読み込みに失敗: {}", e))?;
+            .map_err(|e| t!("config.read_failed", e))?;
             
         let config: ResourceConfig = serde_json::from_str(&config_str)
-            .map_err(|e| format!("JSONのパースに失敗: {}", e))?;
+            .map_err(|e| t!("config.parse_failed", e))?;
             
         Ok(config)
     }
@@ -90,30 +249,74 @@ impl ResourceConfig {
     pub fn save(&self, app_handle: &AppHandle) -> Result<(), String> {
         let config_path = Self::get_config_path(app_handle);
         let config_json = serde_json::to_string_pretty(self)
-            .map_err(|e| format!("設定のシリアライズに失敗: {}", e))?;
+            .map_err(|e| t!("config.serialize_failed", e))?;
             
         fs::write(&config_path, config_json)
-            .map_err(|e| format!("設定ファイルの保存に失敗: {}", e))?;
+            .map_err(|e| t!("config.save_failed", e))?;
             
         Ok(())
     }
 
     // パスの有効性チェック
     pub fn validate_path(path: &str) -> Result<(), String> {
-        let path = Path::new(path);
-        
-        if !path.exists() {
-            return Err(format!("パスが存在しません: {}", path.display()));
+        Self::check_path(path).map_err(|(_, message)| message)
+    }
+
+    // パスを確認し、使えない場合は理由と説明を返す
+    fn check_path(path: &str) -> Result<(), (PathIssue, String)> {
+        if path.is_empty() {
+            return Err((PathIssue::Empty, t!("path.empty")));
         }
+        // 正規化形式の違いを解決し、Windowsでは長いパスも扱えるよう拡張パスで確認する
+        let dir = long_path::extended(&long_path::resolve(Path::new(path)));
+        let offline = || (PathIssue::NetworkOffline, t!("path.network_offline", path));
+        
+        let metadata = match fs::metadata(&dir) {
+            Ok(metadata) => metadata,
+            Err(e) if is_network_error(&e) || (is_network_path(path) && e.kind() == io::ErrorKind::NotFound) => {
+                return Err(offline());
+            },
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                return Err((PathIssue::PermissionDenied, t!("path.access_denied", e)));
+            },
+            Err(_) => return Err((PathIssue::NotFound, t!("path.not_found", path))),
+        };
         
-        if !path.is_dir() {
-            return Err(format!("パスはディレクトリではありません: {}", path.display()));
+        if !metadata.is_dir() {
+            return Err((PathIssue::NotDirectory, t!("path.not_directory", path)));
         }
         
         // 読み取り権限チェック (ディレクトリの内容リストを取得してみる)
-        match fs::read_dir(path) {
+        match fs::read_dir(&dir) {
             Ok(_) => Ok(()),
-            Err(e) => Err(format!("ディレクトリにアクセスできません: {}", e)),
+            Err(e) if is_network_error(&e) => Err(offline()),
+            Err(e) => Err((PathIssue::PermissionDenied, t!("path.access_denied", e))),
+        }
+    }
+
+    // パスを確認し、使えない理由か、使える場合は画像のおおよその数を返す
+    pub fn validate_path_detailed(path: &str) -> PathValidation {
+        match Self::check_path(path) {
+            Ok(()) => {
+                let dir = long_path::extended(&long_path::resolve(Path::new(path)));
+                let (count, truncated) = estimate_images(&dir);
+                PathValidation {
+                    path: path.to_string(),
+                    valid: true,
+                    issue: None,
+                    message: None,
+                    estimated_images: Some(count),
+                    estimate_truncated: truncated,
+                }
+            },
+            Err((issue, message)) => PathValidation {
+                path: path.to_string(),
+                valid: false,
+                issue: Some(issue),
+                message: Some(message),
+                estimated_images: None,
+                estimate_truncated: false,
+            },
         }
     }
 
@@ -129,11 +332,198 @@ impl ResourceConfig {
     //     Ok(())
     // }
 
-    // 設定の有効性チェック
-    pub fn is_valid(&self) -> bool {
-        !self.filters.include.is_empty() && 
-        self.filters.include.iter().all(|path| {
-            Self::validate_path(path).is_ok()
-        })
+    // 複数のパスを検証し、有効で未設定のものだけを追加してパスごとの結果を返す（保存は呼び出し側で行う）
+    pub fn add_include_paths(&mut self, paths: &[String]) -> Vec<AddPathResult> {
+        paths.iter().map(|path| {
+            let result = |outcome, issue, message| AddPathResult { path: path.clone(), outcome, issue, message };
+            if self.filters.include.iter().any(|include| long_path::same_path(include, path)) {
+                return result(AddPathOutcome::AlreadyAdded, None, None);
+            }
+            match Self::check_path(path) {
+                Ok(()) => {
+                    self.filters.include.push(path.clone());
+                    result(AddPathOutcome::Added, None, None)
+                },
+                Err((issue, message)) => result(AddPathOutcome::Invalid, Some(issue), Some(message)),
+            }
+        }).collect()
+    }
+
+    // フォルダのオプションを取得する（未設定なら既定値）
+    pub fn folder_options(&self, path: &str) -> FolderOptions {
+        self.folder_options.get(path).cloned().unwrap_or_default()
+    }
+
+    // フォルダのオプションを設定する（既定値なら記録を削除する）
+    pub fn set_folder_options(&mut self, path: &str, options: FolderOptions) {
+        if options == FolderOptions::default() {
+            self.folder_options.remove(path);
+        } else {
+            self.folder_options.insert(path.to_string(), options);
+        }
+    }
+
+    // スキャン対象の（無効にしていない）設定フォルダ
+    pub fn enabled_folders(&self) -> impl Iterator<Item = &String> {
+        self.filters.include.iter().filter(|path| self.folder_options(path).enabled)
+    }
+
+    // 有効な設定フォルダを、スキャン時と同じく解決したパスで取得する（インデックスの記録と比べるため）
+    pub fn enabled_roots(&self) -> Vec<PathBuf> {
+        self.enabled_folders().map(|dir| long_path::resolve(Path::new(dir))).collect()
+    }
+
+    // スキャン対象のフォルダを優先度の高い順に並べる（同じ優先度なら設定の順）
+    pub fn scan_order(&self) -> Vec<&String> {
+        let mut folders: Vec<&String> = self.enabled_folders().collect();
+        folders.sort_by_key(|path| std::cmp::Reverse(self.folder_options(path).priority));
+        folders
+    }
+
+    // フォルダのオプションを持たない設定（画面からの保存等）に、既存のオプションを引き継ぐ
+    //
+    // `private`はPINを確認する`set_folder_private`でのみ変えられるよう、常に既存の値を使う。
+    // 隠す対象のフォルダは一覧から外しても記録を残し、追加し直して隠す対象から外せないようにする
+    pub fn merge_folder_options(&mut self, previous: &ResourceConfig) {
+        if self.folder_options.is_empty() {
+            self.folder_options = previous.folder_options.clone();
+        }
+        let paths: Vec<String> = self.folder_options.keys()
+            .chain(previous.folder_options.keys())
+            .cloned()
+            .collect();
+        for path in paths {
+            let mut options = self.folder_options(&path);
+            options.private = previous.folder_options(&path).private;
+            self.set_folder_options(&path, options);
+        }
+        let include = &self.filters.include;
+        self.folder_options.retain(|path, options| include.contains(path) || options.private);
+    }
+
+    // 設定フォルダごとに検証し、設定画面で問題を説明できるよう理由とともにまとめる
+    pub fn status(&self) -> ConfigStatus {
+        let paths: Vec<PathStatus> = self.filters.include.iter()
+            .map(|path| {
+                let reason = Self::validate_path(path).err();
+                PathStatus { path: path.clone(), valid: reason.is_none(), reason }
+            })
+            .collect();
+        let valid_count = paths.iter().filter(|status| status.valid).count();
+        let reason = if paths.is_empty() { Some(t!("scan.no_folders")) } else { None };
+
+        ConfigStatus {
+            valid: !paths.is_empty() && valid_count == paths.len(),
+            total: paths.len(),
+            valid_count,
+            invalid_count: paths.len() - valid_count,
+            paths,
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_reports_each_path() {
+        let mut config = ResourceConfig::default();
+        let status = config.status();
+        assert!(!status.valid);
+        assert!(status.reason.is_some());
+
+        let existing = std::env::temp_dir().to_string_lossy().to_string();
+        let missing = std::env::temp_dir().join("poir-config-missing").to_string_lossy().to_string();
+        config.filters.include = vec![existing.clone(), missing.clone()];
+        let status = config.status();
+        assert!(!status.valid);
+        assert_eq!((status.total, status.valid_count, status.invalid_count), (2, 1, 1));
+        assert_eq!(status.paths[0], PathStatus { path: existing, valid: true, reason: None });
+        assert_eq!(status.paths[1].path, missing);
+        assert!(status.paths[1].reason.is_some());
+    }
+
+    #[test]
+    fn test_validate_path_detailed() {
+        let root = std::env::temp_dir().join(format!("poir-validate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sub")).unwrap();
+        for name in ["a.jpg", "b.txt", "sub/c.png"] {
+            fs::write(root.join(name), b"").unwrap();
+        }
+
+        let valid = ResourceConfig::validate_path_detailed(&root.to_string_lossy());
+        assert!(valid.valid);
+        assert_eq!((valid.estimated_images, valid.estimate_truncated), (Some(2), false));
+
+        let issue = |path: &Path| ResourceConfig::validate_path_detailed(&path.to_string_lossy()).issue;
+        assert_eq!(issue(&root.join("a.jpg")), Some(PathIssue::NotDirectory));
+        assert_eq!(issue(&root.join("missing")), Some(PathIssue::NotFound));
+        assert_eq!(ResourceConfig::validate_path_detailed("").issue, Some(PathIssue::Empty));
+        assert!(is_network_path(r"\\nas\photos") && is_network_path(r"\\?\UNC\nas\photos"));
+        assert!(!is_network_path("/home/user"));
+
+        let mut config = ResourceConfig::default();
+        let paths = [root.to_string_lossy().to_string(), root.join("missing").to_string_lossy().to_string()];
+        let results = config.add_include_paths(&[paths[0].clone(), paths[1].clone(), paths[0].clone()]);
+        let outcomes: Vec<AddPathOutcome> = results.iter().map(|result| result.outcome).collect();
+        assert_eq!(outcomes, vec![AddPathOutcome::Added, AddPathOutcome::Invalid, AddPathOutcome::AlreadyAdded]);
+        assert_eq!(results[1].issue, Some(PathIssue::NotFound));
+        assert_eq!(config.filters.include, vec![paths[0].clone()]);
+
+        let _ = fs::remove_dir_all(&root);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_merge_keeps_private_from_previous() {
+        let mut previous = ResourceConfig::default();
+        previous.filters.include = vec!["/photos".to_string(), "/private".to_string()];
+        previous.set_folder_options("/private", FolderOptions { private: true, ..FolderOptions::default() });
+
+        // 画面から送られた設定では隠す対象から外せない
+        let mut config = previous.clone();
+        config.set_folder_options("/private", FolderOptions { priority: 5, ..FolderOptions::default() });
+        config.set_folder_options("/photos", FolderOptions { private: true, ..FolderOptions::default() });
+        config.merge_folder_options(&previous);
+        assert_eq!(config.folder_options("/private"), FolderOptions { private: true, priority: 5, ..FolderOptions::default() });
+        assert!(!config.folder_options("/photos").private);
+
+        // 一覧から外して追加し直しても隠す対象のまま
+        let mut removed = previous.clone();
+        removed.filters.include = vec!["/photos".to_string()];
+        removed.merge_folder_options(&previous);
+        let mut readded = removed.clone();
+        readded.filters.include.push("/private".to_string());
+        readded.folder_options.clear();
+        readded.merge_folder_options(&removed);
+        assert!(readded.folder_options("/private").private);
+    }
+
+    #[test]
+    fn test_disabled_folders_stay_configured() {
+        let mut config = ResourceConfig::default();
+        config.filters.include = vec!["/photos".to_string(), "/archive".to_string()];
+        config.set_folder_options("/archive", FolderOptions { enabled: false, ..FolderOptions::default() });
+        assert_eq!(config.enabled_folders().collect::<Vec<_>>(), vec!["/photos"]);
+        assert_eq!(config.filters.include.len(), 2);
+
+        // 既存の設定ファイルのオプションは有効として読み込む
+        let options: FolderOptions = serde_json::from_str(r#"{"private":true}"#).unwrap();
+        assert!(options.enabled && options.private);
+
+        config.set_folder_options("/archive", FolderOptions::default());
+        assert!(config.folder_options.is_empty());
+    }
+
+    #[test]
+    fn test_scan_order_by_priority() {
+        let mut config = ResourceConfig::default();
+        config.filters.include = ["/nas", "/local", "/usb", "/old"].map(String::from).to_vec();
+        config.set_folder_options("/nas", FolderOptions { priority: -1, ..FolderOptions::default() });
+        config.set_folder_options("/local", FolderOptions { priority: 10, ..FolderOptions::default() });
+        config.set_folder_options("/old", FolderOptions { enabled: false, priority: 20, ..FolderOptions::default() });
+        assert_eq!(config.scan_order(), vec!["/local", "/usb", "/nas"]);
+    }
+}
@@ -0,0 +1,233 @@
+use std::path::{Path, PathBuf};
+use ::image::{imageops, DynamicImage, Rgba, RgbaImage};
+use ab_glyph::FontVec;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::font;
+use crate::i18n::t;
+use crate::path_guard;
+use crate::pdf::PdfBuilder;
+use crate::preview;
+
+/// 1枚にまとめられる画像の上限（大きすぎる画像を作らないため）
+const MAX_CELLS: usize = 400;
+
+/// PDFで出力する際の解像度（ピクセルをポイントに換算する）
+const PDF_DPI: f32 = 150.0;
+
+/// 一覧画像のレイアウト
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ContactSheetLayout {
+    /// 横に並べる枚数
+    pub columns: u32,
+    /// 縮小画像の長辺
+    pub thumb_size: u32,
+    /// 画像どうしの間隔
+    pub spacing: u32,
+    /// 画像の下にファイル名を表示する
+    pub show_names: bool,
+    /// 先頭に表示する見出し（省略可）
+    pub title: Option<String>,
+    /// ファイル名に使うフォント（省略するとシステムのフォントを探す）
+    pub font_path: Option<String>,
+}
+
+impl Default for ContactSheetLayout {
+    fn default() -> Self {
+        Self {
+            columns: 4,
+            thumb_size: 256,
+            spacing: 16,
+            show_names: true,
+            title: None,
+            font_path: None,
+        }
+    }
+}
+
+/// 一覧画像の作成結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContactSheet {
+    /// 保存先のパス
+    pub path: String,
+    /// 画像の大きさ（ピクセル）
+    pub width: u32,
+    pub height: u32,
+    /// 並べた画像の数
+    pub count: usize,
+    /// 読み込めずに飛ばした画像
+    pub skipped: Vec<String>,
+}
+
+/// 出力形式（保存先の拡張子から決める）
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SheetFormat {
+    Png,
+    Pdf,
+}
+
+fn sheet_format(dest: &Path) -> Result<SheetFormat, String> {
+    let extension = dest.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "png" => Ok(SheetFormat::Png),
+        "pdf" => Ok(SheetFormat::Pdf),
+        _ => Err(t!("crop.unsupported_format", extension)),
+    }
+}
+
+/// 文字の大きさ（縮小画像の大きさに合わせる）
+fn label_px(layout: &ContactSheetLayout) -> f32 {
+    (layout.thumb_size as f32 / 14.0).clamp(10.0, 28.0)
+}
+
+/// 一覧画像の大きさとセルの大きさ・見出しの高さを求める
+fn sheet_size(layout: &ContactSheetLayout, count: usize) -> ((u32, u32), (u32, u32), u32) {
+    let columns = layout.columns.max(1).min(count.max(1) as u32);
+    let rows = (count as u32).div_ceil(columns).max(1);
+    let label_height = if layout.show_names { (label_px(layout) * 1.6).ceil() as u32 } else { 0 };
+    let title_height = if layout.title.is_some() { (label_px(layout) * 3.0).ceil() as u32 } else { 0 };
+    let cell = (layout.thumb_size, layout.thumb_size + label_height);
+    let width = columns * cell.0 + (columns + 1) * layout.spacing;
+    let height = title_height + rows * cell.1 + (rows + 1) * layout.spacing;
+    ((width, height), cell, title_height)
+}
+
+/// 縮小画像とファイル名を格子状に並べる
+fn compose(layout: &ContactSheetLayout, tiles: &[(DynamicImage, String)], font: Option<&FontVec>) -> RgbaImage {
+    let ((width, height), cell, title_height) = sheet_size(layout, tiles.len());
+    let columns = layout.columns.max(1).min(tiles.len().max(1) as u32);
+    let mut canvas = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+    let px = label_px(layout);
+    let text_color = Rgba([40, 40, 40, 255]);
+
+    if let (Some(title), Some(font)) = (&layout.title, font) {
+        let title_px = px * 1.6;
+        let title = font::fit(font, title, title_px, width.saturating_sub(layout.spacing * 2));
+        let top = (title_height + layout.spacing).saturating_sub(font::measure(font, &title, title_px).1) / 2;
+        font::draw_text(&mut canvas, font, &title, (layout.spacing as i32, top as i32), title_px, text_color);
+    }
+
+    for (index, (image, name)) in tiles.iter().enumerate() {
+        let column = index as u32 % columns;
+        let row = index as u32 / columns;
+        let left = layout.spacing + column * (cell.0 + layout.spacing);
+        let top = title_height + layout.spacing + row * (cell.1 + layout.spacing);
+
+        let thumb = image.thumbnail(layout.thumb_size, layout.thumb_size).to_rgba8();
+        let x = left + (layout.thumb_size - thumb.width()) / 2;
+        let y = top + (layout.thumb_size - thumb.height()) / 2;
+        imageops::overlay(&mut canvas, &thumb, x as i64, y as i64);
+
+        if let (true, Some(font)) = (layout.show_names, font) {
+            let label = font::fit(font, name, px, cell.0);
+            let label_width = font::measure(font, &label, px).0;
+            let label_x = left + cell.0.saturating_sub(label_width) / 2;
+            let label_y = top + layout.thumb_size + (px * 0.3) as u32;
+            font::draw_text(&mut canvas, font, &label, (label_x as i32, label_y as i32), px, text_color);
+        }
+    }
+    canvas
+}
+
+fn generate(app_handle: &AppHandle, paths: &[String], layout: &ContactSheetLayout, dest: &str) -> Result<ContactSheet, String> {
+    if paths.is_empty() {
+        return Err(t!("contact_sheet.empty"));
+    }
+    if paths.len() > MAX_CELLS {
+        return Err(t!("contact_sheet.too_many", paths.len(), MAX_CELLS));
+    }
+    let dest = PathBuf::from(dest);
+    let format = sheet_format(&dest)?;
+    if dest.parent().is_some_and(|parent| !parent.as_os_str().is_empty() && !parent.is_dir()) {
+        return Err(t!("path.not_directory", dest.display()));
+    }
+    let sources = paths.iter()
+        .map(|path| path_guard::guard(app_handle, path))
+        .collect::<Result<Vec<PathBuf>, String>>()?;
+
+    // ファイル名が不要なら、フォントが見つからなくても作成できる
+    let font = if layout.show_names || layout.title.is_some() {
+        Some(font::load_font(layout.font_path.as_deref())?)
+    } else {
+        None
+    };
+
+    let mut tiles = Vec::new();
+    let mut skipped = Vec::new();
+    for source in &sources {
+        match preview::open_oriented(app_handle, source) {
+            Ok((image, _)) => {
+                // 並べる前に縮小して、メモリに全画像を展開したままにしない
+                let image = image.thumbnail(layout.thumb_size, layout.thumb_size);
+                let name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                tiles.push((image, name));
+            },
+            Err(error) => {
+                tracing::warn!("一覧画像に追加できませんでした: {} - {}", source.display(), error);
+                skipped.push(source.to_string_lossy().to_string());
+            },
+        }
+    }
+    if tiles.is_empty() {
+        return Err(t!("contact_sheet.empty"));
+    }
+
+    let sheet = compose(layout, &tiles, font.as_ref());
+    let (width, height) = sheet.dimensions();
+    let sheet = DynamicImage::ImageRgba8(sheet);
+    match format {
+        SheetFormat::Png => preview::save_png(&sheet, &dest)?,
+        SheetFormat::Pdf => {
+            let page = (width as f32 * 72.0 / PDF_DPI, height as f32 * 72.0 / PDF_DPI);
+            let mut pdf = PdfBuilder::new();
+            pdf.add_image_page(&sheet, page, [0.0, 0.0, page.0, page.1])?;
+            pdf.save(&dest)?;
+        },
+    }
+
+    tracing::info!("一覧画像を作成しました: {} ({}枚)", dest.display(), tiles.len());
+    Ok(ContactSheet {
+        path: dest.to_string_lossy().to_string(),
+        width,
+        height,
+        count: tiles.len(),
+        skipped,
+    })
+}
+
+/// 画像の縮小版とファイル名を格子状に並べた一覧画像（コンタクトシート）を作成する
+///
+/// 保存先の拡張子で形式を決める（`.png`または`.pdf`）
+#[tauri::command]
+pub async fn generate_contact_sheet(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    layout: Option<ContactSheetLayout>,
+    dest: String,
+) -> Result<ContactSheet, String> {
+    let result = generate(&app_handle, &paths, &layout.unwrap_or_default(), &dest);
+    audit::complete(&app_handle, "generate_contact_sheet", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sheet_size_and_placement() {
+        let layout = ContactSheetLayout { columns: 3, thumb_size: 100, spacing: 10, show_names: false, ..Default::default() };
+        assert_eq!(sheet_size(&layout, 5), ((340, 230), (100, 100), 0));
+        // 枚数が列数より少なければ詰める
+        assert_eq!(sheet_size(&layout, 2).0, (230, 120));
+
+        let red = DynamicImage::ImageRgba8(RgbaImage::from_pixel(200, 100, Rgba([255, 0, 0, 255])));
+        let tiles = vec![(red.clone(), "a.jpg".to_string()), (red, "b.jpg".to_string())];
+        let sheet = compose(&layout, &tiles, None);
+        assert_eq!(sheet.get_pixel(60, 60), &Rgba([255, 0, 0, 255]));
+        assert_eq!(sheet.get_pixel(60, 20), &Rgba([255, 255, 255, 255]));
+        assert_eq!(sheet.get_pixel(170, 60), &Rgba([255, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Mutex;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::{AppHandle, Emitter, LogicalPosition, Manager, State, WebviewWindow};
+use serde::{Serialize, Deserialize};
+use crate::file_ops;
+use crate::i18n::t;
+
+/// メニュー項目IDの接頭辞（他のメニューのイベントと区別する）
+const MENU_ID_PREFIX: &str = "image-context:";
+
+/// フロントエンドで処理する操作の通知内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContextMenuAction {
+    /// 操作の種類
+    pub action: String,
+    /// 対象画像のパス
+    pub path: String,
+}
+
+/// 表示中のコンテキストメニューの対象
+#[derive(Default)]
+pub struct ContextMenuState(Mutex<Option<String>>);
+
+/// メニュー項目のIDを作る
+fn item_id(action: &str) -> String {
+    format!("{}{}", MENU_ID_PREFIX, action)
+}
+
+/// メニュー選択時の処理（この画面用のメニュー以外は無視する）
+pub fn handle_menu_event(app_handle: &AppHandle, event: MenuEvent) {
+    let Some(action) = event.id().as_ref().strip_prefix(MENU_ID_PREFIX) else { return };
+    let Some(path) = app_handle.try_state::<ContextMenuState>()
+        .and_then(|state| state.0.lock().ok().and_then(|mut target| target.take()))
+    else {
+        return;
+    };
+
+    let result = match action {
+        "open" => file_ops::open_in_default_app(app_handle, &path),
+        "reveal" => file_ops::reveal_in_folder(app_handle, &path),
+        "copy" => file_ops::copy_path(app_handle, &path),
+        "trash" => file_ops::move_to_trash(app_handle, &path),
+        // タグ編集はダイアログが必要なためフロントエンドに任せる
+        other => {
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.emit("context-menu-action", ContextMenuAction {
+                    action: other.to_string(),
+                    path: path.clone(),
+                });
+            }
+            Ok(())
+        },
+    };
+
+    if let Err(e) = result {
+        tracing::warn!("コンテキストメニューの操作に失敗しました: {}", e);
+        if let Some(window) = app_handle.get_webview_window("main") {
+            let _ = window.emit("image-error", e);
+        }
+    }
+}
+
+/// 画像のコンテキストメニューを指定位置（ウィンドウ内の論理座標）に表示する
+#[tauri::command]
+pub fn show_image_context_menu(
+    app_handle: AppHandle,
+    window: WebviewWindow,
+    state: State<'_, ContextMenuState>,
+    path: String,
+    x: f64,
+    y: f64,
+) -> Result<(), String> {
+    let item = |action: &str, label: String| {
+        MenuItem::with_id(&app_handle, item_id(action), label, true, None::<&str>)
+    };
+    let build = || -> tauri::Result<Menu<tauri::Wry>> {
+        Menu::with_items(&app_handle, &[
+            &item("open", t!("context_menu.open"))?,
+            &item("reveal", t!("context_menu.reveal"))?,
+            &item("copy", t!("context_menu.copy"))?,
+            &PredefinedMenuItem::separator(&app_handle)?,
+            &item("tag", t!("context_menu.tag"))?,
+            &PredefinedMenuItem::separator(&app_handle)?,
+            &item("trash", t!("context_menu.trash"))?,
+        ])
+    };
+    let menu = build().map_err(|e| t!("context_menu.build_failed", e))?;
+
+    *state.0.lock().map_err(|e| t!("common.lock_failed", t!("context_menu.name"), e))? = Some(path);
+    window.popup_menu_at(&menu, LogicalPosition::new(x, y))
+        .map_err(|e| t!("context_menu.build_failed", e))
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::display::HdrTransfer;
+use crate::folders;
+use crate::i18n::t;
+use crate::image::{self, ImageInfo};
+use crate::index::LibraryIndex;
+use crate::path_guard;
+use crate::preview;
+
+/// 表紙を探すサブフォルダの深さ
+const COVER_SEARCH_DEPTH: usize = 2;
+
+/// 表紙の縮小サイズ
+const COVER_THUMBNAIL_SIZE: u32 = 512;
+
+/// フォルダの表紙
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FolderCover {
+    /// フォルダのパス
+    pub folder: String,
+    /// 表紙の画像
+    pub image: ImageInfo,
+    /// ユーザーが固定した表紙か
+    pub pinned: bool,
+    /// 縮小画像のパス（作成できなければNone）
+    pub thumbnail: Option<String>,
+    /// 縮小画像に適用したEXIFの向き（適用していなければNone）
+    pub applied_orientation: Option<u32>,
+    /// 刺激の強い画像のため縮小画像をぼかしたか
+    pub blurred: bool,
+    /// 元の画像がHDRか
+    pub hdr: Option<HdrTransfer>,
+}
+
+/// 表紙の保存先（インデックスと同じSQLiteファイル）
+pub struct CoverStore {
+    conn: Connection,
+}
+
+impl CoverStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("cover.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS folder_covers (
+                folder TEXT PRIMARY KEY,
+                image TEXT NOT NULL,
+                pinned INTEGER NOT NULL
+            );"
+        ).map_err(|e| t!("cover.init_failed", e))
+    }
+
+    /// 記録済みの表紙を取得する（画像のパスと固定されているか）
+    pub fn get(&self, folder: &str) -> Result<Option<(String, bool)>, String> {
+        self.conn.query_row(
+            "SELECT image, pinned FROM folder_covers WHERE folder = ?1",
+            params![folder],
+            |row| Ok((row.get(0)?, row.get::<_, i64>(1)? != 0)),
+        ).optional().map_err(|e| t!("cover.read_failed", folder, e))
+    }
+
+    /// 表紙を記録する
+    pub fn set(&self, folder: &str, image: &str, pinned: bool) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO folder_covers (folder, image, pinned) VALUES (?1, ?2, ?3)",
+            params![folder, image, pinned as i64],
+        ).map(|_| ()).map_err(|e| t!("cover.save_failed", folder, e))
+    }
+
+    /// 表紙の記録を削除する
+    pub fn remove(&self, folder: &str) -> Result<(), String> {
+        self.conn.execute("DELETE FROM folder_covers WHERE folder = ?1", params![folder])
+            .map(|_| ())
+            .map_err(|e| t!("cover.save_failed", folder, e))
+    }
+}
+
+/// フォルダ（なければサブフォルダ）から表紙にする画像を選ぶ
+fn pick_cover(dir: &Path, depth: usize) -> Option<ImageInfo> {
+    folders::first_image(dir, &HashMap::new()).or_else(|| {
+        if depth == 0 {
+            return None;
+        }
+        folders::sub_dirs(dir).iter().find_map(|sub_dir| pick_cover(sub_dir, depth - 1))
+    })
+}
+
+/// 表紙を取得する（記録済みの画像が消えていれば選び直す）
+fn resolve_cover(store: &CoverStore, folder: &str) -> Result<Option<(ImageInfo, bool)>, String> {
+    if let Some((path, pinned)) = store.get(folder)? {
+        if let Ok(info) = image::image_info(Path::new(&path)) {
+            return Ok(Some((info, pinned)));
+        }
+        tracing::info!("表紙の画像が見つからないため選び直します: {}", path);
+    }
+
+    match pick_cover(Path::new(folder), COVER_SEARCH_DEPTH) {
+        Some(info) => {
+            // 一度選んだ表紙は記録し、フォルダの中身が増えても変わらないようにする
+            store.set(folder, &info.path, false)?;
+            Ok(Some((info, false)))
+        },
+        None => {
+            store.remove(folder)?;
+            Ok(None)
+        },
+    }
+}
+
+/// フォルダの表紙を取得する（画像がなければNone）
+#[tauri::command]
+pub async fn get_folder_cover(app_handle: AppHandle, path: String) -> Result<Option<FolderCover>, String> {
+    if !path_guard::guard(&app_handle, &path)?.is_dir() {
+        return Err(t!("path.not_directory", path));
+    }
+
+    let store = CoverStore::open(&app_handle)?;
+    Ok(resolve_cover(&store, &path)?.map(|(image, pinned)| {
+        let thumbnail = preview::thumbnail(&app_handle, Path::new(&image.path), COVER_THUMBNAIL_SIZE)
+            .map_err(|e| tracing::warn!("縮小画像を作成できませんでした: {}", e))
+            .ok();
+        FolderCover {
+            applied_orientation: thumbnail.as_ref().and_then(|thumbnail| thumbnail.applied_orientation),
+            blurred: thumbnail.as_ref().is_some_and(|thumbnail| thumbnail.blurred),
+            hdr: thumbnail.as_ref().and_then(|thumbnail| thumbnail.hdr),
+            thumbnail: thumbnail.map(|thumbnail| thumbnail.path.to_string_lossy().to_string()),
+            folder: path,
+            image,
+            pinned,
+        }
+    }))
+}
+
+/// フォルダの表紙を固定する（`image`がNoneなら固定を解除して自動で選び直す）
+#[tauri::command]
+pub async fn set_folder_cover(app_handle: AppHandle, path: String, image: Option<String>) -> Result<(), String> {
+    let result = CoverStore::open(&app_handle)
+        .and_then(|store| set_cover(&store, &path, image.as_deref(), |path| path_guard::guard(&app_handle, path)));
+    audit::complete(&app_handle, "set_folder_cover", &result);
+    result
+}
+
+/// 表紙を記録する（フォルダと画像は`guard`で読み取りを許可された場所か確かめる）
+fn set_cover(
+    store: &CoverStore,
+    folder: &str,
+    image: Option<&str>,
+    guard: impl Fn(&str) -> Result<PathBuf, String>,
+) -> Result<(), String> {
+    if !guard(folder)?.is_dir() {
+        return Err(t!("path.not_directory", folder));
+    }
+    match image {
+        Some(image) => {
+            let file = guard(image)?;
+            if !file.is_file() || !image::is_image_file(&file) {
+                return Err(t!("path.not_found", image));
+            }
+            store.set(folder, image, true)
+        },
+        None => store.remove(folder),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cover_store_round_trip() {
+        let path = std::env::temp_dir().join(format!("poir-cover-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let store = CoverStore::open_at(&path).unwrap();
+
+        assert_eq!(store.get("/photos").unwrap(), None);
+        store.set("/photos", "/photos/a.jpg", false).unwrap();
+        store.set("/photos", "/photos/b.jpg", true).unwrap();
+        assert_eq!(store.get("/photos").unwrap(), Some(("/photos/b.jpg".to_string(), true)));
+        store.remove("/photos").unwrap();
+        assert_eq!(store.get("/photos").unwrap(), None);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_set_cover_rejects_image_outside_roots() {
+        let root = std::env::temp_dir().join(format!("poir-cover-guard-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("library")).unwrap();
+        fs::create_dir_all(root.join("outside")).unwrap();
+        fs::write(root.join("library/a.jpg"), b"a").unwrap();
+        fs::write(root.join("outside/secret.jpg"), b"s").unwrap();
+        let store = CoverStore::open_at(&root.join("cover.db")).unwrap();
+        let roots = vec![fs::canonicalize(root.join("library")).unwrap()];
+        let guard = |path: &str| path_guard::guard_within(path, &roots);
+
+        let folder = root.join("library").to_string_lossy().to_string();
+        let inside = root.join("library/a.jpg").to_string_lossy().to_string();
+        let outside = root.join("outside/secret.jpg").to_string_lossy().to_string();
+        let escaped = root.join("library/../outside/secret.jpg").to_string_lossy().to_string();
+        assert!(set_cover(&store, &folder, Some(&outside), guard).is_err());
+        assert!(set_cover(&store, &folder, Some(&escaped), guard).is_err());
+        assert!(set_cover(&store, &root.join("outside").to_string_lossy(), None, guard).is_err());
+        assert_eq!(store.get(&folder).unwrap(), None);
+
+        set_cover(&store, &folder, Some(&inside), guard).unwrap();
+        assert_eq!(store.get(&folder).unwrap(), Some((inside, true)));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
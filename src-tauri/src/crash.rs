@@ -0,0 +1,230 @@
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit::{self, CommandRecord};
+use crate::config::{self, ResourceConfig};
+use crate::i18n::t;
+use crate::logging;
+use crate::privacy;
+
+/// クラッシュレポートのファイル名
+const REPORT_FILE: &str = "last_crash.json";
+
+/// 実行中であることを示すマーカーファイル名（正常終了時に削除する）
+const SESSION_MARKER_FILE: &str = "session.lock";
+
+/// レポートに含める直近ログの行数
+const RECENT_LOG_LINES: usize = 100;
+
+/// レポートに含める直近のコマンド呼び出しの件数
+const RECENT_COMMANDS: usize = 20;
+
+/// クラッシュ時点の設定の概要
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfigSnapshot {
+    pub id: String,
+    pub name: String,
+    /// 画像フォルダ（プライバシーモード中は空）
+    pub include: Vec<String>,
+    /// 画像フォルダ数
+    #[serde(default)]
+    pub include_count: usize,
+    /// 除外パターン数
+    pub exclude_count: usize,
+}
+
+/// 前回セッションの異常終了に関するレポート
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CrashReport {
+    /// 発生日時（Unix時間）
+    pub timestamp: u64,
+    /// アプリのバージョン
+    pub app_version: String,
+    /// パニックのメッセージ（パニック以外の異常終了ではNone）
+    pub message: Option<String>,
+    /// 発生箇所
+    pub location: Option<String>,
+    /// スレッド名
+    pub thread: Option<String>,
+    /// バックトレース
+    pub backtrace: Option<String>,
+    /// 直前のログ
+    pub recent_logs: Vec<String>,
+    /// 直前のコマンド呼び出し（古い順）
+    #[serde(default)]
+    pub recent_commands: Vec<CommandRecord>,
+    /// 設定の概要
+    pub config: Option<ConfigSnapshot>,
+}
+
+/// クラッシュレポートの保存先ディレクトリを取得する
+pub fn get_crash_dir(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("crash")
+}
+
+/// 現在時刻をUnix時間で取得する
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 設定の概要を作成する（`redact_paths`ではフォルダのパスを含めない）
+fn summarize_config(config: ResourceConfig, redact_paths: bool) -> ConfigSnapshot {
+    let include_count = config.filters.include.len();
+    ConfigSnapshot {
+        id: config.id,
+        name: config.name,
+        include: if redact_paths { Vec::new() } else { config.filters.include },
+        include_count,
+        exclude_count: config.filters.exclude.len(),
+    }
+}
+
+/// 設定ファイルから概要を作成する
+fn snapshot_config(config_path: &Path, redact_paths: bool) -> Option<ConfigSnapshot> {
+    let content = fs::read_to_string(config_path).ok()?;
+    let config: ResourceConfig = serde_json::from_str(&content).ok()?;
+    Some(summarize_config(config, redact_paths))
+}
+
+/// レポートを書き出す
+fn write_report(crash_dir: &Path, report: &CrashReport) -> Result<(), String> {
+    fs::create_dir_all(crash_dir)
+        .map_err(|e| t!("common.dir_create_failed", crash_dir.display(), e))?;
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| t!("crash.serialize_failed", e))?;
+    fs::write(crash_dir.join(REPORT_FILE), json)
+        .map_err(|e| t!("crash.save_failed", e))
+}
+
+/// 情報を集めてレポートを作成する
+///
+/// プライバシーモード中は設定のフォルダとコマンド引数のパスを伏せる
+fn build_report(app_handle: &AppHandle, log_dir: &Path, config_path: &Path) -> CrashReport {
+    CrashReport {
+        timestamp: now(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        message: None,
+        location: None,
+        thread: None,
+        backtrace: None,
+        recent_logs: logging::read_recent_lines(log_dir, RECENT_LOG_LINES).unwrap_or_default(),
+        recent_commands: audit::recent_for_report(app_handle, RECENT_COMMANDS),
+        config: snapshot_config(config_path, privacy::is_enabled(app_handle)),
+    }
+}
+
+/// パニック時にクラッシュレポートを書き出すフックを登録する
+///
+/// 既存のフック（標準エラーへの出力）はそのまま呼び出す
+pub fn install_panic_hook(app_handle: &AppHandle) {
+    let crash_dir = get_crash_dir(app_handle);
+    let log_dir = logging::get_log_dir(app_handle);
+    let config_path = ResourceConfig::get_config_path(app_handle);
+    let app_handle = app_handle.clone();
+    let previous_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let message = info.payload().downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| t!("crash.unknown_panic"));
+
+        tracing::error!("パニックが発生しました: {}", message);
+
+        let report = CrashReport {
+            message: Some(message),
+            location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            thread: std::thread::current().name().map(|name| name.to_string()),
+            backtrace: Some(std::backtrace::Backtrace::force_capture().to_string()),
+            ..build_report(&app_handle, &log_dir, &config_path)
+        };
+        let _ = write_report(&crash_dir, &report);
+
+        previous_hook(info);
+    }));
+}
+
+/// セッション開始を記録する
+///
+/// 前回のマーカーが残っていれば（パニック以外の）異常終了とみなし、
+/// パニックのレポートがない場合に限り簡易レポートを作成する
+pub fn begin_session(app_handle: &AppHandle) -> Result<(), String> {
+    let crash_dir = get_crash_dir(app_handle);
+    let marker = crash_dir.join(SESSION_MARKER_FILE);
+
+    if marker.exists() && !crash_dir.join(REPORT_FILE).exists() {
+        tracing::warn!("前回のセッションは正常に終了しませんでした");
+        let report = build_report(
+            app_handle,
+            &logging::get_log_dir(app_handle),
+            &ResourceConfig::get_config_path(app_handle),
+        );
+        write_report(&crash_dir, &report)?;
+    }
+
+    fs::create_dir_all(&crash_dir)
+        .map_err(|e| t!("common.dir_create_failed", crash_dir.display(), e))?;
+    fs::write(&marker, now().to_string())
+        .map_err(|e| t!("crash.session_failed", e))
+}
+
+/// 正常終了を記録する
+pub fn end_session(app_handle: &AppHandle) {
+    let _ = fs::remove_file(get_crash_dir(app_handle).join(SESSION_MARKER_FILE));
+}
+
+/// 前回の異常終了時のクラッシュレポートを取得する（なければNone）
+#[tauri::command]
+pub fn get_last_crash_report(app_handle: AppHandle) -> Result<Option<CrashReport>, String> {
+    let path = get_crash_dir(&app_handle).join(REPORT_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| t!("crash.read_failed", e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| t!("crash.parse_failed", e))
+}
+
+/// クラッシュレポートを削除する（送信済み・確認済みの場合）
+#[tauri::command]
+pub fn clear_crash_report(app_handle: AppHandle) -> Result<(), String> {
+    let path = get_crash_dir(&app_handle).join(REPORT_FILE);
+    let result = if path.exists() {
+        fs::remove_file(&path)
+            .map_err(|e| t!("crash.delete_failed", e))
+    } else {
+        Ok(())
+    };
+    audit::complete(&app_handle, "clear_crash_report", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_redacts_paths_in_privacy_mode() {
+        let mut config = ResourceConfig::default();
+        config.filters.include = vec!["/photos".to_string(), "/private".to_string()];
+        config.filters.exclude = vec!["*.tmp".to_string()];
+
+        let snapshot = summarize_config(config.clone(), false);
+        assert_eq!(snapshot.include, vec!["/photos", "/private"]);
+        assert_eq!(snapshot.include_count, 2);
+
+        let redacted = summarize_config(config, true);
+        assert!(redacted.include.is_empty());
+        assert_eq!(redacted.include_count, 2);
+        assert_eq!(redacted.exclude_count, 1);
+        assert!(!serde_json::to_string(&redacted).unwrap().contains("/private"));
+    }
+}
@@ -0,0 +1,64 @@
+use keyring::Entry;
+use tauri::AppHandle;
+use crate::audit;
+use crate::i18n::t;
+
+/// キーチェーンに登録する際のサービス名
+const SERVICE_NAME: &str = "com.poir-viewer-app.app";
+
+/// 資格情報を識別するソースIDを検証する
+fn validate_source_id(source_id: &str) -> Result<&str, String> {
+    let source_id = source_id.trim();
+    if source_id.is_empty() || source_id.chars().any(char::is_control) {
+        return Err(t!("credentials.invalid_source", source_id));
+    }
+    Ok(source_id)
+}
+
+fn entry(source_id: &str) -> Result<Entry, String> {
+    let source_id = validate_source_id(source_id)?;
+    Entry::new(SERVICE_NAME, source_id).map_err(|e| t!("credentials.keychain_failed", e))
+}
+
+/// リモートソースの資格情報をOSのキーチェーンに保存する（resources.jsonには保存しない）
+#[tauri::command]
+pub async fn store_credential(app_handle: AppHandle, source_id: String, secret: String) -> Result<(), String> {
+    let result = entry(&source_id)
+        .and_then(|entry| entry.set_password(&secret).map_err(|e| t!("credentials.keychain_failed", e)));
+    audit::complete(&app_handle, "store_credential", &result);
+    result
+}
+
+/// キーチェーンからリモートソースの資格情報を取得する（未登録ならNone）
+#[tauri::command]
+pub async fn get_credential(source_id: String) -> Result<Option<String>, String> {
+    match entry(&source_id)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(t!("credentials.keychain_failed", e)),
+    }
+}
+
+/// キーチェーンからリモートソースの資格情報を削除する
+#[tauri::command]
+pub async fn delete_credential(app_handle: AppHandle, source_id: String) -> Result<(), String> {
+    let result = entry(&source_id).and_then(|entry| match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(t!("credentials.keychain_failed", e)),
+    });
+    audit::complete(&app_handle, "delete_credential", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_source_id() {
+        assert_eq!(validate_source_id(" webdav:nas ").unwrap(), "webdav:nas");
+        assert!(validate_source_id("").is_err());
+        assert!(validate_source_id("  ").is_err());
+        assert!(validate_source_id("s3\nbucket").is_err());
+    }
+}
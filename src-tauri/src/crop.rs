@@ -0,0 +1,138 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use ::image::codecs::jpeg::JpegEncoder;
+use ::image::{DynamicImage, GenericImageView, ImageFormat};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::i18n::t;
+use crate::path_guard;
+use crate::preview;
+use crate::safe_write::{self, WriteOptions};
+
+/// JPEGで保存する際の品質
+const JPEG_QUALITY: u8 = 95;
+
+/// 切り抜く範囲（EXIFの向きを適用した、表示上の座標）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 切り抜きの結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CropResult {
+    /// 保存先のパス
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// 保存した形式（拡張子）
+    pub format: String,
+    /// 再圧縮せずに切り抜いたか（JPEGも展開して保存し直すため、PNG等の可逆形式のみtrue）
+    pub lossless: bool,
+}
+
+/// 保存形式を名前（拡張子）から決める
+pub(crate) fn parse_format(name: &str) -> Option<ImageFormat> {
+    match name.to_lowercase().as_str() {
+        "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+        "png" => Some(ImageFormat::Png),
+        "webp" => Some(ImageFormat::WebP),
+        "bmp" => Some(ImageFormat::Bmp),
+        _ => None,
+    }
+}
+
+/// 範囲が画像内に収まっているか確認する
+pub(crate) fn validate_rect(rect: &CropRect, width: u32, height: u32) -> Result<(), String> {
+    let fits = rect.width > 0
+        && rect.height > 0
+        && rect.x.checked_add(rect.width).is_some_and(|right| right <= width)
+        && rect.y.checked_add(rect.height).is_some_and(|bottom| bottom <= height);
+    if fits {
+        Ok(())
+    } else {
+        Err(t!("crop.invalid_rect", rect.x, rect.y, rect.width, rect.height, width, height))
+    }
+}
+
+/// 画像を保存する（JPEGは品質を指定し、透過は白背景にせずRGBに変換する）
+pub fn save_as(image: &DynamicImage, dest: &Path, format: ImageFormat) -> Result<(), String> {
+    let result = match format {
+        ImageFormat::Jpeg => {
+            let file = File::create(dest).map_err(|e| t!("crop.save_failed", dest.display(), e))?;
+            let mut encoder = JpegEncoder::new_with_quality(BufWriter::new(file), JPEG_QUALITY);
+            encoder.encode_image(&DynamicImage::ImageRgb8(image.to_rgb8()))
+        },
+        _ => image.save_with_format(dest, format),
+    };
+    result.map_err(|e| t!("crop.save_failed", dest.display(), e))
+}
+
+fn crop(app_handle: &AppHandle, path: &str, rect: CropRect, dest_path: &str, format: Option<&str>) -> Result<CropResult, String> {
+    let source = path_guard::guard(app_handle, path)?;
+    let dest = PathBuf::from(dest_path);
+    if dest.parent().is_some_and(|parent| !parent.as_os_str().is_empty() && !parent.is_dir()) {
+        return Err(t!("path.not_directory", dest.display()));
+    }
+    // 別名保存のみとし、元ファイルは上書きしない
+    if path_guard::canonicalize(dest_path).is_ok_and(|existing| existing == source) {
+        return Err(t!("crop.overwrite_source", dest_path));
+    }
+
+    let format_name = format
+        .map(str::to_string)
+        .or_else(|| dest.extension().and_then(|ext| ext.to_str()).map(str::to_string))
+        .unwrap_or_default();
+    let image_format = parse_format(&format_name).ok_or_else(|| t!("crop.unsupported_format", format_name))?;
+
+    let (image, _) = preview::open_oriented(app_handle, &source)?;
+    let (width, height) = image.dimensions();
+    validate_rect(&rect, width, height)?;
+    let cropped = image.crop_imm(rect.x, rect.y, rect.width, rect.height);
+    safe_write::write_with(&dest, WriteOptions::default(), |temp| save_as(&cropped, temp, image_format))?;
+
+    tracing::info!("画像を切り抜いて保存しました: {} -> {}", path, dest.display());
+    Ok(CropResult {
+        path: dest.to_string_lossy().to_string(),
+        width: cropped.width(),
+        height: cropped.height(),
+        format: image_format.extensions_str().first().unwrap_or(&"").to_string(),
+        lossless: matches!(image_format, ImageFormat::Png | ImageFormat::Bmp),
+    })
+}
+
+/// 画像を切り抜いて別名で保存する（`format`を省略すると保存先の拡張子から決める）
+#[tauri::command]
+pub async fn crop_image(
+    app_handle: AppHandle,
+    path: String,
+    rect: CropRect,
+    dest_path: String,
+    format: Option<String>,
+) -> Result<CropResult, String> {
+    let result = crop(&app_handle, &path, rect, &dest_path, format.as_deref());
+    audit::complete(&app_handle, "crop_image", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rect_and_format() {
+        assert!(validate_rect(&CropRect { x: 10, y: 10, width: 90, height: 40 }, 100, 50).is_ok());
+        assert!(validate_rect(&CropRect { x: 10, y: 10, width: 91, height: 40 }, 100, 50).is_err());
+        assert!(validate_rect(&CropRect { x: 0, y: 0, width: 0, height: 10 }, 100, 50).is_err());
+        assert!(validate_rect(&CropRect { x: u32::MAX, y: 0, width: 2, height: 1 }, 100, 50).is_err());
+
+        assert_eq!(parse_format("JPG"), Some(ImageFormat::Jpeg));
+        assert_eq!(parse_format("png"), Some(ImageFormat::Png));
+        assert_eq!(parse_format("tiff"), None);
+    }
+}
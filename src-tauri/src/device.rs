@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use percent_encoding::percent_decode_str;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::i18n::t;
+use crate::image;
+use crate::jobs::{self, JobHandle};
+use crate::path_guard;
+use crate::preview;
+use crate::watchdog::{self, Operation};
+
+/// 端末内の画像を探す深さ（`DCIM/100CANON`や`Pictures/Screenshots`まで届くよう）
+const DEVICE_SCAN_DEPTH: usize = 4;
+
+/// 接続方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceProtocol {
+    /// スマートフォン等（Media Transfer Protocol）
+    Mtp,
+    /// カメラ等（Picture Transfer Protocol）
+    Ptp,
+}
+
+/// 接続中の端末
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeviceInfo {
+    /// 端末を指定する際のID
+    pub id: String,
+    /// 表示名
+    pub name: String,
+    pub protocol: DeviceProtocol,
+}
+
+/// 端末内の画像
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeviceImage {
+    /// 端末内のパス（`/`区切り）
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub modified: u64,
+}
+
+/// 端末からの取り込みの結果
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct DeviceImportReport {
+    /// 取り込んだファイルのパス
+    pub imported: Vec<String>,
+    /// 取り込み済み（同じ名前・サイズのファイルがある）のため飛ばした画像
+    pub skipped: Vec<String>,
+    /// 失敗した画像とその理由
+    pub failed: Vec<(String, String)>,
+}
+
+/// 端末のファイルシステムを公開するgvfsのマウント先（Linuxのみ）
+///
+/// WindowsのWPD・macOSのImage Captureはパスとして公開されないため、現状は端末を列挙しない
+fn gvfs_dir() -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    std::env::var_os("XDG_RUNTIME_DIR").map(|dir| PathBuf::from(dir).join("gvfs"))
+}
+
+/// gvfsのマウント名（`mtp:host=Google_Pixel_7_XXXX`など）から端末の情報を作る
+fn parse_mount_name(name: &str) -> Option<DeviceInfo> {
+    let (protocol, host) = if let Some(host) = name.strip_prefix("mtp:host=") {
+        (DeviceProtocol::Mtp, host)
+    } else if let Some(host) = name.strip_prefix("gphoto2:host=") {
+        (DeviceProtocol::Ptp, host)
+    } else {
+        return None;
+    };
+    let host = percent_decode_str(host).decode_utf8_lossy();
+    // gphoto2はUSBの位置（`usb:001,005`）になるため角括弧を外す
+    let display = host.trim_matches(|c| c == '[' || c == ']').replace('_', " ");
+    Some(DeviceInfo { id: name.to_string(), name: display, protocol })
+}
+
+/// 接続中の端末を列挙する
+fn devices() -> Vec<DeviceInfo> {
+    let Some(dir) = gvfs_dir() else { return Vec::new() };
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    entries.flatten()
+        .filter_map(|entry| parse_mount_name(&entry.file_name().to_string_lossy()))
+        .collect()
+}
+
+/// 端末のルートフォルダを取得する
+fn device_root(device: &str) -> Result<PathBuf, String> {
+    devices().iter()
+        .find(|info| info.id == device)
+        .and_then(|info| gvfs_dir().map(|dir| dir.join(&info.id)))
+        .ok_or_else(|| t!("device.not_found", device))
+}
+
+/// 端末内のパスを実際のパスにする（端末の外を指すパスは拒否する）
+fn resolve(root: &Path, path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative.components().any(|component| !matches!(component, Component::Normal(_))) {
+        return Err(t!("device.invalid_path", path));
+    }
+    Ok(root.join(relative))
+}
+
+/// 端末内の画像を一覧にする（パスは端末内のパスにする）
+fn list_images(root: &Path) -> Result<Vec<DeviceImage>, String> {
+    Ok(image::list_folder_images(root, DEVICE_SCAN_DEPTH)?
+        .into_iter()
+        .filter_map(|info| {
+            let relative = Path::new(&info.path).strip_prefix(root).ok()?;
+            let path = relative.components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some(DeviceImage { path, name: info.name, size: info.size, modified: info.modified })
+        })
+        .collect())
+}
+
+/// 取り込み先に同じ名前があれば番号を付けた名前にする（同じサイズなら取り込み済みとしてNone）
+fn import_dest(dest_dir: &Path, name: &str, size: u64) -> Option<PathBuf> {
+    let path = Path::new(name);
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let mut candidate = dest_dir.join(name);
+    let mut number = 1;
+    while let Ok(metadata) = fs::metadata(&candidate) {
+        if metadata.len() == size {
+            return None;
+        }
+        candidate = dest_dir.join(format!("{} ({}){}", stem, number, extension));
+        number += 1;
+    }
+    Some(candidate)
+}
+
+fn import(job: &JobHandle, root: &Path, paths: &[String], dest_dir: &Path) -> Result<DeviceImportReport, String> {
+    let mut report = DeviceImportReport::default();
+    for (index, path) in paths.iter().enumerate() {
+        if job.is_cancelled() {
+            break;
+        }
+        job.progress(index, Some(path));
+        let copied = resolve(root, path).and_then(|source| {
+            let name = source.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            let size = fs::metadata(&source).map_err(|e| t!("file.read_failed", source.display(), e))?.len();
+            match import_dest(dest_dir, &name, size) {
+                Some(dest) => fs::copy(&source, &dest)
+                    .map(|_| Some(dest))
+                    .map_err(|e| t!("device.copy_failed", path, e)),
+                None => Ok(None),
+            }
+        });
+        match copied {
+            Ok(Some(dest)) => report.imported.push(dest.to_string_lossy().to_string()),
+            Ok(None) => report.skipped.push(path.clone()),
+            Err(e) => report.failed.push((path.clone(), e)),
+        }
+        job.progress(index + 1, None);
+    }
+    tracing::info!("端末から取り込みました: {}枚（取り込み済み{}枚、失敗{}枚）", report.imported.len(), report.skipped.len(), report.failed.len());
+    Ok(report)
+}
+
+/// 接続中のスマートフォン・カメラを列挙する
+#[tauri::command]
+pub async fn list_devices() -> Vec<DeviceInfo> {
+    devices()
+}
+
+/// 端末内の画像を一覧にする（転送が遅い端末ではスキャンと同じ時間切れを適用する）
+#[tauri::command]
+pub async fn list_device_images(app_handle: AppHandle, device: String) -> Result<Vec<DeviceImage>, String> {
+    let root = device_root(&device)?;
+    watchdog::run(&app_handle, Operation::Scan, move || list_images(&root)).await
+}
+
+/// 端末内の画像1枚をキャッシュに取り出し、そのパスを返す（取り出し済みならそのまま返す）
+///
+/// 返したパスは通常の画像と同じくサムネイル・表示に使える
+#[tauri::command]
+pub async fn fetch_device_image(app_handle: AppHandle, device: String, path: String) -> Result<String, String> {
+    let root = device_root(&device)?;
+    // シンボリックリンクで端末の外を指していないか、正規化してから確かめる
+    let root = path_guard::canonicalize(&root.to_string_lossy())?;
+    let source = path_guard::guard_within(&resolve(&root, &path)?.to_string_lossy(), &[root])?;
+    if !image::is_image_file(&source) {
+        return Err(t!("device.invalid_path", path));
+    }
+    let extension = source.extension().and_then(|ext| ext.to_str()).unwrap_or("img").to_lowercase();
+    let cache_dir = preview::get_cache_dir(&app_handle, "devices");
+    let dest = cache_dir.join(format!("{}.{}", preview::cache_key(&source, "device"), extension));
+    if !dest.exists() {
+        fs::create_dir_all(&cache_dir).map_err(|e| t!("common.dir_create_failed", cache_dir.display(), e))?;
+        fs::copy(&source, &dest).map_err(|e| t!("device.copy_failed", path, e))?;
+    }
+    Ok(dest.to_string_lossy().to_string())
+}
+
+/// 端末内の画像をライブラリのフォルダに取り込むジョブを開始し、ジョブIDを返す
+///
+/// 同じ名前・サイズのファイルがあれば取り込み済みとして飛ばし、名前だけ同じなら番号を付ける。
+/// 進捗と結果（`DeviceImportReport`）は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn import_device_images(app_handle: AppHandle, device: String, paths: Vec<String>, dest: String) -> Result<String, String> {
+    let root = device_root(&device)?;
+    let dest_dir = path_guard::guard(&app_handle, &dest)?;
+    if !dest_dir.is_dir() {
+        return Err(t!("path.not_directory", dest));
+    }
+    jobs::spawn(&app_handle, "device_import", paths.len(), move |job| import(job, &root, &paths, &dest_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mount_name_and_resolve() {
+        let phone = parse_mount_name("mtp:host=Google_Pixel_7_%E5%86%99%E7%9C%9F").unwrap();
+        assert_eq!((phone.name.as_str(), phone.protocol), ("Google Pixel 7 写真", DeviceProtocol::Mtp));
+        let camera = parse_mount_name("gphoto2:host=%5Busb%3A001%2C005%5D").unwrap();
+        assert_eq!((camera.name.as_str(), camera.protocol), ("usb:001,005", DeviceProtocol::Ptp));
+        assert!(parse_mount_name("smb-share:server=nas,share=photos").is_none());
+
+        let root = Path::new("/gvfs/mtp:host=phone");
+        assert_eq!(resolve(root, "/DCIM/Camera/a.jpg").unwrap(), root.join("DCIM/Camera/a.jpg"));
+        assert!(resolve(root, "DCIM/../../other").is_err());
+    }
+
+    #[test]
+    fn test_import_dest_numbers_and_skips() {
+        let dir = std::env::temp_dir().join(format!("poir-device-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("IMG_0001.jpg"), b"abc").unwrap();
+
+        assert_eq!(import_dest(&dir, "IMG_0001.jpg", 3), None);
+        assert_eq!(import_dest(&dir, "IMG_0001.jpg", 5), Some(dir.join("IMG_0001 (1).jpg")));
+        assert_eq!(import_dest(&dir, "IMG_0002.jpg", 3), Some(dir.join("IMG_0002.jpg")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,252 @@
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager};
+use crate::config::{self, ResourceConfig};
+use crate::i18n::t;
+use crate::index::LibraryIndex;
+use crate::logging;
+
+/// 空き容量がこれを下回ると警告する（バイト）
+const LOW_DISK_SPACE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// 詳細に列挙する項目の上限
+const MAX_DETAILS: usize = 20;
+
+/// 診断項目の結果
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// 診断項目1件分
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DiagnosticCheck {
+    /// 項目の識別子
+    pub id: String,
+    /// 結果
+    pub status: CheckStatus,
+    /// 概要メッセージ
+    pub message: String,
+    /// 詳細（問題のあったパスなど）
+    pub details: Vec<String>,
+}
+
+/// 診断結果全体
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    /// 最も重大な結果
+    pub overall: CheckStatus,
+    /// 各項目の結果
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+impl DiagnosticCheck {
+    fn new(id: &str, status: CheckStatus, message: String) -> Self {
+        Self { id: id.to_string(), status, message, details: Vec::new() }
+    }
+
+    fn with_details(mut self, details: Vec<String>) -> Self {
+        self.details = details.into_iter().take(MAX_DETAILS).collect();
+        self
+    }
+}
+
+/// バージョンと実行環境の情報（Aboutダイアログやバグ報告用）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppInfo {
+    /// アプリのバージョン
+    pub app_version: String,
+    /// Tauriのバージョン
+    pub tauri_version: String,
+    /// WebViewのバージョン（取得できない場合はNone）
+    pub webview_version: Option<String>,
+    /// OS（windows, macos, linux等）
+    pub os: String,
+    /// OSの系統（windows, unix）
+    pub os_family: String,
+    /// CPUアーキテクチャ
+    pub arch: String,
+    /// 設定ファイルのパス
+    pub config_path: String,
+    /// アプリデータのディレクトリ
+    pub data_dir: String,
+    /// キャッシュのディレクトリ
+    pub cache_dir: Option<String>,
+    /// ログのディレクトリ
+    pub log_dir: String,
+    /// インデックスのファイルサイズ（バイト）
+    pub index_size_bytes: Option<u64>,
+    /// インデックス済みの画像数
+    pub indexed_images: Option<usize>,
+}
+
+/// 設定ファイルと各フォルダの有効性を確認する
+fn check_config(app_handle: &AppHandle) -> DiagnosticCheck {
+    let config = match ResourceConfig::load(app_handle) {
+        Ok(config) => config,
+        Err(e) => return DiagnosticCheck::new("config", CheckStatus::Error, e),
+    };
+
+    if config.filters.include.is_empty() {
+        return DiagnosticCheck::new("config", CheckStatus::Warning, t!("scan.no_folders"));
+    }
+
+    let invalid: Vec<String> = config.filters.include.iter()
+        .filter_map(|path| ResourceConfig::validate_path(path).err())
+        .collect();
+
+    if invalid.is_empty() {
+        DiagnosticCheck::new(
+            "config",
+            CheckStatus::Ok,
+            t!("diagnostics.folders_ok", config.filters.include.len()),
+        )
+    } else {
+        DiagnosticCheck::new(
+            "config",
+            CheckStatus::Error,
+            t!("diagnostics.folders_invalid", invalid.len()),
+        ).with_details(invalid)
+    }
+}
+
+/// アプリデータのディレクトリに書き込めるか確認する
+fn check_app_data_writable(app_dir: &Path) -> DiagnosticCheck {
+    let probe = app_dir.join(".diagnostics_probe");
+    let result = fs::create_dir_all(app_dir)
+        .and_then(|_| fs::write(&probe, b"ok"))
+        .and_then(|_| fs::remove_file(&probe));
+
+    match result {
+        Ok(()) => DiagnosticCheck::new(
+            "app_data",
+            CheckStatus::Ok,
+            t!("diagnostics.app_data_writable", app_dir.display()),
+        ),
+        Err(e) => DiagnosticCheck::new(
+            "app_data",
+            CheckStatus::Error,
+            t!("diagnostics.app_data_not_writable", app_dir.display(), e),
+        ),
+    }
+}
+
+/// インデックスのデータベースが壊れていないか確認する
+fn check_index_integrity(index: &LibraryIndex) -> DiagnosticCheck {
+    match index.integrity_check() {
+        Ok(problems) if problems.is_empty() => {
+            DiagnosticCheck::new("index_integrity", CheckStatus::Ok, t!("diagnostics.index_ok"))
+        },
+        Ok(problems) => DiagnosticCheck::new(
+            "index_integrity",
+            CheckStatus::Error,
+            t!("diagnostics.index_corrupted"),
+        ).with_details(problems),
+        Err(e) => DiagnosticCheck::new("index_integrity", CheckStatus::Error, e),
+    }
+}
+
+/// インデックスの内容が実際のファイルと一致しているか確認する
+fn check_index_consistency(index: &LibraryIndex) -> DiagnosticCheck {
+    let images = match index.all_images() {
+        Ok(images) => images,
+        Err(e) => return DiagnosticCheck::new("index_consistency", CheckStatus::Error, e),
+    };
+
+    let missing: Vec<String> = images.iter()
+        .filter(|image| !Path::new(&image.path).is_file())
+        .map(|image| image.path.clone())
+        .collect();
+
+    if missing.is_empty() {
+        DiagnosticCheck::new(
+            "index_consistency",
+            CheckStatus::Ok,
+            t!("diagnostics.index_consistent", images.len()),
+        )
+    } else {
+        DiagnosticCheck::new(
+            "index_consistency",
+            CheckStatus::Warning,
+            t!("diagnostics.index_missing", images.len(), missing.len()),
+        ).with_details(missing)
+    }
+}
+
+/// アプリデータのあるドライブの空き容量を確認する
+fn check_disk_space(app_dir: &Path) -> DiagnosticCheck {
+    match fs2::available_space(app_dir) {
+        Ok(available) => {
+            let megabytes = available / (1024 * 1024);
+            if available < LOW_DISK_SPACE_BYTES {
+                DiagnosticCheck::new(
+                    "disk_space",
+                    CheckStatus::Warning,
+                    t!("diagnostics.disk_low", megabytes),
+                )
+            } else {
+                DiagnosticCheck::new("disk_space", CheckStatus::Ok, t!("diagnostics.disk_ok", megabytes))
+            }
+        },
+        Err(e) => DiagnosticCheck::new(
+            "disk_space",
+            CheckStatus::Warning,
+            t!("diagnostics.disk_unknown", e),
+        ),
+    }
+}
+
+/// バージョンと実行環境の情報を取得する
+#[tauri::command]
+pub fn get_app_info(app_handle: AppHandle) -> AppInfo {
+    let index_path = LibraryIndex::get_index_path(&app_handle);
+    // 情報を表示するだけでインデックスを作成・更新しないよう、読み取り専用で開く
+    let indexed_images = LibraryIndex::open_read_only(&index_path)
+        .and_then(|index| index.count())
+        .ok();
+
+    AppInfo {
+        app_version: app_handle.package_info().version.to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        webview_version: tauri::webview_version().ok(),
+        os: std::env::consts::OS.to_string(),
+        os_family: std::env::consts::FAMILY.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        config_path: ResourceConfig::get_config_path(&app_handle).to_string_lossy().to_string(),
+        data_dir: config::app_data_dir(&app_handle).to_string_lossy().to_string(),
+        cache_dir: app_handle.path().app_cache_dir().ok().map(|dir| dir.to_string_lossy().to_string()),
+        log_dir: logging::get_log_dir(&app_handle).to_string_lossy().to_string(),
+        index_size_bytes: fs::metadata(&index_path).ok().map(|metadata| metadata.len()),
+        indexed_images,
+    }
+}
+
+/// トラブルシューティング用の診断を実行する
+#[tauri::command]
+pub async fn run_diagnostics(app_handle: AppHandle) -> Result<DiagnosticsReport, String> {
+    let app_dir = config::app_data_dir(&app_handle);
+
+    let mut checks = vec![
+        check_config(&app_handle),
+        check_app_data_writable(&app_dir),
+    ];
+
+    match LibraryIndex::open(&app_handle) {
+        Ok(index) => {
+            checks.push(check_index_integrity(&index));
+            checks.push(check_index_consistency(&index));
+        },
+        Err(e) => checks.push(DiagnosticCheck::new("index_integrity", CheckStatus::Error, e)),
+    }
+
+    checks.push(check_disk_space(&app_dir));
+
+    let overall = checks.iter().map(|check| check.status).max().unwrap_or(CheckStatus::Ok);
+    tracing::info!("診断を実行しました: {:?}", overall);
+
+    Ok(DiagnosticsReport { overall, checks })
+}
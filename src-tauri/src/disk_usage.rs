@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::i18n::t;
+use crate::image;
+use crate::long_path;
+use crate::path_guard;
+use crate::watchdog::{self, Operation};
+
+/// ファイルの容量の集計
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct UsageTotals {
+    /// 全ファイルの合計バイト数
+    pub bytes: u64,
+    /// 画像の合計バイト数
+    pub image_bytes: u64,
+    pub files: usize,
+    pub images: usize,
+}
+
+impl UsageTotals {
+    fn add(&mut self, bytes: u64, is_image: bool) {
+        self.bytes += bytes;
+        self.files += 1;
+        if is_image {
+            self.image_bytes += bytes;
+            self.images += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &UsageTotals) {
+        self.bytes += other.bytes;
+        self.image_bytes += other.image_bytes;
+        self.files += other.files;
+        self.images += other.images;
+    }
+}
+
+/// 直下のサブフォルダごとの容量（サブフォルダ内をすべて含む）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FolderUsage {
+    pub path: String,
+    pub name: String,
+    pub totals: UsageTotals,
+}
+
+/// 拡張子ごとの容量
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExtensionUsage {
+    /// 小文字の拡張子（拡張子なしは空文字）
+    pub extension: String,
+    /// 画像として扱う拡張子か
+    pub image: bool,
+    pub bytes: u64,
+    pub files: usize,
+}
+
+/// フォルダの容量の内訳
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DiskUsage {
+    pub path: String,
+    /// フォルダ全体
+    pub totals: UsageTotals,
+    /// フォルダ直下のファイルのみ
+    pub direct: UsageTotals,
+    /// 直下のサブフォルダ（大きい順）
+    pub subfolders: Vec<FolderUsage>,
+    /// 拡張子ごと（大きい順）
+    pub extensions: Vec<ExtensionUsage>,
+    /// 読み取れなかったファイル・フォルダの数
+    pub unreadable: usize,
+}
+
+#[derive(Default)]
+struct Walker {
+    extensions: HashMap<String, ExtensionUsage>,
+    unreadable: usize,
+}
+
+impl Walker {
+    /// フォルダ内を再帰的に集計する（シンボリックリンクはたどらない）
+    fn walk(&mut self, dir: &Path) -> UsageTotals {
+        let mut totals = UsageTotals::default();
+        let entries = match fs::read_dir(long_path::extended(dir)) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("{}", t!("common.dir_read_failed", dir.display(), e));
+                self.unreadable += 1;
+                return totals;
+            },
+        };
+        for entry in entries {
+            let Ok(entry) = entry else {
+                self.unreadable += 1;
+                continue;
+            };
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => {
+                    let sub = self.walk(&path);
+                    totals.merge(&sub);
+                },
+                Ok(kind) if kind.is_file() => match entry.metadata() {
+                    Ok(metadata) => totals.add(self.add_file(&path, metadata.len()), image::is_image_file(&path)),
+                    Err(_) => self.unreadable += 1,
+                },
+                Ok(_) => {},
+                Err(_) => self.unreadable += 1,
+            }
+        }
+        totals
+    }
+
+    fn add_file(&mut self, path: &Path, bytes: u64) -> u64 {
+        let extension = path.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let usage = self.extensions.entry(extension.clone()).or_insert_with(|| ExtensionUsage {
+            extension,
+            image: image::is_image_file(path),
+            bytes: 0,
+            files: 0,
+        });
+        usage.bytes += bytes;
+        usage.files += 1;
+        bytes
+    }
+}
+
+/// フォルダの容量を直下のサブフォルダごと・拡張子ごとに集計する
+fn measure(dir: &Path) -> Result<DiskUsage, String> {
+    let entries = fs::read_dir(long_path::extended(dir))
+        .map_err(|e| t!("common.dir_read_failed", dir.display(), e))?;
+
+    let mut walker = Walker::default();
+    let mut direct = UsageTotals::default();
+    let mut subfolders = Vec::new();
+    for entry in entries {
+        let Ok(entry) = entry else {
+            walker.unreadable += 1;
+            continue;
+        };
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => subfolders.push(FolderUsage {
+                path: long_path::strip_extended(&path.to_string_lossy()),
+                name: entry.file_name().to_string_lossy().to_string(),
+                totals: walker.walk(&path),
+            }),
+            Ok(kind) if kind.is_file() => match entry.metadata() {
+                Ok(metadata) => direct.add(walker.add_file(&path, metadata.len()), image::is_image_file(&path)),
+                Err(_) => walker.unreadable += 1,
+            },
+            Ok(_) => {},
+            Err(_) => walker.unreadable += 1,
+        }
+    }
+
+    let mut totals = direct.clone();
+    for folder in &subfolders {
+        totals.merge(&folder.totals);
+    }
+    subfolders.sort_by(|a, b| b.totals.bytes.cmp(&a.totals.bytes).then_with(|| a.name.cmp(&b.name)));
+    let mut extensions: Vec<ExtensionUsage> = walker.extensions.into_values().collect();
+    extensions.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.extension.cmp(&b.extension)));
+
+    Ok(DiskUsage {
+        path: dir.to_string_lossy().to_string(),
+        totals,
+        direct,
+        subfolders,
+        extensions,
+        unreadable: walker.unreadable,
+    })
+}
+
+/// フォルダの容量の内訳を取得する（写真のドライブで何が容量を使っているかを調べるため）
+///
+/// 時間がかかるドライブではスキャンと同じ時間切れを適用する
+#[tauri::command]
+pub async fn get_disk_usage(app_handle: AppHandle, path: String) -> Result<DiskUsage, String> {
+    let dir = path_guard::guard(&app_handle, &path)?;
+    if !dir.is_dir() {
+        return Err(t!("path.not_directory", path));
+    }
+    watchdog::run(&app_handle, Operation::Scan, move || measure(&dir)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_by_subfolder_and_extension() {
+        let dir = std::env::temp_dir().join(format!("poir-disk-usage-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("photos/2024")).unwrap();
+        fs::create_dir_all(dir.join("videos")).unwrap();
+        fs::write(dir.join("notes.txt"), vec![0; 10]).unwrap();
+        fs::write(dir.join("photos/a.JPG"), vec![0; 100]).unwrap();
+        fs::write(dir.join("photos/2024/b.png"), vec![0; 200]).unwrap();
+        fs::write(dir.join("videos/c.mp4"), vec![0; 1000]).unwrap();
+
+        let usage = measure(&dir).unwrap();
+        assert_eq!(usage.totals, UsageTotals { bytes: 1310, image_bytes: 300, files: 4, images: 2 });
+        assert_eq!(usage.direct, UsageTotals { bytes: 10, image_bytes: 0, files: 1, images: 0 });
+        let names: Vec<&str> = usage.subfolders.iter().map(|folder| folder.name.as_str()).collect();
+        assert_eq!(names, vec!["videos", "photos"]);
+        assert_eq!(usage.subfolders[1].totals.image_bytes, 300);
+        let jpg = usage.extensions.iter().find(|ext| ext.extension == "jpg").unwrap();
+        assert!(jpg.image && jpg.bytes == 100);
+        assert_eq!(usage.extensions[0].extension, "mp4");
+        assert!(!usage.extensions[0].image);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
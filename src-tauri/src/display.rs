@@ -0,0 +1,113 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::i18n::t;
+
+/// HDRの判定で読むファイル先頭のバイト数（色の情報はヘッダー内にある）
+const HDR_PROBE_BYTES: u64 = 64 * 1024;
+
+/// HDRの伝達特性
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HdrTransfer {
+    /// PQ（SMPTE ST 2084、HDR10など）
+    Pq,
+    /// HLG（ARIB STD-B67）
+    Hlg,
+}
+
+impl HdrTransfer {
+    /// ITU-T H.273の伝達特性の値から判定する（HDR以外はNone）
+    fn from_code(code: u16) -> Option<Self> {
+        match code {
+            16 => Some(HdrTransfer::Pq),
+            18 => Some(HdrTransfer::Hlg),
+            _ => None,
+        }
+    }
+}
+
+/// モニターの表示能力
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DisplayCapabilities {
+    pub name: Option<String>,
+    /// 表示領域（物理ピクセル）
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    /// 色成分あたりのビット数（OSから取得できなければNone）
+    pub bit_depth: Option<u32>,
+    /// HDRで表示できるか（OSから取得できなければNone）
+    pub hdr: Option<bool>,
+}
+
+/// `needle`の直後から`len`バイトを返す
+fn after<'a>(data: &'a [u8], needle: &[u8], len: usize) -> Option<&'a [u8]> {
+    let start = data.windows(needle.len()).position(|window| window == needle)? + needle.len();
+    data.get(start..start + len)
+}
+
+/// ファイル先頭のバイト列から伝達特性を調べる
+///
+/// PNGは`cICP`チャンク、AVIF・HEICは`colr`ボックスの`nclx`を見る（JPEG XLの色の情報は未対応）
+fn detect_transfer(header: &[u8]) -> Option<HdrTransfer> {
+    if header.starts_with(b"\x89PNG") {
+        // 色域・伝達特性・行列・範囲の各1バイト
+        let cicp = after(header, b"cICP", 4)?;
+        return HdrTransfer::from_code(cicp[1] as u16);
+    }
+    // 色域・伝達特性・行列の各2バイト
+    let nclx = after(header, b"colrnclx", 6)?;
+    HdrTransfer::from_code(u16::from_be_bytes([nclx[2], nclx[3]]))
+}
+
+/// 画像がHDRか調べ、伝達特性を返す（SDRや判定できない場合はNone）
+pub fn detect_hdr(path: &Path) -> Option<HdrTransfer> {
+    let mut header = Vec::new();
+    File::open(path).ok()?.take(HDR_PROBE_BYTES).read_to_end(&mut header).ok()?;
+    detect_transfer(&header)
+}
+
+/// モニターごとの解像度・拡大率・色深度・HDR対応を取得する（トーンマッピングの要否の判断用）
+///
+/// 色深度とHDR対応はOSのAPIから取得できる環境でのみ返す
+#[tauri::command]
+pub fn get_display_capabilities(app_handle: AppHandle) -> Result<Vec<DisplayCapabilities>, String> {
+    let monitors = app_handle.available_monitors().map_err(|e| t!("slideshow.monitors_failed", e))?;
+    Ok(monitors.iter().map(|monitor| DisplayCapabilities {
+        name: monitor.name().cloned(),
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+        scale_factor: monitor.scale_factor(),
+        bit_depth: None,
+        hdr: None,
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_transfer() {
+        let png = |transfer: u8| [b"\x89PNG\r\n\x1a\n".as_slice(), b"\0\0\0\x04cICP", &[9, transfer, 0, 1]].concat();
+        assert_eq!(detect_transfer(&png(16)), Some(HdrTransfer::Pq));
+        assert_eq!(detect_transfer(&png(13)), None);
+
+        let avif = |transfer: u16| {
+            let mut data = b"\0\0\0\x1cftypavif\0\0\0\0\0\0\0\x13colrnclx\0\x09".to_vec();
+            data.extend(transfer.to_be_bytes());
+            data.extend([0, 0, 0x80]);
+            data
+        };
+        assert_eq!(detect_transfer(&avif(18)), Some(HdrTransfer::Hlg));
+        assert_eq!(detect_transfer(&avif(1)), None);
+        assert_eq!(detect_transfer(b"\xff\xd8\xff\xe0"), None);
+    }
+}
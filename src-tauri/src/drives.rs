@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager};
+use crate::config::ResourceConfig;
+use crate::event_bridge::{self, Delivery};
+use crate::index::LibraryIndex;
+use crate::long_path;
+use crate::power::{self, BackgroundMode};
+
+/// 接続状態を確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// リムーバブルメディアの設定フォルダごとの接続状態と、インデックスに記録されたフォルダのパス
+#[derive(Default)]
+pub struct DriveState(Mutex<HashMap<String, (bool, String)>>);
+
+/// 接続状態の変化を通知する内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriveEvent {
+    /// 設定フォルダのパス
+    pub path: String,
+    /// インデックスで利用可能・利用不可にした画像数
+    pub images: usize,
+}
+
+/// パスがリムーバブルメディア上にあると思われるか（マウント先から判定する）
+///
+/// Linuxは`/media`・`/run/media`・`/mnt`、macOSは`/Volumes`、Windowsはシステムドライブ以外のドライブ
+fn is_removable_path(path: &str, system_drive: &str) -> bool {
+    const MOUNT_ROOTS: [&str; 4] = ["/media/", "/run/media/", "/mnt/", "/Volumes/"];
+    if MOUNT_ROOTS.iter().any(|root| path.starts_with(root)) {
+        return true;
+    }
+    let path = long_path::strip_extended(path);
+    match path.as_bytes() {
+        [letter, b':', ..] if letter.is_ascii_alphabetic() => {
+            !path[..2].eq_ignore_ascii_case(system_drive)
+        },
+        _ => false,
+    }
+}
+
+/// パスがリムーバブルメディア上にあると思われるか
+pub fn is_removable(path: &str) -> bool {
+    let system_drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+    is_removable_path(path, &system_drive)
+}
+
+/// 接続中のリムーバブルメディアのマウント先を列挙する
+///
+/// Linuxは`/media`・`/run/media`（ユーザーごとの下位フォルダも含む）と`/mnt`、macOSは`/Volumes`、
+/// Windowsはシステムドライブ以外のドライブ
+pub fn mounted_volumes() -> Vec<PathBuf> {
+    let subdirs = |dir: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect())
+            .unwrap_or_default()
+    };
+    let mut volumes = Vec::new();
+    for root in ["/media", "/run/media"] {
+        // `/media/<ユーザー>/<ボリューム>`の形式と`/media/<ボリューム>`の形式がある
+        for dir in subdirs(Path::new(root)) {
+            let inner = subdirs(&dir);
+            if inner.is_empty() {
+                volumes.push(dir);
+            } else {
+                volumes.extend(inner);
+            }
+        }
+    }
+    volumes.extend(subdirs(Path::new("/mnt")));
+    volumes.extend(subdirs(Path::new("/Volumes")));
+    if cfg!(windows) {
+        volumes.extend(('A'..='Z')
+            .map(|letter| format!(r"{}:\", letter))
+            .filter(|drive| is_removable(drive) && Path::new(drive).is_dir())
+            .map(PathBuf::from));
+    }
+    volumes
+}
+
+/// 接続状態の変化をインデックスに反映して通知する（`folder`はインデックスに記録されたパス）
+fn apply_change(app_handle: &AppHandle, path: &str, folder: &str, connected: bool) {
+    let images = LibraryIndex::open(app_handle)
+        .and_then(|index| index.set_folder_available(folder, connected))
+        .map_err(|e| tracing::warn!("インデックスの更新に失敗しました: {}", e))
+        .unwrap_or(0);
+
+    let event = if connected { "drive-connected" } else { "drive-disconnected" };
+    tracing::info!("{}: {} ({}件)", event, path, images);
+    event_bridge::emit(app_handle, event, Delivery::Batch, DriveEvent { path: path.to_string(), images });
+}
+
+/// リムーバブルメディア上の設定フォルダの接続状態を確認する
+fn check(app_handle: &AppHandle, state: &DriveState) {
+    let Ok(config) = ResourceConfig::load(app_handle) else { return };
+    let mut known = state.0.lock().unwrap_or_else(PoisonError::into_inner);
+    known.retain(|path, _| config.filters.include.contains(path));
+
+    for dir in config.filters.include.iter().filter(|dir| is_removable(dir)) {
+        let connected = long_path::extended(Path::new(dir)).is_dir();
+        // インデックスにはスキャン時に解決したパスで記録されている（取り外した後は解決できないため前回の値を使う）
+        let folder = match known.get(dir) {
+            Some((_, folder)) if !connected => folder.clone(),
+            _ => long_path::resolve(Path::new(dir)).to_string_lossy().to_string(),
+        };
+        // 初回は状態を記録するだけ（起動時点で外れていれば利用不可にはする）
+        match known.insert(dir.clone(), (connected, folder.clone())) {
+            Some((previous, _)) if previous != connected => apply_change(app_handle, dir, &folder, connected),
+            None if !connected => apply_change(app_handle, dir, &folder, false),
+            _ => {},
+        }
+    }
+}
+
+/// 取り外し・再接続を監視する
+pub fn start_monitor(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        // バッテリー駆動中で止める設定なら確認しない
+        if let Some(state) = app_handle.try_state::<DriveState>().filter(|_| power::background_mode() != BackgroundMode::Paused) {
+            check(&app_handle, &state);
+        }
+        std::thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_removable_path() {
+        assert!(is_removable_path("/media/user/SDCARD/DCIM", "C:"));
+        assert!(is_removable_path("/Volumes/USB/写真", "C:"));
+        assert!(!is_removable_path("/home/user/Pictures", "C:"));
+        assert!(is_removable_path(r"E:\DCIM", "C:"));
+        assert!(is_removable_path(r"\\?\E:\DCIM", "C:"));
+        assert!(!is_removable_path(r"c:\Users\写真", "C:"));
+    }
+}
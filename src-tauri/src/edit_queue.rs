@@ -0,0 +1,295 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use ::image::{DynamicImage, GenericImageView, ImageFormat};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager, State};
+use crate::crop::{self, CropRect};
+use crate::event_bridge::{self, Delivery};
+use crate::exif_edit::{self, ExifChanges};
+use crate::exif_info;
+use crate::i18n::t;
+use crate::image;
+use crate::index::LibraryIndex;
+use crate::jobs::{self, JobHandle};
+use crate::originals::{self, OriginalsStore};
+use crate::path_guard;
+use crate::preview;
+use crate::safe_write::{self, WriteOptions};
+use crate::settings::AppSettings;
+
+/// 1枚分の保留中の編集
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PendingEdit {
+    pub path: String,
+    /// 時計回りに回す角度（0・90・180・270）
+    pub rotation: u32,
+    /// 切り抜く範囲（EXIFの向きと保留中の回転を適用した、表示上の座標）
+    pub crop: Option<CropRect>,
+}
+
+/// 適用した編集
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AppliedEdit {
+    pub path: String,
+    /// 再圧縮せずに適用したか（JPEGの回転のみ、またはPNG等の可逆形式）
+    pub lossless: bool,
+}
+
+/// 保留中の編集をまとめて適用した結果
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct EditApplyReport {
+    pub applied: Vec<AppliedEdit>,
+    /// 失敗した画像とその理由（失敗した編集は保留のまま残す）
+    pub failed: Vec<(String, String)>,
+}
+
+/// `pending-edits-changed`で通知する内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PendingEditsChanged {
+    /// 保留中の編集がある画像の数
+    pub count: usize,
+}
+
+/// 保留中の編集（指定した順）を保持するステート
+#[derive(Default)]
+pub struct EditQueueState(Mutex<Vec<PendingEdit>>);
+
+impl EditQueueState {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Vec<PendingEdit>>, String> {
+        self.0.lock().map_err(|e| t!("common.lock_failed", t!("edit_queue.name"), e))
+    }
+}
+
+fn notify(app_handle: &AppHandle, count: usize) {
+    event_bridge::emit(app_handle, "pending-edits-changed", Delivery::Latest, PendingEditsChanged { count });
+}
+
+/// EXIFの向き（1〜8）に時計回りの回転（90度単位）を加えた向きを返す
+fn rotate_orientation(orientation: u32, quarter_turns: u32) -> u32 {
+    // 向きを「時計回りの回転（90度単位）→左右反転」の組で表す
+    const ORIENTATIONS: [(u32, bool); 8] = [
+        (0, false), (0, true), (2, false), (2, true),
+        (1, true), (1, false), (3, true), (3, false),
+    ];
+    let (turns, flipped) = ORIENTATIONS.get(orientation.wrapping_sub(1) as usize).copied().unwrap_or((0, false));
+    // 反転してから回すのは、逆向きに回してから反転するのと同じ
+    let turns = if flipped { (turns + 4 - quarter_turns % 4) % 4 } else { (turns + quarter_turns) % 4 };
+    ORIENTATIONS.iter().position(|entry| *entry == (turns, flipped)).unwrap_or(0) as u32 + 1
+}
+
+fn rotate(image: DynamicImage, rotation: u32) -> DynamicImage {
+    match rotation {
+        90 => image.rotate90(),
+        180 => image.rotate180(),
+        270 => image.rotate270(),
+        _ => image,
+    }
+}
+
+fn is_jpeg(path: &Path) -> bool {
+    crop::parse_format(path.extension().and_then(|ext| ext.to_str()).unwrap_or_default()) == Some(ImageFormat::Jpeg)
+}
+
+/// 画素を変えて保存し直す（JPEGは元のEXIFを引き継ぐ）
+fn rewrite(app_handle: &AppHandle, source: &Path, edit: &PendingEdit, options: WriteOptions) -> Result<bool, String> {
+    let extension = source.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_string();
+    let format = crop::parse_format(&extension).ok_or_else(|| t!("crop.unsupported_format", extension))?;
+    let limits = AppSettings::load(app_handle).unwrap_or_default().decode_limits;
+    // 設定に関わらず、表示上の向きに揃えてから回転・切り抜きをする
+    let image = preview::open_image(app_handle, source, &limits)?;
+    let image = preview::apply_orientation(image, exif_info::orientation(source).unwrap_or(1));
+    let image = rotate(image, edit.rotation);
+    let image = match edit.crop {
+        Some(rect) => {
+            let (width, height) = image.dimensions();
+            crop::validate_rect(&rect, width, height)?;
+            image.crop_imm(rect.x, rect.y, rect.width, rect.height)
+        },
+        None => image,
+    };
+
+    safe_write::write_with(source, options, |temp| {
+        crop::save_as(&image, temp, format)?;
+        if format == ImageFormat::Jpeg {
+            let original = fs::read(source).map_err(|e| t!("file.read_failed", source.display(), e))?;
+            let encoded = fs::read(temp).map_err(|e| t!("file.read_failed", temp.display(), e))?;
+            let with_exif = exif_edit::carry_over_exif(&original, &encoded)?;
+            fs::write(temp, with_exif).map_err(|e| t!("crop.save_failed", temp.display(), e))?;
+        }
+        Ok(())
+    })?;
+    Ok(matches!(format, ImageFormat::Png | ImageFormat::Bmp))
+}
+
+/// 1枚分の編集を適用し、再圧縮せずに済んだかを返す
+///
+/// 回転だけのJPEGはEXIFの向きを書き換えるだけにし、画素は再圧縮しない
+fn apply_edit(
+    app_handle: &AppHandle,
+    index: &LibraryIndex,
+    edit: &PendingEdit,
+    options: WriteOptions,
+    store: OriginalsStore,
+) -> Result<bool, String> {
+    let source = path_guard::guard(app_handle, &edit.path)?;
+    originals::preserve(app_handle, &source, store)?;
+    let lossless = if edit.crop.is_none() && is_jpeg(&source) {
+        let orientation = rotate_orientation(exif_info::orientation(&source).unwrap_or(1), edit.rotation / 90);
+        exif_edit::edit_file(&source, &ExifChanges { orientation: Some(orientation), ..Default::default() }, options)?;
+        true
+    } else {
+        rewrite(app_handle, &source, edit, options)?
+    };
+
+    // 変更後のサイズ・更新日時をインデックスに反映する（縮小画像のキャッシュは更新日時が変わるため作り直される）
+    let info = image::image_info(&source)?;
+    let taken_at = exif_info::taken_at(&source).unwrap_or(info.modified);
+    index.update_file(&edit.path, info.size, info.modified, taken_at)?;
+    Ok(lossless)
+}
+
+fn apply(app_handle: &AppHandle, job: &JobHandle, edits: &[PendingEdit], store: OriginalsStore) -> Result<EditApplyReport, String> {
+    let index = LibraryIndex::open(app_handle)?;
+    let options = WriteOptions::for_edit(app_handle);
+    let mut report = EditApplyReport::default();
+    for (i, edit) in edits.iter().enumerate() {
+        if job.is_cancelled() {
+            break;
+        }
+        job.progress(i, Some(&edit.path));
+        match apply_edit(app_handle, &index, edit, options, store) {
+            Ok(lossless) => {
+                // 適用中に同じ画像へ追加された編集は残す
+                if let Some(state) = app_handle.try_state::<EditQueueState>() {
+                    if let Ok(mut queue) = state.lock() {
+                        queue.retain(|pending| pending != edit);
+                        notify(app_handle, queue.len());
+                    }
+                }
+                event_bridge::emit(app_handle, "image-updated", Delivery::Batch, &edit.path);
+                report.applied.push(AppliedEdit { path: edit.path.clone(), lossless });
+            },
+            Err(e) => report.failed.push((edit.path.clone(), e)),
+        }
+        job.progress(i + 1, None);
+    }
+    tracing::info!("保留中の編集を適用しました: {}枚（失敗{}枚）", report.applied.len(), report.failed.len());
+    Ok(report)
+}
+
+/// 画像の回転を保留中の編集に加え、保留中の編集の一覧を返す
+///
+/// `degrees`は時計回りの角度（90度単位、負の値で反時計回り）。回転を変えると保留中の切り抜きは座標が合わなくなるため取り消す
+#[tauri::command]
+pub fn queue_rotation(
+    app_handle: AppHandle,
+    state: State<'_, EditQueueState>,
+    path: String,
+    degrees: i32,
+) -> Result<Vec<PendingEdit>, String> {
+    if degrees % 90 != 0 {
+        return Err(t!("edit_queue.invalid_rotation", degrees));
+    }
+    path_guard::guard(&app_handle, &path)?;
+    let mut queue = state.lock()?;
+    let rotation = degrees.rem_euclid(360) as u32;
+    match queue.iter_mut().find(|edit| edit.path == path) {
+        Some(edit) => {
+            edit.rotation = (edit.rotation + rotation) % 360;
+            edit.crop = None;
+        },
+        None => queue.push(PendingEdit { path, rotation, crop: None }),
+    }
+    // 一周して何もしない編集は取り除く
+    queue.retain(|edit| edit.rotation != 0 || edit.crop.is_some());
+    notify(&app_handle, queue.len());
+    Ok(queue.clone())
+}
+
+/// 画像の切り抜きを保留中の編集に加え（既存の切り抜きは置き換える）、保留中の編集の一覧を返す
+#[tauri::command]
+pub fn queue_crop(
+    app_handle: AppHandle,
+    state: State<'_, EditQueueState>,
+    path: String,
+    rect: CropRect,
+) -> Result<Vec<PendingEdit>, String> {
+    path_guard::guard(&app_handle, &path)?;
+    let mut queue = state.lock()?;
+    match queue.iter_mut().find(|edit| edit.path == path) {
+        Some(edit) => edit.crop = Some(rect),
+        None => queue.push(PendingEdit { path, rotation: 0, crop: Some(rect) }),
+    }
+    notify(&app_handle, queue.len());
+    Ok(queue.clone())
+}
+
+/// 保留中の編集を指定した順で取得する
+#[tauri::command]
+pub fn get_pending_edits(state: State<'_, EditQueueState>) -> Result<Vec<PendingEdit>, String> {
+    Ok(state.lock()?.clone())
+}
+
+/// 保留中の編集を取り消す（`paths`を省略するとすべて取り消す）
+#[tauri::command]
+pub fn discard_pending_edits(
+    app_handle: AppHandle,
+    state: State<'_, EditQueueState>,
+    paths: Option<Vec<String>>,
+) -> Result<Vec<PendingEdit>, String> {
+    let mut queue = state.lock()?;
+    match paths {
+        Some(paths) => queue.retain(|edit| !paths.contains(&edit.path)),
+        None => queue.clear(),
+    }
+    notify(&app_handle, queue.len());
+    Ok(queue.clone())
+}
+
+/// 保留中の編集をまとめて元の画像に適用するジョブを開始し、ジョブIDを返す
+///
+/// 画面側で確認してから呼び出す。進捗と結果（`EditApplyReport`）は`job-progress`イベントで、
+/// 書き換えた画像は`image-updated`イベントで通知する（縮小画像を読み直すため）。
+/// `keep_original`で編集前のファイルを残すかを指定する（省略すると設定に従う）
+#[tauri::command]
+pub fn apply_pending_edits(
+    app_handle: AppHandle,
+    state: State<'_, EditQueueState>,
+    keep_original: Option<bool>,
+) -> Result<String, String> {
+    let edits = state.lock()?.clone();
+    if edits.is_empty() {
+        return Err(t!("edit_queue.empty"));
+    }
+    let store = originals::store_for(&app_handle, keep_original);
+    let handle = app_handle.clone();
+    jobs::spawn(&app_handle, "apply_edits", edits.len(), move |job| apply(&handle, job, &edits, store))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_orientation() {
+        assert_eq!(rotate_orientation(1, 1), 6);
+        assert_eq!(rotate_orientation(6, 1), 3);
+        assert_eq!(rotate_orientation(8, 1), 1);
+        assert_eq!(rotate_orientation(3, 2), 1);
+        // 左右反転した画像を回すと、反転の組み合わせが変わる
+        assert_eq!(rotate_orientation(2, 1), 7);
+        assert_eq!(rotate_orientation(5, 2), 7);
+        assert_eq!(rotate_orientation(0, 1), 6);
+
+        // 表示上の結果が、先に向きを適用して回した画像と一致する
+        let image = DynamicImage::ImageRgb8(::image::RgbImage::from_fn(3, 2, |x, y| ::image::Rgb([x as u8, y as u8, 0])));
+        for orientation in 1..=8 {
+            for turns in 0..4 {
+                let expected = rotate(preview::apply_orientation(image.clone(), orientation), turns * 90);
+                let actual = preview::apply_orientation(image.clone(), rotate_orientation(orientation, turns));
+                assert_eq!(expected.to_rgb8(), actual.to_rgb8(), "{} + {}", orientation, turns);
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::Path;
+use ::image::{DynamicImage, RgbImage};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::analysis;
+use crate::path_guard;
+use crate::preview;
+
+/// 自動補正の対象外とする両端の割合（外れ値に引きずられないよう0.5%ずつ除く）
+const CLIP_FRACTION: f64 = 0.005;
+
+/// ホワイトバランスの補正量の範囲（極端な色かぶりの画像を壊さないため）
+const MAX_WHITE_BALANCE_GAIN: f64 = 2.0;
+
+/// 自動補正の内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EnhanceOptions {
+    /// 明るさの範囲を0〜255に広げる
+    pub auto_levels: bool,
+    /// 色かぶりを補正する（グレーワールド仮定）
+    pub white_balance: bool,
+    /// 軽いぼかしでノイズを抑える
+    pub denoise: bool,
+    /// プレビューの長辺の最大値
+    pub max_size: u32,
+}
+
+impl Default for EnhanceOptions {
+    fn default() -> Self {
+        Self {
+            auto_levels: true,
+            white_balance: true,
+            denoise: false,
+            max_size: 2048,
+        }
+    }
+}
+
+/// 自動補正したプレビュー
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EnhancedPreview {
+    /// プレビューのパス（元ファイルは変更しない）
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// 適用したEXIFの向き（適用していなければNone）
+    pub applied_orientation: Option<u32>,
+    /// 明るさの補正に使った範囲（黒点・白点）
+    pub levels: Option<(u8, u8)>,
+    /// ホワイトバランスのRGBごとの倍率
+    pub white_balance_gains: Option<[f64; 3]>,
+}
+
+/// 輝度のヒストグラムから、両端を除いた黒点・白点を求める
+fn find_levels(image: &RgbImage) -> (u8, u8) {
+    let mut counts = [0u64; 256];
+    for pixel in image.pixels() {
+        counts[analysis::luminance(pixel[0], pixel[1], pixel[2]) as usize] += 1;
+    }
+    let total: u64 = counts.iter().sum();
+    let clip = (total as f64 * CLIP_FRACTION) as u64;
+
+    let mut low = 0;
+    let mut seen = 0;
+    for (value, count) in counts.iter().enumerate() {
+        seen += count;
+        if seen > clip {
+            low = value;
+            break;
+        }
+    }
+    let mut high = 255;
+    seen = 0;
+    for (value, count) in counts.iter().enumerate().rev() {
+        seen += count;
+        if seen > clip {
+            high = value;
+            break;
+        }
+    }
+    (low as u8, high.max(low) as u8)
+}
+
+fn apply_levels(image: &mut RgbImage, (low, high): (u8, u8)) {
+    if high <= low {
+        return;
+    }
+    let scale = 255.0 / (high - low) as f64;
+    for pixel in image.pixels_mut() {
+        for channel in pixel.0.iter_mut() {
+            *channel = ((*channel as f64 - low as f64) * scale).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// 各チャンネルの平均が揃うような倍率を求める（グレーワールド仮定）
+fn white_balance_gains(image: &RgbImage) -> [f64; 3] {
+    let mut sums = [0.0f64; 3];
+    for pixel in image.pixels() {
+        for (sum, value) in sums.iter_mut().zip(pixel.0) {
+            *sum += value as f64;
+        }
+    }
+    let gray = sums.iter().sum::<f64>() / 3.0;
+    sums.map(|sum| {
+        if sum > 0.0 {
+            (gray / sum).clamp(1.0 / MAX_WHITE_BALANCE_GAIN, MAX_WHITE_BALANCE_GAIN)
+        } else {
+            1.0
+        }
+    })
+}
+
+fn apply_gains(image: &mut RgbImage, gains: [f64; 3]) {
+    for pixel in image.pixels_mut() {
+        for (channel, gain) in pixel.0.iter_mut().zip(gains) {
+            *channel = (*channel as f64 * gain).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// 画像を自動補正したプレビューを作成する（ホワイトバランス→レベル補正→ノイズ低減の順）
+fn enhance(image: RgbImage, options: &EnhanceOptions) -> (RgbImage, Option<(u8, u8)>, Option<[f64; 3]>) {
+    let mut image = image;
+    let gains = options.white_balance.then(|| white_balance_gains(&image));
+    if let Some(gains) = gains {
+        apply_gains(&mut image, gains);
+    }
+    let levels = options.auto_levels.then(|| find_levels(&image));
+    if let Some(levels) = levels {
+        apply_levels(&mut image, levels);
+    }
+    if options.denoise {
+        image = ::image::imageops::blur(&image, 0.8);
+    }
+    (image, levels, gains)
+}
+
+/// 明るさ・ホワイトバランスを自動補正したプレビューを作成する（元ファイルは変更しない）
+#[tauri::command]
+pub async fn get_enhanced_preview(
+    app_handle: AppHandle,
+    path: String,
+    options: Option<EnhanceOptions>,
+) -> Result<EnhancedPreview, String> {
+    let options = options.unwrap_or_default();
+    let source = path_guard::guard(&app_handle, &path)?;
+    let (image, applied_orientation) = preview::open_oriented(&app_handle, &source)?;
+    let image = image.thumbnail(options.max_size, options.max_size).to_rgb8();
+
+    let (enhanced, levels, gains) = enhance(image, &options);
+    let variant = format!(
+        "enhanced-{}-{}-{}-{}-o{}",
+        options.auto_levels, options.white_balance, options.denoise, options.max_size,
+        applied_orientation.unwrap_or(1),
+    );
+    let dest = preview::get_cache_dir(&app_handle, "enhanced")
+        .join(format!("{}.png", preview::cache_key(Path::new(&source), &variant)));
+    preview::save_png(&DynamicImage::ImageRgb8(enhanced.clone()), &dest)?;
+
+    Ok(EnhancedPreview {
+        path: dest.to_string_lossy().to_string(),
+        width: enhanced.width(),
+        height: enhanced.height(),
+        applied_orientation,
+        levels,
+        white_balance_gains: gains,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enhance_removes_cast_and_stretches_levels() {
+        // 青みがかった、明るさの範囲が狭い画像
+        let image = RgbImage::from_fn(10, 10, |x, _| {
+            let base = 80 + x as u8 * 8;
+            ::image::Rgb([base, base, base.saturating_add(40)])
+        });
+        let (enhanced, levels, gains) = enhance(image, &EnhanceOptions::default());
+
+        let gains = gains.unwrap();
+        assert!(gains[2] < 1.0 && gains[0] > 1.0);
+        let (low, high) = levels.unwrap();
+        assert!(low > 0 && high < 255);
+
+        let darkest = enhanced.get_pixel(0, 0);
+        let brightest = enhanced.get_pixel(9, 0);
+        assert_eq!(darkest[1], 0);
+        assert_eq!(brightest[1], 255);
+    }
+}
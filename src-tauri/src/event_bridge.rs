@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::lock;
+use crate::settings::AppSettings;
+
+/// 短時間に続けて発生したイベントのまとめ方
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delivery {
+    /// 最後の内容だけを送る（状態の通知向け）
+    Latest,
+    /// 内容を配列にまとめて送る（個々の変更の通知向け）
+    Batch,
+}
+
+/// 送信待ちのイベント
+#[derive(Debug, PartialEq)]
+enum Pending {
+    Latest(Value),
+    Batch(Vec<Value>),
+}
+
+impl Pending {
+    fn new(delivery: Delivery, payload: Value) -> Self {
+        match delivery {
+            Delivery::Latest => Pending::Latest(payload),
+            Delivery::Batch => Pending::Batch(vec![payload]),
+        }
+    }
+
+    fn push(&mut self, payload: Value) {
+        match self {
+            Pending::Latest(latest) => *latest = payload,
+            Pending::Batch(batch) => batch.push(payload),
+        }
+    }
+
+    /// 送信する内容に変換する（まとめた内容は1回あたりの上限ごとに分ける）
+    fn into_payloads(self, max_batch_size: usize) -> Vec<Value> {
+        match self {
+            Pending::Latest(payload) => vec![payload],
+            Pending::Batch(batch) => batch
+                .chunks(max_batch_size.max(1))
+                .map(|chunk| Value::Array(chunk.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+/// 画面へ送るイベントを一定間隔でまとめて送る（大量の通知でWebViewが固まるのを防ぐ）
+#[derive(Default)]
+pub struct EventBridge {
+    pending: Mutex<HashMap<&'static str, Pending>>,
+    wake: Condvar,
+}
+
+impl EventBridge {
+    /// 送信待ちを取得する（他のスレッドがパニックしても送信待ちの内容はそのまま使う）
+    fn lock_pending(&self) -> MutexGuard<'_, HashMap<&'static str, Pending>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn queue(&self, event: &'static str, delivery: Delivery, payload: Value) {
+        let mut pending = self.lock_pending();
+        match pending.get_mut(event) {
+            Some(queued) => queued.push(payload),
+            None => {
+                pending.insert(event, Pending::new(delivery, payload));
+            },
+        }
+        self.wake.notify_one();
+    }
+
+    fn take(&self) -> HashMap<&'static str, Pending> {
+        std::mem::take(&mut *self.lock_pending())
+    }
+}
+
+/// イベントを送信待ちに加える（ブリッジ未登録時はそのまま送る）
+///
+/// `Delivery::Batch`のイベントは内容の配列として届く
+pub fn emit<S: Serialize>(app_handle: &AppHandle, event: &'static str, delivery: Delivery, payload: S) {
+    if lock::suppresses(app_handle, event) {
+        return;
+    }
+    let payload = match serde_json::to_value(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::warn!("イベントの変換に失敗しました ({}): {}", event, e);
+            return;
+        },
+    };
+    match app_handle.try_state::<EventBridge>() {
+        Some(bridge) => bridge.queue(event, delivery, payload),
+        None => {
+            let payload = if delivery == Delivery::Batch { Value::Array(vec![payload]) } else { payload };
+            let _ = app_handle.emit(event, payload);
+        },
+    }
+}
+
+fn flush(app_handle: &AppHandle, bridge: &EventBridge, max_batch_size: usize) {
+    for (event, pending) in bridge.take() {
+        for payload in pending.into_payloads(max_batch_size) {
+            let _ = app_handle.emit(event, payload);
+        }
+    }
+}
+
+/// 送信待ちのイベントを設定の間隔ごとに送る
+pub fn start(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        let Some(bridge) = app_handle.try_state::<EventBridge>() else { return };
+
+        // イベントが来るまで待ち、来たら間隔分だけ続くイベントを溜めてから送る
+        {
+            let pending = bridge.lock_pending();
+            let _pending = bridge.wake.wait_while(pending, |pending| pending.is_empty())
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        let settings = AppSettings::load(&app_handle).unwrap_or_default().events;
+        std::thread::sleep(Duration::from_millis(settings.flush_interval_ms));
+        flush(&app_handle, &bridge, settings.max_batch_size);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_coalesce_latest_and_batch() {
+        let bridge = EventBridge::default();
+        bridge.queue("selection-changed", Delivery::Latest, json!(1));
+        bridge.queue("selection-changed", Delivery::Latest, json!(2));
+        for i in 0..5 {
+            bridge.queue("image-deleted", Delivery::Batch, json!(i));
+        }
+
+        let mut pending = bridge.take();
+        assert!(bridge.take().is_empty());
+        assert_eq!(pending.remove("selection-changed").unwrap().into_payloads(2), vec![json!(2)]);
+        assert_eq!(
+            pending.remove("image-deleted").unwrap().into_payloads(2),
+            vec![json!([0, 1]), json!([2, 3]), json!([4])],
+        );
+    }
+}
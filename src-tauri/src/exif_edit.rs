@@ -0,0 +1,364 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use exif::experimental::Writer;
+use exif::{Context, Field, In, Rational, Tag, Value};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::exif_info;
+use crate::i18n::t;
+use crate::image;
+use crate::index::LibraryIndex;
+use crate::originals::{self, OriginalsStore};
+use crate::path_guard;
+use crate::safe_write::{self, WriteOptions};
+
+/// APP1セグメントのEXIFの識別子
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+
+/// 撮影日時を記録するタグ
+const DATE_TAGS: [Tag; 3] = [Tag::DateTime, Tag::DateTimeOriginal, Tag::DateTimeDigitized];
+
+/// タイムゾーンを記録するタグ
+const OFFSET_TAGS: [Tag; 3] = [Tag::OffsetTime, Tag::OffsetTimeOriginal, Tag::OffsetTimeDigitized];
+
+/// 位置情報の変更
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum GpsChange {
+    /// 位置情報を設定する（既存の位置情報は置き換える）
+    Set { latitude: f64, longitude: f64, altitude: Option<f64> },
+    /// 位置情報をすべて削除する
+    Remove,
+}
+
+/// EXIFの変更内容
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ExifChanges {
+    /// 撮影日時をずらす秒数（カメラの時計のずれの修正用）
+    pub shift_seconds: i64,
+    /// タイムゾーン（`+09:00`の形式）。時刻は変えずに記録だけを置き換えるため、時差の修正は`shift_seconds`と併せて行う
+    pub offset: Option<String>,
+    pub gps: Option<GpsChange>,
+    /// 向き（1〜8）。画素は変えずに表示の向きだけを変える
+    pub orientation: Option<u32>,
+}
+
+/// 変更できなかった画像
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExifEditFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// EXIFの編集結果
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ExifEditResult {
+    pub edited: Vec<String>,
+    pub failed: Vec<ExifEditFailure>,
+}
+
+/// 変更内容が正しいか確認する
+fn validate(changes: &ExifChanges) -> Result<(), String> {
+    if let Some(offset) = &changes.offset {
+        let bytes = offset.as_bytes();
+        let valid = bytes.len() == 6
+            && matches!(bytes[0], b'+' | b'-')
+            && bytes[3] == b':'
+            && [1, 2, 4, 5].iter().all(|i| bytes[*i].is_ascii_digit())
+            && offset[1..3].parse::<u32>().is_ok_and(|hours| hours <= 14)
+            && offset[4..6].parse::<u32>().is_ok_and(|minutes| minutes < 60);
+        if !valid {
+            return Err(t!("exif_edit.invalid_offset", offset));
+        }
+    }
+    if let Some(orientation) = changes.orientation.filter(|orientation| !(1..=8).contains(orientation)) {
+        return Err(t!("exif_edit.invalid_orientation", orientation));
+    }
+    if let Some(GpsChange::Set { latitude, longitude, .. }) = &changes.gps {
+        if !(-90.0..=90.0).contains(latitude) || !(-180.0..=180.0).contains(longitude) {
+            return Err(t!("exif_edit.invalid_gps", latitude, longitude));
+        }
+    }
+    Ok(())
+}
+
+/// EXIFの日時の文字列を指定した秒数だけずらす（読めない値はNone）
+fn shift_datetime(value: &[u8], seconds: i64) -> Option<Vec<u8>> {
+    let datetime = exif::DateTime::from_ascii(value).ok()?;
+    if datetime.month == 0 || datetime.day == 0 {
+        return None;
+    }
+    let days = exif_info::days_from_civil(datetime.year as i64, datetime.month as u32, datetime.day as u32);
+    let total = days * 86400
+        + datetime.hour as i64 * 3600
+        + datetime.minute as i64 * 60
+        + datetime.second as i64
+        + seconds;
+    let (year, month, day) = exif_info::civil_from_days(total.div_euclid(86400));
+    let time = total.rem_euclid(86400);
+    Some(format!(
+        "{:04}:{:02}:{:02} {:02}:{:02}:{:02}",
+        year, month, day, time / 3600, time % 3600 / 60, time % 60,
+    ).into_bytes())
+}
+
+/// 度を度・分・秒の有理数にする
+fn to_dms(value: f64) -> Vec<Rational> {
+    let value = value.abs();
+    let degrees = value.floor();
+    let minutes = ((value - degrees) * 60.0).floor();
+    let seconds = (value - degrees - minutes / 60.0) * 3600.0;
+    vec![
+        Rational { num: degrees as u32, denom: 1 },
+        Rational { num: minutes as u32, denom: 1 },
+        Rational { num: (seconds * 10000.0).round() as u32, denom: 10000 },
+    ]
+}
+
+fn field(tag: Tag, value: Value) -> Field {
+    let ifd_num = In::PRIMARY;
+    Field { tag, ifd_num, value }
+}
+
+fn gps_fields(latitude: f64, longitude: f64, altitude: Option<f64>) -> Vec<Field> {
+    let reference = |positive: bool, yes: &[u8], no: &[u8]| Value::Ascii(vec![if positive { yes } else { no }.to_vec()]);
+    let mut fields = vec![
+        field(Tag::GPSVersionID, Value::Byte(vec![2, 3, 0, 0])),
+        field(Tag::GPSLatitudeRef, reference(latitude >= 0.0, b"N", b"S")),
+        field(Tag::GPSLatitude, Value::Rational(to_dms(latitude))),
+        field(Tag::GPSLongitudeRef, reference(longitude >= 0.0, b"E", b"W")),
+        field(Tag::GPSLongitude, Value::Rational(to_dms(longitude))),
+    ];
+    if let Some(altitude) = altitude {
+        fields.push(field(Tag::GPSAltitudeRef, Value::Byte(vec![u8::from(altitude < 0.0)])));
+        fields.push(field(Tag::GPSAltitude, Value::Rational(vec![Rational { num: (altitude.abs() * 100.0).round() as u32, denom: 100 }])));
+    }
+    fields
+}
+
+/// 既存のEXIFの項目に変更を反映する
+fn apply_changes(existing: &[Field], changes: &ExifChanges) -> Vec<Field> {
+    let mut fields: Vec<Field> = existing.iter()
+        .filter(|field| !(changes.gps.is_some() && field.tag.context() == Context::Gps))
+        .filter(|field| !(changes.offset.is_some() && OFFSET_TAGS.contains(&field.tag)))
+        .filter(|field| !(changes.orientation.is_some() && field.ifd_num == In::PRIMARY && field.tag == Tag::Orientation))
+        .cloned()
+        .collect();
+
+    if changes.shift_seconds != 0 {
+        for field in fields.iter_mut().filter(|field| field.ifd_num == In::PRIMARY && DATE_TAGS.contains(&field.tag)) {
+            let shifted = match &field.value {
+                Value::Ascii(values) => values.first().and_then(|value| shift_datetime(value, changes.shift_seconds)),
+                _ => None,
+            };
+            if let Some(shifted) = shifted {
+                field.value = Value::Ascii(vec![shifted]);
+            }
+        }
+    }
+    if let Some(offset) = &changes.offset {
+        fields.extend(OFFSET_TAGS.iter().map(|tag| field(*tag, Value::Ascii(vec![offset.as_bytes().to_vec()]))));
+    }
+    if let Some(GpsChange::Set { latitude, longitude, altitude }) = &changes.gps {
+        fields.extend(gps_fields(*latitude, *longitude, *altitude));
+    }
+    if let Some(orientation) = changes.orientation {
+        fields.push(field(Tag::Orientation, Value::Short(vec![orientation as u16])));
+    }
+    fields
+}
+
+/// JPEGのEXIF（APP1）を置き換える（`tiff`がNoneなら削除する）
+fn splice_exif(jpeg: &[u8], tiff: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return Err(t!("exif_edit.unsupported"));
+    }
+    let app1 = match tiff {
+        Some(tiff) => {
+            let length = 2 + EXIF_HEADER.len() + tiff.len();
+            let length = u16::try_from(length).map_err(|_| t!("exif_edit.too_large", length))?;
+            let mut segment = vec![0xFF, 0xE1];
+            segment.extend_from_slice(&length.to_be_bytes());
+            segment.extend_from_slice(EXIF_HEADER);
+            segment.extend_from_slice(tiff);
+            segment
+        },
+        None => Vec::new(),
+    };
+
+    // 画像データ（SOS）より前のセグメントを調べ、既存のEXIFを除く
+    let mut output = vec![0xFF, 0xD8];
+    let mut pos = 2;
+    let mut inserted = false;
+    while pos + 4 <= jpeg.len() && jpeg[pos] == 0xFF {
+        let marker = jpeg[pos + 1];
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+        let length = u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]) as usize;
+        let end = pos + 2 + length;
+        if length < 2 || end > jpeg.len() {
+            return Err(t!("exif_edit.corrupt"));
+        }
+        let segment = &jpeg[pos..end];
+        // JFIF（APP0）は先頭に置く決まりのため、その後ろに入れる
+        if !inserted && marker != 0xE0 {
+            output.extend_from_slice(&app1);
+            inserted = true;
+        }
+        if !(marker == 0xE1 && segment[4..].starts_with(EXIF_HEADER)) {
+            output.extend_from_slice(segment);
+        }
+        pos = end;
+    }
+    if !inserted {
+        output.extend_from_slice(&app1);
+    }
+    output.extend_from_slice(&jpeg[pos..]);
+    Ok(output)
+}
+
+/// JPEGのEXIFを書き換えた内容を返す
+fn edit_jpeg(jpeg: &[u8], changes: &ExifChanges) -> Result<Vec<u8>, String> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return Err(t!("exif_edit.unsupported"));
+    }
+    let existing = exif::Reader::new().read_from_container(&mut Cursor::new(jpeg)).ok();
+    let fields = apply_changes(existing.as_ref().map(|exif| exif.fields().cloned().collect::<Vec<_>>()).as_deref().unwrap_or_default(), changes);
+    if fields.is_empty() {
+        return splice_exif(jpeg, None);
+    }
+
+    // 埋め込みの縮小画像は書き込み時に位置が変わるため、データとして渡し直す
+    let thumbnail = existing.as_ref().and_then(|exif| {
+        let offset = exif.get_field(Tag::JPEGInterchangeFormat, In::THUMBNAIL)?.value.get_uint(0)? as usize;
+        let length = exif.get_field(Tag::JPEGInterchangeFormatLength, In::THUMBNAIL)?.value.get_uint(0)? as usize;
+        exif.buf().get(offset..offset.checked_add(length)?)
+    });
+    let mut writer = Writer::new();
+    for field in &fields {
+        writer.push_field(field);
+    }
+    if let Some(thumbnail) = thumbnail {
+        writer.set_jpeg(thumbnail, In::THUMBNAIL);
+    }
+    let mut tiff = Cursor::new(Vec::new());
+    writer.write(&mut tiff, existing.as_ref().is_some_and(|exif| exif.little_endian()))
+        .map_err(|e| t!("exif_edit.write_failed", e))?;
+    let edited = splice_exif(jpeg, Some(&tiff.into_inner()))?;
+
+    // 元のファイルを置き換える前に、書き換えたEXIFを読み直せることを確かめる
+    exif::Reader::new().read_from_container(&mut Cursor::new(&edited))
+        .map_err(|e| t!("exif_edit.verify_failed", e))?;
+    Ok(edited)
+}
+
+/// 書き出し直したJPEGに元のJPEGのEXIFを移す（向きは画素に反映済みのため1にする）
+pub(crate) fn carry_over_exif(original: &[u8], encoded: &[u8]) -> Result<Vec<u8>, String> {
+    let Ok(existing) = exif::Reader::new().read_from_container(&mut Cursor::new(original)) else {
+        return Ok(encoded.to_vec());
+    };
+    let spliced = splice_exif(encoded, Some(existing.buf()))?;
+    edit_jpeg(&spliced, &ExifChanges { orientation: Some(1), ..Default::default() })
+}
+
+/// 1枚分のEXIFを書き換える（同じフォルダの一時ファイルに書き出してから置き換える）
+pub(crate) fn edit_file(path: &Path, changes: &ExifChanges, options: WriteOptions) -> Result<(), String> {
+    let jpeg = fs::read(path).map_err(|e| t!("file.read_failed", path.display(), e))?;
+    let edited = edit_jpeg(&jpeg, changes)?;
+    safe_write::write_bytes(path, &edited, options)
+}
+
+fn edit(app_handle: &AppHandle, paths: &[String], changes: &ExifChanges, store: OriginalsStore) -> Result<ExifEditResult, String> {
+    validate(changes)?;
+    let index = LibraryIndex::open(app_handle)?;
+    let options = WriteOptions::for_edit(app_handle);
+    let mut result = ExifEditResult::default();
+    for path in paths {
+        let edited = path_guard::guard(app_handle, path).and_then(|source| {
+            originals::preserve(app_handle, &source, store)?;
+            edit_file(&source, changes, options)?;
+            // 変更後のサイズ・更新日時・撮影日時をインデックスに反映する
+            let info = image::image_info(&source)?;
+            let taken_at = exif_info::taken_at(&source).unwrap_or(info.modified);
+            index.update_file(path, info.size, info.modified, taken_at)
+        });
+        match edited {
+            Ok(()) => result.edited.push(path.clone()),
+            Err(error) => result.failed.push(ExifEditFailure { path: path.clone(), error }),
+        }
+    }
+    tracing::info!("EXIFを書き換えました: {}件（失敗{}件）", result.edited.len(), result.failed.len());
+    Ok(result)
+}
+
+/// 複数のJPEGのEXIFをまとめて書き換える（撮影日時のずれ・タイムゾーン・位置情報の追加と削除）
+///
+/// 元のファイルを一時ファイル経由で安全に置き換え、インデックスの撮影日時も更新する。
+/// `keep_original`で編集前のファイルを残すかを指定する（省略すると設定に従う）
+#[tauri::command]
+pub async fn edit_exif(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    changes: ExifChanges,
+    keep_original: Option<bool>,
+) -> Result<ExifEditResult, String> {
+    let store = originals::store_for(&app_handle, keep_original);
+    let result = edit(&app_handle, &paths, &changes, store);
+    audit::complete(&app_handle, "edit_exif", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::codecs::jpeg::JpegEncoder;
+    use ::image::ExtendedColorType;
+
+    fn read_ascii(jpeg: &[u8], tag: Tag) -> Option<String> {
+        let exif = exif::Reader::new().read_from_container(&mut Cursor::new(jpeg)).ok()?;
+        match &exif.get_field(tag, In::PRIMARY)?.value {
+            Value::Ascii(values) => Some(String::from_utf8_lossy(values.first()?).to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_edit_jpeg_shifts_dates_and_gps() {
+        let mut jpeg = Vec::new();
+        JpegEncoder::new(&mut jpeg).encode(&[128; 8 * 8 * 3], 8, 8, ExtendedColorType::Rgb8).unwrap();
+        let taken = field(Tag::DateTimeOriginal, Value::Ascii(vec![b"2024:12:31 23:30:00".to_vec()]));
+        let mut writer = Writer::new();
+        writer.push_field(&taken);
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let original = splice_exif(&jpeg, Some(&tiff.into_inner())).unwrap();
+
+        let changes = ExifChanges {
+            shift_seconds: 3600,
+            offset: Some("+09:00".to_string()),
+            gps: Some(GpsChange::Set { latitude: 35.5, longitude: -139.25, altitude: None }),
+            orientation: None,
+        };
+        let edited = edit_jpeg(&original, &changes).unwrap();
+        assert_eq!(read_ascii(&edited, Tag::DateTimeOriginal).as_deref(), Some("2025:01:01 00:30:00"));
+        assert_eq!(read_ascii(&edited, Tag::OffsetTimeOriginal).as_deref(), Some("+09:00"));
+        assert_eq!(read_ascii(&edited, Tag::GPSLongitudeRef).as_deref(), Some("W"));
+        assert!(::image::load_from_memory(&edited).is_ok());
+
+        let removed = edit_jpeg(&edited, &ExifChanges { gps: Some(GpsChange::Remove), ..Default::default() }).unwrap();
+        assert_eq!(read_ascii(&removed, Tag::GPSLatitudeRef), None);
+        assert_eq!(read_ascii(&removed, Tag::DateTimeOriginal).as_deref(), Some("2025:01:01 00:30:00"));
+
+        let rotated = edit_jpeg(&removed, &ExifChanges { orientation: Some(6), ..Default::default() }).unwrap();
+        let exif = exif::Reader::new().read_from_container(&mut Cursor::new(&rotated)).unwrap();
+        assert_eq!(exif.get_field(Tag::Orientation, In::PRIMARY).and_then(|field| field.value.get_uint(0)), Some(6));
+
+        assert!(validate(&ExifChanges { offset: Some("9:00".to_string()), ..Default::default() }).is_err());
+        assert!(validate(&ExifChanges { orientation: Some(9), ..Default::default() }).is_err());
+        assert!(edit_jpeg(b"\x89PNG", &changes).is_err());
+    }
+}
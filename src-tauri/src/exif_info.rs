@@ -0,0 +1,189 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// 画像ファイルのEXIFを読み込む（EXIFがなければNone）
+pub fn read(path: &Path) -> Option<exif::Exif> {
+    let file = File::open(path).ok()?;
+    exif::Reader::new().read_from_container(&mut BufReader::new(file)).ok()
+}
+
+/// 西暦の年月日から1970-01-01からの日数を求める
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// 1970-01-01からの日数を西暦の年月日に変換する
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// EXIFの撮影日時をUnix時間に変換する
+///
+/// EXIFの日時はタイムゾーンを持たないため、撮影地の時刻をそのままUTCとして扱う
+/// （日付でまとめたときに撮影した日と一致させるため）
+fn datetime_to_unix(datetime: &exif::DateTime) -> Option<u64> {
+    if datetime.month == 0 || datetime.day == 0 {
+        return None;
+    }
+    let days = days_from_civil(datetime.year as i64, datetime.month as u32, datetime.day as u32);
+    let seconds = days * 86400
+        + datetime.hour as i64 * 3600
+        + datetime.minute as i64 * 60
+        + datetime.second as i64;
+    u64::try_from(seconds).ok()
+}
+
+/// EXIFに埋め込まれた縮小画像（JPEG）を取得する（なければNone）
+pub fn embedded_thumbnail(path: &Path) -> Option<Vec<u8>> {
+    let data = read(path)?;
+    let offset = data.get_field(exif::Tag::JPEGInterchangeFormat, exif::In::THUMBNAIL)?.value.get_uint(0)? as usize;
+    let length = data.get_field(exif::Tag::JPEGInterchangeFormatLength, exif::In::THUMBNAIL)?.value.get_uint(0)? as usize;
+    data.buf().get(offset..offset.checked_add(length)?).map(|bytes| bytes.to_vec())
+}
+
+/// EXIFの向き（1〜8、1は回転なし）を取得する
+pub fn orientation(path: &Path) -> Option<u32> {
+    let data = read(path)?;
+    let field = data.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?;
+    field.value.get_uint(0).filter(|value| (1..=8).contains(value))
+}
+
+/// 撮影日時（DateTimeOriginal、なければDateTime）をUnix時間で取得する
+pub fn taken_at(path: &Path) -> Option<u64> {
+    let data = read(path)?;
+    [exif::Tag::DateTimeOriginal, exif::Tag::DateTime].iter().find_map(|tag| {
+        let field = data.get_field(*tag, exif::In::PRIMARY)?;
+        let exif::Value::Ascii(values) = &field.value else { return None };
+        let datetime = exif::DateTime::from_ascii(values.first()?).ok()?;
+        datetime_to_unix(&datetime)
+    })
+}
+
+/// カメラの機種名（Model、メーカー名で始まらない場合はMakeを前に付ける）
+pub fn camera_model(path: &Path) -> Option<String> {
+    let data = read(path)?;
+    let text = |tag: exif::Tag| {
+        let field = data.get_field(tag, exif::In::PRIMARY)?;
+        let exif::Value::Ascii(values) = &field.value else { return None };
+        let value = String::from_utf8_lossy(values.first()?);
+        Some(value.trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string()).filter(|value| !value.is_empty())
+    };
+    let model = text(exif::Tag::Model)?;
+    let Some(make) = text(exif::Tag::Make) else { return Some(model) };
+    // `NIKON CORPORATION`と`NIKON D750`のように、機種名にメーカー名が含まれることが多い
+    let brand = make.split_whitespace().next().unwrap_or_default().to_lowercase();
+    if model.to_lowercase().starts_with(&brand) {
+        Some(model)
+    } else {
+        Some(format!("{} {}", make, model))
+    }
+}
+
+/// EXIFの解像度（XResolution・YResolution）をDPIで取得する
+fn exif_dpi(data: &exif::Exif) -> Option<(f64, f64)> {
+    let rational = |tag| match &data.get_field(tag, exif::In::PRIMARY)?.value {
+        exif::Value::Rational(values) => values.first().map(|value| value.to_f64()),
+        _ => None,
+    };
+    // 単位は2=インチ（既定）、3=センチメートル。1は単位なしで縦横比のみを表す
+    let scale = match data.get_field(exif::Tag::ResolutionUnit, exif::In::PRIMARY).and_then(|field| field.value.get_uint(0)) {
+        None | Some(2) => 1.0,
+        Some(3) => 2.54,
+        _ => return None,
+    };
+    Some((rational(exif::Tag::XResolution)? * scale, rational(exif::Tag::YResolution)? * scale))
+}
+
+/// JPEGのJFIFヘッダー（APP0）の密度をDPIで取得する
+fn jfif_dpi(header: &[u8]) -> Option<(f64, f64)> {
+    if header.len() < 18 || header[..4] != [0xFF, 0xD8, 0xFF, 0xE0] || &header[6..11] != b"JFIF\0" {
+        return None;
+    }
+    let scale = match header[13] {
+        1 => 1.0,
+        2 => 2.54,
+        _ => return None,
+    };
+    let x = u16::from_be_bytes([header[14], header[15]]) as f64;
+    let y = u16::from_be_bytes([header[16], header[17]]) as f64;
+    Some((x * scale, y * scale))
+}
+
+/// PNGのpHYsチャンク（1メートルあたりのピクセル数）をDPIで取得する
+fn png_dpi(header: &[u8]) -> Option<(f64, f64)> {
+    if !header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return None;
+    }
+    let mut pos = 8;
+    while pos + 8 <= header.len() {
+        let length = u32::from_be_bytes(header[pos..pos + 4].try_into().ok()?) as usize;
+        let kind = &header[pos + 4..pos + 8];
+        let data = header.get(pos + 8..(pos + 8).checked_add(length)?)?;
+        match kind {
+            b"pHYs" if length == 9 && data[8] == 1 => {
+                let x = u32::from_be_bytes(data[0..4].try_into().ok()?) as f64;
+                let y = u32::from_be_bytes(data[4..8].try_into().ok()?) as f64;
+                return Some((x * 0.0254, y * 0.0254));
+            },
+            // pHYsは画像データより前に置かれる
+            b"IDAT" | b"IEND" => return None,
+            _ => pos += 12 + length,
+        }
+    }
+    None
+}
+
+/// 画像の物理的な解像度（横・縦のDPI）を取得する（EXIF、なければJFIF・PNGのヘッダーから読む）
+pub fn dpi(path: &Path) -> Option<(f64, f64)> {
+    if let Some(dpi) = read(path).as_ref().and_then(exif_dpi) {
+        return Some(dpi).filter(|(x, y)| *x > 0.0 && *y > 0.0);
+    }
+    let mut header = Vec::new();
+    File::open(path).ok()?.take(64 * 1024).read_to_end(&mut header).ok()?;
+    jfif_dpi(&header).or_else(|| png_dpi(&header)).filter(|(x, y)| *x > 0.0 && *y > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_conversion_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2024, 2, 29), 19782);
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+
+        let datetime = exif::DateTime::from_ascii(b"2024:05:03 12:34:56").unwrap();
+        assert_eq!(datetime_to_unix(&datetime), Some(1714739696));
+    }
+
+    #[test]
+    fn test_header_dpi() {
+        let mut jfif = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+        jfif.extend_from_slice(b"JFIF\0\x01\x01\x02\x00\x76\x00\x76");
+        assert_eq!(jfif_dpi(&jfif), Some((118.0 * 2.54, 118.0 * 2.54)));
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend_from_slice(&[0, 0, 0, 9]);
+        png.extend_from_slice(b"pHYs");
+        png.extend_from_slice(&[0, 0, 0x0B, 0x13, 0, 0, 0x0B, 0x13, 1, 0, 0, 0, 0]);
+        let (x, _) = png_dpi(&png).unwrap();
+        assert!((x - 72.0).abs() < 0.01);
+    }
+}
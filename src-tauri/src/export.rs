@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::hidden;
+use crate::i18n::t;
+use crate::image::{self, ImageInfo};
+use crate::index::LibraryIndex;
+use crate::metadata::{ImageMetadata, MetadataStore};
+use crate::privacy;
+use crate::safe_write::{self, WriteOptions};
+use crate::selection;
+
+/// インデックスのエクスポート形式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+/// エクスポートする1画像分のレコード
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportRecord {
+    /// 画像の基本情報
+    #[serde(flatten)]
+    pub image: ImageInfo,
+    /// タグ一覧
+    pub tags: Vec<String>,
+    /// レーティング
+    pub rating: Option<u8>,
+}
+
+/// エクスポートの結果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportResult {
+    /// 書き出したファイルのパス
+    pub path: String,
+    /// 書き出した画像数
+    pub count: usize,
+}
+
+/// CSVの1フィールドをエスケープする
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 画像一覧をCSV文字列に変換する（タグは`;`区切り）
+fn to_csv(records: &[ExportRecord]) -> String {
+    let mut csv = String::from("path,name,size,modified,extension,tags,rating\n");
+    for record in records {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            csv_field(&record.image.path),
+            csv_field(&record.image.name),
+            record.image.size,
+            record.image.modified,
+            csv_field(&record.image.extension),
+            csv_field(&record.tags.join(";")),
+            record.rating.map(|r| r.to_string()).unwrap_or_default(),
+        ));
+    }
+    csv
+}
+
+/// 画像一覧を指定形式でファイルに書き出す
+fn write_export(records: &[ExportRecord], format: ExportFormat, dest: &Path) -> Result<(), String> {
+    let content = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(records)
+            .map_err(|e| t!("export.serialize_failed", e))?,
+        ExportFormat::Csv => to_csv(records),
+    };
+
+    safe_write::write_bytes(dest, content.as_bytes(), WriteOptions::default())
+        .map_err(|e| t!("export.write_failed", dest.display(), e))
+}
+
+/// ライブラリのインデックスをJSONまたはCSVで書き出す
+///
+/// インデックスが空の場合は先に設定フォルダをスキャンする。
+/// `selection_id`を指定した場合は選択中の画像のみを書き出す
+#[tauri::command]
+pub async fn export_index(
+    app_handle: AppHandle,
+    format: ExportFormat,
+    dest: String,
+    selection_id: Option<String>,
+) -> Result<ExportResult, String> {
+    let index = LibraryIndex::open(&app_handle)?;
+    let mut images = index.all_images()?;
+
+    if images.is_empty() {
+        images = image::scan_library(&app_handle, None)?.images;
+    }
+
+    images = hidden::filter_images(&app_handle, privacy::filter_images(&app_handle, images));
+    if selection_id.is_some() {
+        let selected: HashSet<String> = selection::paths(&app_handle, selection_id)?.into_iter().collect();
+        images.retain(|image| selected.contains(&image.path));
+    }
+
+    // タグ・レーティングがあれば併せて書き出す
+    let mut metadata = MetadataStore::open(&app_handle)?.all()?;
+    let records: Vec<ExportRecord> = images.into_iter()
+        .map(|image| {
+            let ImageMetadata { tags, rating } = metadata.remove(&image.path).unwrap_or_default();
+            ExportRecord { image, tags, rating }
+        })
+        .collect();
+
+    write_export(&records, format, Path::new(&dest))?;
+
+    Ok(ExportResult {
+        path: dest,
+        count: records.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv_escapes_fields() {
+        let records = vec![ExportRecord {
+            image: ImageInfo {
+                path: "C:\\photos\\a,b.jpg".to_string(),
+                name: "say \"hi\".jpg".to_string(),
+                size: 42,
+                modified: 1700000000,
+                extension: "jpg".to_string(),
+                sensitive: false,
+                bit_depth: None,
+            },
+            tags: vec!["cat".to_string(), "旅行".to_string()],
+            rating: Some(3),
+        }];
+
+        assert_eq!(
+            to_csv(&records),
+            "path,name,size,modified,extension,tags,rating\n\
+             \"C:\\photos\\a,b.jpg\",\"say \"\"hi\"\".jpg\",42,1700000000,jpg,cat;旅行,3\n"
+        );
+    }
+}
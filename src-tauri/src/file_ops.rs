@@ -0,0 +1,123 @@
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+#[cfg(desktop)]
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_opener::OpenerExt;
+use crate::audit;
+use crate::event_bridge::{self, Delivery};
+use crate::i18n::t;
+use crate::index::LibraryIndex;
+use crate::path_guard;
+use crate::selection;
+
+/// 一括処理で失敗した画像
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// 一括処理の結果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BatchResult {
+    /// 成功した画像のパス
+    pub succeeded: Vec<String>,
+    /// 失敗した画像
+    pub failed: Vec<BatchFailure>,
+}
+
+/// 対象のファイルが許可された場所に存在するか確認する
+fn ensure_file(app_handle: &AppHandle, path: &str) -> Result<(), String> {
+    if path_guard::guard(app_handle, path)?.is_file() {
+        Ok(())
+    } else {
+        Err(t!("path.not_found", path))
+    }
+}
+
+/// 既定のアプリで開く
+pub fn open_in_default_app(app_handle: &AppHandle, path: &str) -> Result<(), String> {
+    ensure_file(app_handle, path)?;
+    app_handle.opener().open_path(path, None::<&str>)
+        .map_err(|e| t!("file_ops.open_failed", path, e))
+}
+
+/// ファイルマネージャーで表示する
+pub fn reveal_in_folder(app_handle: &AppHandle, path: &str) -> Result<(), String> {
+    ensure_file(app_handle, path)?;
+    app_handle.opener().reveal_item_in_dir(path)
+        .map_err(|e| t!("file_ops.reveal_failed", path, e))
+}
+
+/// パスをクリップボードにコピーする
+#[cfg(desktop)]
+pub fn copy_path(app_handle: &AppHandle, path: &str) -> Result<(), String> {
+    app_handle.clipboard().write_text(path)
+        .map_err(|e| t!("file_ops.copy_failed", e))
+}
+
+/// モバイルではクリップボードのプラグインを登録しないためコピーできない
+#[cfg(mobile)]
+pub fn copy_path(_app_handle: &AppHandle, _path: &str) -> Result<(), String> {
+    Err(t!("file_ops.clipboard_unsupported"))
+}
+
+/// ゴミ箱へ移動し、インデックスからも削除して`image-deleted`を通知する
+pub fn move_to_trash(app_handle: &AppHandle, path: &str) -> Result<(), String> {
+    ensure_file(app_handle, path)?;
+    trash::delete(path).map_err(|e| t!("file_ops.trash_failed", path, e))?;
+
+    if let Err(e) = LibraryIndex::open(app_handle).and_then(|index| index.remove(path)) {
+        tracing::warn!("インデックスからの削除に失敗しました: {}", e);
+    }
+    tracing::info!("ゴミ箱へ移動しました: {}", path);
+
+    event_bridge::emit(app_handle, "image-deleted", Delivery::Batch, path);
+    Ok(())
+}
+
+/// 画像を既定のアプリで開く
+#[tauri::command]
+pub fn open_image_externally(app_handle: AppHandle, path: String) -> Result<(), String> {
+    open_in_default_app(&app_handle, &path)
+}
+
+/// 画像をファイルマネージャーで表示する
+#[tauri::command]
+pub fn reveal_image(app_handle: AppHandle, path: String) -> Result<(), String> {
+    reveal_in_folder(&app_handle, &path)
+}
+
+/// 画像のパスをクリップボードにコピーする
+#[tauri::command]
+pub fn copy_image_path(app_handle: AppHandle, path: String) -> Result<(), String> {
+    copy_path(&app_handle, &path)
+}
+
+/// 画像をゴミ箱へ移動する
+#[tauri::command]
+pub fn trash_image(app_handle: AppHandle, path: String) -> Result<(), String> {
+    let result = move_to_trash(&app_handle, &path);
+    audit::complete(&app_handle, "trash_image", &result);
+    result
+}
+
+/// 選択中の画像をまとめてゴミ箱へ移動する（失敗した画像があっても続行する）
+#[tauri::command]
+pub async fn trash_selection(app_handle: AppHandle, selection_id: Option<String>) -> Result<BatchResult, String> {
+    let mut batch = BatchResult::default();
+    for path in selection::paths(&app_handle, selection_id)? {
+        match move_to_trash(&app_handle, &path) {
+            Ok(()) => batch.succeeded.push(path),
+            Err(error) => batch.failed.push(BatchFailure { path, error }),
+        }
+    }
+    selection::forget(&app_handle, &batch.succeeded);
+
+    let result = match batch.failed.first() {
+        Some(failure) if batch.succeeded.is_empty() => Err(failure.error.clone()),
+        _ => Ok(()),
+    };
+    audit::complete(&app_handle, "trash_selection", &result);
+    result.map(|_| batch)
+}
@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, State};
+use crate::collation::Collation;
+use crate::config;
+use crate::i18n::t;
+use crate::navigation::{self, NavigationFilter, NavigationSource, NavigationState, NavigationSummary, SortOrder};
+
+/// 名前を付けて保存した絞り込み条件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FilterPreset {
+    pub name: String,
+    pub filter: NavigationFilter,
+}
+
+/// 保存先のパスを取得する
+fn get_presets_path(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("filter_presets.json")
+}
+
+/// 保存済みの条件を読み込む（未保存・読み込めない場合は空）
+fn load(app_handle: &AppHandle) -> Vec<FilterPreset> {
+    fs::read_to_string(get_presets_path(app_handle))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(app_handle: &AppHandle, presets: &[FilterPreset]) -> Result<(), String> {
+    let path = get_presets_path(app_handle);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(presets)
+        .map_err(|e| t!("filter_preset.save_failed", e))?;
+    fs::write(&path, json).map_err(|e| t!("filter_preset.save_failed", e))
+}
+
+/// 同じ名前の条件があれば置き換え、なければ末尾に加える
+fn upsert(presets: &mut Vec<FilterPreset>, preset: FilterPreset) {
+    match presets.iter_mut().find(|existing| existing.name == preset.name) {
+        Some(existing) => existing.filter = preset.filter,
+        None => presets.push(preset),
+    }
+}
+
+/// 絞り込み条件に名前を付けて保存する（同じ名前の条件は上書きする）
+#[tauri::command]
+pub async fn save_filter_preset(app_handle: AppHandle, name: String, filter: NavigationFilter) -> Result<Vec<FilterPreset>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(t!("filter_preset.empty_name"));
+    }
+    let mut presets = load(&app_handle);
+    upsert(&mut presets, FilterPreset { name, filter });
+    save(&app_handle, &presets)?;
+    Ok(presets)
+}
+
+/// 保存済みの絞り込み条件の一覧を取得する
+#[tauri::command]
+pub async fn list_filter_presets(app_handle: AppHandle) -> Result<Vec<FilterPreset>, String> {
+    Ok(load(&app_handle))
+}
+
+/// 保存済みの絞り込み条件を削除する
+#[tauri::command]
+pub async fn delete_filter_preset(app_handle: AppHandle, name: String) -> Result<Vec<FilterPreset>, String> {
+    let mut presets = load(&app_handle);
+    let before = presets.len();
+    presets.retain(|preset| preset.name != name);
+    if presets.len() == before {
+        return Err(t!("filter_preset.not_found", name));
+    }
+    save(&app_handle, &presets)?;
+    Ok(presets)
+}
+
+/// 保存済みの絞り込み条件でナビゲーション対象を設定する（`source`を省略するとライブラリ全体）
+#[tauri::command]
+pub async fn apply_filter_preset(
+    app_handle: AppHandle,
+    state: State<'_, NavigationState>,
+    name: String,
+    source: Option<NavigationSource>,
+    sort: Option<SortOrder>,
+    collation: Option<Collation>,
+    wrap: Option<bool>,
+) -> Result<NavigationSummary, String> {
+    let preset = load(&app_handle)
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| t!("filter_preset.not_found", name))?;
+    let source = source.unwrap_or(NavigationSource::Library);
+    navigation::set_navigation(&app_handle, &state, source, preset.filter, sort, collation, wrap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upsert_replaces_same_name() {
+        let raw = NavigationFilter { extensions: vec!["cr3".to_string(), "nef".to_string()], ..Default::default() };
+        let large = NavigationFilter { min_size: Some(10_000_000), ..Default::default() };
+        let mut presets = Vec::new();
+        upsert(&mut presets, FilterPreset { name: "RAW".to_string(), filter: raw });
+        upsert(&mut presets, FilterPreset { name: "大きい画像".to_string(), filter: large.clone() });
+        upsert(&mut presets, FilterPreset { name: "RAW".to_string(), filter: large });
+
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].name, "RAW");
+        assert!(presets[0].filter.extensions.is_empty());
+        assert_eq!(presets[0].filter.min_size, Some(10_000_000));
+    }
+}
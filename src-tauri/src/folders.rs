@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::config::{self, ResourceConfig};
+use crate::hidden;
+use crate::i18n::t;
+use crate::image::{self, ImageInfo};
+use crate::index::LibraryIndex;
+use crate::path_guard;
+use crate::privacy;
+
+/// 階層の既定の深さ
+const DEFAULT_TREE_DEPTH: usize = 2;
+
+/// フォルダツリーの1ノード
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FolderNode {
+    /// フォルダの絶対パス
+    pub path: String,
+    /// フォルダ名
+    pub name: String,
+    /// 直下の画像数（インデックスから集計）
+    pub image_count: usize,
+    /// サブフォルダを含む画像数（インデックスから集計）
+    pub total_count: usize,
+    /// サブフォルダ
+    pub children: Vec<FolderNode>,
+    /// 深さの上限により省略したサブフォルダがあるか
+    pub has_more: bool,
+}
+
+/// 画像をフォルダごとに数える
+pub(crate) fn count_by_dir(images: &[ImageInfo]) -> HashMap<PathBuf, usize> {
+    let mut counts = HashMap::new();
+    for image in images {
+        if let Some(parent) = Path::new(&image.path).parent() {
+            *counts.entry(parent.to_path_buf()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// 直下のサブフォルダを名前順で取得する（隠しフォルダは除く）
+pub(crate) fn sub_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .filter(|path| !folder_name(path).starts_with('.'))
+        .collect();
+    dirs.sort_by_key(|path| folder_name(path).to_lowercase());
+    dirs
+}
+
+/// フォルダ名を取得する（ルートではパス全体）
+pub(crate) fn folder_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// 指定フォルダ以下のノードを組み立てる（プライバシーモード中に隠すフォルダは含めない）
+fn build_node(dir: &Path, counts: &HashMap<PathBuf, usize>, depth: usize, hidden: &[String]) -> FolderNode {
+    let children_dirs: Vec<PathBuf> = sub_dirs(dir)
+        .into_iter()
+        .filter(|child| !privacy::is_hidden(&child.to_string_lossy(), hidden))
+        .collect();
+    let has_more = depth == 0 && !children_dirs.is_empty();
+    let children = if depth == 0 {
+        Vec::new()
+    } else {
+        children_dirs.iter().map(|child| build_node(child, counts, depth - 1, hidden)).collect()
+    };
+
+    FolderNode {
+        path: dir.to_string_lossy().to_string(),
+        name: folder_name(dir),
+        image_count: counts.get(dir).copied().unwrap_or(0),
+        total_count: counts.iter()
+            .filter(|(path, _)| path.starts_with(dir))
+            .map(|(_, count)| count)
+            .sum(),
+        children,
+        has_more,
+    }
+}
+
+/// 設定フォルダ（または指定フォルダ）以下のフォルダ階層を画像数付きで取得する
+///
+/// 画像数はインデックスから集計するため、未スキャンのフォルダは0になる
+#[tauri::command]
+pub async fn get_folder_tree(
+    app_handle: AppHandle,
+    root: Option<String>,
+    depth: Option<usize>,
+) -> Result<Vec<FolderNode>, String> {
+    let roots = match root {
+        Some(root) => vec![path_guard::guard_dir(&app_handle, &root)?.to_string_lossy().to_string()],
+        None => ResourceConfig::load(&app_handle)?.enabled_folders().cloned().collect(),
+    };
+    let hidden = privacy::hidden_folders(&app_handle);
+
+    let images = LibraryIndex::open(&app_handle)?.all_images()?;
+    let images = hidden::filter_images(&app_handle, privacy::filter_images(&app_handle, image::filter_enabled_folders(&app_handle, images)));
+    let counts = count_by_dir(&images);
+    let depth = depth.unwrap_or(DEFAULT_TREE_DEPTH);
+
+    Ok(roots.iter()
+        .map(PathBuf::from)
+        .filter(|root| root.is_dir() && !privacy::is_hidden(&root.to_string_lossy(), &hidden))
+        .map(|root| build_node(&root, &counts, depth, &hidden))
+        .collect())
+}
+
+/// パンくずリストの1要素
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FolderRef {
+    pub path: String,
+    pub name: String,
+}
+
+/// 兄弟フォルダ
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SiblingFolder {
+    pub path: String,
+    pub name: String,
+    /// フォルダ内の最初の画像（代表画像）
+    pub first_image: Option<ImageInfo>,
+}
+
+/// フォルダの位置情報
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FolderContext {
+    /// 対象フォルダ
+    pub path: String,
+    /// 対象フォルダを含む設定フォルダ（設定外ならNone）
+    pub root: Option<String>,
+    /// 設定フォルダから対象フォルダまでの階層（対象フォルダを含む）
+    pub ancestors: Vec<FolderRef>,
+    /// 対象フォルダを含む兄弟フォルダ（設定フォルダ自体の場合は他の設定フォルダ）
+    pub siblings: Vec<SiblingFolder>,
+}
+
+/// 対象パスを含む最も深い設定フォルダを探す
+fn find_root<'a>(path: &Path, roots: &'a [String]) -> Option<&'a String> {
+    roots.iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| Path::new(root).components().count())
+}
+
+/// 設定フォルダから対象フォルダまでの階層を求める
+fn ancestors(path: &Path, root: Option<&Path>) -> Vec<FolderRef> {
+    let mut chain: Vec<FolderRef> = path.ancestors()
+        .take_while(|ancestor| root.map(|root| ancestor.starts_with(root)).unwrap_or(true))
+        .map(|ancestor| FolderRef {
+            path: ancestor.to_string_lossy().to_string(),
+            name: folder_name(ancestor),
+        })
+        .collect();
+    chain.reverse();
+    chain
+}
+
+/// フォルダ内の最初の画像を取得する（インデックス優先、なければファイル名順で最初の画像）
+pub(crate) fn first_image(dir: &Path, indexed: &HashMap<PathBuf, ImageInfo>) -> Option<ImageInfo> {
+    if let Some(image) = indexed.get(dir) {
+        return Some(image.clone());
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(dir).ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && image::is_image_file(path))
+        .collect();
+    files.sort();
+    files.first().and_then(|path| image::image_info(path).ok())
+}
+
+/// パンくずリストと兄弟フォルダの切り替えに使うフォルダの位置情報を取得する
+#[tauri::command]
+pub async fn get_folder_context(app_handle: AppHandle, path: String) -> Result<FolderContext, String> {
+    let dir = Path::new(&path);
+    if !path_guard::guard(&app_handle, &path)?.is_dir() {
+        return Err(t!("path.not_directory", path));
+    }
+
+    let roots = ResourceConfig::load(&app_handle)?.filters.include;
+    let root = find_root(dir, &roots).cloned();
+
+    // インデックスは新しい順なので、フォルダごとに最初に現れた画像を代表にする
+    let mut indexed: HashMap<PathBuf, ImageInfo> = HashMap::new();
+    if let Ok(images) = LibraryIndex::open(&app_handle).and_then(|index| index.all_images()) {
+        for image in hidden::filter_images(&app_handle, images) {
+            if let Some(parent) = Path::new(&image.path).parent() {
+                indexed.entry(parent.to_path_buf()).or_insert(image);
+            }
+        }
+    }
+
+    let sibling_dirs = match (&root, dir.parent()) {
+        (Some(root), _) if Path::new(root) == dir => roots.iter().map(PathBuf::from).filter(|r| r.is_dir()).collect(),
+        (_, Some(parent)) => sub_dirs(parent),
+        (_, None) => vec![dir.to_path_buf()],
+    };
+    let hidden = privacy::hidden_folders(&app_handle);
+    let siblings = sibling_dirs.iter()
+        .filter(|sibling| !privacy::is_hidden(&sibling.to_string_lossy(), &hidden))
+        .map(|sibling| SiblingFolder {
+            path: sibling.to_string_lossy().to_string(),
+            name: folder_name(sibling),
+            first_image: first_image(sibling, &indexed),
+        })
+        .collect();
+
+    Ok(FolderContext {
+        ancestors: ancestors(dir, root.as_deref().map(Path::new)),
+        path,
+        root,
+        siblings,
+    })
+}
+
+/// ピン留めしたフォルダ
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PinnedFolder {
+    pub path: String,
+    pub name: String,
+    /// サブフォルダを含む画像数（インデックスから集計）
+    pub total_count: usize,
+    /// フォルダが今も存在するか（取り外したドライブ上のフォルダはfalse）
+    pub exists: bool,
+}
+
+/// ピン留めの保存先のパスを取得する
+fn get_pins_path(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("pinned_folders.json")
+}
+
+/// ピン留めしたフォルダのパスをピン留めした順に読み込む
+fn load_pins(app_handle: &AppHandle) -> Vec<String> {
+    fs::read_to_string(get_pins_path(app_handle))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_pins(app_handle: &AppHandle, pins: &[String]) -> Result<(), String> {
+    let path = get_pins_path(app_handle);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(pins)
+        .map_err(|e| t!("folders.pins_save_failed", e))?;
+    fs::write(&path, json).map_err(|e| t!("folders.pins_save_failed", e))
+}
+
+/// ピン留めの一覧に加える（すでにあれば順番を変えない）
+fn add_pin(pins: &mut Vec<String>, path: String) {
+    if !pins.contains(&path) {
+        pins.push(path);
+    }
+}
+
+/// ピン留めしたフォルダを画像数付きで組み立てる（非表示のフォルダは除く）
+fn pinned_folders(app_handle: &AppHandle, pins: &[String]) -> Result<Vec<PinnedFolder>, String> {
+    let images = hidden::filter_images(app_handle, LibraryIndex::open(app_handle)?.all_images()?);
+    let counts = count_by_dir(&images);
+    let hidden = privacy::hidden_folders(app_handle);
+    Ok(pins.iter()
+        .filter(|pin| !privacy::is_hidden(pin, &hidden))
+        .map(|pin| {
+            let dir = Path::new(pin);
+            PinnedFolder {
+                path: pin.clone(),
+                name: folder_name(dir),
+                total_count: counts.iter()
+                    .filter(|(path, _)| path.starts_with(dir))
+                    .map(|(_, count)| count)
+                    .sum(),
+                exists: dir.is_dir(),
+            }
+        })
+        .collect())
+}
+
+/// よく開くフォルダをピン留めする（ピン留めした順に並ぶ）
+#[tauri::command]
+pub async fn pin_folder(app_handle: AppHandle, path: String) -> Result<Vec<PinnedFolder>, String> {
+    if !path_guard::guard(&app_handle, &path)?.is_dir() {
+        return Err(t!("path.not_directory", path));
+    }
+    let mut pins = load_pins(&app_handle);
+    add_pin(&mut pins, path);
+    save_pins(&app_handle, &pins)?;
+    pinned_folders(&app_handle, &pins)
+}
+
+/// フォルダのピン留めを外す（存在しなくなったフォルダも外せる）
+#[tauri::command]
+pub async fn unpin_folder(app_handle: AppHandle, path: String) -> Result<Vec<PinnedFolder>, String> {
+    let mut pins = load_pins(&app_handle);
+    pins.retain(|pin| *pin != path);
+    save_pins(&app_handle, &pins)?;
+    pinned_folders(&app_handle, &pins)
+}
+
+/// ピン留めしたフォルダの一覧を取得する
+#[tauri::command]
+pub async fn get_pinned_folders(app_handle: AppHandle) -> Result<Vec<PinnedFolder>, String> {
+    pinned_folders(&app_handle, &load_pins(&app_handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_node_counts_and_depth() {
+        let root = std::env::temp_dir().join(format!("poir-folders-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("b").join("deep")).unwrap();
+        fs::create_dir_all(root.join("A")).unwrap();
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+
+        let image = |path: PathBuf| ImageInfo {
+            path: path.to_string_lossy().to_string(),
+            name: folder_name(&path),
+            size: 1,
+            modified: 0,
+            extension: "png".to_string(),
+            sensitive: false,
+            bit_depth: None,
+        };
+        let images = vec![
+            image(root.join("top.png")),
+            image(root.join("b").join("1.png")),
+            image(root.join("b").join("deep").join("2.png")),
+        ];
+        let node = build_node(&root, &count_by_dir(&images), 1, &[]);
+
+        assert_eq!(node.image_count, 1);
+        assert_eq!(node.total_count, 3);
+        let names: Vec<&str> = node.children.iter().map(|child| child.name.as_str()).collect();
+        assert_eq!(names, vec!["A", "b"]);
+        assert_eq!(node.children[1].total_count, 2);
+        assert!(node.children[1].has_more);
+        assert!(node.children[1].children.is_empty());
+
+        // 隠すフォルダは設定フォルダの中にあってもツリーに含めない
+        let hidden = vec![root.join("b").to_string_lossy().to_string()];
+        let node = build_node(&root, &count_by_dir(&images), 1, &hidden);
+        let names: Vec<&str> = node.children.iter().map(|child| child.name.as_str()).collect();
+        assert_eq!(names, vec!["A"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_add_pin_keeps_order_without_duplicates() {
+        let mut pins = Vec::new();
+        add_pin(&mut pins, "/photos/2023/trip".to_string());
+        add_pin(&mut pins, "/photos/scans".to_string());
+        add_pin(&mut pins, "/photos/2023/trip".to_string());
+        assert_eq!(pins, vec!["/photos/2023/trip", "/photos/scans"]);
+    }
+
+    #[test]
+    fn test_find_root_and_ancestors() {
+        let roots = vec!["/photos".to_string(), "/photos/trips".to_string()];
+        let path = Path::new("/photos/trips/2024/kyoto");
+        let root = find_root(path, &roots).unwrap();
+        assert_eq!(root, "/photos/trips");
+
+        let names: Vec<String> = ancestors(path, Some(Path::new(root))).into_iter().map(|a| a.name).collect();
+        assert_eq!(names, vec!["trips", "2024", "kyoto"]);
+        assert!(find_root(Path::new("/other"), &roots).is_none());
+    }
+}
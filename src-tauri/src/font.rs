@@ -0,0 +1,120 @@
+use std::fs;
+use ::image::{Rgba, RgbaImage};
+use ab_glyph::{point, Font, FontVec, Glyph, PxScale, ScaleFont};
+use crate::i18n::t;
+
+/// フォントを指定しなかったときに探すフォント（日本語を表示できるものを優先する）
+const FONT_CANDIDATES: [&str; 8] = [
+    "C:\\Windows\\Fonts\\meiryo.ttc",
+    "C:\\Windows\\Fonts\\YuGothM.ttc",
+    "C:\\Windows\\Fonts\\arial.ttf",
+    "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc",
+    "/System/Library/Fonts/Helvetica.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/noto/NotoSans-Regular.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+];
+
+/// 長すぎる文字列を切り詰めたときに付ける記号
+const ELLIPSIS: char = '…';
+
+/// フォントを読み込む（指定がなければ候補から最初に見つかったもの）
+pub fn load_font(font_path: Option<&str>) -> Result<FontVec, String> {
+    let candidates: Vec<&str> = match font_path {
+        Some(path) => vec![path],
+        None => FONT_CANDIDATES.to_vec(),
+    };
+    for candidate in candidates {
+        let Ok(data) = fs::read(candidate) else { continue };
+        match FontVec::try_from_vec(data) {
+            Ok(font) => return Ok(font),
+            Err(e) => tracing::warn!("フォントを読み込めません: {} - {}", candidate, e),
+        }
+    }
+    Err(t!("font.not_found", font_path.unwrap_or("")))
+}
+
+/// 1行の文字を並べる（ベースラインは上端からアセントの位置）
+fn layout(font: &FontVec, text: &str, px: f32) -> (Vec<Glyph>, f32) {
+    let scale = PxScale::from(px);
+    let scaled = font.as_scaled(scale);
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut previous = None;
+    for c in text.chars().filter(|c| !c.is_control()) {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        glyphs.push(id.with_scale_and_position(scale, point(caret, scaled.ascent())));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    (glyphs, caret)
+}
+
+/// 1行の文字の幅と高さ
+pub fn measure(font: &FontVec, text: &str, px: f32) -> (u32, u32) {
+    let scaled = font.as_scaled(PxScale::from(px));
+    let (_, width) = layout(font, text, px);
+    (width.ceil() as u32, (scaled.ascent() - scaled.descent()).ceil() as u32)
+}
+
+/// 幅に収まるよう末尾を切り詰める（切り詰めたら`…`を付ける）
+pub fn fit(font: &FontVec, text: &str, px: f32, max_width: u32) -> String {
+    if measure(font, text, px).0 <= max_width {
+        return text.to_string();
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let candidate: String = chars.iter().chain(std::iter::once(&ELLIPSIS)).collect();
+        if measure(font, &candidate, px).0 <= max_width {
+            return candidate;
+        }
+    }
+    String::new()
+}
+
+/// 1画素を重ねる（`over`のアルファに`opacity`を掛けた割合で、透明な下地にも正しく重ねる）
+pub fn blend_pixel(base: Rgba<u8>, over: Rgba<u8>, opacity: f32) -> Rgba<u8> {
+    let alpha = over[3] as f32 / 255.0 * opacity;
+    let base_alpha = base[3] as f32 / 255.0;
+    let out_alpha = alpha + base_alpha * (1.0 - alpha);
+    if out_alpha <= 0.0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+    let mix = |b: u8, o: u8| ((o as f32 * alpha + b as f32 * base_alpha * (1.0 - alpha)) / out_alpha).round() as u8;
+    Rgba([mix(base[0], over[0]), mix(base[1], over[1]), mix(base[2], over[2]), (out_alpha * 255.0).round() as u8])
+}
+
+/// 1行の文字を描画する（`x`・`y`は文字の左上）
+pub fn draw_text(canvas: &mut RgbaImage, font: &FontVec, text: &str, (x, y): (i32, i32), px: f32, color: Rgba<u8>) {
+    let (glyphs, _) = layout(font, text, px);
+    for glyph in glyphs {
+        let Some(outlined) = font.outline_glyph(glyph) else { continue };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|gx, gy, coverage| {
+            let px = x + bounds.min.x as i32 + gx as i32;
+            let py = y + bounds.min.y as i32 + gy as i32;
+            if px < 0 || py < 0 || px as u32 >= canvas.width() || py as u32 >= canvas.height() {
+                return;
+            }
+            let pixel = canvas.get_pixel_mut(px as u32, py as u32);
+            *pixel = blend_pixel(*pixel, color, coverage.clamp(0.0, 1.0));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blend_pixel_over_opaque_and_transparent() {
+        let white = Rgba([255, 255, 255, 255]);
+        assert_eq!(blend_pixel(Rgba([0, 0, 0, 255]), white, 0.5), Rgba([128, 128, 128, 255]));
+        assert_eq!(blend_pixel(Rgba([0, 0, 0, 0]), white, 0.5), Rgba([255, 255, 255, 128]));
+        assert_eq!(blend_pixel(Rgba([10, 20, 30, 255]), Rgba([255, 0, 0, 0]), 1.0), Rgba([10, 20, 30, 255]));
+    }
+}
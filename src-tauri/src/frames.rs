@@ -0,0 +1,285 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use ::image::{DynamicImage, ImageFormat};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::animation;
+use crate::audit;
+use crate::crop;
+use crate::i18n::t;
+use crate::jobs::{self, JobHandle};
+use crate::path_guard;
+use crate::safe_write::{self, WriteOptions};
+use crate::settings::{AppSettings, DecodeLimits};
+
+/// 動画として扱う拡張子（FFmpegで読み込む）
+const VIDEO_EXTENSIONS: [&str; 7] = ["mp4", "m4v", "mov", "webm", "mkv", "avi", "wmv"];
+
+/// 取り出すフレーム
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FrameSelection {
+    /// すべて（`max_frames`まで）
+    #[default]
+    All,
+    /// フレーム番号（0始まり）
+    Indices { indices: Vec<usize> },
+    /// `step`フレームごと
+    Every { step: usize },
+    /// 再生位置（ミリ秒）に表示されているフレーム
+    Times { times_ms: Vec<u64> },
+}
+
+impl FrameSelection {
+    /// 取り出す数が決まっていればその数
+    fn count(&self) -> Option<usize> {
+        match self {
+            FrameSelection::Indices { indices } => Some(indices.len()),
+            FrameSelection::Times { times_ms } => Some(times_ms.len()),
+            _ => None,
+        }
+    }
+
+    /// `start_ms`から`end_ms`まで表示される`index`番目のフレームを取り出すか
+    fn wants(&self, index: usize, start_ms: u64, end_ms: u64) -> bool {
+        match self {
+            FrameSelection::All => true,
+            FrameSelection::Indices { indices } => indices.contains(&index),
+            FrameSelection::Every { step } => index.is_multiple_of((*step).max(1)),
+            FrameSelection::Times { times_ms } => times_ms.iter().any(|time| (start_ms..end_ms.max(start_ms + 1)).contains(time)),
+        }
+    }
+
+    /// これ以降のフレームを取り出すことがないか
+    fn is_done(&self, index: usize, end_ms: u64) -> bool {
+        match self {
+            FrameSelection::Indices { indices } => indices.iter().all(|wanted| *wanted <= index),
+            FrameSelection::Times { times_ms } => times_ms.iter().all(|time| *time < end_ms),
+            _ => false,
+        }
+    }
+}
+
+/// フレームの取り出しの設定
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct FrameExtractOptions {
+    /// 保存先のフォルダ
+    pub dest_dir: String,
+    pub selection: FrameSelection,
+    /// 保存形式（`png`・`jpg`など）
+    pub format: String,
+    /// 取り出すフレーム数の上限
+    pub max_frames: usize,
+}
+
+impl Default for FrameExtractOptions {
+    fn default() -> Self {
+        Self {
+            dest_dir: String::new(),
+            selection: FrameSelection::default(),
+            format: "png".to_string(),
+            max_frames: 500,
+        }
+    }
+}
+
+/// 取り出したフレーム
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExtractedFrame {
+    pub path: String,
+    /// フレーム番号（動画から再生位置で取り出した場合はNone）
+    pub index: Option<usize>,
+    /// 表示が始まる位置（ミリ秒、動画からフレーム番号で取り出した場合はNone）
+    pub time_ms: Option<u64>,
+}
+
+/// フレームの取り出しジョブの結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FrameExtractResult {
+    pub frames: Vec<ExtractedFrame>,
+}
+
+fn is_video_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| VIDEO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// 保存するファイル名（元のファイル名にフレーム番号を付ける）
+fn frame_path(dest_dir: &Path, stem: &str, label: &str, extension: &str) -> PathBuf {
+    dest_dir.join(format!("{}-{}.{}", stem, label, extension))
+}
+
+/// アニメーションGIF・WebPから選んだフレームを保存する（各フレームは画面全体に合成済み）
+fn extract_animation(
+    job: &JobHandle,
+    source: &Path,
+    options: &FrameExtractOptions,
+    format: ImageFormat,
+    stem: &str,
+    limits: &DecodeLimits,
+) -> Result<Vec<ExtractedFrame>, String> {
+    let (frames, _) = animation::open_frames(source, limits)?;
+    let dest_dir = Path::new(&options.dest_dir);
+    let mut extracted = Vec::new();
+    let mut start_ms = 0;
+    for (index, frame) in frames.enumerate() {
+        if job.is_cancelled() {
+            return Err(t!("jobs.cancelled"));
+        }
+        let frame = frame.map_err(|e| t!("animation.decode_failed", e))?;
+        let end_ms = start_ms + animation::delay_ms(&frame).round() as u64;
+        if options.selection.wants(index, start_ms, end_ms) {
+            let dest = frame_path(dest_dir, stem, &format!("{:05}", index), &options.format);
+            let frame = DynamicImage::ImageRgba8(frame.into_buffer());
+            safe_write::write_with(&dest, WriteOptions::default(), |temp| crop::save_as(&frame, temp, format))?;
+            extracted.push(ExtractedFrame { path: dest.to_string_lossy().to_string(), index: Some(index), time_ms: Some(start_ms) });
+            job.progress(extracted.len(), dest.file_name().and_then(|name| name.to_str()));
+        }
+        if extracted.len() >= options.max_frames || options.selection.is_done(index, end_ms) {
+            break;
+        }
+        start_ms = end_ms;
+    }
+    Ok(extracted)
+}
+
+/// FFmpegの`select`フィルター（再生位置で取り出す場合はNone）
+fn select_filter(selection: &FrameSelection) -> Option<String> {
+    match selection {
+        FrameSelection::All => Some("select=1".to_string()),
+        FrameSelection::Indices { indices } => {
+            let terms: Vec<String> = indices.iter().map(|index| format!("eq(n\\,{})", index)).collect();
+            Some(format!("select='{}'", terms.join("+")))
+        },
+        FrameSelection::Every { step } => Some(format!("select='not(mod(n\\,{}))'", (*step).max(1))),
+        FrameSelection::Times { .. } => None,
+    }
+}
+
+fn run_ffmpeg(ffmpeg: &str, args: &[String], source: &Path) -> Result<(), String> {
+    match Command::new(ffmpeg).args(["-v", "error", "-y", "-nostdin"]).args(args).output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(t!("frames.failed", source.display(), String::from_utf8_lossy(&output.stderr).trim())),
+        Err(e) => Err(t!("frames.unavailable", ffmpeg, e)),
+    }
+}
+
+/// 動画から選んだフレームをFFmpegで保存する
+fn extract_video(job: &JobHandle, source: &Path, options: &FrameExtractOptions, stem: &str, ffmpeg: &str) -> Result<Vec<ExtractedFrame>, String> {
+    let dest_dir = Path::new(&options.dest_dir);
+    let input = source.to_string_lossy().to_string();
+
+    let Some(filter) = select_filter(&options.selection) else {
+        // 再生位置ごとにその位置へ移動して1フレームずつ取り出す
+        let FrameSelection::Times { times_ms } = &options.selection else { return Ok(Vec::new()) };
+        let mut extracted = Vec::new();
+        for time in times_ms.iter().take(options.max_frames) {
+            if job.is_cancelled() {
+                return Err(t!("jobs.cancelled"));
+            }
+            let dest = frame_path(dest_dir, stem, &format!("t{}", time), &options.format);
+            let args = [
+                "-ss".to_string(), format!("{}.{:03}", time / 1000, time % 1000),
+                "-i".to_string(), input.clone(),
+                "-frames:v".to_string(), "1".to_string(),
+                dest.to_string_lossy().to_string(),
+            ];
+            run_ffmpeg(ffmpeg, &args, source)?;
+            extracted.push(ExtractedFrame { path: dest.to_string_lossy().to_string(), index: None, time_ms: Some(*time) });
+            job.progress(extracted.len(), dest.file_name().and_then(|name| name.to_str()));
+        }
+        return Ok(extracted);
+    };
+
+    // 連番で書き出してから、元のフレーム番号の名前に付け替える
+    let limit = options.selection.count().unwrap_or(options.max_frames).min(options.max_frames);
+    let pattern = frame_path(dest_dir, &format!("{}-ffmpeg", stem), "%05d", &options.format);
+    let args = [
+        "-i".to_string(), input,
+        "-vf".to_string(), filter,
+        "-vsync".to_string(), "vfr".to_string(),
+        "-frames:v".to_string(), limit.to_string(),
+        "-start_number".to_string(), "0".to_string(),
+        pattern.to_string_lossy().to_string(),
+    ];
+    run_ffmpeg(ffmpeg, &args, source)?;
+
+    let mut indices: Vec<usize> = match &options.selection {
+        FrameSelection::Indices { indices } => indices.clone(),
+        FrameSelection::Every { step } => (0..limit).map(|n| n * (*step).max(1)).collect(),
+        _ => (0..limit).collect(),
+    };
+    indices.sort_unstable();
+    indices.dedup();
+    let mut extracted = Vec::new();
+    for (number, index) in indices.into_iter().enumerate() {
+        let written = frame_path(dest_dir, &format!("{}-ffmpeg", stem), &format!("{:05}", number), &options.format);
+        if !written.exists() {
+            break;
+        }
+        let dest = frame_path(dest_dir, stem, &format!("{:05}", index), &options.format);
+        fs::rename(&written, &dest).map_err(|e| t!("crop.save_failed", dest.display(), e))?;
+        extracted.push(ExtractedFrame { path: dest.to_string_lossy().to_string(), index: Some(index), time_ms: None });
+    }
+    job.progress(extracted.len(), None);
+    Ok(extracted)
+}
+
+fn start(app_handle: &AppHandle, path: &str, options: FrameExtractOptions) -> Result<String, String> {
+    let source = path_guard::guard(app_handle, path)?;
+    if !Path::new(&options.dest_dir).is_dir() {
+        return Err(t!("path.not_directory", options.dest_dir));
+    }
+    let format = crop::parse_format(&options.format).ok_or_else(|| t!("crop.unsupported_format", options.format))?;
+    let stem = source.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let settings = AppSettings::load(app_handle).unwrap_or_default();
+    let total = options.selection.count().unwrap_or(0).min(options.max_frames);
+
+    if is_video_file(&source) {
+        let ffmpeg = settings.video.ffmpeg_path.unwrap_or_else(|| "ffmpeg".to_string());
+        jobs::spawn(app_handle, "frames", total, move |job| {
+            extract_video(job, &source, &options, &stem, &ffmpeg).map(|frames| FrameExtractResult { frames })
+        })
+    } else {
+        let limits = settings.decode_limits;
+        jobs::spawn(app_handle, "frames", total, move |job| {
+            extract_animation(job, &source, &options, format, &stem, &limits).map(|frames| FrameExtractResult { frames })
+        })
+    }
+}
+
+/// アニメーションGIF・WebP・動画から選んだフレームを画像として保存するジョブを開始し、ジョブIDを返す
+///
+/// 動画の読み込みにはFFmpegを使う。進捗と結果は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn extract_frames(app_handle: AppHandle, path: String, options: FrameExtractOptions) -> Result<String, String> {
+    let result = start(&app_handle, &path, options);
+    audit::complete(&app_handle, "extract_frames", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_for_animation_and_ffmpeg() {
+        let times = FrameSelection::Times { times_ms: vec![0, 250] };
+        // 100ミリ秒ずつのフレームでは0番目と2番目が該当し、2番目で終わる
+        let wanted: Vec<usize> = (0..5).filter(|i| times.wants(*i, *i as u64 * 100, (*i as u64 + 1) * 100)).collect();
+        assert_eq!(wanted, vec![0, 2]);
+        assert!(!times.is_done(1, 200));
+        assert!(times.is_done(2, 300));
+
+        let every = FrameSelection::Every { step: 3 };
+        assert_eq!((0..7).filter(|i| every.wants(*i, 0, 0)).collect::<Vec<_>>(), vec![0, 3, 6]);
+        assert!(FrameSelection::Indices { indices: vec![1, 4] }.is_done(4, 0));
+
+        assert_eq!(select_filter(&FrameSelection::Indices { indices: vec![1, 4] }).unwrap(), "select='eq(n\\,1)+eq(n\\,4)'");
+        assert_eq!(select_filter(&every).unwrap(), "select='not(mod(n\\,3))'");
+        assert_eq!(select_filter(&times), None);
+    }
+}
@@ -0,0 +1,298 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use ::image::{GenericImageView, ImageFormat};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::album::AlbumStore;
+use crate::audit;
+use crate::crop;
+use crate::export::ExportFormat;
+use crate::hidden;
+use crate::i18n::t;
+use crate::jobs::{self, JobHandle};
+use crate::lock;
+use crate::manifest;
+use crate::path_guard;
+use crate::preview;
+use crate::privacy;
+use crate::safe_write::{self, WriteOptions};
+
+/// ギャラリーに載せる画像
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GallerySource {
+    /// 指定した画像（この順に並べる）
+    Paths(Vec<String>),
+    /// アルバムの画像（アルバムの順に並べる）
+    Album(String),
+}
+
+/// ギャラリーの書き出しの設定
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct GalleryOptions {
+    /// ページのタイトル（省略するとアルバム名、なければ「Gallery」）
+    pub title: Option<String>,
+    /// 一覧の縮小画像の長辺
+    pub thumbnail_size: u32,
+    /// 拡大表示する画像の長辺（JPEGに変換するため撮影情報や位置情報は含まれない）
+    pub max_image_size: u32,
+    /// 変換せずに元のファイルをそのまま複製する（撮影情報も含まれる）
+    pub copy_originals: bool,
+    /// 書き出したファイルの目録（サイズとSHA-256）を併せて書き出す形式（省略すると書き出さない）
+    pub manifest: Option<ExportFormat>,
+}
+
+impl Default for GalleryOptions {
+    fn default() -> Self {
+        Self {
+            title: None,
+            thumbnail_size: 320,
+            max_image_size: 2048,
+            copy_originals: false,
+            manifest: None,
+        }
+    }
+}
+
+/// ギャラリーの書き出しジョブの結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GalleryResult {
+    /// 書き出した`index.html`のパス
+    pub path: String,
+    pub images: usize,
+    /// 読み込めずに飛ばした画像
+    pub skipped: Vec<String>,
+    /// 書き出した目録のパス
+    pub manifest: Option<String>,
+}
+
+/// ページに並べる画像1枚
+#[derive(Debug, Clone, PartialEq)]
+struct GalleryItem {
+    /// `index.html`からの相対パス
+    image: String,
+    thumbnail: String,
+    /// 元のファイル名
+    name: String,
+    width: u32,
+    height: u32,
+}
+
+/// HTMLの本文・属性値に埋め込めるようにする
+fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// ギャラリーのページを組み立てる（外部のファイルを読み込まずに表示できるよう、CSSとスクリプトは埋め込む）
+fn render_html(title: &str, items: &[GalleryItem]) -> String {
+    let title = escape_html(title);
+    let figures: String = items.iter()
+        .map(|item| format!(
+            "<a href=\"{image}\" title=\"{name}\"><img src=\"{thumbnail}\" alt=\"{name}\" loading=\"lazy\" data-width=\"{width}\" data-height=\"{height}\"></a>\n",
+            image = escape_html(&item.image),
+            thumbnail = escape_html(&item.thumbnail),
+            name = escape_html(&item.name),
+            width = item.width,
+            height = item.height,
+        ))
+        .collect();
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="generator" content="poir-viewer">
+<title>{title}</title>
+<style>
+body {{ margin: 0; background: #111; color: #eee; font-family: sans-serif; }}
+h1 {{ font-size: 1.4em; font-weight: normal; margin: 16px; }}
+main {{ display: grid; grid-template-columns: repeat(auto-fill, minmax(180px, 1fr)); gap: 8px; padding: 0 16px 16px; }}
+main a {{ display: block; aspect-ratio: 1; background: #222; }}
+main img {{ width: 100%; height: 100%; object-fit: cover; }}
+#viewer {{ position: fixed; inset: 0; display: none; align-items: center; justify-content: center; background: rgba(0, 0, 0, 0.92); }}
+#viewer.open {{ display: flex; }}
+#viewer img {{ max-width: 100vw; max-height: 100vh; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<main>
+{figures}</main>
+<div id="viewer"><img alt=""></div>
+<script>
+const links = Array.from(document.querySelectorAll('main a'));
+const viewer = document.getElementById('viewer');
+const image = viewer.querySelector('img');
+let current = -1;
+function show(index) {{
+  current = (index + links.length) % links.length;
+  image.src = links[current].getAttribute('href');
+  image.alt = links[current].title;
+  viewer.classList.add('open');
+}}
+function close() {{ viewer.classList.remove('open'); current = -1; }}
+links.forEach((link, index) => link.addEventListener('click', event => {{ event.preventDefault(); show(index); }}));
+viewer.addEventListener('click', close);
+document.addEventListener('keydown', event => {{
+  if (current < 0) return;
+  if (event.key === 'Escape') close();
+  if (event.key === 'ArrowRight') show(current + 1);
+  if (event.key === 'ArrowLeft') show(current - 1);
+}});
+</script>
+</body>
+</html>
+"#)
+}
+
+/// 画像1枚分の縮小画像と拡大表示用の画像を書き出す
+fn export_item(app_handle: &AppHandle, source: &Path, number: usize, dest_dir: &Path, options: &GalleryOptions) -> Result<GalleryItem, String> {
+    let (image, _) = preview::open_oriented(app_handle, source)?;
+    let stem = format!("{:04}", number);
+
+    let thumbnail = format!("thumbs/{}.jpg", stem);
+    let size = options.thumbnail_size.max(1);
+    let small = image.thumbnail(size, size);
+    safe_write::write_with(&dest_dir.join(&thumbnail), WriteOptions::default(), |temp| crop::save_as(&small, temp, ImageFormat::Jpeg))?;
+
+    let (image_path, (width, height)) = if options.copy_originals {
+        let extension = source.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let name = format!("images/{}.{}", stem, extension);
+        safe_write::write_with(&dest_dir.join(&name), WriteOptions::default(), |temp| {
+            fs::copy(source, temp).map(|_| ()).map_err(|e| t!("gallery.write_failed", dest_dir.join(&name).display(), e))
+        })?;
+        (name, image.dimensions())
+    } else {
+        let name = format!("images/{}.jpg", stem);
+        let max = options.max_image_size.max(1);
+        let resized = if image.width().max(image.height()) > max { image.thumbnail(max, max) } else { image };
+        safe_write::write_with(&dest_dir.join(&name), WriteOptions::default(), |temp| crop::save_as(&resized, temp, ImageFormat::Jpeg))?;
+        (name, resized.dimensions())
+    };
+
+    Ok(GalleryItem {
+        image: image_path,
+        thumbnail,
+        name: source.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+        width,
+        height,
+    })
+}
+
+fn run(
+    app_handle: &AppHandle,
+    job: &JobHandle,
+    sources: &[PathBuf],
+    title: &str,
+    dest_dir: &Path,
+    options: &GalleryOptions,
+) -> Result<GalleryResult, String> {
+    for sub in ["images", "thumbs"] {
+        fs::create_dir_all(dest_dir.join(sub)).map_err(|e| t!("common.dir_create_failed", dest_dir.join(sub).display(), e))?;
+    }
+
+    let mut items = Vec::new();
+    let mut skipped = Vec::new();
+    for (done, source) in sources.iter().enumerate() {
+        if job.is_cancelled() {
+            return Err(t!("jobs.cancelled"));
+        }
+        match export_item(app_handle, source, items.len() + 1, dest_dir, options) {
+            Ok(item) => items.push(item),
+            Err(e) => {
+                tracing::warn!("ギャラリーに追加できない画像を飛ばしました: {} - {}", source.display(), e);
+                skipped.push(source.to_string_lossy().to_string());
+            },
+        }
+        job.progress(done + 1, source.file_name().and_then(|name| name.to_str()));
+    }
+
+    let index = dest_dir.join("index.html");
+    safe_write::write_bytes(&index, render_html(title, &items).as_bytes(), WriteOptions::default())
+        .map_err(|e| t!("gallery.write_failed", index.display(), e))?;
+    tracing::info!("ギャラリーを書き出しました: {} ({}枚)", index.display(), items.len());
+    let manifest = match options.manifest {
+        Some(format) => Some(manifest::write(dest_dir, format, None)?.path),
+        None => None,
+    };
+    Ok(GalleryResult { path: index.to_string_lossy().to_string(), images: items.len(), skipped, manifest })
+}
+
+/// 書き出す画像のパスとページのタイトルを求める
+fn resolve_source(app_handle: &AppHandle, source: GallerySource, title: Option<String>) -> Result<(Vec<PathBuf>, String), String> {
+    match source {
+        GallerySource::Paths(paths) => {
+            let sources = paths.iter()
+                .map(|path| path_guard::guard(app_handle, path))
+                .collect::<Result<Vec<PathBuf>, String>>()?;
+            Ok((sources, title.unwrap_or_else(|| "Gallery".to_string())))
+        },
+        GallerySource::Album(name) => {
+            lock::ensure_album_unlocked(app_handle, &name)?;
+            let images = AlbumStore::open(app_handle)?.images(&name)?;
+            let images = hidden::filter_images(app_handle, privacy::filter_images(app_handle, images));
+            let sources = images.into_iter().map(|image| PathBuf::from(image.path)).collect();
+            Ok((sources, title.unwrap_or(name)))
+        },
+    }
+}
+
+fn start(app_handle: &AppHandle, source: GallerySource, dest_dir: String, options: GalleryOptions) -> Result<String, String> {
+    let (sources, title) = resolve_source(app_handle, source, options.title.clone())?;
+    if sources.is_empty() {
+        return Err(t!("gallery.empty"));
+    }
+    let dest = PathBuf::from(&dest_dir);
+    if dest.parent().is_some_and(|parent| !parent.as_os_str().is_empty() && !parent.is_dir()) {
+        return Err(t!("path.not_directory", dest.display()));
+    }
+    // 既存のファイルを上書きしないよう、空のフォルダか新しいフォルダにのみ書き出す
+    if fs::read_dir(&dest).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(t!("gallery.dest_not_empty", dest_dir));
+    }
+
+    let handle = app_handle.clone();
+    jobs::spawn(app_handle, "gallery", sources.len(), move |job| run(&handle, job, &sources, &title, &dest, &options))
+}
+
+/// アプリがなくてもブラウザで見られる、縮小画像付きのHTMLギャラリーを書き出すジョブを開始し、ジョブIDを返す
+///
+/// 進捗と結果は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn export_static_gallery(
+    app_handle: AppHandle,
+    paths_or_album: GallerySource,
+    dest_dir: String,
+    options: Option<GalleryOptions>,
+) -> Result<String, String> {
+    let result = start(&app_handle, paths_or_album, dest_dir, options.unwrap_or_default());
+    audit::complete(&app_handle, "export_static_gallery", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html_escapes_names() {
+        let items = [GalleryItem {
+            image: "images/0001.jpg".to_string(),
+            thumbnail: "thumbs/0001.jpg".to_string(),
+            name: "<script>\"a\" & b.jpg".to_string(),
+            width: 800,
+            height: 600,
+        }];
+        let html = render_html("Tom & Jerry", &items);
+        assert!(html.contains("<title>Tom &amp; Jerry</title>"));
+        assert!(html.contains("href=\"images/0001.jpg\" title=\"&lt;script&gt;&quot;a&quot; &amp; b.jpg\""));
+        assert!(html.contains("src=\"thumbs/0001.jpg\""));
+        assert!(!html.contains("<script>\"a\""));
+        assert_eq!(html.matches("<a href=").count(), 1);
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{params, Connection};
+use tauri::AppHandle;
+use crate::audit;
+use crate::event_bridge::{self, Delivery};
+use crate::i18n::t;
+use crate::image::ImageInfo;
+use crate::index::LibraryIndex;
+
+/// 一覧から除外する画像の保存先（インデックスと同じSQLiteファイル）
+pub struct HiddenStore {
+    conn: Connection,
+}
+
+impl HiddenStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("hidden.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS hidden_images (
+                path TEXT PRIMARY KEY,
+                hidden_at INTEGER NOT NULL
+            );"
+        ).map_err(|e| t!("hidden.init_failed", e))
+    }
+
+    /// 画像を非表示にする
+    pub fn hide(&self, path: &str, hidden_at: u64) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO hidden_images (path, hidden_at) VALUES (?1, ?2)",
+            params![path, hidden_at as i64],
+        ).map(|_| ()).map_err(|e| t!("hidden.update_failed", path, e))
+    }
+
+    /// 画像の非表示を解除する
+    pub fn unhide(&self, path: &str) -> Result<(), String> {
+        self.conn.execute("DELETE FROM hidden_images WHERE path = ?1", params![path])
+            .map(|_| ())
+            .map_err(|e| t!("hidden.update_failed", path, e))
+    }
+
+    /// 非表示の画像のパスを新しく非表示にした順で取得する
+    pub fn paths(&self) -> Result<Vec<String>, String> {
+        let mut stmt = self.conn.prepare("SELECT path FROM hidden_images ORDER BY hidden_at DESC, rowid DESC")
+            .map_err(|e| t!("hidden.read_failed", e))?;
+        stmt.query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| t!("hidden.read_failed", e))
+    }
+}
+
+/// 非表示の画像のパスを取得する（読み込めなければ空）
+pub fn hidden_paths(app_handle: &AppHandle) -> HashSet<String> {
+    HiddenStore::open(app_handle)
+        .and_then(|store| store.paths())
+        .map(|paths| paths.into_iter().collect())
+        .map_err(|e| tracing::warn!("非表示リストを読み込めませんでした: {}", e))
+        .unwrap_or_default()
+}
+
+/// 非表示の画像を取り除く
+pub fn filter_images(app_handle: &AppHandle, images: Vec<ImageInfo>) -> Vec<ImageInfo> {
+    let hidden = hidden_paths(app_handle);
+    if hidden.is_empty() {
+        return images;
+    }
+    images.into_iter().filter(|image| !hidden.contains(&image.path)).collect()
+}
+
+/// 非表示リストの変更を通知する
+fn notify(app_handle: &AppHandle, path: &str, hidden: bool) {
+    event_bridge::emit(app_handle, "hidden-images-changed", Delivery::Batch, serde_json::json!({ "path": path, "hidden": hidden }));
+}
+
+/// 画像をファイルはそのままに、すべての一覧から除外する
+#[tauri::command]
+pub async fn hide_image(app_handle: AppHandle, path: String) -> Result<(), String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    let result = HiddenStore::open(&app_handle).and_then(|store| store.hide(&path, now));
+    audit::complete(&app_handle, "hide_image", &result);
+    result?;
+    notify(&app_handle, &path, true);
+    Ok(())
+}
+
+/// 画像の非表示を解除する
+#[tauri::command]
+pub async fn unhide_image(app_handle: AppHandle, path: String) -> Result<(), String> {
+    let result = HiddenStore::open(&app_handle).and_then(|store| store.unhide(&path));
+    audit::complete(&app_handle, "unhide_image", &result);
+    result?;
+    notify(&app_handle, &path, false);
+    Ok(())
+}
+
+/// 非表示にしている画像のパス一覧を取得する
+#[tauri::command]
+pub async fn list_hidden_images(app_handle: AppHandle) -> Result<Vec<String>, String> {
+    HiddenStore::open(&app_handle)?.paths()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hide_and_unhide() {
+        let path = std::env::temp_dir().join(format!("poir-hidden-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let store = HiddenStore::open_at(&path).unwrap();
+
+        store.hide("/photos/screenshot.png", 1).unwrap();
+        store.hide("/photos/junk.jpg", 2).unwrap();
+        store.hide("/photos/screenshot.png", 3).unwrap();
+        assert_eq!(store.paths().unwrap(), vec!["/photos/junk.jpg", "/photos/screenshot.png"]);
+
+        store.unhide("/photos/junk.jpg").unwrap();
+        assert_eq!(store.paths().unwrap(), vec!["/photos/screenshot.png"]);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,454 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use serde::{Serialize, Deserialize};
+
+/// バックエンドが返すメッセージの言語
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    Ja,
+    En,
+}
+
+impl Locale {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Locale::En,
+            _ => Locale::Ja,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Locale::Ja => 0,
+            Locale::En => 1,
+        }
+    }
+
+    /// `ja-JP`や`en_US.UTF-8`のようなロケール文字列から判定する（日本語以外は英語）
+    pub fn from_tag(tag: &str) -> Self {
+        if tag.to_lowercase().starts_with("ja") {
+            Locale::Ja
+        } else {
+            Locale::En
+        }
+    }
+}
+
+/// 未設定を表す値（初回参照時にOSのロケールから決定する）
+const UNSET: u8 = u8::MAX;
+
+static CURRENT: AtomicU8 = AtomicU8::new(UNSET);
+
+/// メッセージキーと各言語の文言（`{}`は引数で順に置き換える）
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("common.dir_create_failed", "ディレクトリの作成に失敗 ({}): {}", "Failed to create directory ({}): {}"),
+    ("common.dir_read_failed", "ディレクトリの読み取りに失敗: {} - {}", "Failed to read directory: {} - {}"),
+    ("common.lock_failed", "{}のロックに失敗: {}", "Failed to lock {}: {}"),
+    ("config.default_serialize_failed", "デフォルト設定のシリアライズに失敗: {}", "Failed to serialize default config: {}"),
+    ("config.create_failed", "設定ファイルの作成に失敗 ({}): {}", "Failed to create config file ({}): {}"),
+    ("config.read_failed", "設定ファイルの読み込みに失敗: {}", "Failed to read config file: {}"),
+    ("config.parse_failed", "JSONのパースに失敗: {}", "Failed to parse JSON: {}"),
+    ("config.serialize_failed", "設定のシリアライズに失敗: {}", "Failed to serialize config: {}"),
+    ("config.save_failed", "設定ファイルの保存に失敗: {}", "Failed to save config file: {}"),
+    ("config.path_to_string_failed", "パスを文字列に変換できません", "Failed to convert path to string"),
+    ("config.status_emit_failed", "設定状態の通知に失敗: {}", "Failed to notify config status: {}"),
+    ("config.pick_folder_title", "画像フォルダを選択", "Choose an image folder"),
+    ("config.pick_folder_failed", "選択したフォルダのパスを取得できません: {}", "Cannot get the path of the selected folder: {}"),
+    ("config.folder_not_configured", "設定されていないフォルダです: {}", "Folder is not configured: {}"),
+    ("config.required_emit_failed", "設定要求の通知に失敗: {}", "Failed to notify config requirement: {}"),
+    ("path.not_found", "パスが存在しません: {}", "Path does not exist: {}"),
+    ("path.not_directory", "パスはディレクトリではありません: {}", "Path is not a directory: {}"),
+    ("path.access_denied", "ディレクトリにアクセスできません: {}", "Cannot access directory: {}"),
+    ("path.empty", "パスが指定されていません", "No path was specified"),
+    ("path.network_offline", "ネットワーク上のフォルダに接続できません: {}", "Cannot reach the network folder: {}"),
+    ("path.outside_library", "設定フォルダの外にはアクセスできません: {}", "Cannot access paths outside the configured folders: {}"),
+    ("path.no_parent", "親フォルダが取得できません: {}", "Cannot determine parent folder: {}"),
+    ("file.read_failed", "ファイルの読み込みに失敗: {} - {}", "Failed to read file: {} - {}"),
+    ("exe.path_failed", "実行ファイルパスの取得に失敗: {}", "Failed to get executable path: {}"),
+    ("exe.no_parent", "実行ファイルの親ディレクトリが存在しません", "Executable has no parent directory"),
+    ("scan.payload_truncated", "応答が大きすぎるため{}件中{}件のみ返しました。続きは{}件目以降を取得してください", "Response too large: returned {1} of {0} images. Fetch the rest starting at offset {2}"),
+    ("scan.no_folders", "画像フォルダが設定されていません", "No image folders are configured"),
+    ("scan.entry_failed", "エントリの読み取りに失敗: {}", "Failed to read directory entry: {}"),
+    ("scan.metadata_failed", "ファイルのメタデータ取得に失敗: {} - {}", "Failed to read file metadata: {} - {}"),
+    ("scan.modified_failed", "更新日時の取得に失敗: {}", "Failed to read modification time: {}"),
+    ("scan.time_conversion_failed", "時間変換エラー: {}", "Time conversion error: {}"),
+    ("diagnostics.folders_ok", "{}個のフォルダはすべて有効です", "All {} folders are valid"),
+    ("diagnostics.folders_invalid", "{}個のフォルダにアクセスできません", "{} folders are not accessible"),
+    ("diagnostics.app_data_writable", "アプリデータに書き込めます: {}", "App data is writable: {}"),
+    ("diagnostics.app_data_not_writable", "アプリデータに書き込めません ({}): {}", "App data is not writable ({}): {}"),
+    ("diagnostics.index_ok", "インデックスは正常です", "Index is healthy"),
+    ("diagnostics.index_corrupted", "インデックスが破損しています。再スキャンしてください", "Index is corrupted. Please rescan"),
+    ("diagnostics.index_consistent", "インデックスの{}件はすべて存在します", "All {} indexed files exist"),
+    ("diagnostics.index_missing", "インデックスの{}件中{}件のファイルが見つかりません", "{1} of {0} indexed files are missing"),
+    ("diagnostics.disk_low", "空き容量が少なくなっています: {} MB", "Disk space is low: {} MB"),
+    ("diagnostics.disk_ok", "空き容量: {} MB", "Free space: {} MB"),
+    ("diagnostics.disk_unknown", "空き容量を取得できません: {}", "Cannot determine free space: {}"),
+    ("audit.name", "コマンド履歴", "command history"),
+    ("metrics.name", "計測値", "metrics"),
+    ("launch.state_name", "起動状態", "launch state"),
+    ("viewer.name", "ビューアウィンドウ", "viewer windows"),
+    ("viewer.create_failed", "ウィンドウの作成に失敗: {}", "Failed to create window: {}"),
+    ("viewer.not_found", "ビューアウィンドウではありません: {}", "Not a viewer window: {}"),
+    ("file_ops.open_failed", "アプリで開けません: {} - {}", "Failed to open with default app: {} - {}"),
+    ("file_ops.reveal_failed", "フォルダを表示できません: {} - {}", "Failed to reveal in folder: {} - {}"),
+    ("file_ops.copy_failed", "クリップボードへのコピーに失敗: {}", "Failed to copy to clipboard: {}"),
+    ("file_ops.trash_failed", "ゴミ箱へ移動できません: {} - {}", "Failed to move to trash: {} - {}"),
+    ("context_menu.name", "コンテキストメニュー", "context menu"),
+    ("context_menu.build_failed", "メニューの表示に失敗: {}", "Failed to show menu: {}"),
+    ("context_menu.open", "既定のアプリで開く", "Open with Default App"),
+    ("context_menu.reveal", "フォルダに表示", "Reveal in Folder"),
+    ("context_menu.copy", "パスをコピー", "Copy Path"),
+    ("context_menu.tag", "タグを編集...", "Edit Tags..."),
+    ("context_menu.trash", "ゴミ箱へ移動", "Move to Trash"),
+    ("print.failed", "印刷に失敗しました: {} ({})", "Printing failed: {} ({})"),
+    ("print.unavailable", "印刷コマンドを実行できません: {}", "Cannot run the print command: {}"),
+    ("print.read_failed", "画像の読み込みに失敗: {} - {}", "Failed to read image: {} - {}"),
+    ("slideshow.name", "スライドショー", "slideshow"),
+    ("slideshow.no_images", "表示できる画像がありません", "No images to show"),
+    ("slideshow.not_running", "スライドショーは実行されていません", "Slideshow is not running"),
+    ("slideshow.monitors_failed", "モニターの一覧を取得できません: {}", "Failed to list monitors: {}"),
+    ("slideshow.monitor_not_found", "モニターが見つかりません: {}", "Monitor not found: {}"),
+    ("slideshow.window_failed", "スライドショーのウィンドウを開けません: {}", "Failed to open the slideshow window: {}"),
+    ("archive.open_failed", "書庫を開けません: {} - {}", "Failed to open archive: {} - {}"),
+    ("archive.entry_failed", "書庫のエントリを読めません: {}", "Failed to read archive entry: {}"),
+    ("archive.entry_not_found", "書庫内に見つかりません: {}", "Not found in archive: {}"),
+    ("archive.unsafe_entry", "書庫の外を指すエントリが含まれています: {}", "Archive contains an entry pointing outside it: {}"),
+    ("archive.too_many_entries", "書庫のエントリが多すぎます: {}件（上限{}件）", "Archive has too many entries: {} (limit {})"),
+    ("archive.too_deep", "書庫内のフォルダが深すぎます: {}（上限{}）", "Archive folder nesting is too deep: {} (limit {})"),
+    ("archive.too_large", "書庫の展開後のサイズが上限（{}バイト）を超えています", "Archive expands beyond the limit of {} bytes"),
+    ("archive.suspicious_entry", "展開後のサイズが異常なエントリです: {}", "Entry has a suspicious decompressed size: {}"),
+    ("archive.write_failed", "書庫の書き込みに失敗: {} - {}", "Failed to write archive: {} - {}"),
+    ("archive.no_images", "画像が含まれていません: {}", "Contains no images: {}"),
+    ("archive.already_exists", "書庫がすでにあります: {}", "Archive already exists: {}"),
+    ("archive.dest_not_empty", "展開先のフォルダが空ではありません: {}", "The destination folder is not empty: {}"),
+    ("archive.extract_failed", "書庫からの取り出しに失敗: {} - {}", "Failed to extract from archive: {} - {}"),
+    ("device.not_found", "端末が接続されていません: {}", "Device is not connected: {}"),
+    ("device.invalid_path", "端末内の画像ではありません: {}", "Not an image on the device: {}"),
+    ("device.copy_failed", "端末からのコピーに失敗: {} - {}", "Failed to copy from the device: {} - {}"),
+    ("screenshot.capture_failed", "画面をキャプチャできません: {}", "Failed to capture the screen: {}"),
+    ("screenshot.no_monitor", "モニターが見つかりません", "No monitor found"),
+    ("screenshot.no_window", "キャプチャできるウィンドウがありません", "No window to capture"),
+    ("screenshot.region_outside", "キャプチャする範囲が画面の外にあります", "The capture area is outside the screen"),
+    ("tone_map.not_hdr", "HDRの画像ではありません: {}", "Not an HDR image: {}"),
+    ("crop.invalid_rect", "切り抜く範囲が画像の外にあります: ({}, {}, {}x{}) / {}x{}", "Crop area is outside the image: ({}, {}, {}x{}) / {}x{}"),
+    ("crop.unsupported_format", "保存できない形式です: {}", "Unsupported output format: {}"),
+    ("crop.overwrite_source", "元の画像には上書きできません: {}", "Cannot overwrite the original image: {}"),
+    ("crop.save_failed", "画像の保存に失敗: {} - {}", "Failed to save image: {} - {}"),
+    ("originals.copy_failed", "編集前のファイルのコピーに失敗: {} - {}", "Failed to copy the original file: {} - {}"),
+    ("originals.not_found", "編集前のファイルが残っていません: {}", "No original file has been kept: {}"),
+    ("originals.remove_failed", "残していたファイルの削除に失敗: {} - {}", "Failed to remove the kept original: {} - {}"),
+    ("safe_write.sync_failed", "ディスクへの書き出しに失敗: {} - {}", "Failed to flush to disk: {} - {}"),
+    ("safe_write.times_failed", "更新日時を元に戻せません: {} - {}", "Failed to restore timestamps: {} - {}"),
+    ("safe_write.write_failed", "一時ファイルの書き込みに失敗: {} - {}", "Failed to write the temporary file: {} - {}"),
+    ("safe_write.permissions_failed", "ファイルの権限を引き継げません: {} - {}", "Failed to carry over file permissions: {} - {}"),
+    ("safe_write.replace_failed", "ファイルの置き換えに失敗: {} - {}", "Failed to replace the file: {} - {}"),
+    ("font.not_found", "使えるフォントが見つかりません: {}", "No usable font found: {}"),
+    ("watermark.overwrite_source", "元の画像と同じフォルダには保存できません: {}", "Cannot save into the folder of the original image: {}"),
+    ("watermark.empty", "透かしの画像か文字を指定してください", "Specify a watermark image or text"),
+    ("jobs.unavailable", "ジョブを開始できません", "Cannot start the job"),
+    ("jobs.not_running", "実行中のジョブではありません: {}", "Not a running job: {}"),
+    ("contact_sheet.empty", "一覧画像に並べる画像がありません", "No images to place on the contact sheet"),
+    ("contact_sheet.too_many", "画像が多すぎます（{}枚、上限は{}枚）", "Too many images ({}; the limit is {})"),
+    ("pdf.encode_failed", "PDFに埋め込む画像の変換に失敗: {}", "Failed to encode an image for the PDF: {}"),
+    ("pdf.save_failed", "PDFの保存に失敗: {} - {}", "Failed to save PDF: {} - {}"),
+    ("pdf_export.empty", "PDFにする画像がありません", "No images to export to PDF"),
+    ("pdf_export.invalid_dest", "保存先は拡張子が.pdfのファイルにしてください: {}", "The destination must be a .pdf file: {}"),
+    ("jobs.cancelled", "ジョブは中止されました", "The job was cancelled"),
+    ("ocr.invalid_lang", "OCRの言語コードが不正です: {}", "Invalid OCR language code: {}"),
+    ("ocr.failed", "文字の読み取りに失敗: {} ({})", "Text recognition failed: {} ({})"),
+    ("ocr.unavailable", "Tesseractを実行できません（{}）: {}", "Cannot run Tesseract ({}): {}"),
+    ("concepts.unavailable", "画像の分類はこのビルドでは使えません（mlフィーチャーが必要です）", "Image classification is not available in this build (requires the ml feature)"),
+    ("concepts.model_not_found", "画像分類のモデルが見つかりません: {}", "Classification model not found: {}"),
+    ("concepts.model_failed", "画像分類のモデルを読み込めません: {} - {}", "Failed to load the classification model: {} - {}"),
+    ("concepts.classify_failed", "画像の分類に失敗: {}", "Image classification failed: {}"),
+    ("barcode.failed", "コードの読み取りに失敗: {} ({})", "Code detection failed: {} ({})"),
+    ("barcode.unavailable", "zbarimgを実行できません（{}）: {}", "Cannot run zbarimg ({}): {}"),
+    ("animation.unsupported", "アニメーションGIF・WebPではありません: {}", "Not an animated GIF or WebP: {}"),
+    ("animation.decode_failed", "フレームの読み込みに失敗: {}", "Failed to decode frame: {}"),
+    ("animation.save_failed", "アニメーションの保存に失敗: {} - {}", "Failed to save animation: {} - {}"),
+    ("animation.invalid_dest", "保存先はGIFファイルにしてください: {}", "Destination must be a GIF file: {}"),
+    ("upscale.unavailable", "超解像による拡大はこのビルドでは使えません（mlフィーチャーが必要です）", "Upscaling is not available in this build (requires the ml feature)"),
+    ("upscale.model_not_found", "超解像モデルが見つかりません: {}", "Upscaling model not found: {}"),
+    ("upscale.model_failed", "超解像モデルを読み込めません: {} - {}", "Failed to load the upscaling model: {} - {}"),
+    ("upscale.failed", "画像の拡大に失敗: {}", "Upscaling failed: {}"),
+    ("upscale.unexpected_output", "超解像モデルの出力の大きさが想定と異なります: {}", "Unexpected upscaling model output size: {}"),
+    ("upscale.invalid_factor", "拡大率は2〜{1}倍で指定してください: {0}", "Scale factor must be between 2 and {1}: {0}"),
+    ("backup.invalid_dest", "バックアップの保存先は拡張子が.zipのファイルにしてください: {}", "The backup destination must be a .zip file: {}"),
+    ("backup.write_failed", "バックアップの書き込みに失敗: {} - {}", "Failed to write backup: {} - {}"),
+    ("backup.index_copy_failed", "インデックスの複製に失敗: {}", "Failed to copy the index: {}"),
+    ("backup.invalid_manifest", "バックアップの書庫ではありません: {}", "Not a backup archive: {}"),
+    ("backup.unsupported_format", "対応していないバックアップの形式です: {}", "Unsupported backup format: {}"),
+    ("backup.unknown_file", "バックアップに不明なファイルが含まれています: {}", "Backup contains an unknown file: {}"),
+    ("backup.invalid_file", "バックアップ内のファイルが壊れています: {} - {}", "Backup contains a corrupt file: {} - {}"),
+    ("sync.not_configured", "同期が有効になっていないか、共有フォルダが設定されていません", "Sync is disabled or no shared folder is configured"),
+    ("sync.invalid_file", "同期ファイルを読み込めません: {} - {}", "Failed to read sync file: {} - {}"),
+    ("sync.unsupported_format", "対応していない同期ファイルの形式です: {} ({})", "Unsupported sync file format: {} ({})"),
+    ("sync.write_failed", "同期ファイルの書き込みに失敗: {} - {}", "Failed to write sync file: {} - {}"),
+    ("gallery.empty", "ギャラリーに載せる画像がありません", "No images to put in the gallery"),
+    ("gallery.dest_not_empty", "書き出し先のフォルダが空ではありません: {}", "The destination folder is not empty: {}"),
+    ("gallery.write_failed", "ギャラリーの書き出しに失敗: {} - {}", "Failed to write gallery: {} - {}"),
+    ("frames.failed", "フレームの取り出しに失敗: {} ({})", "Frame extraction failed: {} ({})"),
+    ("frames.unavailable", "ffmpegを実行できません（{}）: {}", "Cannot run ffmpeg ({}): {}"),
+    ("exif_edit.unsupported", "EXIFを書き換えられるのはJPEGのみです", "Only JPEG files support EXIF editing"),
+    ("exif_edit.corrupt", "JPEGの構造が壊れています", "The JPEG structure is corrupt"),
+    ("exif_edit.invalid_offset", "タイムゾーンは+09:00の形式で指定してください: {}", "Timezone must be in +09:00 format: {}"),
+    ("exif_edit.invalid_gps", "緯度・経度が範囲外です: {}, {}", "Latitude/longitude out of range: {}, {}"),
+    ("exif_edit.too_large", "EXIFが大きすぎます: {}バイト", "EXIF data is too large: {} bytes"),
+    ("exif_edit.write_failed", "EXIFの書き込みに失敗: {}", "Failed to write EXIF: {}"),
+    ("exif_edit.verify_failed", "書き換えたEXIFを読み込めません: {}", "Edited EXIF could not be read back: {}"),
+    ("exif_edit.invalid_orientation", "向きは1〜8で指定してください: {}", "Orientation must be between 1 and 8: {}"),
+    ("edit_queue.name", "保留中の編集", "pending edits"),
+    ("edit_queue.invalid_rotation", "回転は90度単位で指定してください: {}", "Rotation must be a multiple of 90 degrees: {}"),
+    ("edit_queue.empty", "保留中の編集がありません", "There are no pending edits"),
+    ("credentials.invalid_source", "資格情報のソースIDが不正です: {}", "Invalid credential source ID: {}"),
+    ("credentials.keychain_failed", "キーチェーンの操作に失敗: {}", "Keychain operation failed: {}"),
+    ("lock.save_failed", "アプリロックの保存に失敗: {}", "Failed to save app lock: {}"),
+    ("lock.locked", "アプリがロックされています", "The app is locked"),
+    ("lock.album_locked", "アルバムがロックされています: {}", "Album is locked: {}"),
+    ("lock.pin_not_set", "PINが設定されていません", "No PIN is set"),
+    ("lock.state_name", "ロック状態", "lock state"),
+    ("watchdog.timed_out", "{}秒以内に処理が終わりませんでした（バックグラウンドで続行しています）", "The operation did not finish within {} seconds (it continues in the background)"),
+    ("watchdog.task_failed", "バックグラウンド処理に失敗: {}", "Background task failed: {}"),
+    ("navigation.name", "ナビゲーション", "navigation"),
+    ("privacy.save_failed", "プライバシーモードの保存に失敗: {}", "Failed to save privacy mode: {}"),
+    ("privacy.pin_mismatch", "PINが正しくありません", "Incorrect PIN"),
+    ("privacy.not_configured", "設定されていないフォルダです: {}", "Folder is not configured: {}"),
+    ("privacy.pin_locked_out", "PINの入力に続けて失敗したため、{}秒後に再度お試しください", "Too many incorrect PIN attempts. Try again in {} seconds"),
+    ("privacy.random_failed", "乱数の生成に失敗: {}", "Failed to generate random bytes: {}"),
+    ("selection.name", "選択", "selection"),
+    ("selection.empty", "選択されている画像がありません: {}", "No images are selected: {}"),
+    ("navigation.not_set", "ナビゲーション対象が設定されていません", "No navigation set has been configured"),
+    ("folders.pins_save_failed", "ピン留めしたフォルダの保存に失敗: {}", "Failed to save pinned folders: {}"),
+    ("filter_preset.save_failed", "絞り込み条件の保存に失敗: {}", "Failed to save filter presets: {}"),
+    ("filter_preset.empty_name", "絞り込み条件の名前が空です", "Filter preset name is empty"),
+    ("filter_preset.not_found", "絞り込み条件が見つかりません: {}", "Filter preset not found: {}"),
+    ("file_ops.clipboard_unsupported", "この端末ではクリップボードを使えません", "The clipboard is not available on this device"),
+    ("screenshot.unsupported", "この端末では画面をキャプチャできません", "Screen capture is not available on this device"),
+    ("shortcut.unsupported", "この端末ではグローバルショートカットを使えません", "Global shortcuts are not available on this device"),
+    ("index.open_failed", "インデックスを開けません ({}): {}", "Failed to open the index ({}): {}"),
+    ("index.init_failed", "インデックスの初期化に失敗: {}", "Failed to initialize the index: {}"),
+    ("index.transaction_failed", "トランザクションの開始に失敗: {}", "Failed to start a transaction: {}"),
+    ("index.read_failed", "インデックスの読み込みに失敗: {}", "Failed to read the index: {}"),
+    ("index.update_failed", "インデックスの更新に失敗: {}", "Failed to update the index: {}"),
+    ("index.update_path_failed", "インデックスの更新に失敗: {} - {}", "Failed to update the index: {} - {}"),
+    ("index.commit_failed", "インデックスの保存に失敗: {}", "Failed to save the index: {}"),
+    ("index.check_failed", "インデックスの検査に失敗: {}", "Failed to check the index: {}"),
+    ("index.optimize_failed", "インデックスの最適化に失敗: {}", "Failed to optimize the index: {}"),
+    ("metadata.open_failed", "メタデータストアを開けません ({}): {}", "Failed to open the metadata store ({}): {}"),
+    ("metadata.init_failed", "メタデータストアの初期化に失敗: {}", "Failed to initialize the metadata store: {}"),
+    ("metadata.tags_save_failed", "タグの保存に失敗: {} - {}", "Failed to save tags: {} - {}"),
+    ("metadata.rating_save_failed", "レーティングの保存に失敗: {} - {}", "Failed to save rating: {} - {}"),
+    ("metadata.tags_delete_failed", "タグの削除に失敗: {} - {}", "Failed to delete tags: {} - {}"),
+    ("metadata.rating_delete_failed", "レーティングの削除に失敗: {} - {}", "Failed to delete rating: {} - {}"),
+    ("metadata.read_failed", "メタデータの読み込みに失敗: {}", "Failed to read metadata: {}"),
+    ("metadata.xmp_read_failed", "XMPの読み込みに失敗: {} - {}", "Failed to read XMP: {} - {}"),
+    ("metadata.digikam_open_failed", "digiKamのデータベースを開けません ({}): {}", "Failed to open the digiKam database ({}): {}"),
+    ("metadata.digikam_tags_failed", "digiKamのタグ読み込みに失敗: {}", "Failed to read digiKam tags: {}"),
+    ("metadata.digikam_ratings_failed", "digiKamのレーティング読み込みに失敗: {}", "Failed to read digiKam ratings: {}"),
+    ("metadata.csv_read_failed", "CSVの読み込みに失敗: {} - {}", "Failed to read CSV: {} - {}"),
+    ("export.serialize_failed", "JSONへの変換に失敗: {}", "Failed to convert to JSON: {}"),
+    ("export.write_failed", "エクスポートファイルの書き込みに失敗 ({}): {}", "Failed to write the export file ({}): {}"),
+    ("crash.serialize_failed", "クラッシュレポートのシリアライズに失敗: {}", "Failed to serialize the crash report: {}"),
+    ("crash.save_failed", "クラッシュレポートの保存に失敗: {}", "Failed to save the crash report: {}"),
+    ("crash.unknown_panic", "不明なパニック", "Unknown panic"),
+    ("crash.session_failed", "セッションの記録に失敗: {}", "Failed to record the session: {}"),
+    ("crash.read_failed", "クラッシュレポートの読み込みに失敗: {}", "Failed to read the crash report: {}"),
+    ("crash.parse_failed", "クラッシュレポートのパースに失敗: {}", "Failed to parse the crash report: {}"),
+    ("crash.delete_failed", "クラッシュレポートの削除に失敗: {}", "Failed to delete the crash report: {}"),
+    ("logging.dir_create_failed", "ログディレクトリの作成に失敗 ({}): {}", "Failed to create the log directory ({}): {}"),
+    ("logging.file_create_failed", "ログファイルの作成に失敗: {}", "Failed to create the log file: {}"),
+    ("logging.init_failed", "ログの初期化に失敗: {}", "Failed to initialize logging: {}"),
+    ("logging.unknown_level", "不明なログレベルです: {}", "Unknown log level: {}"),
+    ("logging.dir_read_failed", "ログディレクトリの読み取りに失敗: {}", "Failed to read the log directory: {}"),
+    ("logging.file_read_failed", "ログファイルの読み込みに失敗: {} - {}", "Failed to read the log file: {} - {}"),
+    ("logging.level_change_failed", "ログレベルの変更に失敗: {}", "Failed to change the log level: {}"),
+    ("album.open_failed", "アルバムストアを開けません ({}): {}", "Failed to open the album store ({}): {}"),
+    ("album.init_failed", "アルバムストアの初期化に失敗: {}", "Failed to initialize the album store: {}"),
+    ("album.empty_name", "アルバム名が空です", "Album name is empty"),
+    ("album.create_failed", "アルバムの作成に失敗: {} - {}", "Failed to create album: {} - {}"),
+    ("album.delete_failed", "アルバムの削除に失敗: {}", "Failed to delete album: {}"),
+    ("album.delete_name_failed", "アルバムの削除に失敗: {} - {}", "Failed to delete album: {} - {}"),
+    ("album.read_failed", "アルバムの読み込みに失敗: {}", "Failed to read albums: {}"),
+    ("album.not_found", "アルバムが存在しません: {}", "Album does not exist: {}"),
+    ("album.update_failed", "アルバムの更新に失敗: {}", "Failed to update album: {}"),
+    ("album.update_path_failed", "アルバムの更新に失敗: {} - {}", "Failed to update album: {} - {}"),
+    ("album.slideshow_serialize_failed", "スライドショーの設定の変換に失敗: {}", "Failed to convert slideshow settings: {}"),
+    ("snapshot.open_failed", "スナップショットストアを開けません ({}): {}", "Failed to open the snapshot store ({}): {}"),
+    ("snapshot.init_failed", "スナップショットストアの初期化に失敗: {}", "Failed to initialize the snapshot store: {}"),
+    ("snapshot.empty_name", "スナップショット名が空です", "Snapshot name is empty"),
+    ("snapshot.already_exists", "スナップショットは既に存在します: {}", "Snapshot already exists: {}"),
+    ("snapshot.create_failed", "スナップショットの作成に失敗: {}", "Failed to create snapshot: {}"),
+    ("snapshot.create_path_failed", "スナップショットの作成に失敗: {} - {}", "Failed to create snapshot: {} - {}"),
+    ("snapshot.read_failed", "スナップショットの読み込みに失敗: {}", "Failed to read snapshots: {}"),
+    ("snapshot.read_name_failed", "スナップショットの読み込みに失敗: {} - {}", "Failed to read snapshot: {} - {}"),
+    ("snapshot.not_found", "スナップショットが存在しません: {}", "Snapshot does not exist: {}"),
+    ("concepts.open_failed", "自動タグのストアを開けません ({}): {}", "Failed to open the auto-tag store ({}): {}"),
+    ("concepts.init_failed", "自動タグのストアの初期化に失敗: {}", "Failed to initialize the auto-tag store: {}"),
+    ("concepts.save_failed", "自動タグの保存に失敗: {}", "Failed to save auto tags: {}"),
+    ("concepts.save_path_failed", "自動タグの保存に失敗: {} - {}", "Failed to save auto tags: {} - {}"),
+    ("concepts.read_failed", "自動タグの読み込みに失敗: {}", "Failed to read auto tags: {}"),
+    ("concepts.search_failed", "自動タグの検索に失敗: {}", "Failed to search auto tags: {}"),
+    ("identity.open_file_failed", "ファイルを開けません: {} - {}", "Failed to open file: {} - {}"),
+    ("identity.metadata_failed", "ファイル情報の取得に失敗: {} - {}", "Failed to read file information: {} - {}"),
+    ("identity.open_failed", "識別子ストアを開けません ({}): {}", "Failed to open the identifier store ({}): {}"),
+    ("identity.init_failed", "識別子ストアの初期化に失敗: {}", "Failed to initialize the identifier store: {}"),
+    ("identity.read_failed", "識別子の読み込みに失敗: {}", "Failed to read identifiers: {}"),
+    ("identity.save_failed", "識別子の保存に失敗: {} - {}", "Failed to save identifier: {} - {}"),
+    ("identity.rebind_path_failed", "情報の付け替えに失敗: {} -> {} - {}", "Failed to move information: {} -> {} - {}"),
+    ("identity.rebind_failed", "情報の付け替えに失敗: {}", "Failed to move information: {}"),
+    ("hidden.open_failed", "非表示リストを開けません ({}): {}", "Failed to open the hidden list ({}): {}"),
+    ("hidden.init_failed", "非表示リストの初期化に失敗: {}", "Failed to initialize the hidden list: {}"),
+    ("hidden.update_failed", "非表示リストの更新に失敗: {} - {}", "Failed to update the hidden list: {} - {}"),
+    ("hidden.read_failed", "非表示リストの読み込みに失敗: {}", "Failed to read the hidden list: {}"),
+    ("ocr.open_failed", "文字のインデックスを開けません ({}): {}", "Failed to open the text index ({}): {}"),
+    ("ocr.init_failed", "文字のインデックスの初期化に失敗: {}", "Failed to initialize the text index: {}"),
+    ("ocr.save_failed", "文字の保存に失敗: {} - {}", "Failed to save recognized text: {} - {}"),
+    ("ocr.search_failed", "文字の検索に失敗: {}", "Failed to search recognized text: {}"),
+    ("sensitive.open_failed", "判定結果のストアを開けません ({}): {}", "Failed to open the classification store ({}): {}"),
+    ("sensitive.init_failed", "判定結果のストアの初期化に失敗: {}", "Failed to initialize the classification store: {}"),
+    ("sensitive.save_failed", "判定結果の保存に失敗: {} - {}", "Failed to save classification: {} - {}"),
+    ("sensitive.read_failed", "判定結果の読み込みに失敗: {}", "Failed to read classifications: {}"),
+    ("cover.open_failed", "表紙ストアを開けません ({}): {}", "Failed to open the cover store ({}): {}"),
+    ("cover.init_failed", "表紙ストアの初期化に失敗: {}", "Failed to initialize the cover store: {}"),
+    ("cover.read_failed", "表紙の読み込みに失敗: {} - {}", "Failed to read cover: {} - {}"),
+    ("cover.save_failed", "表紙の保存に失敗: {} - {}", "Failed to save cover: {} - {}"),
+    ("view_state.open_failed", "表示状態ストアを開けません ({}): {}", "Failed to open the view state store ({}): {}"),
+    ("view_state.init_failed", "表示状態ストアの初期化に失敗: {}", "Failed to initialize the view state store: {}"),
+    ("view_state.save_path_failed", "表示状態の保存に失敗: {} - {}", "Failed to save view state: {} - {}"),
+    ("view_state.save_failed", "表示状態の保存に失敗: {}", "Failed to save view state: {}"),
+    ("view_state.read_failed", "表示状態の読み込みに失敗: {} - {}", "Failed to read view state: {} - {}"),
+    ("session.serialize_failed", "セッションのシリアライズに失敗: {}", "Failed to serialize the session: {}"),
+    ("session.save_failed", "セッションの保存に失敗: {}", "Failed to save the session: {}"),
+    ("session.read_failed", "セッションの読み込みに失敗: {}", "Failed to read the session: {}"),
+    ("session.name", "セッション", "session"),
+    ("settings.read_failed", "アプリ設定の読み込みに失敗: {}", "Failed to read app settings: {}"),
+    ("settings.parse_failed", "アプリ設定のパースに失敗: {}", "Failed to parse app settings: {}"),
+    ("settings.serialize_failed", "アプリ設定のシリアライズに失敗: {}", "Failed to serialize app settings: {}"),
+    ("settings.save_failed", "アプリ設定の保存に失敗: {}", "Failed to save app settings: {}"),
+    ("updater.client_failed", "HTTPクライアントの作成に失敗: {}", "Failed to create the HTTP client: {}"),
+    ("updater.fetch_failed", "最新リリースの取得に失敗: {}", "Failed to fetch the latest release: {}"),
+    ("updater.parse_failed", "リリース情報のパースに失敗: {}", "Failed to parse release information: {}"),
+    ("window_state.serialize_failed", "ウィンドウ状態のシリアライズに失敗: {}", "Failed to serialize the window state: {}"),
+    ("window_state.save_failed", "ウィンドウ状態の保存に失敗: {}", "Failed to save the window state: {}"),
+    ("window_state.name", "ウィンドウ状態", "window state"),
+    ("shortcut.invalid", "ショートカットの形式が不正です ({}): {}", "Invalid shortcut ({}): {}"),
+    ("shortcut.unregister_failed", "ショートカットの解除に失敗: {}", "Failed to unregister the shortcut: {}"),
+    ("shortcut.register_failed", "ショートカットの登録に失敗（他のアプリが使用中の可能性があります）: {}", "Failed to register the shortcut (another app may be using it): {}"),
+    ("maintenance.serialize_failed", "メンテナンスの記録のシリアライズに失敗: {}", "Failed to serialize the maintenance record: {}"),
+    ("maintenance.save_failed", "メンテナンスの記録の保存に失敗: {}", "Failed to save the maintenance record: {}"),
+    ("animation.read_failed", "画像の読み込みに失敗: {} - {}", "Failed to read image: {} - {}"),
+    ("metadata.digikam_roots_failed", "digiKamのアルバムルート読み込みに失敗: {}", "Failed to read digiKam album roots: {}"),
+    ("settings.confirm_protected_title", "設定の変更の確認", "Confirm settings change"),
+    ("settings.confirm_protected", "次の設定を変更します。よろしいですか？\n{}", "The following settings will change. Continue?\n{}"),
+    ("settings.protected_restrict_to_library", "読み取りを設定フォルダ内に制限する: {}", "Restrict reads to library folders: {}"),
+    ("settings.protected_executable", "{}の実行ファイル: {}", "{} executable: {}"),
+    ("settings.protected_declined", "設定の変更が承認されなかったため保存しませんでした", "The settings were not saved because the change was not approved"),
+];
+
+/// 現在のロケールを取得する
+pub fn locale() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        UNSET => {
+            let detected = sys_locale::get_locale()
+                .map(|tag| Locale::from_tag(&tag))
+                .unwrap_or(Locale::Ja);
+            CURRENT.store(detected.as_u8(), Ordering::Relaxed);
+            detected
+        },
+        value => Locale::from_u8(value),
+    }
+}
+
+/// ロケールを変更する
+pub fn set_locale(locale: Locale) {
+    CURRENT.store(locale.as_u8(), Ordering::Relaxed);
+}
+
+/// キーに対応する文言を指定ロケールで組み立てる（未登録のキーはキーそのものを返す）
+///
+/// `{}`は引数を順に、`{0}`のような番号付きは指定位置の引数を埋め込む
+pub fn message_in(locale: Locale, key: &str, args: &[&dyn Display]) -> String {
+    let template = MESSAGES.iter()
+        .find(|(k, _, _)| *k == key)
+        .map(|(_, ja, en)| match locale {
+            Locale::Ja => *ja,
+            Locale::En => *en,
+        })
+        .unwrap_or(key);
+
+    let mut result = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else { break };
+        result.push_str(&rest[..start]);
+        let placeholder = &rest[start + 1..end];
+        let position = if placeholder.is_empty() {
+            next += 1;
+            Some(next - 1)
+        } else {
+            placeholder.parse::<usize>().ok()
+        };
+        match position.and_then(|i| args.get(i)) {
+            Some(arg) => result.push_str(&arg.to_string()),
+            None => result.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// キーに対応する文言を現在のロケールで組み立てる
+pub fn message(key: &str, args: &[&dyn Display]) -> String {
+    message_in(locale(), key, args)
+}
+
+/// `t!("config.read_failed", e)`の形でメッセージを組み立てる
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::message($key, &[])
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::message($key, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+pub(crate) use t;
+
+/// バックエンドのメッセージの言語を取得する
+#[tauri::command]
+pub fn get_backend_locale() -> Locale {
+    locale()
+}
+
+/// バックエンドのメッセージの言語を変更する
+#[tauri::command]
+pub fn set_backend_locale(locale: Locale) {
+    set_locale(locale);
+    tracing::info!("バックエンドのロケールを変更しました: {:?}", locale);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_in() {
+        assert_eq!(
+            message_in(Locale::En, "path.not_found", &[&"/tmp/a"]),
+            "Path does not exist: /tmp/a"
+        );
+        assert_eq!(
+            message_in(Locale::Ja, "diagnostics.index_missing", &[&10, &2]),
+            "インデックスの10件中2件のファイルが見つかりません"
+        );
+        assert_eq!(
+            message_in(Locale::En, "diagnostics.index_missing", &[&10, &2]),
+            "2 of 10 indexed files are missing"
+        );
+        assert_eq!(message_in(Locale::En, "unknown.key", &[]), "unknown.key");
+        assert_eq!(Locale::from_tag("ja-JP"), Locale::Ja);
+        assert_eq!(Locale::from_tag("en_US.UTF-8"), Locale::En);
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use rusqlite::{params, Connection};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+use crate::album::AlbumStore;
+use crate::audit;
+use crate::hidden::HiddenStore;
+use crate::i18n::t;
+use crate::index::LibraryIndex;
+use crate::metadata::MetadataStore;
+use crate::view_state::ViewStateStore;
+use crate::watchdog::{self, Operation};
+
+/// ハッシュに使う先頭部分の大きさ
+const HASH_PREFIX_BYTES: u64 = 64 * 1024;
+
+/// パスをキーに画像ごとの情報を持つテーブル（移動時に付け替える）
+const LINKED_TABLES: [&str; 5] = ["image_tags", "image_ratings", "image_view_states", "album_images", "hidden_images"];
+
+/// ファイルの内容から識別子を求める（サイズと先頭64KiBのSHA-256）
+///
+/// 全体を読まずに済むよう先頭のみを使うため、サイズも併せて比較する
+pub fn content_hash(path: &Path) -> Result<String, String> {
+    let file = File::open(path)
+        .map_err(|e| t!("identity.open_file_failed", path.display(), e))?;
+    let size = file.metadata()
+        .map_err(|e| t!("identity.metadata_failed", path.display(), e))?
+        .len();
+
+    let mut prefix = Vec::new();
+    file.take(HASH_PREFIX_BYTES).read_to_end(&mut prefix)
+        .map_err(|e| t!("file.read_failed", path.display(), e))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+    hasher.update(&prefix);
+    Ok(hasher.finalize().iter().take(16).map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// 付け替えの結果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReconcileResult {
+    /// 付け替えた画像（移動前のパス、移動後のパス）
+    pub relinked: Vec<(String, String)>,
+    /// 移動先が見つからなかった画像数
+    pub missing: usize,
+    /// 新たに識別子を記録した画像数
+    pub identified: usize,
+}
+
+/// 画像の識別子の保存先（インデックスと同じSQLiteファイル）
+pub struct IdentityStore {
+    conn: Connection,
+}
+
+impl IdentityStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        // 付け替え対象のテーブルを用意しておく
+        MetadataStore::open(app_handle)?;
+        AlbumStore::open(app_handle)?;
+        ViewStateStore::open(app_handle)?;
+        HiddenStore::open(app_handle)?;
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("identity.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS image_identities (
+                path TEXT PRIMARY KEY,
+                hash TEXT NOT NULL,
+                size INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_image_identities_hash ON image_identities(hash);"
+        ).map_err(|e| t!("identity.init_failed", e))
+    }
+
+    /// タグ・レーティング等が記録されている画像のパスを取得する
+    fn referenced_paths(&self) -> Result<Vec<String>, String> {
+        let sql = LINKED_TABLES.iter()
+            .map(|table| format!("SELECT path FROM {}", table))
+            .collect::<Vec<_>>()
+            .join(" UNION ");
+        let mut stmt = self.conn.prepare(&sql)
+            .map_err(|e| t!("identity.read_failed", e))?;
+        stmt.query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| t!("identity.read_failed", e))
+    }
+
+    /// 記録済みの識別子（ハッシュとサイズ）をパスごとに取得する
+    fn identities(&self) -> Result<HashMap<String, (String, u64)>, String> {
+        let mut stmt = self.conn.prepare("SELECT path, hash, size FROM image_identities")
+            .map_err(|e| t!("identity.read_failed", e))?;
+        stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get::<_, i64>(2)? as u64))))
+            .and_then(|rows| rows.collect::<Result<HashMap<_, _>, _>>())
+            .map_err(|e| t!("identity.read_failed", e))
+    }
+
+    /// 識別子を記録する
+    pub fn remember(&self, path: &str, hash: &str, size: u64) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO image_identities (path, hash, size) VALUES (?1, ?2, ?3)",
+            params![path, hash, size as i64],
+        ).map(|_| ()).map_err(|e| t!("identity.save_failed", path, e))
+    }
+
+    /// 画像ごとの情報を新しいパスに付け替える（移動先に既にある情報を優先する）
+    pub fn relink(&mut self, old: &str, new: &str) -> Result<(), String> {
+        let tx = self.conn.transaction()
+            .map_err(|e| t!("index.transaction_failed", e))?;
+        for table in LINKED_TABLES.iter().chain(["image_identities"].iter()) {
+            tx.execute(&format!("UPDATE OR IGNORE {} SET path = ?2 WHERE path = ?1", table), params![old, new])
+                .and_then(|_| tx.execute(&format!("DELETE FROM {} WHERE path = ?1", table), params![old]))
+                .map_err(|e| t!("identity.rebind_path_failed", old, new, e))?;
+        }
+        tx.commit().map_err(|e| t!("identity.rebind_failed", e))
+    }
+}
+
+/// 移動された画像の情報を付け替え、存在する画像の識別子を記録する
+///
+/// 移動前に識別子を記録できていた画像のみ付け替えられるため、スキャンのたびに実行する
+pub fn reconcile(app_handle: &AppHandle) -> Result<ReconcileResult, String> {
+    let mut store = IdentityStore::open(app_handle)?;
+    let identities = store.identities()?;
+    let mut result = ReconcileResult::default();
+
+    // 移動先の候補はサイズで絞り込む
+    let mut by_size: HashMap<u64, Vec<String>> = HashMap::new();
+    for image in LibraryIndex::open(app_handle)?.all_images()? {
+        by_size.entry(image.size).or_default().push(image.path);
+    }
+
+    for path in store.referenced_paths()? {
+        if Path::new(&path).exists() {
+            if !identities.contains_key(&path) {
+                if let Ok(hash) = content_hash(Path::new(&path)) {
+                    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                    store.remember(&path, &hash, size)?;
+                    result.identified += 1;
+                }
+            }
+            continue;
+        }
+
+        let Some((hash, size)) = identities.get(&path) else {
+            result.missing += 1;
+            continue;
+        };
+        let moved_to = by_size.get(size).and_then(|candidates| {
+            candidates.iter()
+                .filter(|candidate| **candidate != path)
+                .find(|candidate| content_hash(Path::new(candidate)).ok().as_ref() == Some(hash))
+        });
+        match moved_to {
+            Some(new_path) => {
+                store.relink(&path, new_path)?;
+                tracing::info!("移動された画像の情報を付け替えました: {} -> {}", path, new_path);
+                result.relinked.push((path, new_path.clone()));
+            },
+            None => result.missing += 1,
+        }
+    }
+    Ok(result)
+}
+
+/// 移動された画像のタグ・レーティング・表示状態・アルバムを新しい場所に付け替える
+#[tauri::command]
+pub async fn reconcile_metadata(app_handle: AppHandle) -> Result<ReconcileResult, String> {
+    let handle = app_handle.clone();
+    let result = watchdog::run(&app_handle, Operation::Checksum, move || reconcile(&handle)).await;
+    audit::complete(&app_handle, "reconcile_metadata", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relink_moves_metadata_to_new_path() {
+        let dir = std::env::temp_dir().join(format!("poir-identity-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let db = dir.join("index.db");
+
+        fs::write(dir.join("a.jpg"), b"same content").unwrap();
+        fs::write(dir.join("b.jpg"), b"same content").unwrap();
+        fs::write(dir.join("c.jpg"), b"other content").unwrap();
+        let hash = content_hash(&dir.join("a.jpg")).unwrap();
+        assert_eq!(hash, content_hash(&dir.join("b.jpg")).unwrap());
+        assert_ne!(hash, content_hash(&dir.join("c.jpg")).unwrap());
+
+        let metadata = MetadataStore::open_at(&db).unwrap();
+        AlbumStore::open_at(&db).unwrap();
+        ViewStateStore::open_at(&db).unwrap();
+        HiddenStore::open_at(&db).unwrap();
+        metadata.add_tags("/old/a.jpg", &["cat".to_string()]).unwrap();
+        metadata.set_rating("/old/a.jpg", 4).unwrap();
+
+        let mut store = IdentityStore::open_at(&db).unwrap();
+        store.relink("/old/a.jpg", "/new/a.jpg").unwrap();
+
+        let moved = metadata.get("/new/a.jpg").unwrap();
+        assert_eq!(moved.tags, vec!["cat"]);
+        assert_eq!(moved.rating, Some(4));
+        assert_eq!(metadata.get("/old/a.jpg").unwrap(), Default::default());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use crate::lock;
+use crate::settings::{AppSettings, ScreensaverSettings};
+use crate::slideshow::{self, SlideshowOptions, SlideshowState};
+
+/// 無操作時間を確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// `start-screensaver`で通知する内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScreensaverStart {
+    /// 表示するアルバム（Noneはライブラリ全体）
+    pub album: Option<String>,
+}
+
+/// 最後の操作時刻とスクリーンセーバーの状態
+pub struct IdleState {
+    last_activity: Mutex<Instant>,
+    screensaver_active: AtomicBool,
+}
+
+impl Default for IdleState {
+    fn default() -> Self {
+        Self {
+            last_activity: Mutex::new(Instant::now()),
+            screensaver_active: AtomicBool::new(false),
+        }
+    }
+}
+
+impl IdleState {
+    /// 操作があったことを記録する
+    pub fn touch(&self) {
+        if let Ok(mut last) = self.last_activity.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    pub(crate) fn idle_for(&self) -> Duration {
+        self.last_activity.lock().map(|last| last.elapsed()).unwrap_or_default()
+    }
+}
+
+/// スクリーンセーバーを開始すべきか判定する（アプリのロック中は開始しない）
+fn should_start(settings: &ScreensaverSettings, idle_for: Duration, active: bool, locked: bool) -> bool {
+    settings.enabled && !active && !locked && idle_for >= Duration::from_secs(settings.idle_minutes.max(1) * 60)
+}
+
+/// invokeハンドラから呼ばれ、コマンド呼び出しを操作として記録する
+///
+/// スクリーンセーバーの終了は`report_user_activity`でのみ行う
+/// （表示中の画面からのコマンド呼び出しで止まらないようにするため）
+pub fn record_invoke<R: Runtime>(invoke: &Invoke<R>) {
+    if let Some(state) = invoke.message.state_ref().try_get::<IdleState>() {
+        state.touch();
+    }
+}
+
+/// スクリーンセーバーを開始する（アプリのロック中は何もしない）
+pub fn start_screensaver(app_handle: &AppHandle, settings: &ScreensaverSettings) {
+    if lock::is_locked(app_handle) {
+        return;
+    }
+    let (Some(idle), Some(slideshow_state)) = (
+        app_handle.try_state::<IdleState>(),
+        app_handle.try_state::<SlideshowState>(),
+    ) else {
+        return;
+    };
+
+    let options = SlideshowOptions {
+        folder: None,
+        album: settings.album.clone(),
+        interval_ms: settings.interval_ms,
+        shuffle: true,
+        repeat: true,
+        start_index: 0,
+        preload: 2,
+        use_navigation: false,
+        transition: Default::default(),
+        music: None,
+        // スクリーンセーバーの設定を優先する
+        ignore_album_settings: true,
+    };
+
+    idle.screensaver_active.store(true, Ordering::Relaxed);
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit("start-screensaver", ScreensaverStart { album: settings.album.clone() });
+    }
+
+    match slideshow::start(app_handle, &slideshow_state, options) {
+        Ok(_) => tracing::info!("スクリーンセーバーを開始しました"),
+        Err(e) => {
+            // 画像がない等で開始できない場合は、次の無操作時間まで待ち直す
+            tracing::warn!("スクリーンセーバーを開始できませんでした: {}", e);
+            idle.screensaver_active.store(false, Ordering::Relaxed);
+            idle.touch();
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.emit("stop-screensaver", ());
+            }
+        },
+    }
+}
+
+/// 無操作時間を監視するスレッドを起動する
+pub fn start_monitor(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+
+        let Some(idle) = app_handle.try_state::<IdleState>() else { continue };
+        // 設定の変更をすぐに反映するため毎回読み込む
+        let settings = AppSettings::load(&app_handle).unwrap_or_default().screensaver;
+        let active = idle.screensaver_active.load(Ordering::Relaxed);
+        if should_start(&settings, idle.idle_for(), active, lock::is_locked(&app_handle)) {
+            start_screensaver(&app_handle, &settings);
+        }
+    });
+}
+
+/// ユーザーの操作（マウス・キー入力）を通知する。スクリーンセーバー中なら終了する
+#[tauri::command]
+pub fn report_user_activity(
+    app_handle: AppHandle,
+    state: State<'_, IdleState>,
+    slideshow_state: State<'_, SlideshowState>,
+) -> Result<(), String> {
+    state.touch();
+    if state.screensaver_active.swap(false, Ordering::Relaxed) {
+        slideshow::stop(&slideshow_state)?;
+        if let Some(window) = app_handle.get_webview_window("main") {
+            let _ = window.emit("stop-screensaver", ());
+        }
+        tracing::info!("スクリーンセーバーを終了しました");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_start() {
+        let settings = ScreensaverSettings { enabled: true, idle_minutes: 5, ..Default::default() };
+        assert!(!should_start(&settings, Duration::from_secs(299), false, false));
+        assert!(should_start(&settings, Duration::from_secs(300), false, false));
+        assert!(!should_start(&settings, Duration::from_secs(600), true, false));
+        assert!(!should_start(&settings, Duration::from_secs(600), false, true));
+        assert!(!should_start(&ScreensaverSettings::default(), Duration::from_secs(3600), false, false));
+    }
+}
@@ -1,8 +1,31 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use ::image::{ImageDecoder, ImageReader};
 use serde::{Serialize, Deserialize};
 use tauri::AppHandle;
+use crate::concepts;
 use crate::config::ResourceConfig;
+use crate::event_bridge::{self, Delivery};
+use crate::hidden;
+use crate::i18n::t;
+use crate::identity;
+use crate::index::LibraryIndex;
+use crate::long_path;
+use crate::path_guard;
+use crate::metrics;
+use crate::collation::Collation;
+use crate::navigation::{self, GroupBy, ImageGroup, SortOrder};
+use crate::originals;
+use crate::privacy;
+use crate::sensitive;
+use crate::settings::{AppSettings, ListLimits};
+use crate::watchdog::{self, Operation};
+
+/// 起動直後の一覧のバックグラウンドでの再スキャン中か（重ねて実行しないため）
+static REFRESHING: AtomicBool = AtomicBool::new(false);
 
 /// 画像ファイルに関する情報を格納する構造体
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -17,6 +40,12 @@ pub struct ImageInfo {
     pub modified: u64,
     /// 画像の種類（拡張子）
     pub extension: String,
+    /// 刺激の強い画像と判定されたか（プレビューはぼかして返す）
+    #[serde(default)]
+    pub sensitive: bool,
+    /// 色成分あたりのビット数（16bitのPNG・TIFFなどの判別用、不明ならNone）
+    #[serde(default)]
+    pub bit_depth: Option<u8>,
 }
 
 /// 画像一覧の取得結果
@@ -28,13 +57,66 @@ pub struct ImageListResult {
     pub total: usize,
     /// 処理されたフォルダ
     pub folders: Vec<String>,
+    /// 応答サイズの上限により省略した場合の、続きの画像の位置
+    #[serde(default)]
+    pub next_offset: Option<usize>,
+    /// 応答サイズの上限により省略した場合の警告
+    #[serde(default)]
+    pub warning: Option<String>,
+    /// `group_by`を指定した場合の区切り（`images`は区切りごとに並ぶ）
+    #[serde(default)]
+    pub groups: Vec<ImageGroup>,
+}
+
+impl ImageListResult {
+    /// 応答サイズの上限に収まるよう画像一覧を切り詰める
+    ///
+    /// `offset`は`images`の先頭がライブラリ全体の何番目かを表し、続きの位置の計算に使う
+    fn cap_payload(mut self, offset: usize, limits: &ListLimits) -> Self {
+        let fit = fit_payload(&self.images, limits.max_payload_bytes);
+        if fit < self.images.len() {
+            let next_offset = offset + fit;
+            tracing::warn!("応答サイズの上限により画像一覧を{}件に切り詰めました（全{}件）", fit, self.images.len());
+            self.warning = Some(t!("scan.payload_truncated", self.images.len(), fit, next_offset));
+            self.images.truncate(fit);
+            self.next_offset = Some(next_offset);
+        }
+        self
+    }
+}
+
+/// JSONにしたときに上限のバイト数に収まる件数を求める（最低1件は返す）
+fn fit_payload<T: Serialize>(items: &[T], max_bytes: usize) -> usize {
+    let mut total = 2; // 配列の括弧
+    for (count, item) in items.iter().enumerate() {
+        // 要素ごとのJSONの長さと区切りのカンマ
+        total += serde_json::to_vec(item).map(|json| json.len()).unwrap_or(0) + 1;
+        if total > max_bytes {
+            return count.max(1);
+        }
+    }
+    items.len()
+}
+
+/// `offset`番目から、1ページの最大件数と応答サイズの上限に収まる分だけを取り出す
+///
+/// 続きは`offset`に受け取った件数を足した位置から取得する
+pub(crate) fn page_of<T: Serialize>(items: Vec<T>, offset: usize, limit: Option<usize>, limits: &ListLimits) -> Vec<T> {
+    let limit = limit.unwrap_or(usize::MAX).min(limits.max_items_per_page.max(1));
+    let mut page: Vec<T> = items.into_iter().skip(offset).take(limit).collect();
+    let fit = fit_payload(&page, limits.max_payload_bytes);
+    page.truncate(fit);
+    page
 }
 
 /// 画像ファイルのフィルタリング条件
-const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "gif", "webp", "bmp"];
+const IMAGE_EXTENSIONS: [&str; 8] = ["jpg", "jpeg", "png", "gif", "webp", "bmp", "tif", "tiff"];
+
+/// 色成分あたり8bitを超えうる形式（ヘッダーを読んでビット数を調べる）
+const HIGH_BIT_DEPTH_EXTENSIONS: [&str; 3] = ["png", "tif", "tiff"];
 
 /// 与えられたパスが画像ファイルかどうかを判定する
-fn is_image_file(path: &Path) -> bool {
+pub(crate) fn is_image_file(path: &Path) -> bool {
     if let Some(extension) = path.extension() {
         if let Some(ext_str) = extension.to_str() {
             return IMAGE_EXTENSIONS.contains(&ext_str.to_lowercase().as_str());
@@ -43,126 +125,403 @@ fn is_image_file(path: &Path) -> bool {
     false
 }
 
+/// 色成分あたりのビット数を調べる（8bitを超えうる形式のみヘッダーを読む、読めなければNone）
+pub(crate) fn bit_depth(path: &Path, extension: &str) -> Option<u8> {
+    if !HIGH_BIT_DEPTH_EXTENSIONS.contains(&extension) {
+        return Some(8);
+    }
+    let decoder = ImageReader::open(long_path::extended(path)).ok()?
+        .with_guessed_format().ok()?
+        .into_decoder().ok()?;
+    let color = decoder.color_type();
+    Some((color.bits_per_pixel() / color.channel_count() as u16) as u8)
+}
+
 /// 指定されたディレクトリから画像ファイルを再帰的に取得する
-fn get_images_from_directory(dir_path: &Path, max_depth: usize, current_depth: usize) -> Result<Vec<ImageInfo>, String> {
+///
+/// 更新されていない画像のビット数は`known_depths`（`(パス, 更新日時)`ごと）から引き継ぐ。
+/// 読めなくなったエントリがあった場合は`complete`をfalseにする
+fn get_images_from_directory(
+    dir_path: &Path,
+    max_depth: usize,
+    current_depth: usize,
+    known_depths: &HashMap<(String, u64), u8>,
+    complete: &mut bool,
+) -> Result<Vec<ImageInfo>, String> {
     if current_depth > max_depth {
         return Ok(Vec::new());
     }
 
+    // 260文字を超える深いフォルダも読めるよう、Windowsでは拡張パスで列挙する
+    let dir_path = &long_path::extended(dir_path);
     if !dir_path.exists() || !dir_path.is_dir() {
-        return Err(format!("指定されたパスはディレクトリではありません: {}", dir_path.display()));
+        return Err(t!("path.not_directory", dir_path.display()));
     }
 
     let mut images = Vec::new();
 
     let entries = fs::read_dir(dir_path)
-        .map_err(|e| format!("ディレクトリの読み取りに失敗: {} - {}", dir_path.display(), e))?;
+        .map_err(|e| t!("common.dir_read_failed", dir_path.display(), e))?;
 
+    // ドライブの取り外しなどで読めなくなったエントリは飛ばし、読めた分を返す
     for entry in entries {
-        let entry = entry.map_err(|e| format!("エントリの読み取りに失敗: {}", e))?;
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!("{}", t!("scan.entry_failed", e));
+                *complete = false;
+                continue;
+            },
+        };
         let path = entry.path();
 
-        if path.is_dir() && current_depth < max_depth {
+        // 編集前に残したファイルは一覧に出さない
+        if path.is_dir() && current_depth < max_depth && !originals::is_originals_dir(&path) {
             // 再帰的にサブディレクトリを処理
-            match get_images_from_directory(&path, max_depth, current_depth + 1) {
+            match get_images_from_directory(&path, max_depth, current_depth + 1, known_depths, complete) {
                 Ok(sub_images) => images.extend(sub_images),
-                Err(e) => eprintln!("サブディレクトリの処理中にエラー: {}", e),
+                Err(e) => tracing::warn!("サブディレクトリの処理中にエラー: {}", e),
             }
         } else if path.is_file() && is_image_file(&path) {
             // 画像ファイルの情報を取得
-            let metadata = fs::metadata(&path)
-                .map_err(|e| format!("ファイルのメタデータ取得に失敗: {} - {}", path.display(), e))?;
-            
-            let modified = metadata.modified()
-                .map_err(|e| format!("更新日時の取得に失敗: {}", e))?
-                .duration_since(std::time::UNIX_EPOCH)
-                .map_err(|e| format!("時間変換エラー: {}", e))?
-                .as_secs();
-            
-            let extension = path.extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-            
-            let name = path.file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or("")
-                .to_string();
-            
-            images.push(ImageInfo {
-                path: path.to_string_lossy().to_string(),
-                name,
-                size: metadata.len(),
-                modified,
-                extension,
-            });
+            match image_info_with(&path, known_depths) {
+                Ok(info) => images.push(info),
+                Err(e) => tracing::warn!("画像情報の取得中にエラー: {}", e),
+            }
         }
     }
 
     Ok(images)
 }
 
-/// resources.jsonの設定から画像ファイルのリストを取得する
+/// 画像ファイル1枚分の情報を取得する
+pub(crate) fn image_info(path: &Path) -> Result<ImageInfo, String> {
+    image_info_with(path, &HashMap::new())
+}
+
+/// `image_info`と同じ（更新されていなければビット数をヘッダーから読み直さず`known_depths`の値を使う）
+fn image_info_with(path: &Path, known_depths: &HashMap<(String, u64), u8>) -> Result<ImageInfo, String> {
+    let metadata = fs::metadata(long_path::extended(path))
+        .map_err(|e| t!("scan.metadata_failed", path.display(), e))?;
+    
+    let modified = metadata.modified()
+        .map_err(|e| t!("scan.modified_failed", e))?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| t!("scan.time_conversion_failed", e))?
+        .as_secs();
+    
+    let extension = path.extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    
+    let name = path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("")
+        .to_string();
+    
+    // 拡張パスの接頭辞は画面や設定との比較に不要なため外す
+    let display_path = long_path::strip_extended(&path.to_string_lossy());
+    let bit_depth = match known_depths.get(&(display_path.clone(), modified)) {
+        Some(depth) => Some(*depth),
+        None => bit_depth(path, &extension),
+    };
+
+    Ok(ImageInfo {
+        path: display_path,
+        name,
+        size: metadata.len(),
+        modified,
+        bit_depth,
+        extension,
+        sensitive: false,
+    })
+}
+
+/// 画像一覧を日付順（新しい順）に並べ替える
+fn sort_by_modified_desc(images: &mut [ImageInfo]) {
+    images.sort_by_key(|image| std::cmp::Reverse(image.modified));
+}
+
+/// 指定されたフォルダの画像一覧を日付順で取得する（設定ファイルは参照しない）
+pub(crate) fn list_folder_images(dir_path: &Path, max_depth: usize) -> Result<Vec<ImageInfo>, String> {
+    let mut images = get_images_from_directory(dir_path, max_depth, 0, &HashMap::new(), &mut true)?;
+    sort_by_modified_desc(&mut images);
+    Ok(images)
+}
+
+/// resources.jsonの設定から画像ファイルのリストを取得する（`sort`を省略すると更新日時の新しい順）
+///
+/// 名前順の文字の比べ方は`collation`で指定する（省略すると設定に従う）。
+/// `group_by`を指定すると拡張子・カメラの機種名・フォルダごとに並べ直し、区切りと件数を`groups`で返す。
+/// 応答サイズの上限で切り詰めた場合は、返された`next_offset`を`offset`に指定して続きを取得する
 #[tauri::command]
-pub async fn get_image_list(app_handle: AppHandle, max_depth: Option<usize>) -> Result<ImageListResult, String> {
+pub async fn get_image_list(
+    app_handle: AppHandle,
+    max_depth: Option<usize>,
+    sort: Option<SortOrder>,
+    collation: Option<Collation>,
+    group_by: Option<GroupBy>,
+    offset: Option<usize>,
+) -> Result<ImageListResult, String> {
+    let settings = AppSettings::load(&app_handle).unwrap_or_default();
+    let collation = collation.unwrap_or(settings.sorting.collation);
+    let handle = app_handle.clone();
+    let offset = offset.unwrap_or(0);
+    let mut result = watchdog::run(&app_handle, Operation::Scan, move || {
+        let mut result = scan_library(&handle, max_depth)?;
+        if let Some(sort) = sort {
+            navigation::sort_images_with(&mut result.images, sort, collation);
+        }
+        // 機種名ごとにまとめる場合はEXIFを読むため、スキャンと同じ時間制限の中で行う
+        if let Some(group_by) = group_by {
+            result.groups = navigation::group_images(&handle, &mut result.images, group_by);
+        }
+        Ok(result)
+    }).await?;
+    result.images.drain(..offset.min(result.images.len()));
+    Ok(result.cap_payload(offset, &settings.list_limits))
+}
+
+/// 設定フォルダを1つスキャンし、結果をインデックスにも反映する（フォルダがなければNone）
+pub(crate) fn scan_folder(
+    app_handle: &AppHandle,
+    dir: &str,
+    max_depth: usize,
+    index: Option<&mut LibraryIndex>,
+) -> Result<Option<Vec<ImageInfo>>, String> {
+    // 設定とディスク上でUnicodeの正規化形式が異なっても見つけられるよう解決する
+    let dir_path = long_path::resolve(Path::new(dir));
+    if !long_path::extended(&dir_path).is_dir() {
+        return Ok(None);
+    }
+    let resolved_dir = dir_path.to_string_lossy().to_string();
+
+    // PNGやTIFFのヘッダーを毎回読み直さないよう、更新されていない画像のビット数は前回の値を使う
+    let known_depths = index.as_ref()
+        .and_then(|index| index.bit_depths(&resolved_dir).map_err(|e| tracing::warn!("{}", e)).ok())
+        .unwrap_or_default();
+
+    let started = Instant::now();
+    let mut complete = true;
+    let result = get_images_from_directory(&dir_path, max_depth, 0, &known_depths, &mut complete);
+    metrics::record_scan(app_handle, dir, started.elapsed());
+    let images = result?;
+
+    // スキャン中にドライブが外れた場合など、一部しか読めなかった結果でインデックスを置き換えると
+    // 読めなかった画像の撮影日時などが失われ、再接続後に新着扱いになるため反映しない
+    let complete = complete && fs::read_dir(long_path::extended(&dir_path)).is_ok();
+    if !complete {
+        tracing::warn!("フォルダを最後まで読めなかったため、インデックスを更新しません: {}", resolved_dir);
+    }
+    if let Some(index) = index.filter(|_| complete) {
+        let started = Instant::now();
+        if let Err(e) = index.sync_folder(&resolved_dir, &images) {
+            tracing::warn!("インデックスの更新中にエラー: {}", e);
+        }
+        metrics::record_operation(app_handle, "index_sync", started.elapsed());
+    }
+    Ok(Some(images))
+}
+
+/// 設定された全フォルダをスキャンし、結果をインデックスにも反映する
+pub(crate) fn scan_library(app_handle: &AppHandle, max_depth: Option<usize>) -> Result<ImageListResult, String> {
     // 設定ファイルを読み込む
-    let config = ResourceConfig::load(&app_handle)?;
+    let config = ResourceConfig::load(app_handle)?;
     
     // 設定が有効かチェック
     if config.filters.include.is_empty() {
-        return Err("画像フォルダが設定されていません".to_string());
+        return Err(t!("scan.no_folders"));
     }
     
     let max_search_depth = max_depth.unwrap_or(3); // デフォルトの深さを3に設定
     let mut all_images = Vec::new();
     let mut processed_folders = Vec::new();
+
+    // インデックスが開けなくてもスキャン自体は続行する
+    let mut index = LibraryIndex::open(app_handle)
+        .map_err(|e| tracing::warn!("インデックスを開けませんでした: {}", e))
+        .ok();
     
-    // includeに含まれる各ディレクトリを処理
-    for dir in &config.filters.include {
-        let dir_path = PathBuf::from(dir);
-        if !dir_path.exists() || !dir_path.is_dir() {
-            eprintln!("ディレクトリが存在しません: {}", dir);
+    // プライバシーモード中は隠しているフォルダをスキャンしない
+    let hidden = privacy::hidden_folders(app_handle);
+
+    let limits = AppSettings::load(app_handle).unwrap_or_default().list_limits;
+
+    // includeに含まれる各ディレクトリを優先度の高い順に処理（無効にしたフォルダは除く）
+    for dir in config.scan_order() {
+        if privacy::is_hidden(dir, &hidden) {
             continue;
         }
-        
-        match get_images_from_directory(&dir_path, max_search_depth, 0) {
-            Ok(images) => {
+        match scan_folder(app_handle, dir, max_search_depth, index.as_mut()) {
+            Ok(Some(mut images)) => {
+                // 設定フォルダの中に隠すフォルダがある場合も、インデックスには残して一覧からは除く
+                images.retain(|image| !privacy::is_hidden(&image.path, &hidden));
+                notify_folder_scanned(app_handle, dir, &images, &limits);
                 all_images.extend(images);
                 processed_folders.push(dir.clone());
             },
+            Ok(None) => tracing::warn!("ディレクトリが存在しません: {}", dir),
             Err(e) => {
-                eprintln!("画像リストの取得中にエラー: {}", e);
+                tracing::error!("画像リストの取得中にエラー: {}", e);
             }
         }
     }
     
+    // フォルダの整理で移動された画像のタグ・レーティング等を付け替える
+    if index.is_some() {
+        if let Err(e) = identity::reconcile(app_handle) {
+            tracing::warn!("移動された画像の付け替え中にエラー: {}", e);
+        }
+        // 設定で有効なら、新しく見つかった画像の自動タグ付けを始める
+        concepts::index_in_background(app_handle);
+        sensitive::index_in_background(app_handle);
+    }
+
+    // 非表示にした画像は一覧から除く（インデックスには残す）
+    let mut all_images = sensitive::mark(app_handle, hidden::filter_images(app_handle, all_images));
+
     // 結果を日付順にソート（新しい順）
-    all_images.sort_by(|a, b| b.modified.cmp(&a.modified));
+    sort_by_modified_desc(&mut all_images);
     
     Ok(ImageListResult {
         images: all_images.clone(),
         total: all_images.len(),
         folders: processed_folders,
+        next_offset: None,
+        warning: None,
+        groups: Vec::new(),
     })
 }
 
+/// スキャンを終えたフォルダの画像を`folder-scanned`で通知する（全体のスキャンを待たずに表示できるよう）
+///
+/// 内容は`get_image_list`と同じ形で、`folders`はスキャンしたフォルダだけになる
+fn notify_folder_scanned(app_handle: &AppHandle, dir: &str, images: &[ImageInfo], limits: &ListLimits) {
+    let mut images = sensitive::mark(app_handle, hidden::filter_images(app_handle, images.to_vec()));
+    sort_by_modified_desc(&mut images);
+    let result = ImageListResult {
+        total: images.len(),
+        images,
+        folders: vec![dir.to_string()],
+        next_offset: None,
+        warning: None,
+        groups: Vec::new(),
+    };
+    event_bridge::emit(app_handle, "folder-scanned", Delivery::Batch, result.cap_payload(0, limits));
+}
+
+/// パスが有効な設定フォルダ（`ResourceConfig::enabled_roots`）内にあるか
+pub(crate) fn is_in_roots(path: &str, roots: &[PathBuf]) -> bool {
+    roots.iter().any(|root| Path::new(path).starts_with(root))
+}
+
+/// インデックスから取得した画像のうち、有効な設定フォルダ内のものだけを残す
+///
+/// 無効にしたフォルダや設定から外したフォルダの画像もインデックスには残っているため、一覧に出す前に除く
+pub(crate) fn filter_enabled_folders(app_handle: &AppHandle, images: Vec<ImageInfo>) -> Vec<ImageInfo> {
+    let roots = ResourceConfig::load(app_handle).map(|config| config.enabled_roots()).unwrap_or_default();
+    images.into_iter().filter(|image| is_in_roots(&image.path, &roots)).collect()
+}
+
+/// 前回のスキャン結果をインデックスから組み立てる（フォルダは走査しない）
+fn cached_library(app_handle: &AppHandle, config: &ResourceConfig) -> Result<ImageListResult, String> {
+    let hidden = privacy::hidden_folders(app_handle);
+    let folders: Vec<String> = config.enabled_folders()
+        .filter(|dir| !privacy::is_hidden(dir, &hidden))
+        .cloned()
+        .collect();
+    // インデックスにはスキャン時に解決したパスで記録されている
+    let roots: Vec<PathBuf> = folders.iter().map(|dir| long_path::resolve(Path::new(dir))).collect();
+    let images = LibraryIndex::open(app_handle)?.all_images()?
+        .into_iter()
+        .filter(|image| is_in_roots(&image.path, &roots))
+        .collect();
+    let images = sensitive::mark(app_handle, hidden::filter_images(app_handle, privacy::filter_images(app_handle, images)));
+
+    Ok(ImageListResult {
+        total: images.len(),
+        images,
+        folders,
+        next_offset: None,
+        warning: None,
+        groups: Vec::new(),
+    })
+}
+
+/// 設定された全フォルダをバックグラウンドで再スキャンし、`list-refreshed`で新しい一覧を通知する
+fn refresh_in_background(app_handle: &AppHandle, max_depth: Option<usize>) {
+    if REFRESHING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        let result = scan_library(&app_handle, max_depth);
+        REFRESHING.store(false, Ordering::SeqCst);
+        match result {
+            Ok(result) => {
+                let limits = AppSettings::load(&app_handle).unwrap_or_default().list_limits;
+                tracing::info!("画像一覧を更新しました: {}件", result.total);
+                event_bridge::emit(&app_handle, "list-refreshed", Delivery::Latest, result.cap_payload(0, &limits));
+            },
+            Err(e) => tracing::warn!("画像一覧の更新に失敗しました: {}", e),
+        }
+    });
+}
+
+/// 前回の画像一覧をインデックスからすぐに返し、バックグラウンドで再スキャンする（起動直後の表示用）
+///
+/// 再スキャンが終わると`list-refreshed`で`get_image_list`と同じ形の一覧を通知する
+#[tauri::command]
+pub async fn get_startup_image_list(app_handle: AppHandle, max_depth: Option<usize>) -> Result<ImageListResult, String> {
+    let config = ResourceConfig::load(&app_handle)?;
+    if config.filters.include.is_empty() {
+        return Err(t!("scan.no_folders"));
+    }
+    // インデックスを読めない場合も空の一覧を返し、再スキャンの結果を待つ
+    let cached = cached_library(&app_handle, &config).unwrap_or_else(|e| {
+        tracing::warn!("前回の画像一覧を読み込めませんでした: {}", e);
+        ImageListResult { images: Vec::new(), total: 0, folders: config.filters.include.clone(), next_offset: None, warning: None, groups: Vec::new() }
+    });
+    refresh_in_background(&app_handle, max_depth);
+
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().list_limits;
+    Ok(cached.cap_payload(0, &limits))
+}
+
 /// 指定された画像ファイルのパスが有効かどうかを検証する
 #[tauri::command]
-pub fn validate_image_path(path: String) -> bool {
-    let file_path = Path::new(&path);
-    file_path.exists() && file_path.is_file() && is_image_file(file_path)
+pub fn validate_image_path(app_handle: AppHandle, path: String) -> bool {
+    path_guard::guard(&app_handle, &path)
+        .map(|file_path| file_path.is_file() && is_image_file(&file_path))
+        .unwrap_or(false)
 }
 
 /// 画像リストをページング処理して返す
+///
+/// 1ページの件数と応答サイズは設定の上限に収める（切り詰めた場合は`next_offset`と`warning`を返す）。
+/// `offset`を指定すると`page`の代わりにその位置から取得する（`next_offset`からの続きの取得に使う）。
+/// `group_by`を指定すると区切りごとに並べ直してからページに分け、全体の区切りを`groups`で返す
 #[tauri::command]
 pub async fn get_paginated_images(
     app_handle: AppHandle, 
     page: usize, 
-    items_per_page: usize
+    items_per_page: usize,
+    group_by: Option<GroupBy>,
+    offset: Option<usize>,
 ) -> Result<ImageListResult, String> {
-    let full_list = get_image_list(app_handle, Some(3)).await?;
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().list_limits;
+    let items_per_page = items_per_page.clamp(1, limits.max_items_per_page.max(1));
+    let handle = app_handle.clone();
+    let full_list = watchdog::run(&app_handle, Operation::Scan, move || {
+        let mut full_list = scan_library(&handle, Some(3))?;
+        if let Some(group_by) = group_by {
+            full_list.groups = navigation::group_images(&handle, &mut full_list.images, group_by);
+        }
+        Ok(full_list)
+    }).await?;
     
-    let start_index = page * items_per_page;
+    let start_index = offset.unwrap_or(page * items_per_page);
     let end_index = std::cmp::min(start_index + items_per_page, full_list.images.len());
     
     if start_index >= full_list.images.len() {
@@ -170,12 +529,77 @@ pub async fn get_paginated_images(
             images: Vec::new(),
             total: full_list.total,
             folders: full_list.folders,
+            next_offset: None,
+            warning: None,
+            groups: full_list.groups,
         });
     }
     
-    Ok(ImageListResult {
+    let result = ImageListResult {
         images: full_list.images[start_index..end_index].to_vec(),
         total: full_list.total,
         folders: full_list.folders,
-    })
-}
\ No newline at end of file
+        next_offset: None,
+        warning: None,
+        groups: full_list.groups,
+    };
+    Ok(result.cap_payload(start_index, &limits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_payload_truncates_and_reports_offset() {
+        let images: Vec<ImageInfo> = (0..10)
+            .map(|i| ImageInfo {
+                path: format!("/photos/{:02}.jpg", i),
+                name: format!("{:02}.jpg", i),
+                size: 1,
+                modified: 0,
+                extension: "jpg".to_string(),
+                sensitive: false,
+                bit_depth: None,
+            })
+            .collect();
+        let item_len = serde_json::to_vec(&images[0]).unwrap().len() + 1;
+        let limits = ListLimits { max_items_per_page: 100, max_payload_bytes: 2 + item_len * 3 };
+
+        let result = ImageListResult { images, total: 10, folders: Vec::new(), next_offset: None, warning: None, groups: Vec::new() }
+            .cap_payload(20, &limits);
+        assert_eq!(result.images.len(), 3);
+        assert_eq!(result.next_offset, Some(23));
+        assert!(result.warning.is_some());
+
+        // 続きは受け取った件数を足した位置から取得できる
+        let numbers: Vec<usize> = (0..10).collect();
+        let limits = ListLimits { max_items_per_page: 4, max_payload_bytes: 1024 };
+        assert_eq!(page_of(numbers.clone(), 0, None, &limits), vec![0, 1, 2, 3]);
+        assert_eq!(page_of(numbers.clone(), 8, None, &limits), vec![8, 9]);
+        assert_eq!(page_of(numbers, 2, Some(1), &limits), vec![2]);
+    }
+
+    #[test]
+    fn test_bit_depth_of_16bit_png() {
+        let dir = std::env::temp_dir().join(format!("poir-bit-depth-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let deep = dir.join("deep.png");
+        ::image::ImageBuffer::from_pixel(2, 2, ::image::Rgb([40_000u16, 0, 65_535])).save(&deep).unwrap();
+        let shallow = dir.join("shallow.png");
+        ::image::RgbImage::new(2, 2).save(&shallow).unwrap();
+
+        assert_eq!(bit_depth(&deep, "png"), Some(16));
+        assert_eq!(bit_depth(&shallow, "png"), Some(8));
+        assert_eq!(bit_depth(&dir.join("missing.png"), "png"), None);
+
+        // 更新されていなければ記録済みの値を使い、更新されていればヘッダーを読み直す
+        let info = image_info(&shallow).unwrap();
+        let known = HashMap::from([((info.path.clone(), info.modified), 16)]);
+        assert_eq!(image_info_with(&shallow, &known).unwrap().bit_depth, Some(16));
+        let stale = HashMap::from([((info.path, info.modified + 1), 16)]);
+        assert_eq!(image_info_with(&shallow, &stale).unwrap().bit_depth, Some(8));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,485 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{params, Connection, OpenFlags};
+use tauri::AppHandle;
+use crate::config;
+use crate::i18n::t;
+use crate::image::ImageInfo;
+
+/// インデックスに記録された画像1件
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexEntry {
+    pub path: String,
+    /// 画像を見つけた設定フォルダ
+    pub folder: String,
+    pub size: u64,
+    pub modified: u64,
+}
+
+/// スキャン結果を保持する画像インデックス（SQLite）
+pub struct LibraryIndex {
+    conn: Connection,
+}
+
+impl LibraryIndex {
+    /// インデックスファイルのパスを取得する
+    pub fn get_index_path(app_handle: &AppHandle) -> PathBuf {
+        config::app_data_dir(app_handle).join("index.db")
+    }
+
+    /// アプリデータ内のインデックスを開く（なければ作成する）
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&Self::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのインデックスを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("index.open_failed", path.display(), e))?;
+
+        let index = Self { conn };
+        index.migrate()?;
+        Ok(index)
+    }
+
+    /// 既存のインデックスを読み取り専用で開く（ファイルの作成やテーブルの更新は行わない）
+    pub fn open_read_only(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| t!("index.open_failed", path.display(), e))?;
+        Ok(Self { conn })
+    }
+
+    /// テーブルを作成する
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS images (
+                path TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                modified INTEGER NOT NULL,
+                extension TEXT NOT NULL,
+                folder TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_images_folder ON images(folder);"
+        ).map_err(|e| t!("index.init_failed", e))?;
+
+        // 撮影日時（EXIFがなければ更新日時、未取得ならNULL）は後から追加した列
+        let has_taken_at = self.conn.prepare("SELECT taken_at FROM images LIMIT 0").is_ok();
+        if !has_taken_at {
+            self.conn.execute_batch("ALTER TABLE images ADD COLUMN taken_at INTEGER")
+                .map_err(|e| t!("index.init_failed", e))?;
+        }
+
+        // 取り外されたドライブ上の画像を一覧から外すための列（0で利用不可）
+        let has_available = self.conn.prepare("SELECT available FROM images LIMIT 0").is_ok();
+        if !has_available {
+            self.conn.execute_batch("ALTER TABLE images ADD COLUMN available INTEGER NOT NULL DEFAULT 1")
+                .map_err(|e| t!("index.init_failed", e))?;
+        }
+
+        // 色成分あたりのビット数（不明ならNULL）
+        let has_bit_depth = self.conn.prepare("SELECT bit_depth FROM images LIMIT 0").is_ok();
+        if !has_bit_depth {
+            self.conn.execute_batch("ALTER TABLE images ADD COLUMN bit_depth INTEGER")
+                .map_err(|e| t!("index.init_failed", e))?;
+        }
+
+        // 初めてインデックスに加えた日時（列を追加する前からある画像はいつ加えたか分からないため0）
+        let has_first_indexed_at = self.conn.prepare("SELECT first_indexed_at FROM images LIMIT 0").is_ok();
+        if !has_first_indexed_at {
+            self.conn.execute_batch(
+                "ALTER TABLE images ADD COLUMN first_indexed_at INTEGER NOT NULL DEFAULT 0;
+                 CREATE INDEX IF NOT EXISTS idx_images_first_indexed_at ON images(first_indexed_at);"
+            ).map_err(|e| t!("index.init_failed", e))?;
+        }
+
+        // カメラの機種名（EXIFにない場合は空文字、未取得ならNULL）
+        let has_camera_model = self.conn.prepare("SELECT camera_model FROM images LIMIT 0").is_ok();
+        if !has_camera_model {
+            self.conn.execute_batch("ALTER TABLE images ADD COLUMN camera_model TEXT")
+                .map_err(|e| t!("index.init_failed", e))?;
+        }
+        Ok(())
+    }
+
+    /// 設定フォルダ1つ分のスキャン結果でインデックスを置き換える
+    pub fn sync_folder(&mut self, folder: &str, images: &[ImageInfo]) -> Result<(), String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        self.sync_folder_at(folder, images, now)
+    }
+
+    /// `sync_folder`と同じ（新しく見つけた画像の追加日時を`now`にする）
+    fn sync_folder_at(&mut self, folder: &str, images: &[ImageInfo], now: u64) -> Result<(), String> {
+        let tx = self.conn.transaction()
+            .map_err(|e| t!("index.transaction_failed", e))?;
+
+        // 更新されていない画像の撮影日時は読み直さずに引き継ぐ
+        let taken_at: HashMap<(String, i64), i64> = {
+            let mut stmt = tx.prepare(
+                "SELECT path, modified, taken_at FROM images WHERE folder = ?1 AND taken_at IS NOT NULL"
+            ).map_err(|e| t!("index.read_failed", e))?;
+            let rows = stmt.query_map(params![folder], |row| Ok(((row.get(0)?, row.get(1)?), row.get(2)?)))
+                .and_then(|rows| rows.collect::<Result<HashMap<_, _>, _>>())
+                .map_err(|e| t!("index.read_failed", e))?;
+            rows
+        };
+
+        // 機種名も同じく更新されていない画像は引き継ぐ
+        let camera_model: HashMap<(String, i64), String> = {
+            let mut stmt = tx.prepare(
+                "SELECT path, modified, camera_model FROM images WHERE folder = ?1 AND camera_model IS NOT NULL"
+            ).map_err(|e| t!("index.read_failed", e))?;
+            let rows = stmt.query_map(params![folder], |row| Ok(((row.get(0)?, row.get(1)?), row.get(2)?)))
+                .and_then(|rows| rows.collect::<Result<HashMap<_, _>, _>>())
+                .map_err(|e| t!("index.read_failed", e))?;
+            rows
+        };
+
+        // 以前から記録されていた画像は最初に加えた日時を引き継ぐ（ファイルを更新しても新着にしない）
+        let first_indexed_at: HashMap<String, i64> = {
+            let mut stmt = tx.prepare("SELECT path, first_indexed_at FROM images WHERE folder = ?1")
+                .map_err(|e| t!("index.read_failed", e))?;
+            let rows = stmt.query_map(params![folder], |row| Ok((row.get(0)?, row.get(1)?)))
+                .and_then(|rows| rows.collect::<Result<HashMap<_, _>, _>>())
+                .map_err(|e| t!("index.read_failed", e))?;
+            rows
+        };
+
+        tx.execute("DELETE FROM images WHERE folder = ?1", params![folder])
+            .map_err(|e| t!("index.update_failed", e))?;
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO images (path, name, size, modified, extension, folder, taken_at, bit_depth, first_indexed_at, camera_model)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+            ).map_err(|e| t!("index.update_failed", e))?;
+
+            for image in images {
+                stmt.execute(params![
+                    image.path,
+                    image.name,
+                    image.size as i64,
+                    image.modified as i64,
+                    image.extension,
+                    folder,
+                    taken_at.get(&(image.path.clone(), image.modified as i64)),
+                    image.bit_depth,
+                    first_indexed_at.get(&image.path).copied().unwrap_or(now as i64),
+                    camera_model.get(&(image.path.clone(), image.modified as i64)),
+                ]).map_err(|e| t!("index.update_path_failed", image.path, e))?;
+            }
+        }
+
+        tx.commit().map_err(|e| t!("index.commit_failed", e))
+    }
+
+    /// データベースの整合性を検査し、問題の一覧を返す（正常なら空）
+    pub fn integrity_check(&self) -> Result<Vec<String>, String> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")
+            .map_err(|e| t!("index.check_failed", e))?;
+        let results = stmt.query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| t!("index.check_failed", e))?;
+
+        Ok(results.into_iter().filter(|result| result != "ok").collect())
+    }
+
+    /// 削除で空いた領域を詰めてファイルを小さくする
+    pub fn vacuum(&self) -> Result<(), String> {
+        self.conn.execute_batch("VACUUM")
+            .map_err(|e| t!("index.optimize_failed", e))
+    }
+
+    /// 画像をインデックスから削除する
+    pub fn remove(&self, path: &str) -> Result<(), String> {
+        self.conn.execute("DELETE FROM images WHERE path = ?1", params![path])
+            .map(|_| ())
+            .map_err(|e| t!("index.update_path_failed", path, e))
+    }
+
+    /// 設定フォルダ1つ分の画像をインデックスから削除し、削除した件数を返す
+    pub fn remove_folder(&self, folder: &str) -> Result<usize, String> {
+        self.conn.execute("DELETE FROM images WHERE folder = ?1", params![folder])
+            .map_err(|e| t!("index.update_path_failed", folder, e))
+    }
+
+    /// 利用可能な全画像の記録を取得する（ファイルとの突き合わせ用）
+    pub fn entries(&self) -> Result<Vec<IndexEntry>, String> {
+        let mut stmt = self.conn.prepare("SELECT path, folder, size, modified FROM images WHERE available = 1")
+            .map_err(|e| t!("index.read_failed", e))?;
+        stmt.query_map([], |row| {
+            Ok(IndexEntry {
+                path: row.get(0)?,
+                folder: row.get(1)?,
+                size: row.get::<_, i64>(2)? as u64,
+                modified: row.get::<_, i64>(3)? as u64,
+            })
+        })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| t!("index.read_failed", e))
+    }
+
+    /// 設定フォルダ内の画像を利用可能・利用不可にし、変更した件数を返す
+    pub fn set_folder_available(&self, folder: &str, available: bool) -> Result<usize, String> {
+        self.conn.execute(
+            "UPDATE images SET available = ?2 WHERE folder = ?1 AND available != ?2",
+            params![folder, available as i64],
+        ).map_err(|e| t!("index.update_path_failed", folder, e))
+    }
+
+    /// インデックス済みの画像数を取得する
+    pub fn count(&self) -> Result<usize, String> {
+        self.conn.query_row("SELECT COUNT(*) FROM images", [], |row| row.get::<_, i64>(0))
+            .map(|count| count as usize)
+            .map_err(|e| t!("index.read_failed", e))
+    }
+
+    /// 撮影日時が未取得の画像のパスを取得する
+    pub fn paths_without_taken_at(&self) -> Result<Vec<String>, String> {
+        let mut stmt = self.conn.prepare("SELECT path FROM images WHERE taken_at IS NULL AND available = 1")
+            .map_err(|e| t!("index.read_failed", e))?;
+        stmt.query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| t!("index.read_failed", e))
+    }
+
+    /// 撮影日時をまとめて記録する
+    pub fn set_taken_at(&mut self, entries: &[(String, u64)]) -> Result<(), String> {
+        let tx = self.conn.transaction()
+            .map_err(|e| t!("index.transaction_failed", e))?;
+        {
+            let mut stmt = tx.prepare("UPDATE images SET taken_at = ?2 WHERE path = ?1")
+                .map_err(|e| t!("index.update_failed", e))?;
+            for (path, taken_at) in entries {
+                stmt.execute(params![path, *taken_at as i64])
+                    .map_err(|e| t!("index.update_path_failed", path, e))?;
+            }
+        }
+        tx.commit().map_err(|e| t!("index.commit_failed", e))
+    }
+
+    /// フォルダ内の画像について記録済みのビット数を`(パス, 更新日時)`ごとに取得する
+    pub fn bit_depths(&self, folder: &str) -> Result<HashMap<(String, u64), u8>, String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, modified, bit_depth FROM images WHERE folder = ?1 AND bit_depth IS NOT NULL"
+        ).map_err(|e| t!("index.read_failed", e))?;
+        stmt.query_map(params![folder], |row| Ok(((row.get(0)?, row.get::<_, i64>(1)? as u64), row.get(2)?)))
+            .and_then(|rows| rows.collect::<Result<HashMap<_, _>, _>>())
+            .map_err(|e| t!("index.read_failed", e))
+    }
+
+    /// 記録済みのカメラの機種名を取得する（EXIFに機種名がない画像はNone）
+    pub fn camera_models(&self) -> Result<HashMap<String, Option<String>>, String> {
+        let mut stmt = self.conn.prepare("SELECT path, camera_model FROM images WHERE camera_model IS NOT NULL")
+            .map_err(|e| t!("index.read_failed", e))?;
+        stmt.query_map([], |row| {
+            let model: String = row.get(1)?;
+            Ok((row.get(0)?, Some(model).filter(|model| !model.is_empty())))
+        })
+            .and_then(|rows| rows.collect::<Result<HashMap<_, _>, _>>())
+            .map_err(|e| t!("index.read_failed", e))
+    }
+
+    /// カメラの機種名をまとめて記録する（機種名がない画像は空文字で記録し、次回から読み直さない）
+    pub fn set_camera_models(&mut self, entries: &[(String, Option<String>)]) -> Result<(), String> {
+        let tx = self.conn.transaction()
+            .map_err(|e| t!("index.transaction_failed", e))?;
+        {
+            let mut stmt = tx.prepare("UPDATE images SET camera_model = ?2 WHERE path = ?1")
+                .map_err(|e| t!("index.update_failed", e))?;
+            for (path, model) in entries {
+                stmt.execute(params![path, model.as_deref().unwrap_or_default()])
+                    .map_err(|e| t!("index.update_path_failed", path, e))?;
+            }
+        }
+        tx.commit().map_err(|e| t!("index.commit_failed", e))
+    }
+
+    /// 書き換えたファイルのサイズ・更新日時・撮影日時を反映する（機種名は次に必要になったときに読み直す）
+    pub fn update_file(&self, path: &str, size: u64, modified: u64, taken_at: u64) -> Result<(), String> {
+        self.conn.execute(
+            "UPDATE images SET size = ?2, modified = ?3, taken_at = ?4, camera_model = NULL WHERE path = ?1",
+            params![path, size as i64, modified as i64, taken_at as i64],
+        ).map(|_| ()).map_err(|e| t!("index.update_path_failed", path, e))
+    }
+
+    /// 利用可能な全画像を撮影日時（未取得なら更新日時）付きで、新しい順に取得する
+    pub fn dated_images(&self) -> Result<Vec<(ImageInfo, u64)>, String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, name, size, modified, extension, COALESCE(taken_at, modified) AS date, bit_depth
+             FROM images WHERE available = 1 ORDER BY date DESC"
+        ).map_err(|e| t!("index.read_failed", e))?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                ImageInfo {
+                    path: row.get(0)?,
+                    name: row.get(1)?,
+                    size: row.get::<_, i64>(2)? as u64,
+                    modified: row.get::<_, i64>(3)? as u64,
+                    extension: row.get(4)?,
+                    sensitive: false,
+                    bit_depth: row.get(6)?,
+                },
+                row.get::<_, i64>(5)? as u64,
+            ))
+        }).map_err(|e| t!("index.read_failed", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| t!("index.read_failed", e))
+    }
+
+    /// `since`（Unix時間）以降に初めてインデックスに加えた利用可能な画像を、加えた日時付きで新しい順に取得する
+    pub fn recently_added(&self, since: u64) -> Result<Vec<(ImageInfo, u64)>, String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, name, size, modified, extension, bit_depth, first_indexed_at
+             FROM images WHERE available = 1 AND first_indexed_at >= ?1 AND first_indexed_at > 0
+             ORDER BY first_indexed_at DESC, modified DESC"
+        ).map_err(|e| t!("index.read_failed", e))?;
+
+        let rows = stmt.query_map(params![since as i64], |row| {
+            Ok((
+                ImageInfo {
+                    path: row.get(0)?,
+                    name: row.get(1)?,
+                    size: row.get::<_, i64>(2)? as u64,
+                    modified: row.get::<_, i64>(3)? as u64,
+                    extension: row.get(4)?,
+                    sensitive: false,
+                    bit_depth: row.get(5)?,
+                },
+                row.get::<_, i64>(6)? as u64,
+            ))
+        }).map_err(|e| t!("index.read_failed", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| t!("index.read_failed", e))
+    }
+
+    /// インデックス済みの利用可能な全画像を日付順（新しい順）で取得する
+    pub fn all_images(&self) -> Result<Vec<ImageInfo>, String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, name, size, modified, extension, bit_depth FROM images WHERE available = 1 ORDER BY modified DESC"
+        ).map_err(|e| t!("index.read_failed", e))?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(ImageInfo {
+                path: row.get(0)?,
+                name: row.get(1)?,
+                size: row.get::<_, i64>(2)? as u64,
+                modified: row.get::<_, i64>(3)? as u64,
+                extension: row.get(4)?,
+                sensitive: false,
+                bit_depth: row.get(5)?,
+            })
+        }).map_err(|e| t!("index.read_failed", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| t!("index.read_failed", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(path: &str, modified: u64) -> ImageInfo {
+        ImageInfo {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            size: 10,
+            modified,
+            extension: "png".to_string(),
+            sensitive: false,
+            bit_depth: None,
+        }
+    }
+
+    #[test]
+    fn test_sync_folder_replaces_entries() {
+        let path = std::env::temp_dir().join(format!("poir-index-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut index = LibraryIndex::open_at(&path).unwrap();
+
+        index.sync_folder("/a", &[image("/a/1.png", 1), image("/a/2.png", 2)]).unwrap();
+        index.sync_folder("/b", &[image("/b/3.png", 3)]).unwrap();
+        index.sync_folder("/a", &[image("/a/2.png", 2)]).unwrap();
+
+        let paths: Vec<String> = index.all_images().unwrap().into_iter().map(|i| i.path).collect();
+        assert_eq!(paths, vec!["/b/3.png", "/a/2.png"]);
+
+        // 取り外されたフォルダの画像は一覧から外れ、再接続で戻る
+        assert_eq!(index.set_folder_available("/b", false).unwrap(), 1);
+        assert_eq!(index.all_images().unwrap().len(), 1);
+        index.set_folder_available("/b", true).unwrap();
+        assert_eq!(index.all_images().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_recently_added_keeps_first_indexed_at() {
+        let path = std::env::temp_dir().join(format!("poir-index-recent-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut index = LibraryIndex::open_at(&path).unwrap();
+
+        index.sync_folder_at("/a", &[image("/a/old.png", 1)], 100).unwrap();
+        // 更新されたファイルは新着にならず、新しく現れたファイルだけが新着になる
+        index.sync_folder_at("/a", &[image("/a/old.png", 50), image("/a/new.png", 2)], 200).unwrap();
+
+        let recent: Vec<(String, u64)> = index.recently_added(150).unwrap()
+            .into_iter()
+            .map(|(image, added)| (image.path, added))
+            .collect();
+        assert_eq!(recent, vec![("/a/new.png".to_string(), 200)]);
+        assert_eq!(index.recently_added(0).unwrap().len(), 2);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_camera_models_survive_rescan_until_modified() {
+        let path = std::env::temp_dir().join(format!("poir-index-camera-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut index = LibraryIndex::open_at(&path).unwrap();
+
+        index.sync_folder("/a", &[image("/a/1.png", 1), image("/a/2.png", 2)]).unwrap();
+        index.set_camera_models(&[
+            ("/a/1.png".to_string(), Some("Canon EOS R6".to_string())),
+            ("/a/2.png".to_string(), None),
+        ]).unwrap();
+        let cameras = index.camera_models().unwrap();
+        assert_eq!(cameras.get("/a/1.png"), Some(&Some("Canon EOS R6".to_string())));
+        assert_eq!(cameras.get("/a/2.png"), Some(&None));
+
+        // 更新されたファイルの機種名は読み直す
+        index.sync_folder("/a", &[image("/a/1.png", 1), image("/a/2.png", 5)]).unwrap();
+        let cameras = index.camera_models().unwrap();
+        assert_eq!(cameras.len(), 1);
+        assert!(cameras.contains_key("/a/1.png"));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_bit_depths_are_keyed_on_modified() {
+        let path = std::env::temp_dir().join(format!("poir-index-depth-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut index = LibraryIndex::open_at(&path).unwrap();
+
+        let deep = ImageInfo { bit_depth: Some(16), ..image("/a/deep.png", 3) };
+        index.sync_folder("/a", &[deep, image("/a/unknown.png", 4)]).unwrap();
+        index.sync_folder("/b", &[ImageInfo { bit_depth: Some(8), ..image("/b/1.png", 1) }]).unwrap();
+
+        let depths = index.bit_depths("/a").unwrap();
+        assert_eq!(depths.len(), 1);
+        assert_eq!(depths.get(&("/a/deep.png".to_string(), 3)), Some(&16));
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::config::ResourceConfig;
+use crate::image::{self, ImageInfo};
+use crate::index::{IndexEntry, LibraryIndex};
+use crate::long_path;
+use crate::privacy;
+use crate::watchdog::{self, Operation};
+
+/// スキャンと同じ探索深さ
+const SCAN_DEPTH: usize = 3;
+
+/// 突き合わせの後に行う修復
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ReconcileIndexOptions {
+    /// ファイルがなくなった画像と、設定から外したフォルダの画像をインデックスから削除する
+    pub purge: bool,
+    /// 食い違いのあるフォルダをスキャンし直してインデックスを更新する
+    pub reindex: bool,
+}
+
+/// インデックスとファイルの突き合わせ結果
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct IndexReconcileReport {
+    /// インデックスにあるがファイルがない画像
+    pub missing: Vec<String>,
+    /// サイズ・更新日時がインデックスと異なる画像
+    pub modified: Vec<String>,
+    /// ファイルはあるがインデックスにない画像
+    pub unindexed: Vec<String>,
+    /// インデックスに残っている、設定にないフォルダ
+    pub unconfigured_folders: Vec<String>,
+    /// 削除したインデックスの件数
+    pub purged: usize,
+    /// スキャンし直したフォルダ
+    pub reindexed_folders: Vec<String>,
+}
+
+/// 設定フォルダ1つ分の記録とファイルを比べる（`exists`はファイルの有無の確認）
+///
+/// ファイルの一覧はスキャンの深さまでなので、なくなったかどうかは一覧ではなくファイルの有無で判断する
+fn compare<F>(entries: &[&IndexEntry], on_disk: &[ImageInfo], exists: F, report: &mut IndexReconcileReport)
+where
+    F: Fn(&str) -> bool,
+{
+    let disk: HashMap<&str, &ImageInfo> = on_disk.iter().map(|image| (image.path.as_str(), image)).collect();
+    let indexed: HashSet<&str> = entries.iter().map(|entry| entry.path.as_str()).collect();
+    for entry in entries {
+        match disk.get(entry.path.as_str()) {
+            Some(image) if image.size != entry.size || image.modified != entry.modified => report.modified.push(entry.path.clone()),
+            Some(_) => {},
+            None if !exists(&entry.path) => report.missing.push(entry.path.clone()),
+            None => {},
+        }
+    }
+    report.unindexed.extend(on_disk.iter().filter(|image| !indexed.contains(image.path.as_str())).map(|image| image.path.clone()));
+}
+
+fn reconcile(app_handle: &AppHandle, options: &ReconcileIndexOptions) -> Result<IndexReconcileReport, String> {
+    let config = ResourceConfig::load(app_handle)?;
+    let mut index = LibraryIndex::open(app_handle)?;
+    let entries = index.entries()?;
+    let hidden = privacy::hidden_folders(app_handle);
+
+    let mut by_folder: HashMap<&str, Vec<&IndexEntry>> = HashMap::new();
+    for entry in &entries {
+        by_folder.entry(entry.folder.as_str()).or_default().push(entry);
+    }
+
+    let mut report = IndexReconcileReport::default();
+    let mut configured = HashSet::new();
+    let mut stale_folders = Vec::new();
+    for dir in &config.filters.include {
+        // プライバシーモード中に隠しているフォルダや無効にしたフォルダは調べない（インデックスにも残す）
+        if privacy::is_hidden(dir, &hidden) || !config.folder_options(dir).enabled {
+            configured.extend([dir.clone(), long_path::resolve(Path::new(dir)).to_string_lossy().to_string()]);
+            continue;
+        }
+        let dir_path = long_path::resolve(Path::new(dir));
+        let folder = dir_path.to_string_lossy().to_string();
+        configured.insert(folder.clone());
+        // 取り外されたドライブなどで見つからないフォルダは利用不可として扱い、削除の対象にしない
+        if !long_path::extended(&dir_path).is_dir() {
+            continue;
+        }
+
+        let on_disk = match image::list_folder_images(&dir_path, SCAN_DEPTH) {
+            Ok(images) => images,
+            Err(e) => {
+                tracing::warn!("突き合わせのためのフォルダの読み込みに失敗: {}", e);
+                continue;
+            },
+        };
+        let before = (report.missing.len(), report.modified.len(), report.unindexed.len());
+        let folder_entries = by_folder.get(folder.as_str()).map(Vec::as_slice).unwrap_or_default();
+        compare(folder_entries, &on_disk, |path| long_path::extended(Path::new(path)).exists(), &mut report);
+        if before != (report.missing.len(), report.modified.len(), report.unindexed.len()) {
+            stale_folders.push((folder, on_disk));
+        }
+    }
+    report.unconfigured_folders = by_folder.keys()
+        .filter(|folder| !configured.contains(**folder))
+        .map(|folder| folder.to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    if options.reindex {
+        for (folder, images) in &stale_folders {
+            index.sync_folder(folder, images)?;
+            report.reindexed_folders.push(folder.clone());
+        }
+    }
+    if options.purge {
+        // スキャンし直したフォルダの削除済みの画像は置き換えで消えている
+        let reindexed: HashSet<&String> = report.reindexed_folders.iter().collect();
+        let folder_of: HashMap<&str, &String> = entries.iter().map(|entry| (entry.path.as_str(), &entry.folder)).collect();
+        for path in &report.missing {
+            if !folder_of.get(path.as_str()).is_some_and(|folder| reindexed.contains(folder)) {
+                index.remove(path)?;
+            }
+            report.purged += 1;
+        }
+        for folder in &report.unconfigured_folders {
+            report.purged += index.remove_folder(folder)?;
+        }
+    }
+
+    tracing::info!(
+        "インデックスとファイルを突き合わせました: 消失{}件・変更{}件・未登録{}件・設定外のフォルダ{}件（削除{}件・再スキャン{}フォルダ）",
+        report.missing.len(), report.modified.len(), report.unindexed.len(), report.unconfigured_folders.len(),
+        report.purged, report.reindexed_folders.len(),
+    );
+    Ok(report)
+}
+
+/// インデックスとファイルを突き合わせ、なくなった画像・変更された画像・未登録の画像を報告する
+///
+/// `options`で、なくなった画像の削除や食い違いのあるフォルダのスキャンし直しも行える
+#[tauri::command]
+pub async fn reconcile_index(app_handle: AppHandle, options: Option<ReconcileIndexOptions>) -> Result<IndexReconcileReport, String> {
+    let options = options.unwrap_or_default();
+    let changes = options.purge || options.reindex;
+    let handle = app_handle.clone();
+    let result = watchdog::run(&app_handle, Operation::Scan, move || reconcile(&handle, &options)).await;
+    if changes {
+        audit::complete(&app_handle, "reconcile_index", &result);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(path: &str, size: u64, modified: u64) -> ImageInfo {
+        ImageInfo {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            size,
+            modified,
+            extension: "jpg".to_string(),
+            sensitive: false,
+            bit_depth: None,
+        }
+    }
+
+    fn entry(path: &str, size: u64, modified: u64) -> IndexEntry {
+        IndexEntry { path: path.to_string(), folder: "/photos".to_string(), size, modified }
+    }
+
+    #[test]
+    fn test_compare_reports_missing_modified_and_unindexed() {
+        let entries = [entry("/photos/a.jpg", 10, 1), entry("/photos/b.jpg", 10, 1), entry("/photos/c.jpg", 10, 1), entry("/photos/deep/d.jpg", 10, 1)];
+        let refs: Vec<&IndexEntry> = entries.iter().collect();
+        let on_disk = [image("/photos/a.jpg", 10, 1), image("/photos/b.jpg", 12, 2), image("/photos/new.jpg", 5, 3)];
+
+        let mut report = IndexReconcileReport::default();
+        // 一覧にない深い階層の画像は、ファイルがあればなくなったとはみなさない
+        compare(&refs, &on_disk, |path| path == "/photos/deep/d.jpg", &mut report);
+        assert_eq!(report.missing, vec!["/photos/c.jpg"]);
+        assert_eq!(report.modified, vec!["/photos/b.jpg"]);
+        assert_eq!(report.unindexed, vec!["/photos/new.jpg"]);
+    }
+}
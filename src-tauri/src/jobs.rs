@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::{Mutex, PoisonError};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+use crate::event_bridge::{self, Delivery};
+use crate::i18n::t;
+use crate::power::{self, BackgroundMode};
+
+/// 終了したジョブを残しておく件数（古いものから削除する）
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// `pause_background_work`で一時停止中か（アプリを終了すると解除される）
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// ジョブの状態
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// ジョブの進捗（`job-progress`イベントでも通知する）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct JobProgress {
+    pub id: String,
+    /// ジョブの種類（`watermark`など）
+    pub kind: String,
+    pub status: JobStatus,
+    /// 処理済みの件数
+    pub done: usize,
+    pub total: usize,
+    /// 処理中の対象
+    pub current: Option<String>,
+    /// 完了時の結果
+    pub result: Option<Value>,
+    /// 失敗時のエラー
+    pub error: Option<String>,
+}
+
+/// ジョブ全体の状態
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct JobQueueStatus {
+    /// `pause_background_work`で一時停止中か
+    pub paused: bool,
+    /// バックグラウンド処理の現在の動作（一時停止と電源の状態を反映）
+    pub mode: BackgroundMode,
+    /// 実行中のジョブの数
+    pub running: usize,
+    pub jobs: Vec<JobProgress>,
+}
+
+struct Job {
+    progress: JobProgress,
+    cancel: Arc<AtomicBool>,
+    /// 登録順（古い終了済みジョブを削除するため）
+    seq: u64,
+}
+
+/// 実行中・終了済みのジョブ
+#[derive(Default)]
+pub struct JobState {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<String, Job>>,
+}
+
+impl JobState {
+    fn register(&self, kind: &str, total: usize) -> (JobProgress, Arc<AtomicBool>) {
+        let seq = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let progress = JobProgress {
+            id: format!("{}-{}", kind, seq),
+            kind: kind.to_string(),
+            status: JobStatus::Running,
+            done: 0,
+            total,
+            current: None,
+            result: None,
+            error: None,
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner).insert(progress.id.clone(), Job { progress: progress.clone(), cancel: cancel.clone(), seq });
+        (progress, cancel)
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut JobProgress)) -> Option<JobProgress> {
+        let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        let job = jobs.get_mut(id)?;
+        apply(&mut job.progress);
+        let progress = job.progress.clone();
+        if progress.status != JobStatus::Running {
+            prune(&mut jobs);
+        }
+        Some(progress)
+    }
+
+    fn cancel(&self, id: &str) -> bool {
+        match self.jobs.lock().unwrap_or_else(PoisonError::into_inner).get(id) {
+            Some(job) if job.progress.status == JobStatus::Running => {
+                job.cancel.store(true, Ordering::SeqCst);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn snapshot(&self) -> Vec<JobProgress> {
+        let jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut list: Vec<(&u64, &JobProgress)> = jobs.values().map(|job| (&job.seq, &job.progress)).collect();
+        list.sort_by_key(|(seq, _)| **seq);
+        list.into_iter().map(|(_, progress)| progress.clone()).collect()
+    }
+}
+
+/// ユーザーがバックグラウンド処理を一時停止しているか
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::SeqCst)
+}
+
+fn queue_status(state: &JobState) -> JobQueueStatus {
+    let jobs = state.snapshot();
+    JobQueueStatus {
+        paused: is_paused(),
+        mode: power::background_mode(),
+        running: jobs.iter().filter(|job| job.status == JobStatus::Running).count(),
+        jobs,
+    }
+}
+
+/// 終了済みのジョブが上限を超えたら古いものから削除する
+fn prune(jobs: &mut HashMap<String, Job>) {
+    let mut finished: Vec<(u64, String)> = jobs.iter()
+        .filter(|(_, job)| job.progress.status != JobStatus::Running)
+        .map(|(id, job)| (job.seq, id.clone()))
+        .collect();
+    if finished.len() <= MAX_FINISHED_JOBS {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() - MAX_FINISHED_JOBS) {
+        jobs.remove(id);
+    }
+}
+
+/// ジョブの中から進捗を報告・中止を確認するためのハンドル
+pub struct JobHandle {
+    app_handle: AppHandle,
+    id: String,
+    cancel: Arc<AtomicBool>,
+}
+
+impl JobHandle {
+    /// 中止が要求されたか
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    /// 進捗を更新して通知する
+    ///
+    /// バッテリー駆動中は設定に応じてここで待つ（一時停止中は再開か中止まで戻らない）
+    pub fn progress(&self, done: usize, current: Option<&str>) {
+        let Some(state) = self.app_handle.try_state::<JobState>() else { return };
+        if let Some(progress) = state.update(&self.id, |progress| {
+            progress.done = done;
+            progress.current = current.map(str::to_string);
+        }) {
+            event_bridge::emit(&self.app_handle, "job-progress", Delivery::Batch, progress);
+        }
+        power::checkpoint(|| self.is_cancelled());
+    }
+}
+
+/// 処理をバックグラウンドのジョブとして開始し、ジョブIDを返す
+///
+/// 進捗と終了は`job-progress`イベントで通知する（短時間の通知は配列にまとめて届く）
+pub fn spawn<T, F>(app_handle: &AppHandle, kind: &str, total: usize, task: F) -> Result<String, String>
+where
+    T: Serialize,
+    F: FnOnce(&JobHandle) -> Result<T, String> + Send + 'static,
+{
+    let state = app_handle.try_state::<JobState>().ok_or_else(|| t!("jobs.unavailable"))?;
+    let (progress, cancel) = state.register(kind, total);
+    let id = progress.id.clone();
+    event_bridge::emit(app_handle, "job-progress", Delivery::Batch, &progress);
+    tracing::info!("ジョブを開始しました: {} ({}件)", id, total);
+
+    let handle = JobHandle { app_handle: app_handle.clone(), id: id.clone(), cancel };
+    std::thread::spawn(move || {
+        // 一時停止中に開始したジョブは再開まで待つ
+        power::checkpoint(|| handle.is_cancelled());
+        let result = task(&handle);
+        let cancelled = handle.is_cancelled();
+        let Some(state) = handle.app_handle.try_state::<JobState>() else { return };
+        let finished = state.update(&handle.id, |progress| {
+            progress.current = None;
+            match result {
+                _ if cancelled => progress.status = JobStatus::Cancelled,
+                Ok(value) => {
+                    progress.status = JobStatus::Completed;
+                    progress.result = serde_json::to_value(value).ok();
+                },
+                Err(error) => {
+                    progress.status = JobStatus::Failed;
+                    progress.error = Some(error);
+                },
+            }
+        });
+        if let Some(progress) = finished {
+            tracing::info!("ジョブが終了しました: {} ({:?})", progress.id, progress.status);
+            event_bridge::emit(&handle.app_handle, "job-progress", Delivery::Batch, progress);
+        }
+    });
+    Ok(id)
+}
+
+/// 実行中・終了済みのジョブの一覧を開始順に取得する
+#[tauri::command]
+pub fn get_jobs(state: State<'_, JobState>) -> Vec<JobProgress> {
+    state.snapshot()
+}
+
+/// 一時停止の状態とジョブの一覧を取得する
+#[tauri::command]
+pub fn get_job_status(state: State<'_, JobState>) -> JobQueueStatus {
+    queue_status(&state)
+}
+
+/// ジョブ・メンテナンス・リムーバブルメディアの監視をまとめて一時停止する（ゲーム中や従量制の回線の利用時など）
+///
+/// 実行中のジョブは処理中の1件が終わった時点で止まり、再開すると続きから処理する
+#[tauri::command]
+pub fn pause_background_work(state: State<'_, JobState>) -> JobQueueStatus {
+    if !PAUSED.swap(true, Ordering::SeqCst) {
+        tracing::info!("バックグラウンド処理を一時停止しました");
+    }
+    queue_status(&state)
+}
+
+/// 一時停止したバックグラウンド処理を再開する（バッテリー駆動中の節電は引き続き適用される）
+#[tauri::command]
+pub fn resume_background_work(state: State<'_, JobState>) -> JobQueueStatus {
+    if PAUSED.swap(false, Ordering::SeqCst) {
+        tracing::info!("バックグラウンド処理を再開しました");
+    }
+    queue_status(&state)
+}
+
+/// 実行中のジョブの中止を要求する（処理中の1件が終わった時点で止まる）
+#[tauri::command]
+pub fn cancel_job(state: State<'_, JobState>, id: String) -> Result<(), String> {
+    if state.cancel(&id) {
+        Ok(())
+    } else {
+        Err(t!("jobs.not_running", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_update_cancel_and_prune() {
+        let state = JobState::default();
+        let (first, cancel) = state.register("watermark", 3);
+        assert_eq!(first.id, "watermark-1");
+
+        state.update(&first.id, |progress| progress.done = 2);
+        assert!(state.cancel(&first.id));
+        assert!(cancel.load(Ordering::SeqCst));
+        state.update(&first.id, |progress| progress.status = JobStatus::Cancelled);
+        assert!(!state.cancel(&first.id));
+
+        for _ in 0..MAX_FINISHED_JOBS {
+            let (job, _) = state.register("pdf", 1);
+            state.update(&job.id, |progress| progress.status = JobStatus::Completed);
+        }
+        let jobs = state.snapshot();
+        assert_eq!(jobs.len(), MAX_FINISHED_JOBS);
+        assert_eq!(jobs[0].id, "pdf-2");
+    }
+}
@@ -0,0 +1,211 @@
+use std::path::Path;
+use ::image::{DynamicImage, GenericImageView};
+use ::image::imageops::FilterType;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::hidden;
+use crate::i18n::t;
+use crate::image::{self, ImageInfo};
+use crate::jobs::{self, JobHandle};
+use crate::path_guard;
+use crate::preview;
+use crate::privacy;
+use crate::settings::AppSettings;
+
+/// サブフォルダを含めるときの探索深さ
+const RECURSIVE_SEARCH_DEPTH: usize = 3;
+
+/// 特徴を求める前に縮小する最大の大きさ（同じ色の並びを保つため最近傍で縮小する）
+const FEATURE_MAX_SIZE: u32 = 256;
+
+/// アイコンとみなす長辺の大きさ
+const TINY_ICON_SIZE: u32 = 64;
+
+/// 単色とみなす色のエントロピー（ビット）の上限
+const SOLID_ENTROPY: f64 = 1.0;
+
+/// スクリーンショットとみなす、隣と同じ色の画素の割合の下限（写真ではほとんどない）
+const SCREENSHOT_FLAT_RATIO: f64 = 0.5;
+
+/// よくある画面の解像度（縦向きも含めて比較する）
+const SCREEN_SIZES: [(u32, u32); 16] = [
+    (1280, 720), (1280, 800), (1366, 768), (1440, 900), (1536, 864), (1600, 900), (1920, 1080), (1920, 1200),
+    (2560, 1440), (2560, 1600), (2880, 1800), (3840, 2160), (750, 1334), (1080, 2400), (1170, 2532), (1179, 2556),
+];
+
+/// ファイル名に含まれていればスクリーンショットとみなす語
+const SCREENSHOT_NAME_HINTS: [&str; 4] = ["screenshot", "screen shot", "スクリーンショット", "スクショ"];
+
+/// 整理候補の理由
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JunkKind {
+    /// スクリーンショットらしい画像
+    Screenshot,
+    /// ほぼ単色の画像
+    SolidColor,
+    /// 小さなアイコン
+    TinyIcon,
+}
+
+/// 判定に使う画像の特徴
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImageFeatures {
+    pub width: u32,
+    pub height: u32,
+    /// 色（各チャンネル16段階）のエントロピー（ビット、0で単色）
+    pub entropy: f64,
+    /// 右隣と同じ色の画素の割合
+    pub flat_ratio: f64,
+}
+
+/// 整理候補の画像
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JunkCandidate {
+    pub image: ImageInfo,
+    pub kinds: Vec<JunkKind>,
+    pub features: ImageFeatures,
+}
+
+/// 整理候補の検出結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JunkReport {
+    /// 調べた画像の数
+    pub scanned: usize,
+    pub candidates: Vec<JunkCandidate>,
+    /// 読み込めなかった画像
+    pub skipped: Vec<String>,
+}
+
+fn features(image: &DynamicImage) -> ImageFeatures {
+    let (width, height) = image.dimensions();
+    let small = if width.max(height) > FEATURE_MAX_SIZE {
+        image.resize(FEATURE_MAX_SIZE, FEATURE_MAX_SIZE, FilterType::Nearest).to_rgb8()
+    } else {
+        image.to_rgb8()
+    };
+
+    let mut bins = vec![0u32; 16 * 16 * 16];
+    for pixel in small.pixels() {
+        let [r, g, b] = pixel.0;
+        bins[((r as usize >> 4) << 8) | ((g as usize >> 4) << 4) | (b as usize >> 4)] += 1;
+    }
+    let total = small.pixels().len().max(1) as f64;
+    let entropy = bins.iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / total;
+            -p * p.log2()
+        })
+        .sum::<f64>();
+
+    let pairs = (small.width().saturating_sub(1) * small.height()) as f64;
+    let flat = small.rows()
+        .map(|row| row.collect::<Vec<_>>().windows(2).filter(|pair| pair[0] == pair[1]).count())
+        .sum::<usize>();
+
+    ImageFeatures {
+        width,
+        height,
+        entropy,
+        flat_ratio: if pairs > 0.0 { flat as f64 / pairs } else { 0.0 },
+    }
+}
+
+fn is_screen_size(width: u32, height: u32) -> bool {
+    SCREEN_SIZES.iter().any(|&(w, h)| (width, height) == (w, h) || (width, height) == (h, w))
+}
+
+/// 特徴から整理候補の理由を判定する（該当しなければ空）
+fn classify(name: &str, features: &ImageFeatures) -> Vec<JunkKind> {
+    let mut kinds = Vec::new();
+    if features.width.max(features.height) <= TINY_ICON_SIZE {
+        kinds.push(JunkKind::TinyIcon);
+    }
+    let solid = features.entropy < SOLID_ENTROPY;
+    if solid {
+        kinds.push(JunkKind::SolidColor);
+    }
+    let name = name.to_lowercase();
+    let named = SCREENSHOT_NAME_HINTS.iter().any(|hint| name.contains(hint));
+    let looks_like_screen = is_screen_size(features.width, features.height) && features.flat_ratio >= SCREENSHOT_FLAT_RATIO;
+    if !solid && (named || looks_like_screen) {
+        kinds.push(JunkKind::Screenshot);
+    }
+    kinds
+}
+
+fn run(app_handle: &AppHandle, job: &JobHandle, images: Vec<ImageInfo>) -> Result<JunkReport, String> {
+    let limits = AppSettings::load(app_handle).unwrap_or_default().decode_limits;
+    let scanned = images.len();
+    let mut candidates = Vec::new();
+    let mut skipped = Vec::new();
+    for (index, info) in images.into_iter().enumerate() {
+        if job.is_cancelled() {
+            return Err(t!("jobs.cancelled"));
+        }
+        job.progress(index, Some(&info.path));
+        match preview::open_image(app_handle, Path::new(&info.path), &limits) {
+            Ok(decoded) => {
+                let features = features(&decoded);
+                let kinds = classify(&info.name, &features);
+                if !kinds.is_empty() {
+                    candidates.push(JunkCandidate { image: info, kinds, features });
+                }
+            },
+            Err(e) => {
+                tracing::warn!("整理候補の判定で画像を読み込めませんでした: {} - {}", info.path, e);
+                skipped.push(info.path);
+            },
+        }
+        job.progress(index + 1, None);
+    }
+    tracing::info!("整理候補を検出しました: {}枚中{}枚", scanned, candidates.len());
+    Ok(JunkReport { scanned, candidates, skipped })
+}
+
+/// フォルダ内のスクリーンショット・単色の画像・小さなアイコンを検出するジョブを開始し、ジョブIDを返す
+///
+/// ダウンロードフォルダなどの整理用。大きさ・色のエントロピー・同じ色の並びから推定するため誤判定もありうる。
+/// 進捗と結果（`JunkReport`）は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn find_junk_images(app_handle: AppHandle, folder: String, recursive: Option<bool>) -> Result<String, String> {
+    let dir = path_guard::guard(&app_handle, &folder)?;
+    if !dir.is_dir() {
+        return Err(t!("path.not_directory", folder));
+    }
+    let depth = if recursive.unwrap_or(false) { RECURSIVE_SEARCH_DEPTH } else { 0 };
+    let images = hidden::filter_images(&app_handle, privacy::filter_images(&app_handle, image::list_folder_images(&dir, depth)?));
+
+    let handle = app_handle.clone();
+    jobs::spawn(&app_handle, "junk", images.len(), move |job| run(&handle, job, images))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_classify_solid_icon_screenshot_and_photo() {
+        let solid = DynamicImage::ImageRgb8(RgbImage::from_pixel(32, 32, Rgb([200, 30, 30])));
+        let solid_features = features(&solid);
+        assert_eq!(solid_features.entropy, 0.0);
+        assert_eq!(classify("icon.png", &solid_features), vec![JunkKind::TinyIcon, JunkKind::SolidColor]);
+
+        // 平坦な背景にいくつかの帯がある画面
+        let screen = DynamicImage::ImageRgb8(RgbImage::from_fn(1920, 1080, |_, y| match y / 120 {
+            0 => Rgb([30, 30, 30]),
+            n if n % 2 == 0 => Rgb([240, 240, 240]),
+            n => Rgb([(n * 40) as u8, 120, 200]),
+        }));
+        assert_eq!(classify("image.png", &features(&screen)), vec![JunkKind::Screenshot]);
+
+        // 隣り合う画素の色がほとんど異なる写真のような画像
+        let photo = DynamicImage::ImageRgb8(RgbImage::from_fn(1920, 1080, |x, y| {
+            Rgb([(x * 7 + y * 3) as u8, ((x * 13) ^ (y * 5)) as u8, (x * y) as u8])
+        }));
+        assert!(classify("IMG_0001.jpg", &features(&photo)).is_empty());
+        assert_eq!(classify("Screenshot 2024-01-01.jpg", &features(&photo)), vec![JunkKind::Screenshot]);
+    }
+}
@@ -0,0 +1,405 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use percent_encoding::percent_decode_str;
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Emitter, Manager, State, Url};
+use crate::album::AlbumStore;
+use crate::config::ResourceConfig;
+use crate::i18n::t;
+use crate::image::{self, ImageInfo};
+use crate::lock;
+
+/// 起動引数で指定された表示対象
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum LaunchTarget {
+    /// フォルダを一時的に開く（設定には保存しない）
+    Folder { path: String },
+    /// 画像を開き、同じフォルダの画像をナビゲーション対象にする
+    Image { path: String },
+    /// アルバムを名前で開く（ディープリンク経由）
+    Album { name: String },
+}
+
+/// 起動対象を解決した結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LaunchView {
+    /// 起動引数で指定された対象
+    pub target: LaunchTarget,
+    /// ナビゲーション対象の画像一覧
+    pub images: Vec<ImageInfo>,
+    /// 最初に表示する画像のインデックス
+    pub current_index: Option<usize>,
+}
+
+/// ドロップされたフォルダの検証結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DroppedFolder {
+    /// フォルダのパス
+    pub path: String,
+    /// 設定に追加可能かどうか
+    pub valid: bool,
+    /// 追加できない理由
+    pub error: Option<String>,
+    /// 既に設定に含まれているかどうか
+    pub already_included: bool,
+}
+
+/// ドロップされたパスの分類結果
+#[derive(Debug, Default, PartialEq)]
+pub struct DroppedPaths {
+    /// 設定への追加候補となるフォルダ
+    pub folders: Vec<DroppedFolder>,
+    /// すぐに開く画像ファイル
+    pub images: Vec<String>,
+}
+
+/// 起動引数の解析結果を保持するステート
+#[derive(Default)]
+pub struct LaunchState(pub Mutex<Option<LaunchTarget>>);
+
+/// フォルダを開く際の探索深さ
+const FOLDER_SEARCH_DEPTH: usize = 3;
+
+/// ディープリンクのURLスキーム
+pub const DEEP_LINK_SCHEME: &str = "poirviewer";
+
+/// 作業ディレクトリを基準に絶対パスへ変換する
+fn to_absolute(path: &Path, cwd: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::path::absolute(cwd.join(path)).unwrap_or_else(|_| cwd.join(path))
+    }
+}
+
+/// パス文字列から起動対象を判定する（相対パスは`cwd`を基準に解決）
+pub fn target_from_path(arg: &str, cwd: &Path) -> Option<LaunchTarget> {
+    let path = to_absolute(Path::new(arg), cwd);
+
+    if path.is_dir() {
+        Some(LaunchTarget::Folder { path: path.to_string_lossy().to_string() })
+    } else if path.is_file() && image::is_image_file(&path) {
+        Some(LaunchTarget::Image { path: path.to_string_lossy().to_string() })
+    } else {
+        None
+    }
+}
+
+/// ディープリンクURLから起動対象を判定する
+///
+/// - `poirviewer://open?path=<パス>` : フォルダまたは画像を開く
+/// - `poirviewer://album/<アルバム名>` : アルバムを開く
+pub fn target_from_deep_link(url: &Url) -> Option<LaunchTarget> {
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return None;
+    }
+
+    match url.host_str()? {
+        "open" => {
+            let path = url.query_pairs()
+                .find(|(key, _)| key == "path")
+                .map(|(_, value)| value.into_owned())?;
+            let cwd = std::env::current_dir().unwrap_or_default();
+            target_from_path(&path, &cwd)
+        },
+        "album" => {
+            let segment = url.path_segments()?.find(|s| !s.is_empty())?;
+            let name = percent_decode_str(segment).decode_utf8().ok()?.into_owned();
+            Some(LaunchTarget::Album { name })
+        },
+        _ => None,
+    }
+}
+
+/// 引数1つを解析する（ディープリンクURLとファイルパスの両方に対応）
+fn target_from_arg(arg: &str, cwd: &Path) -> Option<LaunchTarget> {
+    if arg.starts_with(&format!("{}://", DEEP_LINK_SCHEME)) {
+        return Url::parse(arg).ok().and_then(|url| target_from_deep_link(&url));
+    }
+    target_from_path(arg, cwd)
+}
+
+/// コマンドライン引数を解析し、最初に見つかった有効なパスを起動対象とする
+///
+/// 先頭要素（実行ファイル名）とオプション（`-`で始まる引数）は無視する。
+/// Windows/Linuxではディープリンクも引数として渡されるため、ここで併せて解釈する
+pub fn parse_launch_args<I>(args: I, cwd: &Path) -> Option<LaunchTarget>
+where
+    I: IntoIterator<Item = String>,
+{
+    args.into_iter()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .find_map(|arg| target_from_arg(&arg, cwd))
+}
+
+/// 起動対象から表示用の画像一覧を構築する
+pub fn resolve_target(target: &LaunchTarget) -> Result<LaunchView, String> {
+    match target {
+        LaunchTarget::Folder { path } => {
+            let images = image::list_folder_images(Path::new(path), FOLDER_SEARCH_DEPTH)?;
+            let current_index = if images.is_empty() { None } else { Some(0) };
+            Ok(LaunchView { target: target.clone(), images, current_index })
+        },
+        LaunchTarget::Image { path } => {
+            let parent = Path::new(path)
+                .parent()
+                .ok_or_else(|| t!("path.no_parent", path))?;
+            // 同じフォルダの画像のみを兄弟として扱う
+            let images = image::list_folder_images(parent, 0)?;
+            let current_index = images.iter().position(|img| &img.path == path);
+            Ok(LaunchView { target: target.clone(), images, current_index })
+        },
+        // アルバムの中身はストアが必要なため`resolve`で解決する
+        LaunchTarget::Album { .. } => {
+            Ok(LaunchView { target: target.clone(), images: Vec::new(), current_index: None })
+        },
+    }
+}
+
+/// 表示対象を解決する（アルバムはアルバムストアから画像一覧を取得する）
+pub fn resolve(app_handle: &AppHandle, target: &LaunchTarget) -> Result<LaunchView, String> {
+    match target {
+        LaunchTarget::Album { name } => {
+            lock::ensure_album_unlocked(app_handle, name)?;
+            let images = AlbumStore::open(app_handle)?.images(name)?;
+            let current_index = if images.is_empty() { None } else { Some(0) };
+            Ok(LaunchView { target: target.clone(), images, current_index })
+        },
+        _ => resolve_target(target),
+    }
+}
+
+/// 起動対象を保存し、対応するイベントで起動済みウィンドウへ通知する
+pub fn open_target(app_handle: &AppHandle, target: LaunchTarget) {
+    let Some(main_window) = app_handle.get_webview_window("main") else {
+        return;
+    };
+
+    if let Some(state) = app_handle.try_state::<LaunchState>() {
+        if let Ok(mut current) = state.0.lock() {
+            *current = Some(target.clone());
+        }
+    }
+
+    let event = match target {
+        LaunchTarget::Folder { .. } => "open-folder",
+        LaunchTarget::Image { .. } => "open-image",
+        LaunchTarget::Album { .. } => "open-album",
+    };
+
+    match resolve(app_handle, &target) {
+        Ok(view) => {
+            let _ = main_window.emit(event, view);
+        },
+        Err(e) => {
+            tracing::warn!("指定された対象を開けませんでした: {}", e);
+            let _ = main_window.emit("image-error", e);
+        }
+    }
+}
+
+/// ディープリンクで受け取ったURLを処理する
+pub fn handle_deep_links(app_handle: &AppHandle, urls: Vec<Url>) {
+    if let Some(target) = urls.iter().find_map(target_from_deep_link) {
+        open_target(app_handle, target);
+    }
+}
+
+/// ドロップされたパスをフォルダと画像に分類し、フォルダは検証する
+///
+/// 画像でもフォルダでもないパスは無視する
+pub fn classify_dropped_paths(paths: &[PathBuf], included: &[String]) -> DroppedPaths {
+    let mut dropped = DroppedPaths::default();
+
+    for path in paths {
+        if path.is_dir() {
+            let path_str = path.to_string_lossy().to_string();
+            let validation = ResourceConfig::validate_path(&path_str);
+            dropped.folders.push(DroppedFolder {
+                already_included: included.contains(&path_str),
+                valid: validation.is_ok(),
+                error: validation.err(),
+                path: path_str,
+            });
+        } else if path.is_file() && image::is_image_file(path) {
+            dropped.images.push(path.to_string_lossy().to_string());
+        }
+    }
+
+    dropped
+}
+
+/// ウィンドウへのファイルドロップを処理する
+///
+/// フォルダは検証結果を`drop-folders`イベントで通知して追加の確認をフロントエンドに任せ、
+/// 画像は最初の1枚をその場で開く
+pub fn handle_dropped_paths(app_handle: &AppHandle, paths: &[PathBuf]) {
+    let included = ResourceConfig::load(app_handle)
+        .map(|config| config.filters.include)
+        .unwrap_or_default();
+    let dropped = classify_dropped_paths(paths, &included);
+
+    if !dropped.folders.is_empty() {
+        if let Some(main_window) = app_handle.get_webview_window("main") {
+            let _ = main_window.emit("drop-folders", &dropped.folders);
+        }
+    }
+
+    if let Some(path) = dropped.images.into_iter().next() {
+        open_target(app_handle, LaunchTarget::Image { path });
+    }
+}
+
+/// 二重起動時に後発プロセスの引数を受け取り、起動済みのウィンドウで開く
+///
+/// 新しいウィンドウは作らず、`open-image` / `open-folder` / `open-album` イベントで通知する
+#[cfg(desktop)]
+pub fn handle_second_instance(app_handle: &AppHandle, argv: Vec<String>, cwd: String) {
+    // 既存ウィンドウを前面に表示する
+    if let Some(main_window) = app_handle.get_webview_window("main") {
+        let _ = main_window.unminimize();
+        let _ = main_window.show();
+        let _ = main_window.set_focus();
+    }
+
+    if let Some(target) = parse_launch_args(argv, Path::new(&cwd)) {
+        open_target(app_handle, target);
+    }
+}
+
+/// 起動引数で指定された対象を取得する（指定がなければNone）
+#[tauri::command]
+pub fn get_launch_target(
+    app_handle: AppHandle,
+    state: State<'_, LaunchState>,
+) -> Result<Option<LaunchView>, String> {
+    let target = state.0.lock()
+        .map_err(|e| t!("common.lock_failed", t!("launch.state_name"), e))?
+        .clone();
+
+    target.as_ref().map(|target| resolve(&app_handle, target)).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn cwd() -> PathBuf {
+        std::env::current_dir().unwrap()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("poir-launch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_parse_launch_args_folder() {
+        let dir = temp_dir("folder");
+        let args = vec!["poir-viewer".to_string(), dir.to_string_lossy().to_string()];
+        assert_eq!(
+            parse_launch_args(args, &cwd()),
+            Some(LaunchTarget::Folder { path: dir.to_string_lossy().to_string() })
+        );
+    }
+
+    #[test]
+    fn test_parse_launch_args_image_skips_flags_and_non_images() {
+        let dir = temp_dir("image");
+        let text = dir.join("note.txt");
+        let img = dir.join("photo.JPG");
+        fs::write(&text, "x").unwrap();
+        fs::write(&img, "x").unwrap();
+
+        let args = vec![
+            "poir-viewer".to_string(),
+            "--verbose".to_string(),
+            text.to_string_lossy().to_string(),
+            img.to_string_lossy().to_string(),
+        ];
+        assert_eq!(
+            parse_launch_args(args, &cwd()),
+            Some(LaunchTarget::Image { path: img.to_string_lossy().to_string() })
+        );
+    }
+
+    #[test]
+    fn test_parse_launch_args_without_path() {
+        assert_eq!(parse_launch_args(vec!["poir-viewer".to_string()], &cwd()), None);
+    }
+
+    #[test]
+    fn test_parse_launch_args_relative_to_cwd() {
+        let dir = temp_dir("relative");
+        fs::write(dir.join("c.webp"), "x").unwrap();
+        let args = vec!["poir-viewer".to_string(), "c.webp".to_string()];
+        assert_eq!(
+            parse_launch_args(args, &dir),
+            Some(LaunchTarget::Image { path: dir.join("c.webp").to_string_lossy().to_string() })
+        );
+    }
+
+    #[test]
+    fn test_deep_link_album() {
+        let url = Url::parse("poirviewer://album/%E6%97%85%E8%A1%8C%202024").unwrap();
+        assert_eq!(
+            target_from_deep_link(&url),
+            Some(LaunchTarget::Album { name: "旅行 2024".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_deep_link_open_path_in_args() {
+        let dir = temp_dir("deeplink");
+        let url = Url::parse_with_params(
+            "poirviewer://open",
+            &[("path", dir.to_string_lossy().to_string())],
+        ).unwrap();
+        let args = vec!["poir-viewer".to_string(), url.to_string()];
+        assert_eq!(
+            parse_launch_args(args, &cwd()),
+            Some(LaunchTarget::Folder { path: dir.to_string_lossy().to_string() })
+        );
+    }
+
+    #[test]
+    fn test_deep_link_rejects_unknown() {
+        assert_eq!(target_from_deep_link(&Url::parse("poirviewer://delete?path=/").unwrap()), None);
+        assert_eq!(target_from_deep_link(&Url::parse("https://album/x").unwrap()), None);
+    }
+
+    #[test]
+    fn test_classify_dropped_paths() {
+        let dir = temp_dir("drop");
+        let sub = dir.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(dir.join("d.gif"), "x").unwrap();
+        fs::write(dir.join("readme.md"), "x").unwrap();
+
+        let included = vec![sub.to_string_lossy().to_string()];
+        let paths = vec![sub.clone(), dir.join("d.gif"), dir.join("readme.md"), dir.join("missing")];
+        let dropped = classify_dropped_paths(&paths, &included);
+
+        assert_eq!(dropped.folders.len(), 1);
+        assert!(dropped.folders[0].valid);
+        assert!(dropped.folders[0].already_included);
+        assert_eq!(dropped.images, vec![dir.join("d.gif").to_string_lossy().to_string()]);
+    }
+
+    #[test]
+    fn test_resolve_image_target_uses_siblings() {
+        let dir = temp_dir("siblings");
+        fs::write(dir.join("a.png"), "x").unwrap();
+        fs::write(dir.join("b.png"), "x").unwrap();
+        let img = dir.join("b.png").to_string_lossy().to_string();
+
+        let view = resolve_target(&LaunchTarget::Image { path: img.clone() }).unwrap();
+        assert_eq!(view.images.len(), 2);
+        assert_eq!(view.images[view.current_index.unwrap()].path, img);
+    }
+}
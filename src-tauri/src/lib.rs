@@ -1,8 +1,107 @@
+mod album;
+mod analysis;
+mod animation;
+mod archive;
+mod audit;
+mod backup;
+mod barcode;
+mod clipboard_watch;
+mod collation;
+mod color_filter;
+mod compare;
+mod concepts;
 mod config;
+mod contact_sheet;
+mod context_menu;
+mod cover;
+mod crash;
+mod credentials;
+mod crop;
+mod device;
+mod diagnostics;
+mod display;
+mod disk_usage;
+mod drives;
+mod edit_queue;
+mod enhance;
+mod event_bridge;
+mod exif_edit;
+mod exif_info;
+mod export;
+mod file_ops;
+mod filter_preset;
+mod folders;
+mod font;
+mod frames;
+mod gallery;
+mod hidden;
+mod i18n;
+mod identity;
+mod idle;
 mod image;
+mod index;
+mod index_reconcile;
+mod jobs;
+mod junk;
+mod launch;
+mod lock;
+mod logging;
+mod long_path;
+mod maintenance;
+mod manifest;
+mod metadata;
+mod metrics;
+mod navigation;
+mod ocr;
+mod onboarding;
+mod originals;
+mod path_guard;
+mod pdf;
+mod pdf_export;
+mod power;
+mod preview;
+mod print;
+mod privacy;
+mod safe_write;
+mod screenshot;
+mod selection;
+mod sensitive;
+mod session;
+mod settings;
+mod shortcut;
+mod slideshow;
+mod snapshot;
+mod sync;
+mod timeline;
+mod tone_map;
+mod updater;
+mod upscale;
+mod view_state;
+mod viewer;
+mod watchdog;
+mod watermark;
+mod window_state;
 
-use config::ResourceConfig;
-use tauri::{Manager, Window, Emitter};
+use audit::AuditLog;
+use config::{AddPathOutcome, AddPathResult, FolderOptions, PathValidation, PickedFolder, ResourceConfig};
+use context_menu::ContextMenuState;
+use drives::DriveState;
+use event_bridge::EventBridge;
+use idle::IdleState;
+use index::LibraryIndex;
+use i18n::t;
+use launch::LaunchState;
+use lock::LockState;
+use metrics::PerfMetrics;
+use navigation::NavigationState;
+use selection::SelectionState;
+use session::SessionState;
+use slideshow::{MonitorSlideshows, SlideshowState};
+use viewer::ViewerWindows;
+use std::sync::Mutex;
+use tauri::{DragDropEvent, Manager, RunEvent, Window, WindowEvent, Emitter};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::DialogExt;
 
 // 既存のgreetコマンド
 #[tauri::command]
@@ -12,15 +111,16 @@ fn greet(name: &str) -> String {
 
 // 既存のファイル読み込みコマンド
 #[tauri::command]
-async fn read_file_content(file_path: String) -> Result<String, String> {
+async fn read_file_content(app_handle: tauri::AppHandle, file_path: String) -> Result<String, String> {
     use std::fs;
     
-    // 受け取ったパスでファイルを読み込む
-    match fs::read_to_string(&file_path) {
+    // 許可された場所か確認してから、正規化したパスでファイルを読み込む
+    let path = path_guard::guard(&app_handle, &file_path)?;
+    match fs::read_to_string(&path) {
         Ok(content) => Ok(content),
         Err(e) => {
             // エラーの詳細を返す
-            Err(format!("Failed to read file: {} - {}", file_path, e))
+            Err(t!("file.read_failed", file_path, e))
         }
     }
 }
@@ -31,7 +131,7 @@ async fn get_config_path(app_handle: tauri::AppHandle) -> Result<String, String>
     let path = ResourceConfig::get_config_path(&app_handle);
     path.to_str()
         .map(|s| s.to_string())
-        .ok_or_else(|| "Failed to convert path to string".to_string())
+        .ok_or_else(|| t!("config.path_to_string_failed"))
 }
 
 // リソース設定ファイルを読み込む
@@ -47,8 +147,14 @@ async fn save_resource_config(
     app_handle: tauri::AppHandle,
     config: ResourceConfig
 ) -> Result<(), String> {
-    // 設定ファイル保存
-    config.save(&app_handle)
+    // 設定ファイル保存（画面側が扱わないフォルダごとのオプションは引き継ぐ）
+    let mut config = config;
+    if let Ok(previous) = ResourceConfig::load(&app_handle) {
+        config.merge_folder_options(&previous);
+    }
+    let result = config.save(&app_handle);
+    audit::complete(&app_handle, "save_resource_config", &result);
+    result
 }
 
 // パスの有効性を確認するコマンド
@@ -63,26 +169,135 @@ async fn validate_resource_path(path: String) -> bool {
     ResourceConfig::validate_path(&path).is_ok()
 }
 
+// パスを確認し、使えない理由（存在しない・フォルダでない・権限がない・ネットワークに接続できない）か、
+// 使える場合は画像のおおよその数を返すコマンド
+#[tauri::command]
+async fn validate_resource_path_detailed(path: String) -> PathValidation {
+    ResourceConfig::validate_path_detailed(&path)
+}
+
 // パスを直接追加するコマンド
 #[tauri::command]
 async fn add_resource_path(app_handle: tauri::AppHandle, path: String) -> Result<(), String> {
+    let result = add_path_to_config(&app_handle, path);
+    audit::complete(&app_handle, "add_resource_path", &result);
+    result
+}
+
+// パスを検証して設定に追加する
+fn add_path_to_config(app_handle: &tauri::AppHandle, path: String) -> Result<(), String> {
     // パスの有効性を確認
     ResourceConfig::validate_path(&path)?;
     
     // 現在の設定を読み込む
-    let mut config = ResourceConfig::load(&app_handle)?;
+    let mut config = ResourceConfig::load(app_handle)?;
     
-    // 重複チェックを行い、パスを追加
-    if !config.filters.include.contains(&path) {
+    // 重複チェック（Unicodeの正規化形式の違いは同じパスとみなす）を行い、パスを追加
+    if !config.filters.include.iter().any(|include| long_path::same_path(include, &path)) {
         config.filters.include.push(path);
         
         // 設定を保存
-        config.save(&app_handle)?;
+        config.save(app_handle)?;
     }
     
     Ok(())
 }
 
+// 複数のパスをまとめて検証し、有効なものだけを追加するコマンド（パスごとの結果を返す）
+#[tauri::command]
+async fn add_resource_paths(app_handle: tauri::AppHandle, paths: Vec<String>) -> Result<Vec<AddPathResult>, String> {
+    let result = add_paths_to_config(&app_handle, &paths);
+    audit::complete(&app_handle, "add_resource_paths", &result);
+    result
+}
+
+// パスを検証し、追加したものがあれば設定を保存する
+fn add_paths_to_config(app_handle: &tauri::AppHandle, paths: &[String]) -> Result<Vec<AddPathResult>, String> {
+    let mut config = ResourceConfig::load(app_handle)?;
+    let results = config.add_include_paths(paths);
+    if results.iter().any(|result| result.outcome == AddPathOutcome::Added) {
+        config.save(app_handle)?;
+    }
+    Ok(results)
+}
+
+// フォルダ選択ダイアログで選んだフォルダを検証して追加し、そのフォルダだけをスキャンするコマンド
+//
+// ダイアログがキャンセルされた場合はNoneを返す
+#[tauri::command]
+async fn pick_and_add_folder(app_handle: tauri::AppHandle) -> Result<Option<PickedFolder>, String> {
+    let result = pick_and_add(&app_handle);
+    if !matches!(result, Ok(None)) {
+        audit::complete(&app_handle, "pick_and_add_folder", &result);
+    }
+    result
+}
+
+// ダイアログを開き、選ばれたフォルダを設定に追加してスキャンする
+fn pick_and_add(app_handle: &tauri::AppHandle) -> Result<Option<PickedFolder>, String> {
+    let Some(picked) = app_handle.dialog().file().set_title(t!("config.pick_folder_title")).blocking_pick_folder() else {
+        return Ok(None);
+    };
+    let path = picked.into_path()
+        .map_err(|e| t!("config.pick_folder_failed", e))?
+        .to_string_lossy()
+        .to_string();
+
+    let mut config = ResourceConfig::load(app_handle)?;
+    let added = config.add_include_paths(std::slice::from_ref(&path)).remove(0);
+    match added.outcome {
+        AddPathOutcome::Invalid => return Err(added.message.unwrap_or_default()),
+        AddPathOutcome::Added => config.save(app_handle)?,
+        AddPathOutcome::AlreadyAdded => {},
+    }
+
+    // 追加したフォルダだけをスキャンしてインデックスに反映する（深さは一覧のスキャンの既定値）
+    let mut index = LibraryIndex::open(app_handle)
+        .map_err(|e| tracing::warn!("インデックスを開けませんでした: {}", e))
+        .ok();
+    let images = image::scan_folder(app_handle, &path, 3, index.as_mut())?.map_or(0, |images| images.len());
+    tracing::info!("フォルダを追加しました: {} ({}枚)", path, images);
+    Ok(Some(PickedFolder { path, outcome: added.outcome, images }))
+}
+
+// 設定フォルダのスキャンを一時的に無効にする・元に戻すコマンド（設定からは削除しない）
+#[tauri::command]
+async fn set_folder_enabled(app_handle: tauri::AppHandle, path: String, enabled: bool) -> Result<(), String> {
+    let result = set_enabled(&app_handle, &path, enabled);
+    audit::complete(&app_handle, "set_folder_enabled", &result);
+    result
+}
+
+fn set_enabled(app_handle: &tauri::AppHandle, path: &str, enabled: bool) -> Result<(), String> {
+    update_folder_options(app_handle, path, |options| options.enabled = enabled)?;
+    tracing::info!("フォルダのスキャンを{}にしました: {}", if enabled { "有効" } else { "無効" }, path);
+    Ok(())
+}
+
+// 設定フォルダのスキャンの優先度を設定するコマンド（大きいほど先にスキャンし、`folder-scanned`で先に届く）
+#[tauri::command]
+async fn set_folder_priority(app_handle: tauri::AppHandle, path: String, priority: i32) -> Result<(), String> {
+    let result = update_folder_options(&app_handle, &path, |options| options.priority = priority);
+    audit::complete(&app_handle, "set_folder_priority", &result);
+    result
+}
+
+// 設定フォルダのオプションを変更して保存する
+fn update_folder_options(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    update: impl FnOnce(&mut FolderOptions),
+) -> Result<(), String> {
+    let mut config = ResourceConfig::load(app_handle)?;
+    if !config.filters.include.iter().any(|include| include == path) {
+        return Err(t!("config.folder_not_configured", path));
+    }
+    let mut options = config.folder_options(path);
+    update(&mut options);
+    config.set_folder_options(path, options);
+    config.save(app_handle)
+}
+
 // 起動時に設定を初期化し、その状態を通知する
 #[tauri::command]
 async fn initialize_config(
@@ -95,86 +310,407 @@ async fn initialize_config(
     // 設定を読み込む
     let config = ResourceConfig::load(&app_handle)?;
     
-    // 設定の有効性を確認
-    let is_valid = config.is_valid();
+    // 設定フォルダごとの有効性を確認
+    let status = config.status();
     
     // 設定状態をフロントエンドに通知
-    window.emit("config-status", is_valid)
-        .map_err(|e| format!("設定状態の通知に失敗: {}", e))?;
+    window.emit("config-status", &status)
+        .map_err(|e| t!("config.status_emit_failed", e))?;
     
     // 有効でない場合、設定が必要であることをフロントエンドに通知
-    if !is_valid {
+    if !status.valid {
         window.emit("config-required", true)
-            .map_err(|e| format!("設定要求の通知に失敗: {}", e))?;
+            .map_err(|e| t!("config.required_emit_failed", e))?;
     }
     
     Ok(config)
 }
 
+// 設定を読み込んで検証し、結果をメインウィンドウに通知する
+//
+// 検証では設定フォルダごとにディスクを確認するため、起動処理とは別のスレッドで行う
+fn check_config_in_background(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(main_window) = app_handle.get_webview_window("main") else { return };
+        match ResourceConfig::load(&app_handle) {
+            Ok(config) => {
+                let status = config.status();
+                let _ = main_window.emit("config-status", &status);
+                
+                if !status.valid {
+                    let _ = main_window.emit("config-required", true);
+                }
+            },
+            Err(e) => {
+                tracing::error!("設定の読み込みに失敗しました: {}", e);
+                let _ = main_window.emit("config-error", e);
+            }
+        }
+    });
+}
+
 // アプリケーションの実行ファイルのディレクトリパスを取得する
 #[tauri::command]
 fn get_executable_dir() -> Result<String, String> {
     std::env::current_exe()
-        .map_err(|e| format!("実行ファイルパスの取得に失敗: {}", e))
+        .map_err(|e| t!("exe.path_failed", e))
         .and_then(|path| {
             path.parent()
-                .ok_or_else(|| "実行ファイルの親ディレクトリが存在しません".to_string())
+                .ok_or_else(|| t!("exe.no_parent"))
                 .map(|p| p.to_string_lossy().to_string())
         })
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    // 起動引数でフォルダ・画像が指定されていれば一時的に開く（設定には保存しない）
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let launch_target = launch::parse_launch_args(std::env::args(), &cwd);
+    let launch_log = launch_target.clone();
+
+    let builder = tauri::Builder::default();
+    // 二重起動時は引数を既存インスタンスへ転送する（最初に登録する必要がある。デスクトップのみ）
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+        launch::handle_second_instance(app, argv, cwd);
+    }));
+    // グローバルショートカット・クリップボードもデスクトップのみ
+    #[cfg(desktop)]
+    let builder = builder
+        .plugin(shortcut::init_plugin())
+        .plugin(tauri_plugin_clipboard_manager::init());
+
+    builder
+        .plugin(tauri_plugin_deep_link::init())
+        .manage(LaunchState(Mutex::new(launch_target)))
+        .manage(PerfMetrics::default())
+        .manage(AuditLog::default())
+        .manage(SlideshowState::default())
+        .manage(MonitorSlideshows::default())
+        .manage(IdleState::default())
+        .manage(ViewerWindows::default())
+        .manage(ContextMenuState::default())
+        .manage(NavigationState::default())
+        .manage(SessionState::default())
+        .manage(SelectionState::default())
+        .manage(edit_queue::EditQueueState::default())
+        .manage(LockState::default())
+        .manage(DriveState::default())
+        .manage(EventBridge::default())
+        .manage(jobs::JobState::default())
+        .manage(sensitive::SensitiveState::default())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
-        .setup(|app| {
+        .on_menu_event(context_menu::handle_menu_event)
+        .setup(move |app| {
             // アプリケーション起動時に設定ファイルの存在確認を行う
             let app_handle = app.handle();
+
+            // ログ出力を初期化する（以降のログはアプリデータのlogsにも記録される）
+            match logging::init(&logging::get_log_dir(app_handle)) {
+                Ok(state) => {
+                    app.manage(state);
+                },
+                Err(e) => eprintln!("ログの初期化に失敗しました: {}", e),
+            }
+
+            // パニック時のレポート出力と、前回セッションの異常終了検出
+            crash::install_panic_hook(app_handle);
+            if let Err(e) = crash::begin_session(app_handle) {
+                tracing::warn!("セッションの記録に失敗しました: {}", e);
+            }
+
+            // 起動時ロックが有効なら、PINで解除されるまで一覧・プレビューを拒否する
+            lock::init(app_handle);
+
+            // 前回のウィンドウの位置・大きさ・全画面状態を復元する
+            app.manage(window_state::load(app_handle));
+            if let Some(main_window) = app.get_webview_window("main") {
+                window_state::restore(&main_window.as_ref().window());
+            }
+
+            if let Some(target) = &launch_log {
+                tracing::info!("起動引数で指定された対象を開きます: {:?}", target);
+            }
+
+            // poirviewer:// のディープリンクを受け付ける
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                tracing::warn!("ディープリンクの登録に失敗しました: {}", e);
+            }
+            let deep_link_handle = app_handle.clone();
+            app.deep_link().on_open_url(move |event| {
+                launch::handle_deep_links(&deep_link_handle, event.urls());
+            });
             
-            match ResourceConfig::ensure_config_exists(&app_handle) {
-                Ok(_) => println!("設定ファイルの初期化に成功しました"),
-                Err(e) => eprintln!("設定ファイルの初期化に失敗しました: {}", e),
+            match ResourceConfig::ensure_config_exists(app_handle) {
+                Ok(_) => tracing::info!("設定ファイルの初期化に成功しました"),
+                Err(e) => tracing::error!("設定ファイルの初期化に失敗しました: {}", e),
             }
             
-            // メインウィンドウの取得
-            if let Some(main_window) = app.get_webview_window("main") {
-                // 設定状態をチェックして通知
-                match ResourceConfig::load(&app_handle) {
-                    Ok(config) => {
-                        let is_valid = config.is_valid();
-                        let _ = main_window.emit("config-status", is_valid);
-                        
-                        if !is_valid {
-                            let _ = main_window.emit("config-required", true);
-                        }
-                    },
-                    Err(e) => {
-                        eprintln!("設定の読み込みに失敗しました: {}", e);
-                        let _ = main_window.emit("config-error", e);
+            // 設定で有効な場合は更新を確認する
+            updater::check_on_startup(app_handle);
+
+            // 頻繁に発生するイベントをまとめて画面へ送る
+            event_bridge::start(app_handle);
+
+            // 無操作時のスクリーンセーバー
+            idle::start_monitor(app_handle);
+
+            // リムーバブルメディア上の設定フォルダの取り外し・再接続
+            drives::start_monitor(app_handle);
+
+            // バッテリー駆動中のバックグラウンド処理の節電
+            power::start_monitor(app_handle);
+
+            // 設定で有効な場合はクリップボードにコピーされた画像を保存する
+            clipboard_watch::start_monitor(app_handle);
+
+            // 定期的なメンテナンス（再スキャン・キャッシュの整理など）
+            maintenance::start_scheduler(app_handle);
+
+            // 共有フォルダを使った同期（他の端末でのタグ・アルバムの変更を取り込む）
+            sync::sync_on_startup(app_handle);
+
+            // 設定されたグローバルショートカットを登録する
+            #[cfg(desktop)]
+            shortcut::register_saved(app_handle);
+
+            // 設定状態をチェックして通知する（ウィンドウの表示を待たせないよう起動後に行う）
+            check_config_in_background(app_handle);
+            
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            // フォルダ・画像のドロップを受け付ける
+            match event {
+                WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) => {
+                    launch::handle_dropped_paths(window.app_handle(), paths);
+                },
+                // モニターごとのスライドショーのウィンドウは全画面で開き直すため、位置・大きさを記録しない
+                WindowEvent::Moved(_) | WindowEvent::Resized(_) | WindowEvent::CloseRequested { .. }
+                    if slideshow::is_slideshow_window(window.label()) => {},
+                // ウィンドウの位置・大きさを記録し、閉じる際に保存する
+                WindowEvent::Moved(_) | WindowEvent::Resized(_) => window_state::track(window),
+                WindowEvent::CloseRequested { .. } => {
+                    window_state::track(window);
+                    if let Err(e) = window_state::save(window.app_handle()) {
+                        tracing::warn!("ウィンドウ状態の保存に失敗しました: {}", e);
                     }
+                    // メインウィンドウのUI状態を次回起動時に復元できるよう保存する
+                    if window.label() == "main" {
+                        session::autosave(window.app_handle());
+                    }
+                },
+                // 閉じられたビューアウィンドウの表示状態と、スライドショーのウィンドウのスライドショーを破棄する
+                WindowEvent::Destroyed => {
+                    viewer::forget_window(window.app_handle(), window.label());
+                    slideshow::forget_window(window.app_handle(), window.label());
+                },
+                _ => {},
+            }
+        })
+        .invoke_handler({
+            let handler: fn(tauri::ipc::Invoke) -> bool = tauri::generate_handler![
+                greet,
+                read_file_content,
+                get_config_path,
+                load_resource_config,
+                save_resource_config,
+                initialize_config,
+                get_executable_dir,
+                validate_resource_path,
+                validate_resource_path_detailed,
+                add_resource_path,
+                add_resource_paths,
+                pick_and_add_folder,
+                set_folder_enabled,
+                set_folder_priority,
+                onboarding::get_suggested_folders,
+                onboarding::scan_drive_for_image_hotspots,
+                // 新しい画像関連のコマンドを登録
+                image::get_image_list,
+                image::validate_image_path,
+                image::get_paginated_images,
+                launch::get_launch_target,
+                export::export_index,
+                metadata::import_metadata,
+                metadata::get_image_metadata,
+                logging::get_recent_logs,
+                logging::set_log_level,
+                crash::get_last_crash_report,
+                crash::clear_crash_report,
+                diagnostics::run_diagnostics,
+                diagnostics::get_app_info,
+                metrics::get_perf_metrics,
+                metrics::reset_perf_metrics,
+                metrics::benchmark_scan,
+                i18n::get_backend_locale,
+                i18n::set_backend_locale,
+                audit::get_command_history,
+                audit::set_command_history_redaction,
+                settings::load_app_settings,
+                settings::save_app_settings,
+                updater::check_for_updates,
+                slideshow::start_slideshow,
+                slideshow::pause_slideshow,
+                slideshow::resume_slideshow,
+                slideshow::next_slideshow,
+                slideshow::stop_slideshow,
+                slideshow::get_slideshow_status,
+                slideshow::list_monitors,
+                slideshow::start_monitor_slideshow,
+                slideshow::start_slideshow_per_monitor,
+                slideshow::stop_monitor_slideshows,
+                album::list_albums,
+                album::create_album,
+                album::delete_album,
+                album::add_to_album,
+                album::remove_from_album,
+                album::get_album_images,
+                album::set_album_slideshow_settings,
+                album::get_album_slideshow_settings,
+                idle::report_user_activity,
+                viewer::open_viewer_window,
+                viewer::get_viewer_state,
+                viewer::navigate_viewer,
+                viewer::list_viewer_windows,
+                compare::compare_images,
+                shortcut::set_global_shortcut,
+                context_menu::show_image_context_menu,
+                file_ops::open_image_externally,
+                file_ops::reveal_image,
+                file_ops::copy_image_path,
+                file_ops::trash_image,
+                print::print_image,
+                print::get_print_info,
+                navigation::set_navigation_set,
+                navigation::get_next_image,
+                navigation::get_previous_image,
+                navigation::get_navigation_images,
+                filter_preset::save_filter_preset,
+                filter_preset::list_filter_presets,
+                filter_preset::delete_filter_preset,
+                filter_preset::apply_filter_preset,
+                folders::get_folder_tree,
+                folders::get_folder_context,
+                folders::pin_folder,
+                folders::unpin_folder,
+                folders::get_pinned_folders,
+                timeline::get_timeline,
+                timeline::get_recently_added,
+                session::save_session,
+                session::update_session,
+                session::load_session,
+                selection::add_to_selection,
+                selection::remove_from_selection,
+                selection::clear_selection,
+                selection::get_selection,
+                file_ops::trash_selection,
+                metadata::tag_selection,
+                cover::get_folder_cover,
+                cover::set_folder_cover,
+                view_state::save_view_state,
+                view_state::get_view_state,
+                privacy::get_privacy_mode,
+                privacy::set_privacy_mode,
+                privacy::set_folder_private,
+                identity::reconcile_metadata,
+                hidden::hide_image,
+                hidden::unhide_image,
+                hidden::list_hidden_images,
+                lock::get_lock_status,
+                lock::set_app_lock,
+                lock::set_album_locked,
+                lock::unlock_app,
+                lock::lock_app,
+                credentials::store_credential,
+                credentials::get_credential,
+                credentials::delete_credential,
+                archive::list_archive_images,
+                archive::extract_archive_image,
+                archive::pack_folder_to_cbz,
+                archive::unpack_archive,
+                device::list_devices,
+                device::list_device_images,
+                device::fetch_device_image,
+                device::import_device_images,
+                screenshot::capture_screenshot,
+                display::get_display_capabilities,
+                tone_map::get_tone_mapped_preview,
+                analysis::get_histogram,
+                analysis::get_sharpness_map,
+                enhance::get_enhanced_preview,
+                crop::crop_image,
+                edit_queue::queue_rotation,
+                edit_queue::queue_crop,
+                edit_queue::get_pending_edits,
+                edit_queue::discard_pending_edits,
+                edit_queue::apply_pending_edits,
+                originals::restore_original,
+                jobs::get_jobs,
+                jobs::cancel_job,
+                watermark::apply_watermark,
+                contact_sheet::generate_contact_sheet,
+                pdf_export::export_selection_to_pdf,
+                ocr::extract_text,
+                ocr::search_image_text,
+                concepts::search_by_concept,
+                concepts::get_image_concepts,
+                concepts::get_classifier_status,
+                sensitive::reveal_sensitive_image,
+                sensitive::set_image_sensitive,
+                barcode::detect_codes,
+                junk::find_junk_images,
+                animation::analyze_animation,
+                animation::optimize_animation,
+                upscale::upscale_image,
+                color_filter::get_preview_with_filter,
+                disk_usage::get_disk_usage,
+                index_reconcile::reconcile_index,
+                maintenance::get_maintenance_schedule,
+                maintenance::set_maintenance_schedule,
+                snapshot::create_snapshot,
+                snapshot::list_snapshots,
+                snapshot::diff_snapshots,
+                backup::backup_app_data,
+                backup::restore_app_data,
+                sync::get_sync_status,
+                sync::sync_metadata_now,
+                gallery::export_static_gallery,
+                manifest::write_export_manifest,
+                frames::extract_frames,
+                exif_edit::edit_exif,
+                power::get_power_status,
+                power::set_power_policy,
+                jobs::get_job_status,
+                jobs::pause_background_work,
+                jobs::resume_background_work,
+                image::get_startup_image_list
+            ];
+            // すべてのコマンド呼び出しを履歴と操作時刻に記録してから処理する（ロック中は解除系以外を拒否する）
+            move |invoke| {
+                audit::record_invoke(&invoke);
+                idle::record_invoke(&invoke);
+                if lock::is_blocked(&invoke) {
+                    invoke.resolver.reject(lock::locked_error());
+                    return true;
                 }
+                handler(invoke)
             }
-            
-            Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            read_file_content,
-            load_resource_config,
-            save_resource_config,
-            initialize_config,
-            get_executable_dir,
-            validate_resource_path,
-            add_resource_path,
-            // 新しい画像関連のコマンドを登録
-            image::get_image_list,
-            image::validate_image_path,
-            image::get_paginated_images
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app_handle, event| {
+            // 正常終了時はセッションのマーカーを削除する
+            if let RunEvent::Exit = event {
+                let _ = window_state::save(app_handle);
+                session::autosave(app_handle);
+                crash::end_session(app_handle);
+            }
+        });
 }
 
 #[cfg(test)]
@@ -0,0 +1,328 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri::ipc::Invoke;
+use crate::audit;
+use crate::config;
+use crate::event_bridge::{self, Delivery};
+use crate::i18n::t;
+use crate::privacy::{self, new_pin_hash, PinAttempts};
+
+/// ロック中でも受け付けるコマンド（解除とロック状態の確認に必要なもの）
+const ALLOWED_WHILE_LOCKED: [&str; 7] = [
+    "unlock_app",
+    "lock_app",
+    "get_lock_status",
+    "get_backend_locale",
+    "set_backend_locale",
+    "report_user_activity",
+    "greet",
+];
+
+/// ロック中は送らないイベント（画像のパスや一覧を含むもの）
+const SUPPRESSED_WHILE_LOCKED: [&str; 7] = [
+    "folder-scanned",
+    "list-refreshed",
+    "image-updated",
+    "image-deleted",
+    "clipboard-captured",
+    "selection-changed",
+    "slideshow-tick",
+];
+
+/// アプリロックの設定（画面からの設定保存で解除されないよう、アプリ設定とは別に保存する）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct LockFile {
+    /// 解除に必要なPINのハッシュ（`pbkdf2-sha256$反復回数$ソルト$ハッシュ`）
+    pin_hash: Option<String>,
+    /// PINの照合に続けて失敗した記録
+    attempts: PinAttempts,
+    /// 起動時にロックするか
+    lock_on_start: bool,
+    /// 開く際にPINが必要なアルバム
+    locked_albums: Vec<String>,
+}
+
+/// 実行中のロック状態
+#[derive(Default)]
+pub struct LockState {
+    locked: AtomicBool,
+    /// このセッションで解除済みのアルバム
+    unlocked_albums: Mutex<HashSet<String>>,
+}
+
+impl LockState {
+    fn unlocked_albums(&self) -> Result<MutexGuard<'_, HashSet<String>>, String> {
+        self.unlocked_albums.lock()
+            .map_err(|e| t!("common.lock_failed", t!("lock.state_name"), e))
+    }
+}
+
+/// アプリロックの状態
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LockStatus {
+    pub locked: bool,
+    pub pin_set: bool,
+    pub lock_on_start: bool,
+    pub locked_albums: Vec<String>,
+}
+
+/// 保存先のパスを取得する
+fn get_lock_path(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("lock.json")
+}
+
+fn load(app_handle: &AppHandle) -> LockFile {
+    fs::read_to_string(get_lock_path(app_handle))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(app_handle: &AppHandle, lock: &LockFile) -> Result<(), String> {
+    let path = get_lock_path(app_handle);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(lock)
+        .map_err(|e| t!("lock.save_failed", e))?;
+    fs::write(&path, json).map_err(|e| t!("lock.save_failed", e))
+}
+
+/// 起動時のロック状態を設定する（PINが設定され、起動時ロックが有効ならロックする）
+pub fn init(app_handle: &AppHandle) {
+    let lock = load(app_handle);
+    if lock.pin_hash.is_some() && lock.lock_on_start {
+        if let Some(state) = app_handle.try_state::<LockState>() {
+            state.locked.store(true, Ordering::SeqCst);
+            tracing::info!("アプリをロックした状態で起動します");
+        }
+    }
+}
+
+/// コマンド名がロック中に拒否されるものか
+fn is_blocked_command(command: &str, locked: bool) -> bool {
+    locked && !ALLOWED_WHILE_LOCKED.contains(&command)
+}
+
+/// ロック中に受け付けないコマンドの呼び出しか（ステート未登録時は常に受け付ける）
+pub fn is_blocked<R: Runtime>(invoke: &Invoke<R>) -> bool {
+    invoke.message.state_ref().try_get::<LockState>()
+        .map(|state| is_blocked_command(invoke.message.command(), state.locked.load(Ordering::SeqCst)))
+        .unwrap_or(false)
+}
+
+/// アプリがロック中か（ステート未登録時はロックしていないとみなす）
+pub fn is_locked(app_handle: &AppHandle) -> bool {
+    app_handle.try_state::<LockState>()
+        .is_some_and(|state| state.locked.load(Ordering::SeqCst))
+}
+
+/// イベントがロック中に送らないものか
+fn is_suppressed_event(event: &str, locked: bool) -> bool {
+    locked && SUPPRESSED_WHILE_LOCKED.contains(&event)
+}
+
+/// ロック中のため送らずに捨てるべきイベントか
+pub fn suppresses(app_handle: &AppHandle, event: &str) -> bool {
+    is_suppressed_event(event, is_locked(app_handle))
+}
+
+/// ロック中に拒否する際のエラー
+pub fn locked_error() -> String {
+    t!("lock.locked")
+}
+
+/// アルバムがロックされていればエラーを返す（このセッションで解除済みなら通す）
+pub fn ensure_album_unlocked(app_handle: &AppHandle, name: &str) -> Result<(), String> {
+    let lock = load(app_handle);
+    if lock.pin_hash.is_none() || !lock.locked_albums.iter().any(|album| album == name) {
+        return Ok(());
+    }
+    let unlocked = match app_handle.try_state::<LockState>() {
+        Some(state) => state.unlocked_albums()?.contains(name),
+        None => false,
+    };
+    if unlocked {
+        Ok(())
+    } else {
+        Err(t!("lock.album_locked", name))
+    }
+}
+
+fn status(app_handle: &AppHandle, state: &LockState) -> LockStatus {
+    let lock = load(app_handle);
+    LockStatus {
+        locked: state.locked.load(Ordering::SeqCst),
+        pin_set: lock.pin_hash.is_some(),
+        lock_on_start: lock.lock_on_start,
+        locked_albums: lock.locked_albums,
+    }
+}
+
+/// ロック状態の変更を通知する
+fn notify(app_handle: &AppHandle, status: &LockStatus) {
+    event_bridge::emit(app_handle, "lock-state-changed", Delivery::Latest, status);
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 保存されたPINと照合する（PIN未設定なら常に一致とみなす。失敗が続くとしばらく照合しない）
+fn check_pin(lock: &mut LockFile, pin: Option<&str>, now: u64) -> Result<(), String> {
+    privacy::verify_with_attempts(lock.pin_hash.as_deref(), &mut lock.attempts, pin, now)
+}
+
+/// PINを照合し、失敗回数が変わっていれば保存する
+fn verify(app_handle: &AppHandle, lock: &mut LockFile, pin: Option<&str>) -> Result<(), String> {
+    let before = lock.attempts.clone();
+    let result = check_pin(lock, pin, now());
+    if lock.attempts != before {
+        save(app_handle, lock)?;
+    }
+    result
+}
+
+/// アプリロックの状態を取得する
+#[tauri::command]
+pub async fn get_lock_status(app_handle: AppHandle, state: tauri::State<'_, LockState>) -> Result<LockStatus, String> {
+    Ok(status(&app_handle, &state))
+}
+
+/// アプリロックのPINと起動時ロックを設定する
+///
+/// 既にPINが設定されている場合は`current_pin`が必要。`pin`を空にするとPINを削除する
+#[tauri::command]
+pub async fn set_app_lock(
+    app_handle: AppHandle,
+    state: tauri::State<'_, LockState>,
+    pin: Option<String>,
+    current_pin: Option<String>,
+    lock_on_start: bool,
+) -> Result<LockStatus, String> {
+    let result = set_lock(&app_handle, pin.as_deref(), current_pin.as_deref(), lock_on_start);
+    audit::complete(&app_handle, "set_app_lock", &result);
+    result?;
+
+    let status = status(&app_handle, &state);
+    notify(&app_handle, &status);
+    Ok(status)
+}
+
+fn set_lock(app_handle: &AppHandle, pin: Option<&str>, current_pin: Option<&str>, lock_on_start: bool) -> Result<(), String> {
+    let mut lock = load(app_handle);
+    verify(app_handle, &mut lock, current_pin)?;
+    if let Some(pin) = pin {
+        lock.pin_hash = if pin.is_empty() { None } else { Some(new_pin_hash(pin)?) };
+    }
+    lock.lock_on_start = lock_on_start;
+    save(app_handle, &lock)?;
+    tracing::info!("アプリロックの設定を更新しました");
+    Ok(())
+}
+
+/// アルバムを開く際にPINを必要とするか設定する
+#[tauri::command]
+pub async fn set_album_locked(
+    app_handle: AppHandle,
+    state: tauri::State<'_, LockState>,
+    name: String,
+    locked: bool,
+    pin: Option<String>,
+) -> Result<LockStatus, String> {
+    let result = set_album(&app_handle, &state, &name, locked, pin.as_deref());
+    audit::complete(&app_handle, "set_album_locked", &result);
+    result?;
+
+    let status = status(&app_handle, &state);
+    notify(&app_handle, &status);
+    Ok(status)
+}
+
+fn set_album(app_handle: &AppHandle, state: &LockState, name: &str, locked: bool, pin: Option<&str>) -> Result<(), String> {
+    let mut lock = load(app_handle);
+    if lock.pin_hash.is_none() {
+        return Err(t!("lock.pin_not_set"));
+    }
+    verify(app_handle, &mut lock, pin)?;
+    lock.locked_albums.retain(|album| album != name);
+    if locked {
+        lock.locked_albums.push(name.to_string());
+        state.unlocked_albums()?.remove(name);
+    }
+    save(app_handle, &lock)
+}
+
+/// PINでアプリのロックを解除する（`album`を指定するとそのアルバムのロックも解除する）
+#[tauri::command]
+pub async fn unlock_app(
+    app_handle: AppHandle,
+    state: tauri::State<'_, LockState>,
+    pin: String,
+    album: Option<String>,
+) -> Result<LockStatus, String> {
+    let result = verify(&app_handle, &mut load(&app_handle), Some(&pin));
+    audit::complete(&app_handle, "unlock_app", &result);
+    result?;
+
+    state.locked.store(false, Ordering::SeqCst);
+    if let Some(album) = album {
+        state.unlocked_albums()?.insert(album);
+    }
+    let status = status(&app_handle, &state);
+    notify(&app_handle, &status);
+    Ok(status)
+}
+
+/// アプリをロックする（解除済みのアルバムも再びロックする）
+#[tauri::command]
+pub async fn lock_app(app_handle: AppHandle, state: tauri::State<'_, LockState>) -> Result<LockStatus, String> {
+    if load(&app_handle).pin_hash.is_none() {
+        return Err(t!("lock.pin_not_set"));
+    }
+    state.locked.store(true, Ordering::SeqCst);
+    state.unlocked_albums()?.clear();
+
+    let status = status(&app_handle, &state);
+    notify(&app_handle, &status);
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_state_blocks_all_but_unlock_commands() {
+        assert!(!is_blocked_command("get_image_list", false));
+        assert!(is_blocked_command("get_image_list", true));
+        assert!(is_blocked_command("get_image_preview", true));
+        assert!(!is_blocked_command("unlock_app", true));
+        assert!(!is_blocked_command("get_lock_status", true));
+
+        let mut lock = LockFile { pin_hash: Some(new_pin_hash("0000").unwrap()), ..Default::default() };
+        assert!(check_pin(&mut lock, Some("0000"), 0).is_ok());
+        assert!(check_pin(&mut lock, Some("1111"), 0).is_err());
+        assert!(check_pin(&mut lock, None, 0).is_err());
+        assert!(check_pin(&mut LockFile::default(), None, 0).is_ok());
+    }
+
+    #[test]
+    fn test_locked_state_suppresses_image_events() {
+        assert!(is_suppressed_event("slideshow-tick", true));
+        assert!(is_suppressed_event("folder-scanned", true));
+        assert!(!is_suppressed_event("folder-scanned", false));
+        assert!(!is_suppressed_event("lock-state-changed", true));
+    }
+}
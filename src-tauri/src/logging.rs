@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, Registry};
+use crate::config;
+use crate::i18n::t;
+
+/// ログファイル名の接頭辞（`poir-viewer.YYYY-MM-DD.log`）
+const LOG_FILE_PREFIX: &str = "poir-viewer";
+
+/// 保持するログファイル数（日単位でローテーション）
+const MAX_LOG_FILES: usize = 7;
+
+/// 既定のログレベル
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
+
+/// ログ出力の状態（レベル変更用ハンドルと書き込みスレッドのガード）
+pub struct LoggingState {
+    level_handle: reload::Handle<LevelFilter, Registry>,
+    log_dir: PathBuf,
+    _guard: WorkerGuard,
+}
+
+/// ログの保存先ディレクトリを取得する
+pub fn get_log_dir(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("logs")
+}
+
+/// ログ出力を初期化する（ファイルと標準出力の両方に出力）
+pub fn init(log_dir: &Path) -> Result<LoggingState, String> {
+    fs::create_dir_all(log_dir)
+        .map_err(|e| t!("logging.dir_create_failed", log_dir.display(), e))?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir)
+        .map_err(|e| t!("logging.file_create_failed", e))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let (level_layer, level_handle) = reload::Layer::new(DEFAULT_LEVEL);
+
+    tracing_subscriber::registry()
+        .with(level_layer)
+        .with(fmt::layer().with_writer(writer).with_ansi(false))
+        .with(fmt::layer())
+        .try_init()
+        .map_err(|e| t!("logging.init_failed", e))?;
+
+    Ok(LoggingState {
+        level_handle,
+        log_dir: log_dir.to_path_buf(),
+        _guard: guard,
+    })
+}
+
+/// ログレベル文字列を解析する
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    match level.to_lowercase().as_str() {
+        "off" => Ok(LevelFilter::OFF),
+        "error" => Ok(LevelFilter::ERROR),
+        "warn" => Ok(LevelFilter::WARN),
+        "info" => Ok(LevelFilter::INFO),
+        "debug" => Ok(LevelFilter::DEBUG),
+        "trace" => Ok(LevelFilter::TRACE),
+        _ => Err(t!("logging.unknown_level", level)),
+    }
+}
+
+/// ログディレクトリから新しい順に最大`limit`行を読み出し、古い順で返す
+pub(crate) fn read_recent_lines(log_dir: &Path, limit: usize) -> Result<Vec<String>, String> {
+    let mut files: Vec<PathBuf> = fs::read_dir(log_dir)
+        .map_err(|e| t!("logging.dir_read_failed", e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with(LOG_FILE_PREFIX))
+                .unwrap_or(false)
+        })
+        .collect();
+    // ファイル名に日付が含まれるため、名前順がそのまま時系列になる
+    files.sort();
+
+    let mut lines = Vec::new();
+    for file in files.iter().rev() {
+        if lines.len() >= limit {
+            break;
+        }
+        let content = fs::read_to_string(file)
+            .map_err(|e| t!("logging.file_read_failed", file.display(), e))?;
+        let remaining = limit - lines.len();
+        let file_lines: Vec<&str> = content.lines().collect();
+        let start = file_lines.len().saturating_sub(remaining);
+        lines.splice(0..0, file_lines[start..].iter().map(|line| line.to_string()));
+    }
+
+    Ok(lines)
+}
+
+/// 直近のログを取得する（バグ報告への添付用）
+#[tauri::command]
+pub fn get_recent_logs(state: State<'_, LoggingState>, limit: Option<usize>) -> Result<Vec<String>, String> {
+    read_recent_lines(&state.log_dir, limit.unwrap_or(500))
+}
+
+/// ログレベルを変更する
+#[tauri::command]
+pub fn set_log_level(state: State<'_, LoggingState>, level: String) -> Result<(), String> {
+    let filter = parse_level(&level)?;
+    state.level_handle.modify(|current| *current = filter)
+        .map_err(|e| t!("logging.level_change_failed", e))?;
+    tracing::info!("ログレベルを変更しました: {}", filter);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("DEBUG").unwrap(), LevelFilter::DEBUG);
+        assert!(parse_level("verbose").is_err());
+    }
+
+    #[test]
+    fn test_read_recent_lines_spans_files() {
+        let dir = std::env::temp_dir().join(format!("poir-logs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("poir-viewer.2024-01-01.log"), "a\nb\nc\n").unwrap();
+        fs::write(dir.join("poir-viewer.2024-01-02.log"), "d\ne\n").unwrap();
+        fs::write(dir.join("other.log"), "x\n").unwrap();
+
+        assert_eq!(read_recent_lines(&dir, 3).unwrap(), vec!["c", "d", "e"]);
+        assert_eq!(read_recent_lines(&dir, 10).unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+/// Windowsの拡張パスの接頭辞
+const EXTENDED_PREFIX: &str = r"\\?\";
+const EXTENDED_UNC_PREFIX: &str = r"\\?\UNC\";
+
+/// 文字列をNFC（合成済み）に正規化する
+pub fn nfc(s: &str) -> String {
+    s.nfc().collect()
+}
+
+/// 正規化形式の違い（NFD/NFC）を無視して同じパスか比較する
+pub fn same_path(a: &str, b: &str) -> bool {
+    a == b || nfc(a) == nfc(b)
+}
+
+/// 絶対パスの文字列を`\\?\`付きの拡張パスに変換する（260文字を超えるパスを扱うため）
+fn to_extended(path: &str) -> String {
+    if path.starts_with(EXTENDED_PREFIX) {
+        return path.to_string();
+    }
+    // 拡張パスでは`/`が区切りとして解釈されないため置き換える
+    let path = path.replace('/', r"\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        format!("{}{}", EXTENDED_UNC_PREFIX, unc)
+    } else if path.as_bytes().get(1) == Some(&b':') {
+        format!("{}{}", EXTENDED_PREFIX, path)
+    } else {
+        // 相対パスは拡張パスにできない
+        path
+    }
+}
+
+/// 拡張パスの接頭辞を取り除く（画面に返すパスや設定と比較するパス用）
+pub fn strip_extended(path: &str) -> String {
+    if let Some(unc) = path.strip_prefix(EXTENDED_UNC_PREFIX) {
+        format!(r"\\{}", unc)
+    } else {
+        path.strip_prefix(EXTENDED_PREFIX).unwrap_or(path).to_string()
+    }
+}
+
+/// ファイル操作に使うパスを取得する（Windowsでは拡張パス、それ以外はそのまま）
+pub fn extended(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(to_extended(&path.to_string_lossy()))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// 存在するパスに解決する
+///
+/// 設定にNFCで保存されたパスがディスク上ではNFDの場合（またはその逆）、
+/// 正規化形式の違いを無視して一致する実在のフォルダ名に置き換える
+pub fn resolve(path: &Path) -> PathBuf {
+    if extended(path).exists() {
+        return path.to_path_buf();
+    }
+
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        let Component::Normal(name) = component else {
+            resolved.push(component.as_os_str());
+            continue;
+        };
+        let candidate = resolved.join(name);
+        if extended(&candidate).exists() {
+            resolved = candidate;
+            continue;
+        }
+        let wanted = nfc(&name.to_string_lossy());
+        let matched = fs::read_dir(extended(&resolved)).ok().and_then(|entries| {
+            entries.flatten()
+                .map(|entry| entry.file_name())
+                .find(|entry_name| nfc(&entry_name.to_string_lossy()) == wanted)
+        });
+        match matched {
+            Some(entry_name) => resolved.push(entry_name),
+            // 見つからなければ元のパスのまま返し、呼び出し側で存在しない扱いにする
+            None => return path.to_path_buf(),
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_paths_and_normalization() {
+        assert_eq!(to_extended(r"C:\写真\2024"), r"\\?\C:\写真\2024");
+        assert_eq!(to_extended("C:/写真/2024"), r"\\?\C:\写真\2024");
+        assert_eq!(to_extended(r"\\nas\photos"), r"\\?\UNC\nas\photos");
+        assert_eq!(to_extended(r"\\?\C:\a"), r"\\?\C:\a");
+        assert_eq!(strip_extended(r"\\?\C:\写真"), r"C:\写真");
+        assert_eq!(strip_extended(r"\\?\UNC\nas\photos"), r"\\nas\photos");
+        assert_eq!(strip_extended("/home/user"), "/home/user");
+
+        // 「が」の合成済み（NFC）と分解（NFD）
+        assert!(same_path("/写真/が", "/写真/か\u{3099}"));
+        assert!(!same_path("/写真/が", "/写真/か"));
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager};
+use crate::audit;
+use crate::config;
+use crate::event_bridge::{self, Delivery};
+use crate::i18n::t;
+use crate::idle::IdleState;
+use crate::image;
+use crate::index::LibraryIndex;
+use crate::power::{self, BackgroundMode};
+use crate::preview;
+use crate::settings::{AppSettings, MaintenanceSettings};
+
+/// 実行すべき作業があるか確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 実行中の作業（同時に1つだけ実行する）
+static RUNNING: Mutex<Option<MaintenanceTask>> = Mutex::new(None);
+
+/// メンテナンス作業の種類
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceTask {
+    /// 設定フォルダのスキャンし直し
+    Rescan,
+    /// インデックス内の画像の縮小画像の事前作成
+    Thumbnails,
+    /// キャッシュの古いファイルの削除
+    CacheEviction,
+    /// インデックスの最適化（VACUUM）
+    Vacuum,
+}
+
+/// `maintenance-task`で通知する作業の状態
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MaintenanceStatus {
+    Started,
+    Completed,
+    Failed,
+}
+
+/// `maintenance-task`イベントの内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MaintenanceEvent {
+    pub task: MaintenanceTask,
+    pub status: MaintenanceStatus,
+    /// 結果の概要またはエラー
+    pub detail: Option<String>,
+}
+
+/// 作業ごとの実行予定
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskSchedule {
+    pub task: MaintenanceTask,
+    pub enabled: bool,
+    pub interval_hours: u64,
+    /// 最後に実行した時刻（UNIX秒）
+    pub last_run: Option<u64>,
+    /// 次に実行できる時刻（UNIX秒、無効ならNone）
+    pub next_run: Option<u64>,
+}
+
+/// メンテナンスの設定と実行予定
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MaintenanceSchedule {
+    pub settings: MaintenanceSettings,
+    pub tasks: Vec<TaskSchedule>,
+    /// 実行中の作業
+    pub running: Option<MaintenanceTask>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// 最後に実行した時刻の保存先
+fn get_state_path(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("maintenance.json")
+}
+
+fn load_last_runs(app_handle: &AppHandle) -> HashMap<MaintenanceTask, u64> {
+    fs::read_to_string(get_state_path(app_handle))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_last_run(app_handle: &AppHandle, task: MaintenanceTask, at: u64) -> Result<(), String> {
+    let mut last_runs = load_last_runs(app_handle);
+    last_runs.insert(task, at);
+    let path = get_state_path(app_handle);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&last_runs)
+        .map_err(|e| t!("maintenance.serialize_failed", e))?;
+    fs::write(&path, json).map_err(|e| t!("maintenance.save_failed", e))
+}
+
+/// 作業ごとの次の実行時刻を求める
+fn schedule(settings: &MaintenanceSettings, last_runs: &HashMap<MaintenanceTask, u64>) -> Vec<TaskSchedule> {
+    settings.tasks.iter()
+        .map(|scheduled| {
+            let last_run = last_runs.get(&scheduled.task).copied();
+            let enabled = settings.enabled && scheduled.enabled && scheduled.interval_hours > 0;
+            TaskSchedule {
+                task: scheduled.task,
+                enabled: scheduled.enabled,
+                interval_hours: scheduled.interval_hours,
+                last_run,
+                next_run: enabled.then(|| last_run.map_or(0, |last| last + scheduled.interval_hours * 3600)),
+            }
+        })
+        .collect()
+}
+
+/// キャッシュ内のファイルを再帰的に列挙する（パス・サイズ・更新日時）
+fn cache_files(dir: &Path, files: &mut Vec<(PathBuf, u64, SystemTime)>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else { continue };
+        if metadata.is_dir() {
+            cache_files(&entry.path(), files);
+        } else if let Ok(modified) = metadata.modified() {
+            files.push((entry.path(), metadata.len(), modified));
+        }
+    }
+}
+
+/// キャッシュ全体が上限に収まるよう古いファイルから削除し、削除した数とバイト数を返す
+fn evict_cache(dir: &Path, max_bytes: u64) -> (usize, u64) {
+    let mut files = Vec::new();
+    cache_files(dir, &mut files);
+    files.sort_by_key(|(_, _, modified)| *modified);
+
+    let mut used: u64 = files.iter().map(|(_, size, _)| size).sum();
+    let (mut removed, mut freed) = (0, 0);
+    for (path, size, _) in files {
+        if used <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            used = used.saturating_sub(size);
+            removed += 1;
+            freed += size;
+        }
+    }
+    (removed, freed)
+}
+
+/// 作業を1つ実行し、結果の概要を返す
+fn run_task(app_handle: &AppHandle, task: MaintenanceTask, settings: &MaintenanceSettings) -> Result<String, String> {
+    match task {
+        MaintenanceTask::Rescan => image::scan_library(app_handle, None).map(|result| format!("{}", result.total)),
+        MaintenanceTask::Thumbnails => {
+            let images = LibraryIndex::open(app_handle)?.all_images()?;
+            let created = images.iter()
+                .filter(|image| {
+                    power::checkpoint(|| false);
+                    preview::thumbnail(app_handle, Path::new(&image.path), settings.thumbnail_size).is_ok()
+                })
+                .count();
+            Ok(format!("{}/{}", created, images.len()))
+        },
+        MaintenanceTask::CacheEviction => {
+            let (removed, freed) = evict_cache(&preview::get_cache_dir(app_handle, ""), settings.max_cache_bytes);
+            Ok(format!("{} ({} bytes)", removed, freed))
+        },
+        MaintenanceTask::Vacuum => LibraryIndex::open(app_handle)?.vacuum().map(|_| String::new()),
+    }
+}
+
+fn emit(app_handle: &AppHandle, task: MaintenanceTask, status: MaintenanceStatus, detail: Option<String>) {
+    event_bridge::emit(app_handle, "maintenance-task", Delivery::Batch, &MaintenanceEvent { task, status, detail });
+}
+
+/// 実行時刻を過ぎた作業を順に実行する
+fn run_due(app_handle: &AppHandle) {
+    let settings = AppSettings::load(app_handle).unwrap_or_default().maintenance;
+    // バッテリー駆動中で止める設定なら、電源に接続されてから実行する
+    if !settings.enabled || power::background_mode() == BackgroundMode::Paused {
+        return;
+    }
+    let idle = app_handle.try_state::<IdleState>().map(|state| state.idle_for()).unwrap_or_default();
+    if idle < Duration::from_secs(settings.idle_minutes * 60) {
+        return;
+    }
+
+    let due: Vec<MaintenanceTask> = schedule(&settings, &load_last_runs(app_handle))
+        .into_iter()
+        .filter(|task| task.next_run.is_some_and(|next| next <= now()))
+        .map(|task| task.task)
+        .collect();
+    for task in due {
+        *RUNNING.lock().unwrap_or_else(PoisonError::into_inner) = Some(task);
+        emit(app_handle, task, MaintenanceStatus::Started, None);
+        tracing::info!("メンテナンスを開始しました: {:?}", task);
+        match run_task(app_handle, task, &settings) {
+            Ok(detail) => {
+                tracing::info!("メンテナンスが完了しました: {:?} {}", task, detail);
+                emit(app_handle, task, MaintenanceStatus::Completed, Some(detail));
+            },
+            Err(e) => {
+                tracing::warn!("メンテナンスに失敗しました: {:?} - {}", task, e);
+                emit(app_handle, task, MaintenanceStatus::Failed, Some(e));
+            },
+        }
+        // 失敗しても間隔を空けて再試行する
+        if let Err(e) = save_last_run(app_handle, task, now()) {
+            tracing::warn!("{}", e);
+        }
+        *RUNNING.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+/// 定期的なメンテナンスを行うスレッドを起動する
+pub fn start_scheduler(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+        run_due(&app_handle);
+    });
+}
+
+/// メンテナンスの設定と作業ごとの実行予定を取得する
+#[tauri::command]
+pub fn get_maintenance_schedule(app_handle: AppHandle) -> MaintenanceSchedule {
+    let settings = AppSettings::load(&app_handle).unwrap_or_default().maintenance;
+    MaintenanceSchedule {
+        tasks: schedule(&settings, &load_last_runs(&app_handle)),
+        settings,
+        running: *RUNNING.lock().unwrap_or_else(PoisonError::into_inner),
+    }
+}
+
+/// メンテナンスの設定を保存する（次の確認から反映される）
+#[tauri::command]
+pub fn set_maintenance_schedule(app_handle: AppHandle, settings: MaintenanceSettings) -> Result<MaintenanceSchedule, String> {
+    let result = AppSettings::load(&app_handle).and_then(|mut app_settings| {
+        app_settings.maintenance = settings;
+        app_settings.save(&app_handle)
+    });
+    audit::complete(&app_handle, "set_maintenance_schedule", &result);
+    result.map(|_| get_maintenance_schedule(app_handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_and_cache_eviction() {
+        let settings = MaintenanceSettings::default();
+        let last_runs = HashMap::from([(MaintenanceTask::Rescan, 1000)]);
+        let tasks = schedule(&settings, &last_runs);
+        assert_eq!(tasks[0].next_run, Some(1000 + 24 * 3600));
+        assert_eq!(tasks[1].next_run, None);
+        assert_eq!(tasks[2].next_run, Some(0));
+        assert!(schedule(&MaintenanceSettings { enabled: false, ..settings }, &last_runs).iter().all(|task| task.next_run.is_none()));
+
+        let dir = std::env::temp_dir().join(format!("poir-maintenance-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("thumbnails")).unwrap();
+        for (name, size) in [("thumbnails/old.png", 100), ("new.png", 50), ("thumbnails/newest.png", 30)] {
+            fs::write(dir.join(name), vec![0u8; size]).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(evict_cache(&dir, 100), (1, 100));
+        assert!(!dir.join("thumbnails/old.png").exists());
+        assert!(dir.join("thumbnails/newest.png").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
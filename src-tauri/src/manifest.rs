@@ -0,0 +1,149 @@
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+use crate::export::{self, ExportFormat};
+use crate::i18n::t;
+use crate::jobs::{self, JobHandle};
+use crate::safe_write::{self, WriteOptions};
+
+/// 目録のファイル名（拡張子は形式に合わせる）
+const MANIFEST_NAME: &str = "manifest";
+
+/// 目録の1ファイル分
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ManifestEntry {
+    /// 書き出し先のフォルダからの相対パス（`/`区切り）
+    pub file: String,
+    pub size: u64,
+    /// 内容全体のSHA-256（16進数）
+    pub sha256: String,
+}
+
+/// 目録の書き出しの結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManifestResult {
+    /// 書き出した目録のパス
+    pub path: String,
+    pub files: usize,
+}
+
+/// ファイル全体のSHA-256を求める
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let file = File::open(path).map_err(|e| t!("file.read_failed", path.display(), e))?;
+    let mut reader = BufReader::new(file);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer).map_err(|e| t!("file.read_failed", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// 以前に書き出した目録か
+fn is_manifest(path: &Path) -> bool {
+    path.file_stem().is_some_and(|stem| stem == MANIFEST_NAME)
+        && path.extension().is_some_and(|extension| extension == "json" || extension == "csv")
+}
+
+/// フォルダ内のファイルを再帰的に列挙する（目録自体は除く、名前順）
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = fs::read_dir(&current).map_err(|e| t!("common.dir_read_failed", current.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.is_file() && !is_manifest(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn to_csv(entries: &[ManifestEntry]) -> String {
+    let mut csv = String::from("file,size,sha256\n");
+    for entry in entries {
+        csv.push_str(&format!("{},{},{}\n", export::csv_field(&entry.file), entry.size, entry.sha256));
+    }
+    csv
+}
+
+/// 書き出し先のフォルダ内のファイルの目録（サイズとSHA-256）を作り、フォルダ直下に保存する
+///
+/// 受け取った側や保管の際に、書き出した内容が壊れていないか確かめるために使う。`job`を渡すと進捗を報告する
+pub fn write(dir: &Path, format: ExportFormat, job: Option<&JobHandle>) -> Result<ManifestResult, String> {
+    let files = list_files(dir)?;
+    let mut entries = Vec::new();
+    for (done, path) in files.iter().enumerate() {
+        if job.is_some_and(|job| job.is_cancelled()) {
+            return Err(t!("jobs.cancelled"));
+        }
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        let file = relative.components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let size = fs::metadata(path).map_err(|e| t!("file.read_failed", path.display(), e))?.len();
+        entries.push(ManifestEntry { file, size, sha256: sha256_file(path)? });
+        if let Some(job) = job {
+            job.progress(done + 1, relative.to_str());
+        }
+    }
+
+    let (content, extension) = match format {
+        ExportFormat::Json => (serde_json::to_string_pretty(&entries).map_err(|e| format!("JSONへの変換に失敗: {}", e))?, "json"),
+        ExportFormat::Csv => (to_csv(&entries), "csv"),
+    };
+    let dest = dir.join(format!("{}.{}", MANIFEST_NAME, extension));
+    safe_write::write_bytes(&dest, content.as_bytes(), WriteOptions::default())?;
+    tracing::info!("書き出した内容の目録を作成しました: {} ({}件)", dest.display(), entries.len());
+    Ok(ManifestResult { path: dest.to_string_lossy().to_string(), files: entries.len() })
+}
+
+/// 書き出し済みのフォルダの目録（ファイル名・サイズ・SHA-256）を作成するジョブを開始し、ジョブIDを返す
+///
+/// 目録はフォルダ直下の`manifest.json`または`manifest.csv`に保存する。進捗と結果は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn write_export_manifest(app_handle: AppHandle, dir: String, format: Option<ExportFormat>) -> Result<String, String> {
+    let dir = PathBuf::from(&dir);
+    if !dir.is_dir() {
+        return Err(t!("path.not_directory", dir.display()));
+    }
+    let total = list_files(&dir)?.len();
+    jobs::spawn(&app_handle, "manifest", total, move |job| write(&dir, format.unwrap_or(ExportFormat::Json), Some(job)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_manifest_lists_files_with_hashes() {
+        let dir = std::env::temp_dir().join(format!("poir-manifest-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("images")).unwrap();
+        fs::write(dir.join("index.html"), b"").unwrap();
+        fs::write(dir.join("images/a, b.jpg"), b"abc").unwrap();
+
+        let result = write(&dir, ExportFormat::Csv, None).unwrap();
+        assert_eq!(result.files, 2);
+        let csv = fs::read_to_string(&result.path).unwrap();
+        assert!(csv.contains("\"images/a, b.jpg\",3,ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\n"));
+        assert!(csv.contains("index.html,0,e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855\n"));
+
+        // 作り直しても目録自体は含めない
+        assert_eq!(write(&dir, ExportFormat::Json, None).unwrap().files, 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,556 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use percent_encoding::percent_decode_str;
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::i18n::t;
+use crate::image;
+use crate::index::LibraryIndex;
+use crate::path_guard;
+use crate::selection;
+use crate::watchdog::{self, Operation};
+
+/// 画像ごとのタグ・レーティング
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ImageMetadata {
+    /// タグ一覧
+    pub tags: Vec<String>,
+    /// レーティング（0〜5）
+    pub rating: Option<u8>,
+}
+
+/// タグ・レーティングの保存先（インデックスと同じSQLiteファイル）
+pub struct MetadataStore {
+    conn: Connection,
+}
+
+impl MetadataStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("metadata.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS image_tags (
+                path TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (path, tag)
+            );
+            CREATE TABLE IF NOT EXISTS image_ratings (
+                path TEXT PRIMARY KEY,
+                rating INTEGER NOT NULL
+            );"
+        ).map_err(|e| t!("metadata.init_failed", e))
+    }
+
+    /// タグを追加する（既存のタグは残す）
+    pub fn add_tags(&self, path: &str, tags: &[String]) -> Result<(), String> {
+        for tag in tags {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO image_tags (path, tag) VALUES (?1, ?2)",
+                params![path, tag],
+            ).map_err(|e| t!("metadata.tags_save_failed", path, e))?;
+        }
+        Ok(())
+    }
+
+    /// レーティングを設定する
+    pub fn set_rating(&self, path: &str, rating: u8) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO image_ratings (path, rating) VALUES (?1, ?2)",
+            params![path, rating.min(5)],
+        ).map_err(|e| t!("metadata.rating_save_failed", path, e))?;
+        Ok(())
+    }
+
+    /// タグを外す
+    pub fn remove_tag(&self, path: &str, tag: &str) -> Result<(), String> {
+        self.conn.execute("DELETE FROM image_tags WHERE path = ?1 AND tag = ?2", params![path, tag])
+            .map_err(|e| t!("metadata.tags_delete_failed", path, e))?;
+        Ok(())
+    }
+
+    /// レーティングを外す
+    pub fn clear_rating(&self, path: &str) -> Result<(), String> {
+        self.conn.execute("DELETE FROM image_ratings WHERE path = ?1", params![path])
+            .map_err(|e| t!("metadata.rating_delete_failed", path, e))?;
+        Ok(())
+    }
+
+    /// 1枚分のメタデータを取得する
+    pub fn get(&self, path: &str) -> Result<ImageMetadata, String> {
+        let mut stmt = self.conn.prepare("SELECT tag FROM image_tags WHERE path = ?1 ORDER BY tag")
+            .map_err(|e| t!("metadata.read_failed", e))?;
+        let tags = stmt.query_map(params![path], |row| row.get(0))
+            .and_then(|rows| rows.collect::<Result<Vec<String>, _>>())
+            .map_err(|e| t!("metadata.read_failed", e))?;
+
+        let rating = self.conn.query_row(
+            "SELECT rating FROM image_ratings WHERE path = ?1",
+            params![path],
+            |row| row.get::<_, u8>(0),
+        ).ok();
+
+        Ok(ImageMetadata { tags, rating })
+    }
+
+    /// 全画像のメタデータをパスごとに取得する
+    pub fn all(&self) -> Result<HashMap<String, ImageMetadata>, String> {
+        let mut result: HashMap<String, ImageMetadata> = HashMap::new();
+
+        let mut stmt = self.conn.prepare("SELECT path, tag FROM image_tags ORDER BY path, tag")
+            .map_err(|e| t!("metadata.read_failed", e))?;
+        let tags = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| t!("metadata.read_failed", e))?;
+        for (path, tag) in tags {
+            result.entry(path).or_default().tags.push(tag);
+        }
+
+        let mut stmt = self.conn.prepare("SELECT path, rating FROM image_ratings")
+            .map_err(|e| t!("metadata.read_failed", e))?;
+        let ratings = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u8>(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| t!("metadata.read_failed", e))?;
+        for (path, rating) in ratings {
+            result.entry(path).or_default().rating = Some(rating);
+        }
+
+        Ok(result)
+    }
+
+    /// 取り込んだメタデータを反映する（タグは追加、レーティングは上書き）
+    fn merge(&self, path: &str, metadata: &ImageMetadata) -> Result<(), String> {
+        self.add_tags(path, &metadata.tags)?;
+        if let Some(rating) = metadata.rating {
+            self.set_rating(path, rating)?;
+        }
+        Ok(())
+    }
+}
+
+/// 取り込み元の形式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    /// XMPサイドカー（Lightroom等）。ファイルまたはフォルダを指定
+    Xmp,
+    /// digiKamのデータベース（digikam4.db）
+    Digikam,
+    /// `path,tags,rating` 形式のCSV（タグは`;`区切り）
+    Csv,
+}
+
+/// 取り込みの結果
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ImportResult {
+    /// メタデータを反映した画像数
+    pub imported: usize,
+    /// 対象画像が見つからずスキップした数
+    pub skipped: usize,
+    /// 個別のエラー
+    pub errors: Vec<String>,
+}
+
+/// XMLの基本的な文字参照を戻す
+fn unescape_xml(value: &str) -> String {
+    value.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// XMPの内容からタグ（dc:subject）とレーティング（xmp:Rating）を取り出す
+fn parse_xmp(content: &str) -> ImageMetadata {
+    let mut metadata = ImageMetadata::default();
+
+    // 属性形式（xmp:Rating="3"）と要素形式（<xmp:Rating>3</xmp:Rating>）の両方に対応
+    if let Some(pos) = content.find("xmp:Rating") {
+        let rest = content[pos + "xmp:Rating".len()..].trim_start_matches(['=', '"', '\'', '>', ' ']);
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit() || *c == '-').collect();
+        metadata.rating = digits.parse::<i32>().ok()
+            .filter(|rating| *rating > 0)
+            .map(|rating| rating.min(5) as u8);
+    }
+
+    if let (Some(start), Some(end)) = (content.find("<dc:subject>"), content.find("</dc:subject>")) {
+        let mut section = &content[start..end];
+        while let Some(open) = section.find("<rdf:li") {
+            let Some(tag_end) = section[open..].find('>') else { break };
+            let body = &section[open + tag_end + 1..];
+            let Some(close) = body.find("</rdf:li>") else { break };
+            let tag = unescape_xml(body[..close].trim());
+            if !tag.is_empty() && !metadata.tags.contains(&tag) {
+                metadata.tags.push(tag);
+            }
+            section = &body[close..];
+        }
+    }
+
+    metadata
+}
+
+/// XMPサイドカーに対応する画像ファイルを探す
+///
+/// `IMG_001.jpg.xmp`（digiKam/darktable）と`IMG_001.xmp`（Lightroom）の両方に対応
+fn image_for_sidecar(sidecar: &Path) -> Option<PathBuf> {
+    let without_xmp = sidecar.with_extension("");
+    if without_xmp.is_file() && image::is_image_file(&without_xmp) {
+        return Some(without_xmp);
+    }
+
+    let stem = sidecar.file_stem()?.to_string_lossy().to_string();
+    let parent = sidecar.parent()?;
+    fs::read_dir(parent).ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.is_file()
+                && image::is_image_file(path)
+                && path.file_stem().map(|s| s.to_string_lossy() == stem).unwrap_or(false)
+        })
+}
+
+/// フォルダ以下のXMPファイルを再帰的に集める
+fn collect_sidecars(dir: &Path, sidecars: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| t!("common.dir_read_failed", dir.display(), e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sidecars(&path, sidecars)?;
+        } else if path.extension().map(|ext| ext.eq_ignore_ascii_case("xmp")).unwrap_or(false) {
+            sidecars.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// XMPサイドカーを読み込む（読み込めないサイドカーはエラーに記録して続ける）
+fn read_xmp(source: &Path) -> Result<(Vec<(String, ImageMetadata)>, Vec<String>), String> {
+    let mut sidecars = Vec::new();
+    if source.is_dir() {
+        collect_sidecars(source, &mut sidecars)?;
+    } else {
+        sidecars.push(source.to_path_buf());
+    }
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for sidecar in sidecars {
+        let content = match fs::read_to_string(&sidecar) {
+            Ok(content) => content,
+            Err(e) => {
+                errors.push(t!("metadata.xmp_read_failed", sidecar.display(), e));
+                continue;
+            },
+        };
+        // 対応する画像がない場合は空パスとしてスキップ扱いにする
+        let image_path = image_for_sidecar(&sidecar)
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        entries.push((image_path, parse_xmp(&content)));
+    }
+    Ok((entries, errors))
+}
+
+/// `/proc/mounts`の内容からデバイスのマウント先を探す
+fn mount_point_in(mounts: &str, device: &Path) -> Option<PathBuf> {
+    mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let source = fields.next()?;
+        let target = fields.next()?;
+        let same = Path::new(source) == device
+            || fs::canonicalize(source).is_ok_and(|source| source == device);
+        // マウント先の空白などは`\040`の形で書かれている
+        same.then(|| PathBuf::from(target.replace("\\040", " ").replace("\\011", "\t").replace("\\134", "\\")))
+    })
+}
+
+/// ボリュームのUUIDからマウント先を探す（Linuxのみ）
+fn mount_point_for_uuid(uuid: &str) -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let device = fs::canonicalize(Path::new("/dev/disk/by-uuid").join(uuid)).ok()?;
+    mount_point_in(&fs::read_to_string("/proc/mounts").ok()?, &device)
+}
+
+/// digiKamのアルバムルートの識別子からボリュームのマウント先を求める
+///
+/// 識別子は`volumeid:?uuid=...`・`volumeid:?path=...`・`networkshareid:?mountpath=...`の形式
+fn volume_mount_point(identifier: &str) -> Option<PathBuf> {
+    let (_, query) = identifier.split_once('?')?;
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        let value = percent_decode_str(value).decode_utf8().ok()?;
+        match key {
+            "path" | "mountpath" => Some(PathBuf::from(value.as_ref())),
+            "uuid" => mount_point_for_uuid(&value),
+            _ => None,
+        }
+    })
+}
+
+/// digiKamのアルバムルートのパス（マウント先と`specificPath`をつなげる）
+///
+/// マウント先が見つからない場合は`specificPath`をそのまま使う
+fn album_root_path(identifier: Option<&str>, specific_path: &str) -> String {
+    match identifier.and_then(volume_mount_point) {
+        Some(mount) => format!(
+            "{}{}",
+            mount.to_string_lossy().trim_end_matches(['/', '\\']),
+            specific_path.trim_end_matches('/'),
+        ),
+        None => specific_path.to_string(),
+    }
+}
+
+/// digiKamのデータベースからタグ・レーティングを読み込む
+fn read_digikam(source: &Path) -> Result<Vec<(String, ImageMetadata)>, String> {
+    let conn = Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| t!("metadata.digikam_open_failed", source.display(), e))?;
+
+    // アルバムルートごとにボリュームのマウント先を解決しておく
+    let mut stmt = conn.prepare("SELECT id, identifier, specificPath FROM AlbumRoots")
+        .map_err(|e| t!("metadata.digikam_roots_failed", e))?;
+    let roots: HashMap<i64, String> = stmt.query_map([], |row| {
+        let identifier: Option<String> = row.get(1)?;
+        let specific_path: String = row.get(2)?;
+        Ok((row.get(0)?, album_root_path(identifier.as_deref(), &specific_path)))
+    }).and_then(|rows| rows.collect::<Result<_, _>>())
+        .map_err(|e| t!("metadata.digikam_roots_failed", e))?;
+
+    let image_path = |root: i64, relative: String, name: String| {
+        let root = roots.get(&root).map(String::as_str).unwrap_or_default();
+        format!("{}{}/{}", root.trim_end_matches('/'), relative.trim_end_matches('/'), name)
+    };
+
+    let mut entries: HashMap<String, ImageMetadata> = HashMap::new();
+
+    // digiKam内部用のタグは除外する
+    let mut stmt = conn.prepare(
+        "SELECT Albums.albumRoot, Albums.relativePath, Images.name, Tags.name
+         FROM ImageTags
+         JOIN Images ON Images.id = ImageTags.imageid
+         JOIN Albums ON Albums.id = Images.album
+         JOIN Tags ON Tags.id = ImageTags.tagid
+         LEFT JOIN Tags AS Parent ON Parent.id = Tags.pid
+         WHERE Parent.name IS NULL OR Parent.name != '_Digikam_Internal_Tags_'"
+    ).map_err(|e| t!("metadata.digikam_tags_failed", e))?;
+    let tags = stmt.query_map([], |row| {
+        Ok((image_path(row.get(0)?, row.get(1)?, row.get(2)?), row.get::<_, String>(3)?))
+    }).and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| t!("metadata.digikam_tags_failed", e))?;
+    for (path, tag) in tags {
+        entries.entry(path).or_default().tags.push(tag);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT Albums.albumRoot, Albums.relativePath, Images.name, ImageInformation.rating
+         FROM ImageInformation
+         JOIN Images ON Images.id = ImageInformation.imageid
+         JOIN Albums ON Albums.id = Images.album
+         WHERE ImageInformation.rating > 0"
+    ).map_err(|e| t!("metadata.digikam_ratings_failed", e))?;
+    let ratings = stmt.query_map([], |row| {
+        Ok((image_path(row.get(0)?, row.get(1)?, row.get(2)?), row.get::<_, i64>(3)?))
+    }).and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| t!("metadata.digikam_ratings_failed", e))?;
+    for (path, rating) in ratings {
+        entries.entry(path).or_default().rating = Some(rating.clamp(0, 5) as u8);
+    }
+
+    Ok(entries.into_iter().collect())
+}
+
+/// CSVの1行をフィールドに分割する（ダブルクォートによるエスケープに対応）
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// `path,tags,rating` 形式のCSVを読み込む
+fn read_csv(source: &Path) -> Result<Vec<(String, ImageMetadata)>, String> {
+    let content = fs::read_to_string(source)
+        .map_err(|e| t!("metadata.csv_read_failed", source.display(), e))?;
+
+    let mut entries = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let fields = split_csv_line(line);
+        // ヘッダー行と空行は読み飛ばす
+        if line.trim().is_empty() || (i == 0 && fields[0].eq_ignore_ascii_case("path")) {
+            continue;
+        }
+
+        let tags = fields.get(1)
+            .map(|tags| tags.split(';').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+            .unwrap_or_default();
+        let rating = fields.get(2)
+            .and_then(|rating| rating.trim().parse::<u8>().ok())
+            .map(|rating| rating.min(5));
+        entries.push((fields[0].clone(), ImageMetadata { tags, rating }));
+    }
+    Ok(entries)
+}
+
+/// 読み込んだメタデータをストアへ反映する
+fn import_entries(store: &MetadataStore, entries: Vec<(String, ImageMetadata)>) -> ImportResult {
+    let mut result = ImportResult::default();
+
+    for (path, metadata) in entries {
+        if path.is_empty() || !Path::new(&path).is_file() {
+            result.skipped += 1;
+            continue;
+        }
+        match store.merge(&path, &metadata) {
+            Ok(()) => result.imported += 1,
+            Err(e) => result.errors.push(e),
+        }
+    }
+
+    result
+}
+
+/// 指定された形式のファイルを読み込んでストアに取り込む
+fn import_from(app_handle: &AppHandle, path: &str, format: ImportFormat) -> Result<ImportResult, String> {
+    let source = Path::new(path);
+    if !source.exists() {
+        return Err(t!("path.not_found", source.display()));
+    }
+
+    let (entries, errors) = match format {
+        ImportFormat::Xmp => read_xmp(source)?,
+        ImportFormat::Digikam => (read_digikam(source)?, Vec::new()),
+        ImportFormat::Csv => (read_csv(source)?, Vec::new()),
+    };
+
+    let store = MetadataStore::open(app_handle)?;
+    let mut result = import_entries(&store, entries);
+    result.errors.extend(errors);
+    Ok(result)
+}
+
+/// 他のアプリケーションから書き出されたタグ・レーティングを取り込む
+#[tauri::command]
+pub async fn import_metadata(
+    app_handle: AppHandle,
+    path: String,
+    format: ImportFormat
+) -> Result<ImportResult, String> {
+    let handle = app_handle.clone();
+    let result = match path_guard::guard(&app_handle, &path) {
+        Ok(_) => watchdog::run(&app_handle, Operation::Metadata, move || import_from(&handle, &path, format)).await,
+        Err(e) => Err(e),
+    };
+    audit::complete(&app_handle, "import_metadata", &result);
+    result
+}
+
+/// 画像のタグ・レーティングを取得する
+#[tauri::command]
+pub async fn get_image_metadata(app_handle: AppHandle, path: String) -> Result<ImageMetadata, String> {
+    let handle = app_handle.clone();
+    watchdog::run(&app_handle, Operation::Metadata, move || MetadataStore::open(&handle)?.get(&path)).await
+}
+
+/// 選択中の画像にまとめてタグを追加し、対象の画像数を返す
+#[tauri::command]
+pub async fn tag_selection(
+    app_handle: AppHandle,
+    selection_id: Option<String>,
+    tags: Vec<String>,
+) -> Result<usize, String> {
+    let result = selection::paths(&app_handle, selection_id).and_then(|paths| {
+        let store = MetadataStore::open(&app_handle)?;
+        for path in &paths {
+            store.add_tags(path, &tags)?;
+        }
+        Ok(paths.len())
+    });
+    audit::complete(&app_handle, "tag_selection", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xmp_lightroom() {
+        let xmp = r#"<x:xmpmeta><rdf:RDF><rdf:Description xmp:Rating="4">
+            <dc:subject><rdf:Bag>
+                <rdf:li>旅行</rdf:li>
+                <rdf:li>Tom &amp; Jerry</rdf:li>
+            </rdf:Bag></dc:subject>
+        </rdf:Description></rdf:RDF></x:xmpmeta>"#;
+
+        assert_eq!(parse_xmp(xmp), ImageMetadata {
+            tags: vec!["旅行".to_string(), "Tom & Jerry".to_string()],
+            rating: Some(4),
+        });
+    }
+
+    #[test]
+    fn test_parse_xmp_rating_element_and_rejected() {
+        assert_eq!(parse_xmp("<xmp:Rating>2</xmp:Rating>").rating, Some(2));
+        assert_eq!(parse_xmp(r#"xmp:Rating="-1""#).rating, None);
+    }
+
+    #[test]
+    fn test_split_csv_line() {
+        assert_eq!(
+            split_csv_line(r#""C:\a,b.jpg","x;""y""",3"#),
+            vec!["C:\\a,b.jpg", "x;\"y\"", "3"]
+        );
+    }
+
+    #[test]
+    fn test_digikam_album_root_path() {
+        assert_eq!(album_root_path(Some("volumeid:?path=%2Fhome%2Fuser%2FPictures"), "/"), "/home/user/Pictures");
+        assert_eq!(album_root_path(Some("networkshareid:?mountpath=/mnt/nas"), "/photos"), "/mnt/nas/photos");
+        assert_eq!(album_root_path(None, "/srv/photos"), "/srv/photos");
+
+        let mounts = "/dev/sdb1 /media/user/My\\040Disk vfat rw 0 0\nproc /proc proc rw 0 0\n";
+        assert_eq!(mount_point_in(mounts, Path::new("/dev/sdb1")), Some(PathBuf::from("/media/user/My Disk")));
+        assert_eq!(mount_point_in(mounts, Path::new("/dev/sdc1")), None);
+    }
+}
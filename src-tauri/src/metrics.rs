@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager, State};
+use crate::i18n::t;
+use crate::image;
+use crate::path_guard;
+
+/// 処理時間の集計
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TimingStats {
+    /// 計測回数
+    pub count: u64,
+    /// 合計時間（ミリ秒）
+    pub total_ms: f64,
+    /// 最短時間（ミリ秒）
+    pub min_ms: f64,
+    /// 最長時間（ミリ秒）
+    pub max_ms: f64,
+    /// 直近の時間（ミリ秒）
+    pub last_ms: f64,
+}
+
+impl TimingStats {
+    fn record(&mut self, duration: Duration) {
+        let ms = duration.as_secs_f64() * 1000.0;
+        self.min_ms = if self.count == 0 { ms } else { self.min_ms.min(ms) };
+        self.max_ms = self.max_ms.max(ms);
+        self.count += 1;
+        self.total_ms += ms;
+        self.last_ms = ms;
+    }
+}
+
+/// キャッシュの利用状況
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CacheStats {
+    /// キャッシュを使えた回数
+    pub hits: u64,
+    /// 作り直した回数
+    pub misses: u64,
+}
+
+/// 計測結果の一覧
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PerfMetricsSnapshot {
+    /// フォルダごとのスキャン時間
+    pub scans: HashMap<String, TimingStats>,
+    /// 処理の種類（インデックス更新・画像の展開等）ごとの時間
+    pub operations: HashMap<String, TimingStats>,
+    /// キャッシュの種類（縮小画像等）ごとの利用状況
+    #[serde(default)]
+    pub caches: HashMap<String, CacheStats>,
+}
+
+/// アプリ全体の性能計測値を保持するステート
+#[derive(Default)]
+pub struct PerfMetrics(pub Mutex<PerfMetricsSnapshot>);
+
+impl PerfMetrics {
+    /// フォルダのスキャン時間を記録する
+    pub fn record_scan(&self, folder: &str, duration: Duration) {
+        if let Ok(mut metrics) = self.0.lock() {
+            metrics.scans.entry(folder.to_string()).or_default().record(duration);
+        }
+    }
+
+    /// 処理時間を記録する
+    pub fn record_operation(&self, name: &str, duration: Duration) {
+        if let Ok(mut metrics) = self.0.lock() {
+            metrics.operations.entry(name.to_string()).or_default().record(duration);
+        }
+    }
+
+    /// キャッシュを使えたかを記録する
+    pub fn record_cache(&self, name: &str, hit: bool) {
+        if let Ok(mut metrics) = self.0.lock() {
+            let stats = metrics.caches.entry(name.to_string()).or_default();
+            if hit {
+                stats.hits += 1;
+            } else {
+                stats.misses += 1;
+            }
+        }
+    }
+}
+
+/// AppHandle経由でスキャン時間を記録する（ステート未登録時は何もしない）
+pub fn record_scan(app_handle: &AppHandle, folder: &str, duration: Duration) {
+    if let Some(metrics) = app_handle.try_state::<PerfMetrics>() {
+        metrics.record_scan(folder, duration);
+    }
+}
+
+/// AppHandle経由で処理時間を記録する（ステート未登録時は何もしない）
+pub fn record_operation(app_handle: &AppHandle, name: &str, duration: Duration) {
+    if let Some(metrics) = app_handle.try_state::<PerfMetrics>() {
+        metrics.record_operation(name, duration);
+    }
+}
+
+/// AppHandle経由でキャッシュの利用状況を記録する（ステート未登録時は何もしない）
+pub fn record_cache(app_handle: &AppHandle, name: &str, hit: bool) {
+    if let Some(metrics) = app_handle.try_state::<PerfMetrics>() {
+        metrics.record_cache(name, hit);
+    }
+}
+
+/// ベンチマークの結果
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct BenchmarkResult {
+    /// 対象フォルダ
+    pub path: String,
+    /// 走査したディレクトリ数
+    pub directories: u64,
+    /// 見つかったファイル数
+    pub files: u64,
+    /// うち画像ファイル数
+    pub images: u64,
+    /// 画像ファイルの合計サイズ（バイト）
+    pub total_bytes: u64,
+    /// ディレクトリ一覧の取得にかかった時間（ミリ秒）
+    pub read_dir_ms: f64,
+    /// メタデータ取得にかかった時間（ミリ秒）
+    pub metadata_ms: f64,
+    /// 全体の時間（ミリ秒）
+    pub total_ms: f64,
+    /// 1秒あたりの画像処理数
+    pub images_per_second: f64,
+}
+
+/// ディレクトリを走査し、一覧取得とメタデータ取得の時間を分けて計測する
+fn benchmark_directory(
+    dir: &Path,
+    max_depth: usize,
+    depth: usize,
+    result: &mut BenchmarkResult,
+) -> Result<(), String> {
+    let started = Instant::now();
+    let entries: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| t!("common.dir_read_failed", dir.display(), e))?
+        .filter_map(|entry| entry.ok())
+        .collect();
+    result.read_dir_ms += started.elapsed().as_secs_f64() * 1000.0;
+    result.directories += 1;
+
+    for entry in entries {
+        let path = entry.path();
+
+        let started = Instant::now();
+        let metadata = fs::metadata(&path);
+        result.metadata_ms += started.elapsed().as_secs_f64() * 1000.0;
+
+        let Ok(metadata) = metadata else { continue };
+        if metadata.is_dir() {
+            if depth < max_depth {
+                benchmark_directory(&path, max_depth, depth + 1, result)?;
+            }
+        } else {
+            result.files += 1;
+            if image::is_image_file(&path) {
+                result.images += 1;
+                result.total_bytes += metadata.len();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 計測値を取得する
+#[tauri::command]
+pub fn get_perf_metrics(state: State<'_, PerfMetrics>) -> Result<PerfMetricsSnapshot, String> {
+    state.0.lock()
+        .map(|metrics| metrics.clone())
+        .map_err(|e| t!("common.lock_failed", t!("metrics.name"), e))
+}
+
+/// 計測値をリセットする
+#[tauri::command]
+pub fn reset_perf_metrics(state: State<'_, PerfMetrics>) -> Result<(), String> {
+    let mut metrics = state.0.lock()
+        .map_err(|e| t!("common.lock_failed", t!("metrics.name"), e))?;
+    *metrics = PerfMetricsSnapshot::default();
+    Ok(())
+}
+
+/// 指定フォルダのスキャン性能を計測する（インデックスは更新しない）
+#[tauri::command]
+pub async fn benchmark_scan(app_handle: AppHandle, path: String, max_depth: Option<usize>) -> Result<BenchmarkResult, String> {
+    let dir = path_guard::guard(&app_handle, &path)?;
+    let dir = dir.as_path();
+    if !dir.is_dir() {
+        return Err(t!("path.not_directory", dir.display()));
+    }
+
+    let mut result = BenchmarkResult { path: path.clone(), ..Default::default() };
+    let started = Instant::now();
+    benchmark_directory(dir, max_depth.unwrap_or(3), 0, &mut result)?;
+    let elapsed = started.elapsed();
+
+    result.total_ms = elapsed.as_secs_f64() * 1000.0;
+    result.images_per_second = if elapsed.as_secs_f64() > 0.0 {
+        result.images as f64 / elapsed.as_secs_f64()
+    } else {
+        0.0
+    };
+
+    tracing::info!(
+        "ベンチマーク完了: {} ({}枚, {:.1}ms, 一覧{:.1}ms, メタデータ{:.1}ms)",
+        path, result.images, result.total_ms, result.read_dir_ms, result.metadata_ms
+    );
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_stats() {
+        let metrics = PerfMetrics::default();
+        metrics.record_operation("index_sync", Duration::from_millis(10));
+        metrics.record_operation("index_sync", Duration::from_millis(30));
+
+        let snapshot = metrics.0.lock().unwrap().clone();
+        let sync = &snapshot.operations["index_sync"];
+        assert_eq!(sync.count, 2);
+        assert_eq!(sync.min_ms, 10.0);
+        assert_eq!(sync.max_ms, 30.0);
+        assert_eq!(sync.last_ms, 30.0);
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let metrics = PerfMetrics::default();
+        metrics.record_cache("thumbnail", false);
+        metrics.record_cache("thumbnail", true);
+        metrics.record_cache("thumbnail", true);
+
+        let snapshot = metrics.0.lock().unwrap().clone();
+        assert_eq!(snapshot.caches["thumbnail"], CacheStats { hits: 2, misses: 1 });
+    }
+}
@@ -0,0 +1,511 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+use std::sync::Mutex;
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, State};
+use crate::album::AlbumStore;
+use crate::collation::{self, Collation};
+use crate::exif_info;
+use crate::hidden;
+use crate::i18n::t;
+use crate::image::{self, ImageInfo};
+use crate::index::LibraryIndex;
+use crate::lock;
+use crate::metadata::MetadataStore;
+use crate::path_guard;
+use crate::privacy;
+use crate::sensitive;
+use crate::settings::AppSettings;
+
+/// フォルダ指定時の探索深さ
+const FOLDER_SEARCH_DEPTH: usize = 3;
+
+/// ナビゲーション対象の取得元
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum NavigationSource {
+    /// 設定された全フォルダ
+    Library,
+    /// 指定フォルダ
+    Folder { path: String },
+    /// アルバム
+    Album { name: String },
+}
+
+/// 絞り込み条件（すべて満たす画像のみを対象にする）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct NavigationFilter {
+    /// 拡張子（空なら全て）
+    pub extensions: Vec<String>,
+    /// ファイル名に含む文字列（大文字小文字を区別しない）
+    pub name_contains: Option<String>,
+    /// 最低レーティング
+    pub min_rating: Option<u8>,
+    /// 付いているべきタグ
+    pub tags: Vec<String>,
+    /// ファイルサイズの下限・上限（バイト）
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// 更新日時の範囲（Unix時間、`modified_before`は含まない）
+    pub modified_after: Option<u64>,
+    pub modified_before: Option<u64>,
+}
+
+/// 並び順
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    /// 更新日時の新しい順
+    #[default]
+    ModifiedDesc,
+    /// 更新日時の古い順
+    ModifiedAsc,
+    /// ファイル名順（数字は数値として比べるため`img2`が`img10`より先）
+    NameAsc,
+    /// ファイル名の逆順（数字は数値として比べる）
+    NameDesc,
+    /// ファイルサイズの大きい順
+    SizeDesc,
+    /// ファイルサイズの小さい順
+    SizeAsc,
+}
+
+/// 一覧をまとめる単位
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    /// 拡張子（小文字）
+    Extension,
+    /// EXIFのカメラの機種名
+    Camera,
+    /// 画像のあるフォルダ
+    Folder,
+}
+
+/// まとめた一覧の1区切り
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImageGroup {
+    /// 区切りの名前（拡張子・機種名・フォルダ、分からない場合はNone）
+    pub key: Option<String>,
+    /// 区切り内の画像数
+    pub count: usize,
+    /// 区切りの最初の画像の一覧全体での位置
+    pub offset: usize,
+}
+
+/// 現在のナビゲーション対象
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NavigationSet {
+    pub source: NavigationSource,
+    pub filter: NavigationFilter,
+    pub sort: SortOrder,
+    /// 端で反対側へ回り込むか
+    pub wrap: bool,
+    /// 並べ替え済みの画像一覧
+    pub images: Vec<ImageInfo>,
+}
+
+/// ナビゲーション対象の概要
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NavigationSummary {
+    pub source: NavigationSource,
+    pub sort: SortOrder,
+    /// 名前順の文字の比べ方
+    pub collation: Collation,
+    /// 対象の画像数
+    pub total: usize,
+}
+
+/// ナビゲーション対象を保持するステート
+#[derive(Default)]
+pub struct NavigationState(pub Mutex<Option<NavigationSet>>);
+
+/// 先頭から続く数字を取り出す
+fn take_digits(chars: &mut Peekable<Chars<'_>>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+/// 人が読む順で文字列を比べる（大文字小文字を区別せず、連続する数字は数値として比べる）
+///
+/// 同じ順になる場合（`01`と`1`など）は元の文字列で比べ、並びが毎回変わらないようにする
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut left, mut right) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ordering = match (left.peek().copied(), right.peek().copied()) {
+            (None, None) => break,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) if l.is_ascii_digit() && r.is_ascii_digit() => {
+                let (l, r) = (take_digits(&mut left), take_digits(&mut right));
+                let (l, r) = (l.trim_start_matches('0'), r.trim_start_matches('0'));
+                l.len().cmp(&r.len()).then_with(|| l.cmp(r))
+            },
+            (Some(l), Some(r)) => {
+                left.next();
+                right.next();
+                l.to_lowercase().cmp(r.to_lowercase())
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.cmp(b)
+}
+
+/// 並び順に従って並べ替える（名前順は`natural_cmp`で比べる）
+pub fn sort_images(images: &mut [ImageInfo], sort: SortOrder) {
+    sort_images_with(images, sort, Collation::Natural);
+}
+
+/// 並び順に従って並べ替える（名前順は`collation`の比べ方を使う）
+pub fn sort_images_with(images: &mut [ImageInfo], sort: SortOrder, collation: Collation) {
+    match sort {
+        SortOrder::ModifiedDesc => images.sort_by_key(|image| std::cmp::Reverse(image.modified)),
+        SortOrder::ModifiedAsc => images.sort_by_key(|image| image.modified),
+        SortOrder::NameAsc if collation == Collation::Natural => images.sort_by(|a, b| natural_cmp(&a.name, &b.name)),
+        SortOrder::NameDesc if collation == Collation::Natural => images.sort_by(|a, b| natural_cmp(&b.name, &a.name)),
+        // 正規化やローマ字への変換は比べるたびに行うと重いため、キーを先に作る
+        SortOrder::NameAsc => images.sort_by_cached_key(|image| collation::sort_key(&image.name, collation)),
+        SortOrder::NameDesc => images.sort_by_cached_key(|image| std::cmp::Reverse(collation::sort_key(&image.name, collation))),
+        SortOrder::SizeDesc => images.sort_by_key(|image| std::cmp::Reverse(image.size)),
+        SortOrder::SizeAsc => images.sort_by_key(|image| image.size),
+    }
+}
+
+/// 画像が属する区切りの名前（機種名は`cameras`から引く）
+fn group_key(image: &ImageInfo, group_by: GroupBy, cameras: &HashMap<String, Option<String>>) -> Option<String> {
+    match group_by {
+        GroupBy::Extension => Some(image.extension.to_lowercase()).filter(|extension| !extension.is_empty()),
+        GroupBy::Camera => cameras.get(&image.path).cloned().flatten(),
+        GroupBy::Folder => Path::new(&image.path).parent().map(|parent| parent.to_string_lossy().to_string()),
+    }
+}
+
+/// 画像のカメラの機種名を取得する
+///
+/// インデックスに記録済みの機種名を使い、未記録の画像だけEXIFを読んで記録する
+fn camera_models(app_handle: &AppHandle, images: &[ImageInfo]) -> HashMap<String, Option<String>> {
+    let mut index = LibraryIndex::open(app_handle)
+        .map_err(|e| tracing::warn!("インデックスを開けませんでした: {}", e))
+        .ok();
+    let mut cameras = index.as_ref()
+        .and_then(|index| index.camera_models().map_err(|e| tracing::warn!("{}", e)).ok())
+        .unwrap_or_default();
+
+    let missing: Vec<(String, Option<String>)> = images.iter()
+        .filter(|image| !cameras.contains_key(&image.path))
+        .map(|image| (image.path.clone(), exif_info::camera_model(Path::new(&image.path))))
+        .collect();
+    if let Some(index) = index.as_mut().filter(|_| !missing.is_empty()) {
+        if let Err(e) = index.set_camera_models(&missing) {
+            tracing::warn!("{}", e);
+        }
+    }
+    cameras.extend(missing);
+    cameras
+}
+
+/// 同じ区切りの画像が続くよう並べ直し、区切りの一覧を返す
+///
+/// 区切りは名前順で、名前が分からない画像は最後にまとめる。区切り内では元の並び順を保つ
+pub fn group_images(app_handle: &AppHandle, images: &mut Vec<ImageInfo>, group_by: GroupBy) -> Vec<ImageGroup> {
+    let cameras = match group_by {
+        GroupBy::Camera => camera_models(app_handle, images),
+        _ => HashMap::new(),
+    };
+    group_images_by(images, group_by, &cameras)
+}
+
+fn group_images_by(images: &mut Vec<ImageInfo>, group_by: GroupBy, cameras: &HashMap<String, Option<String>>) -> Vec<ImageGroup> {
+    let mut keyed: Vec<(Option<String>, ImageInfo)> = images.drain(..)
+        .map(|image| (group_key(&image, group_by, cameras), image))
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) => natural_cmp(a, b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+
+    let mut groups: Vec<ImageGroup> = Vec::new();
+    for (offset, (key, image)) in keyed.into_iter().enumerate() {
+        match groups.last_mut() {
+            Some(group) if group.key == key => group.count += 1,
+            _ => groups.push(ImageGroup { key, count: 1, offset }),
+        }
+        images.push(image);
+    }
+    groups
+}
+
+/// ファイル情報だけで判定できる条件を満たすか
+fn matches_basic(image: &ImageInfo, filter: &NavigationFilter) -> bool {
+    let extension_ok = filter.extensions.is_empty()
+        || filter.extensions.iter().any(|ext| ext.eq_ignore_ascii_case(&image.extension));
+    let name_ok = filter.name_contains.as_ref()
+        .map(|needle| image.name.to_lowercase().contains(&needle.to_lowercase()))
+        .unwrap_or(true);
+    let size_ok = filter.min_size.map(|min| image.size >= min).unwrap_or(true)
+        && filter.max_size.map(|max| image.size <= max).unwrap_or(true);
+    let modified_ok = filter.modified_after.map(|after| image.modified >= after).unwrap_or(true)
+        && filter.modified_before.map(|before| image.modified < before).unwrap_or(true);
+    extension_ok && name_ok && size_ok && modified_ok
+}
+
+/// 取得元から画像一覧を読み込む（非表示の画像と、プライバシーモード中は隠しているフォルダの画像を除く）
+pub(crate) fn load_source(app_handle: &AppHandle, source: &NavigationSource) -> Result<Vec<ImageInfo>, String> {
+    let images = match source {
+        NavigationSource::Library => {
+            let indexed = LibraryIndex::open(app_handle)
+                .and_then(|index| index.all_images())
+                .map(|images| image::filter_enabled_folders(app_handle, images));
+            match indexed {
+                Ok(images) if !images.is_empty() => Ok(images),
+                _ => image::scan_library(app_handle, None).map(|result| result.images),
+            }
+        },
+        NavigationSource::Folder { path } => {
+            let dir = path_guard::guard_dir(app_handle, path)?;
+            image::list_folder_images(&dir, FOLDER_SEARCH_DEPTH)
+        },
+        NavigationSource::Album { name } => {
+            lock::ensure_album_unlocked(app_handle, name)?;
+            AlbumStore::open(app_handle)?.images(name)
+        },
+    }?;
+    Ok(sensitive::mark(app_handle, hidden::filter_images(app_handle, privacy::filter_images(app_handle, images))))
+}
+
+/// 絞り込み条件を適用する（タグ・レーティングはメタデータストアを参照する）
+fn apply_filter(app_handle: &AppHandle, images: Vec<ImageInfo>, filter: &NavigationFilter) -> Result<Vec<ImageInfo>, String> {
+    let images: Vec<ImageInfo> = images.into_iter().filter(|image| matches_basic(image, filter)).collect();
+    if filter.min_rating.is_none() && filter.tags.is_empty() {
+        return Ok(images);
+    }
+
+    let metadata = MetadataStore::open(app_handle)?.all()?;
+    Ok(images.into_iter()
+        .filter(|image| {
+            let entry = metadata.get(&image.path);
+            let rating_ok = filter.min_rating
+                .map(|min| entry.and_then(|m| m.rating).unwrap_or(0) >= min)
+                .unwrap_or(true);
+            let tags_ok = filter.tags.iter()
+                .all(|tag| entry.map(|m| m.tags.contains(tag)).unwrap_or(false));
+            rating_ok && tags_ok
+        })
+        .collect())
+}
+
+impl NavigationSet {
+    /// 現在の画像から`step`だけ移動した画像を返す（見つからない・端ではNone）
+    fn step(&self, current: &str, step: i64) -> Option<ImageInfo> {
+        let len = self.images.len() as i64;
+        let position = self.images.iter().position(|image| image.path == current)? as i64;
+        let next = position + step;
+        let next = if self.wrap {
+            next.rem_euclid(len)
+        } else if (0..len).contains(&next) {
+            next
+        } else {
+            return None;
+        };
+        self.images.get(next as usize).cloned()
+    }
+}
+
+/// 現在のナビゲーション対象の画像一覧を取得する（未設定ならNone）
+pub fn current_images(state: &NavigationState) -> Option<Vec<ImageInfo>> {
+    state.0.lock().ok()?.as_ref().map(|set| set.images.clone())
+}
+
+/// 取得元を絞り込んで並べ替え、ナビゲーション対象にする
+pub(crate) fn set_navigation(
+    app_handle: &AppHandle,
+    state: &NavigationState,
+    source: NavigationSource,
+    filter: NavigationFilter,
+    sort: Option<SortOrder>,
+    collation: Option<Collation>,
+    wrap: Option<bool>,
+) -> Result<NavigationSummary, String> {
+    let sort = sort.unwrap_or_default();
+    let collation = collation.unwrap_or_else(|| AppSettings::load(app_handle).unwrap_or_default().sorting.collation);
+
+    let mut images = apply_filter(app_handle, load_source(app_handle, &source)?, &filter)?;
+    sort_images_with(&mut images, sort, collation);
+
+    let summary = NavigationSummary { source: source.clone(), sort, collation, total: images.len() };
+    *state.0.lock().map_err(|e| t!("common.lock_failed", t!("navigation.name"), e))? = Some(NavigationSet {
+        source,
+        filter,
+        sort,
+        wrap: wrap.unwrap_or(false),
+        images,
+    });
+    Ok(summary)
+}
+
+/// ナビゲーション対象を設定する（`collation`を省略すると設定の比べ方で名前順に並べる）
+#[tauri::command]
+pub async fn set_navigation_set(
+    app_handle: AppHandle,
+    state: State<'_, NavigationState>,
+    source: NavigationSource,
+    filter: Option<NavigationFilter>,
+    sort: Option<SortOrder>,
+    collation: Option<Collation>,
+    wrap: Option<bool>,
+) -> Result<NavigationSummary, String> {
+    set_navigation(&app_handle, &state, source, filter.unwrap_or_default(), sort, collation, wrap)
+}
+
+/// 次の画像を取得する
+#[tauri::command]
+pub fn get_next_image(state: State<'_, NavigationState>, current: String) -> Result<Option<ImageInfo>, String> {
+    let set = state.0.lock().map_err(|e| t!("common.lock_failed", t!("navigation.name"), e))?;
+    let set = set.as_ref().ok_or_else(|| t!("navigation.not_set"))?;
+    Ok(set.step(&current, 1))
+}
+
+/// 前の画像を取得する
+#[tauri::command]
+pub fn get_previous_image(state: State<'_, NavigationState>, current: String) -> Result<Option<ImageInfo>, String> {
+    let set = state.0.lock().map_err(|e| t!("common.lock_failed", t!("navigation.name"), e))?;
+    let set = set.as_ref().ok_or_else(|| t!("navigation.not_set"))?;
+    Ok(set.step(&current, -1))
+}
+
+/// ナビゲーション対象の画像一覧を範囲指定で取得する（先読み用）
+///
+/// 1ページの最大件数と応答サイズの上限に収まる分だけ返す（続きは受け取った件数を`offset`に足して取得する）
+#[tauri::command]
+pub fn get_navigation_images(
+    app_handle: AppHandle,
+    state: State<'_, NavigationState>,
+    offset: usize,
+    limit: usize,
+) -> Result<Vec<ImageInfo>, String> {
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().list_limits;
+    let set = state.0.lock().map_err(|e| t!("common.lock_failed", t!("navigation.name"), e))?;
+    let set = set.as_ref().ok_or_else(|| t!("navigation.not_set"))?;
+    let images: Vec<&ImageInfo> = set.images.iter().skip(offset).take(limit).collect();
+    Ok(image::page_of(images, 0, None, &limits).into_iter().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(name: &str, modified: u64, size: u64) -> ImageInfo {
+        ImageInfo {
+            path: format!("/photos/{}", name),
+            name: name.to_string(),
+            size,
+            modified,
+            extension: name.rsplit('.').next().unwrap_or("").to_string(),
+            sensitive: false,
+            bit_depth: None,
+        }
+    }
+
+    #[test]
+    fn test_sort_and_step() {
+        let mut images = vec![image("b.jpg", 2, 10), image("a.png", 3, 30), image("c.jpg", 1, 20)];
+        sort_images(&mut images, SortOrder::NameAsc);
+        let mut set = NavigationSet {
+            source: NavigationSource::Library,
+            filter: NavigationFilter::default(),
+            sort: SortOrder::NameAsc,
+            wrap: false,
+            images,
+        };
+
+        assert_eq!(set.step("/photos/a.png", 1).unwrap().name, "b.jpg");
+        assert!(set.step("/photos/c.jpg", 1).is_none());
+        set.wrap = true;
+        assert_eq!(set.step("/photos/c.jpg", 1).unwrap().name, "a.png");
+        assert_eq!(set.step("/photos/a.png", -1).unwrap().name, "c.jpg");
+        assert!(set.step("/photos/missing.jpg", 1).is_none());
+
+        sort_images(&mut set.images, SortOrder::SizeDesc);
+        assert_eq!(set.images[0].name, "a.png");
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut names = vec!["img10.jpg", "IMG2.jpg", "img1.jpg", "img02.jpg", "img.jpg", "a100b2", "a100b10"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["a100b2", "a100b10", "img.jpg", "img1.jpg", "IMG2.jpg", "img02.jpg", "img10.jpg"]);
+
+        let mut images = vec![image("page10.png", 0, 0), image("page9.png", 0, 0)];
+        sort_images(&mut images, SortOrder::NameAsc);
+        assert_eq!(images[0].name, "page9.png");
+    }
+
+    #[test]
+    fn test_group_images_keeps_order_within_groups() {
+        let mut images = vec![
+            image("c.PNG", 0, 0),
+            image("a.jpg", 0, 0),
+            image("noext", 0, 0),
+            image("b.png", 0, 0),
+            image("d.jpg", 0, 0),
+        ];
+        images[2].extension = String::new();
+        let groups = group_images_by(&mut images, GroupBy::Extension, &HashMap::new());
+
+        let names: Vec<&str> = images.iter().map(|image| image.name.as_str()).collect();
+        assert_eq!(names, vec!["a.jpg", "d.jpg", "c.PNG", "b.png", "noext"]);
+        assert_eq!(groups, vec![
+            ImageGroup { key: Some("jpg".to_string()), count: 2, offset: 0 },
+            ImageGroup { key: Some("png".to_string()), count: 2, offset: 2 },
+            ImageGroup { key: None, count: 1, offset: 4 },
+        ]);
+
+        let groups = group_images_by(&mut images, GroupBy::Folder, &HashMap::new());
+        assert_eq!(groups, vec![ImageGroup { key: Some("/photos".to_string()), count: 5, offset: 0 }]);
+
+        let cameras = HashMap::from([
+            ("/photos/b.png".to_string(), Some("Canon EOS R6".to_string())),
+            ("/photos/c.PNG".to_string(), None),
+        ]);
+        let groups = group_images_by(&mut images, GroupBy::Camera, &cameras);
+        assert_eq!(groups, vec![
+            ImageGroup { key: Some("Canon EOS R6".to_string()), count: 1, offset: 0 },
+            ImageGroup { key: None, count: 4, offset: 1 },
+        ]);
+        assert_eq!(images[0].name, "b.png");
+    }
+
+    #[test]
+    fn test_matches_basic() {
+        let filter = NavigationFilter {
+            extensions: vec!["JPG".to_string()],
+            name_contains: Some("IMG".to_string()),
+            ..Default::default()
+        };
+        assert!(matches_basic(&image("img_001.jpg", 0, 0), &filter));
+        assert!(!matches_basic(&image("img_001.png", 0, 0), &filter));
+        assert!(!matches_basic(&image("photo.jpg", 0, 0), &filter));
+
+        let filter = NavigationFilter {
+            min_size: Some(10),
+            modified_after: Some(100),
+            modified_before: Some(200),
+            ..Default::default()
+        };
+        assert!(matches_basic(&image("a.jpg", 100, 10), &filter));
+        assert!(!matches_basic(&image("a.jpg", 100, 9), &filter));
+        assert!(!matches_basic(&image("a.jpg", 200, 10), &filter));
+    }
+}
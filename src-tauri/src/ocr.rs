@@ -0,0 +1,195 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{params, Connection};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::hidden;
+use crate::i18n::t;
+use crate::image::{self, ImageInfo};
+use crate::index::LibraryIndex;
+use crate::path_guard;
+use crate::preview;
+use crate::privacy;
+use crate::sensitive;
+use crate::settings::AppSettings;
+use crate::watchdog::{self, Operation};
+
+/// 検索結果の最大件数
+const MAX_SEARCH_RESULTS: usize = 500;
+
+/// 文字の読み取り結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OcrResult {
+    pub path: String,
+    /// 使った言語（Tesseractの言語コード）
+    pub lang: String,
+    pub text: String,
+    /// インデックスに保存したか
+    pub indexed: bool,
+}
+
+/// 読み取った文字の保存先（インデックスと同じSQLiteファイル）
+pub struct OcrStore {
+    conn: Connection,
+}
+
+impl OcrStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("ocr.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS image_text (
+                path TEXT PRIMARY KEY,
+                lang TEXT NOT NULL,
+                text TEXT NOT NULL,
+                extracted_at INTEGER NOT NULL
+            );"
+        ).map_err(|e| t!("ocr.init_failed", e))
+    }
+
+    /// 画像の文字を保存する（読み取り済みなら置き換える）
+    pub fn save(&self, path: &str, lang: &str, text: &str) -> Result<(), String> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+        self.conn.execute(
+            "INSERT OR REPLACE INTO image_text (path, lang, text, extracted_at) VALUES (?1, ?2, ?3, ?4)",
+            params![path, lang, text, now],
+        ).map_err(|e| t!("ocr.save_failed", path, e))?;
+        Ok(())
+    }
+
+    /// 文字を含む画像のパスを取得する（大文字・小文字は区別しない）
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<String>, String> {
+        let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let mut stmt = self.conn.prepare(
+            "SELECT path FROM image_text WHERE text LIKE ?1 ESCAPE '\\' ORDER BY path LIMIT ?2"
+        ).map_err(|e| t!("ocr.search_failed", e))?;
+
+        let rows = stmt.query_map(params![format!("%{}%", escaped), limit as i64], |row| row.get(0))
+            .map_err(|e| t!("ocr.search_failed", e))?;
+        rows.collect::<Result<Vec<String>, _>>()
+            .map_err(|e| t!("ocr.search_failed", e))
+    }
+}
+
+/// 言語コードを確認する（コマンドの引数に渡すため英数字と`_`・`+`のみ許可する）
+fn validate_lang(lang: &str) -> Result<(), String> {
+    let valid = !lang.is_empty() && lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+');
+    if valid { Ok(()) } else { Err(t!("ocr.invalid_lang", lang)) }
+}
+
+/// 読み取った文字を整える（行末の空白・空行・改ページを除く）
+fn normalize_text(raw: &str) -> String {
+    raw.lines()
+        .map(|line| line.trim_end_matches(['\u{c}', ' ', '\t']).trim_start_matches('\u{c}'))
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Tesseractで画像内の文字を読み取る
+///
+/// 形式やEXIFの向きによらず読めるよう、正立させたPNGに変換してから渡す
+fn recognize(app_handle: &AppHandle, source: &Path, lang: &str) -> Result<String, String> {
+    let settings = AppSettings::load(app_handle).unwrap_or_default().ocr;
+    let (image, orientation) = preview::open_oriented(app_handle, source)?;
+    let input = preview::get_cache_dir(app_handle, "ocr")
+        .join(format!("{}.png", preview::cache_key(source, &format!("ocr-o{}", orientation.unwrap_or(1)))));
+    preview::save_png(&image, &input)?;
+
+    let tesseract = settings.tesseract_path.unwrap_or_else(|| "tesseract".to_string());
+    let output = Command::new(&tesseract).arg(&input).arg("stdout").args(["-l", lang]).output();
+    let _ = fs::remove_file(&input);
+
+    match output {
+        Ok(output) if output.status.success() => Ok(normalize_text(&String::from_utf8_lossy(&output.stdout))),
+        Ok(output) => Err(t!("ocr.failed", source.display(), String::from_utf8_lossy(&output.stderr).trim())),
+        Err(e) => Err(t!("ocr.unavailable", tesseract, e)),
+    }
+}
+
+/// 画像内の文字を読み取る（スクリーンショットやスキャン画像向け）
+///
+/// 設定で有効なら読み取った文字をインデックスに保存し、`search_image_text`で検索できるようにする。
+/// Tesseractと使う言語のデータがインストールされている必要がある
+#[tauri::command]
+pub async fn extract_text(app_handle: AppHandle, path: String, lang: Option<String>) -> Result<OcrResult, String> {
+    let source = path_guard::guard(&app_handle, &path)?;
+    let settings = AppSettings::load(&app_handle).unwrap_or_default().ocr;
+    let lang = lang.unwrap_or(settings.default_lang);
+    validate_lang(&lang)?;
+
+    let handle = app_handle.clone();
+    let task_lang = lang.clone();
+    let text = watchdog::run(&app_handle, Operation::Ocr, move || recognize(&handle, &source, &task_lang)).await?;
+
+    let indexed = settings.index_text && match OcrStore::open(&app_handle).and_then(|store| store.save(&path, &lang, &text)) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("読み取った文字を保存できませんでした: {}", e);
+            false
+        },
+    };
+    Ok(OcrResult { path, lang, text, indexed })
+}
+
+/// 読み取った文字に`query`を含む画像の一覧を取得する
+#[tauri::command]
+pub async fn search_image_text(app_handle: AppHandle, query: String) -> Result<Vec<ImageInfo>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let images = OcrStore::open(&app_handle)?
+        .search(query.trim(), MAX_SEARCH_RESULTS)?
+        .iter()
+        .filter_map(|path| image::image_info(Path::new(path)).ok())
+        .collect();
+    Ok(sensitive::mark(&app_handle, hidden::filter_images(&app_handle, privacy::filter_images(&app_handle, images))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_search_and_lang_validation() {
+        let path = std::env::temp_dir().join(format!("poir-ocr-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let store = OcrStore::open_at(&path).unwrap();
+
+        store.save("/a.png", "eng", "Boarding Pass\nGate 12").unwrap();
+        store.save("/b.png", "jpn", "領収書 100%").unwrap();
+        store.save("/a.png", "eng", "Boarding pass\nGate 14").unwrap();
+
+        assert_eq!(store.search("gate 14", 10).unwrap(), vec!["/a.png"]);
+        assert!(store.search("gate 12", 10).unwrap().is_empty());
+        assert_eq!(store.search("100%", 10).unwrap(), vec!["/b.png"]);
+        assert!(store.search("0_", 10).unwrap().is_empty());
+
+        assert!(validate_lang("jpn+eng").is_ok());
+        assert!(validate_lang("eng; rm").is_err());
+        assert_eq!(normalize_text("  Hello  \n\n\u{c}World\n\u{c}"), "  Hello\nWorld");
+
+        let _ = fs::remove_file(&path);
+    }
+}
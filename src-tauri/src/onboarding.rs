@@ -0,0 +1,294 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager};
+use crate::config::ResourceConfig;
+use crate::drives;
+use crate::image;
+use crate::jobs::{self, JobHandle};
+use crate::long_path;
+use crate::watchdog::{self, Operation};
+
+/// OneDriveの中で写真が置かれることの多いフォルダ
+const ONEDRIVE_PHOTO_DIRS: [&str; 3] = ["Pictures", "Photos", "画像"];
+
+/// 「ピクチャ」の中でスクリーンショットが保存されるフォルダ（Windows・Linuxのデスクトップ環境）
+const SCREENSHOT_DIRS: [&str; 2] = ["Screenshots", "スクリーンショット"];
+
+/// 画像が多いフォルダを探す際にたどる深さ
+const HOTSPOT_MAX_DEPTH: usize = 12;
+
+/// 画像が多いフォルダとみなす、直下の画像の数の下限
+const HOTSPOT_MIN_IMAGES: usize = 20;
+
+/// 結果に含める画像が多いフォルダの数の上限
+const MAX_HOTSPOTS: usize = 50;
+
+/// 進捗を通知する間隔（調べたフォルダの数）
+const HOTSPOT_PROGRESS_INTERVAL: usize = 200;
+
+/// 画像を探しても見つからないシステム・開発用のフォルダ
+const HOTSPOT_SKIP_DIRS: [&str; 8] = [
+    "Windows", "Program Files", "Program Files (x86)", "ProgramData", "$Recycle.Bin", "System Volume Information", "node_modules", "proc",
+];
+
+/// 提案するフォルダの種類
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    /// OSの「ピクチャ」フォルダ
+    Pictures,
+    /// OSの「ダウンロード」フォルダ
+    Downloads,
+    /// OneDriveの写真フォルダ
+    OneDrive,
+    /// 接続中のカメラ・SDカード等の`DCIM`フォルダ
+    Device,
+    /// OSのスクリーンショットの保存先
+    Screenshots,
+    /// 壁紙のフォルダ・キャッシュ
+    Wallpapers,
+}
+
+impl SuggestionKind {
+    /// 画像がなければ提案しない種類か（標準のフォルダ以外は空なら見落としではないため）
+    fn skip_if_empty(self) -> bool {
+        matches!(self, SuggestionKind::Screenshots | SuggestionKind::Wallpapers)
+    }
+}
+
+/// 初回設定で提案するフォルダ
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SuggestedFolder {
+    pub path: String,
+    pub kind: SuggestionKind,
+    /// スキャンで見つかる画像のおおよその数
+    pub estimated_images: usize,
+    /// 上限に達して数えるのを打ち切ったか（実際はこれより多い）
+    pub estimate_truncated: bool,
+    /// 既に設定されているか
+    pub already_added: bool,
+}
+
+/// OneDriveのフォルダ（Windowsは環境変数、それ以外はホームフォルダ直下）
+fn onedrive_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    std::env::var_os("OneDrive")
+        .map(PathBuf::from)
+        .or_else(|| app_handle.path().home_dir().ok().map(|home| home.join("OneDrive")))
+}
+
+/// 提案の候補を集める（存在しないフォルダも含む）
+fn candidates(app_handle: &AppHandle) -> Vec<(SuggestionKind, PathBuf)> {
+    let paths = app_handle.path();
+    let mut candidates = Vec::new();
+    candidates.extend(paths.picture_dir().ok().map(|dir| (SuggestionKind::Pictures, dir)));
+    candidates.extend(paths.download_dir().ok().map(|dir| (SuggestionKind::Downloads, dir)));
+    if let Some(onedrive) = onedrive_dir(app_handle) {
+        candidates.extend(ONEDRIVE_PHOTO_DIRS.iter().map(|name| (SuggestionKind::OneDrive, onedrive.join(name))));
+    }
+    candidates.extend(drives::mounted_volumes().into_iter().map(|volume| (SuggestionKind::Device, volume.join("DCIM"))));
+    candidates.extend(screenshot_dirs(app_handle).into_iter().map(|dir| (SuggestionKind::Screenshots, dir)));
+    candidates.extend(wallpaper_dirs(app_handle).into_iter().map(|dir| (SuggestionKind::Wallpapers, dir)));
+    candidates
+}
+
+/// OSのスクリーンショットの保存先（macOSは既定でデスクトップに保存する）
+fn screenshot_dirs(app_handle: &AppHandle) -> Vec<PathBuf> {
+    let paths = app_handle.path();
+    let mut dirs = Vec::new();
+    if let Ok(pictures) = paths.picture_dir() {
+        dirs.extend(SCREENSHOT_DIRS.iter().map(|name| pictures.join(name)));
+    }
+    if cfg!(target_os = "macos") {
+        dirs.extend(paths.desktop_dir().ok());
+    }
+    dirs
+}
+
+/// 壁紙のフォルダ・キャッシュ
+///
+/// Windowsはテーマのキャッシュ、macOSはシステムの壁紙、Linuxはユーザーとシステムの`backgrounds`
+fn wallpaper_dirs(app_handle: &AppHandle) -> Vec<PathBuf> {
+    let data_dir = app_handle.path().data_dir().ok();
+    if cfg!(windows) {
+        data_dir.map(|dir| dir.join(r"Microsoft\Windows\Themes\CachedFiles")).into_iter().collect()
+    } else if cfg!(target_os = "macos") {
+        vec![PathBuf::from("/System/Library/Desktop Pictures"), PathBuf::from("/Library/Desktop Pictures")]
+    } else {
+        data_dir.map(|dir| dir.join("backgrounds")).into_iter()
+            .chain([PathBuf::from("/usr/share/backgrounds"), PathBuf::from("/usr/share/wallpapers")])
+            .collect()
+    }
+}
+
+/// 使えるフォルダだけを残し、画像の数を見積もって提案にする
+fn suggest(candidates: Vec<(SuggestionKind, PathBuf)>, configured: &[String]) -> Vec<SuggestedFolder> {
+    let mut suggestions: Vec<SuggestedFolder> = Vec::new();
+    for (kind, dir) in candidates {
+        let path = dir.to_string_lossy().to_string();
+        // 「ピクチャ」がOneDrive内にある場合など、同じフォルダは最初の種類で1回だけ提案する
+        if suggestions.iter().any(|suggestion| long_path::same_path(&suggestion.path, &path)) {
+            continue;
+        }
+        let validation = ResourceConfig::validate_path_detailed(&path);
+        let Some(estimated_images) = validation.estimated_images else { continue };
+        if estimated_images == 0 && kind.skip_if_empty() {
+            continue;
+        }
+        suggestions.push(SuggestedFolder {
+            already_added: configured.iter().any(|include| long_path::same_path(include, &path)),
+            path,
+            kind,
+            estimated_images,
+            estimate_truncated: validation.estimate_truncated,
+        });
+    }
+    suggestions
+}
+
+/// 初回設定のウィザードで提案する画像フォルダを取得する（画像の数の見積もり付き）
+///
+/// 存在しない・読めないフォルダは含めない。見積もりに時間がかかるドライブではスキャンと同じ時間切れを適用する
+#[tauri::command]
+pub async fn get_suggested_folders(app_handle: AppHandle) -> Result<Vec<SuggestedFolder>, String> {
+    let configured = ResourceConfig::load(&app_handle)?.filters.include;
+    let candidates = candidates(&app_handle);
+    watchdog::run(&app_handle, Operation::Scan, move || Ok(suggest(candidates, &configured))).await
+}
+
+/// 画像が多いフォルダ
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImageHotspot {
+    pub path: String,
+    /// 直下の画像の数
+    pub images: usize,
+    /// 既に設定されているか
+    pub already_added: bool,
+}
+
+/// ドライブ内の画像が多いフォルダの検出結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HotspotReport {
+    pub drive: String,
+    /// 調べたフォルダの数
+    pub scanned_folders: usize,
+    /// 画像が多い順
+    pub hotspots: Vec<ImageHotspot>,
+}
+
+/// 探索しないフォルダか（隠しフォルダとシステム・開発用のフォルダ）
+fn skip_hotspot_dir(name: &str) -> bool {
+    name.starts_with('.') || HOTSPOT_SKIP_DIRS.iter().any(|skip| skip.eq_ignore_ascii_case(name))
+}
+
+/// フォルダ以下をたどり、直下の画像が多いフォルダを集める（シンボリックリンクはたどらない）
+fn find_hotspots(root: &Path, configured: &[String], job: Option<&JobHandle>) -> (usize, Vec<ImageHotspot>) {
+    let mut hotspots = Vec::new();
+    let mut scanned = 0;
+    let mut pending = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        if job.is_some_and(JobHandle::is_cancelled) {
+            break;
+        }
+        let Ok(entries) = fs::read_dir(long_path::extended(&dir)) else { continue };
+        scanned += 1;
+        let mut images = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if depth < HOTSPOT_MAX_DEPTH && !skip_hotspot_dir(&name) {
+                        pending.push((path, depth + 1));
+                    }
+                },
+                Ok(kind) if kind.is_file() && image::is_image_file(&path) => images += 1,
+                _ => {},
+            }
+        }
+        if let Some(job) = job.filter(|_| scanned % HOTSPOT_PROGRESS_INTERVAL == 0) {
+            job.progress(scanned, Some(&dir.to_string_lossy()));
+        }
+        if images >= HOTSPOT_MIN_IMAGES {
+            let path = dir.to_string_lossy().to_string();
+            let already_added = configured.iter().any(|include| dir.starts_with(include));
+            hotspots.push(ImageHotspot { path, images, already_added });
+        }
+    }
+    hotspots.sort_by(|a, b| b.images.cmp(&a.images).then_with(|| a.path.cmp(&b.path)));
+    hotspots.truncate(MAX_HOTSPOTS);
+    (scanned, hotspots)
+}
+
+/// ドライブ（任意のフォルダも可）の中で画像が多いフォルダを探すジョブを開始し、ジョブIDを返す
+///
+/// 設定していない場所から画像の置き場所を見つけるためのもので、ライブラリ外も対象にする。
+/// 総数は分からないため`total`は0のまま進み、結果（`HotspotReport`）は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn scan_drive_for_image_hotspots(app_handle: AppHandle, drive: String) -> Result<String, String> {
+    ResourceConfig::validate_path(&drive)?;
+    let configured = ResourceConfig::load(&app_handle)?.filters.include;
+    jobs::spawn(&app_handle, "hotspots", 0, move |job| {
+        let (scanned_folders, hotspots) = find_hotspots(Path::new(&drive), &configured, Some(job));
+        tracing::info!("画像が多いフォルダを検出しました: {} ({}フォルダ中{}件)", drive, scanned_folders, hotspots.len());
+        Ok(HotspotReport { drive, scanned_folders, hotspots })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_skips_missing_and_duplicates() {
+        let root = std::env::temp_dir().join(format!("poir-onboarding-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("Pictures")).unwrap();
+        fs::write(root.join("Pictures/a.jpg"), b"").unwrap();
+
+        let pictures = root.join("Pictures");
+        let candidates = vec![
+            (SuggestionKind::Pictures, pictures.clone()),
+            (SuggestionKind::Downloads, root.join("Downloads")),
+            (SuggestionKind::OneDrive, pictures.clone()),
+        ];
+        let configured = vec![pictures.to_string_lossy().to_string()];
+        let suggestions = suggest(candidates, &configured);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].kind, SuggestionKind::Pictures);
+        assert_eq!(suggestions[0].estimated_images, 1);
+        assert!(suggestions[0].already_added);
+        assert!(Path::new(&suggestions[0].path).ends_with("Pictures"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_find_hotspots() {
+        let root = std::env::temp_dir().join(format!("poir-hotspots-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in ["photos/2024", "few", ".cache", "node_modules"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let fill = |dir: &str, count: usize| {
+            for i in 0..count {
+                fs::write(root.join(dir).join(format!("{}.jpg", i)), b"").unwrap();
+            }
+        };
+        fill("photos/2024", HOTSPOT_MIN_IMAGES + 5);
+        fill("photos", HOTSPOT_MIN_IMAGES);
+        fill("few", 3);
+        fill(".cache", HOTSPOT_MIN_IMAGES);
+        fill("node_modules", HOTSPOT_MIN_IMAGES);
+
+        let configured = vec![root.join("photos/2024").to_string_lossy().to_string()];
+        let (scanned, hotspots) = find_hotspots(&root, &configured, None);
+        assert_eq!(scanned, 4);
+        let found: Vec<(&str, usize, bool)> = hotspots.iter()
+            .map(|hotspot| (Path::new(&hotspot.path).file_name().unwrap().to_str().unwrap(), hotspot.images, hotspot.already_added))
+            .collect();
+        assert_eq!(found, vec![("2024", HOTSPOT_MIN_IMAGES + 5, true), ("photos", HOTSPOT_MIN_IMAGES, false)]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
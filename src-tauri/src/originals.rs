@@ -0,0 +1,148 @@
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::config;
+use crate::event_bridge::{self, Delivery};
+use crate::exif_info;
+use crate::i18n::t;
+use crate::image;
+use crate::index::LibraryIndex;
+use crate::long_path;
+use crate::path_guard;
+use crate::safe_write::{self, WriteOptions};
+use crate::settings::AppSettings;
+
+/// 画像と同じフォルダに元のファイルを残す際のフォルダ名（スキャンの対象外）
+pub const ORIGINALS_DIR: &str = ".originals";
+
+/// 編集前の元のファイルの残し先
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OriginalsStore {
+    /// 残さない
+    #[default]
+    Off,
+    /// 画像と同じフォルダの`.originals`
+    Sibling,
+    /// アプリデータの`originals`（元のフォルダ構成を再現する）
+    Vault,
+}
+
+/// 元に戻した結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RestoredOriginal {
+    pub path: String,
+    /// 元のファイルを残していた場所（元に戻した後は削除する）
+    pub backup: String,
+}
+
+/// スキャンで飛ばすフォルダか
+pub fn is_originals_dir(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == ORIGINALS_DIR)
+}
+
+/// コマンドの指定（省略時は設定）から残し先を決める（指定で残す場合、設定が無効ならアプリデータに残す）
+pub fn store_for(app_handle: &AppHandle, keep_original: Option<bool>) -> OriginalsStore {
+    let configured = AppSettings::load(app_handle).unwrap_or_default().file_writes.keep_originals;
+    match keep_original {
+        None => configured,
+        Some(false) => OriginalsStore::Off,
+        Some(true) if configured == OriginalsStore::Off => OriginalsStore::Vault,
+        Some(true) => configured,
+    }
+}
+
+/// アプリデータ内の、元のパスを再現した残し先（`C:\photos\a.jpg`は`originals/C/photos/a.jpg`）
+fn vault_path(vault: &Path, path: &Path) -> PathBuf {
+    let mut dest = vault.to_path_buf();
+    let path = PathBuf::from(long_path::strip_extended(&path.to_string_lossy()));
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => dest.push(prefix.as_os_str().to_string_lossy().replace([':', '\\', '?'], "")),
+            Component::Normal(name) => dest.push(name),
+            _ => {},
+        }
+    }
+    dest
+}
+
+/// 残し先のパス（`Off`ならNone）
+fn backup_path(app_handle: &AppHandle, store: OriginalsStore, path: &Path) -> Option<PathBuf> {
+    match store {
+        OriginalsStore::Off => None,
+        OriginalsStore::Sibling => Some(path.parent()?.join(ORIGINALS_DIR).join(path.file_name()?)),
+        OriginalsStore::Vault => Some(vault_path(&config::app_data_dir(app_handle).join("originals"), path)),
+    }
+}
+
+/// 編集前のファイルを残す（すでに残していれば最初の状態を保つため上書きしない）
+///
+/// 書き換えるコマンドが元のファイルを変更する前に呼び出す
+pub fn preserve(app_handle: &AppHandle, path: &Path, store: OriginalsStore) -> Result<(), String> {
+    let Some(backup) = backup_path(app_handle, store, path) else { return Ok(()) };
+    if backup.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = backup.parent() {
+        fs::create_dir_all(parent).map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+    }
+    safe_write::write_with(&backup, WriteOptions::default(), |temp| {
+        fs::copy(path, temp).map(|_| ()).map_err(|e| t!("originals.copy_failed", path.display(), e))
+    })?;
+    // 元に戻した際に更新日時も戻せるよう、残したファイルに元の日時を付ける
+    if let Ok(modified) = fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        let _ = File::options().write(true).open(&backup).and_then(|file| file.set_modified(modified));
+    }
+    tracing::info!("編集前のファイルを残しました: {} -> {}", path.display(), backup.display());
+    Ok(())
+}
+
+fn restore(app_handle: &AppHandle, path: &str) -> Result<RestoredOriginal, String> {
+    let source = path_guard::guard(app_handle, path)?;
+    let backup = [OriginalsStore::Sibling, OriginalsStore::Vault].iter()
+        .filter_map(|store| backup_path(app_handle, *store, &source))
+        .find(|backup| backup.is_file())
+        .ok_or_else(|| t!("originals.not_found", path))?;
+
+    safe_write::write_with(&source, WriteOptions::default(), |temp| {
+        fs::copy(&backup, temp).map(|_| ()).map_err(|e| t!("originals.copy_failed", backup.display(), e))
+    })?;
+    if let Ok(modified) = fs::metadata(&backup).and_then(|metadata| metadata.modified()) {
+        let _ = File::options().write(true).open(&source).and_then(|file| file.set_modified(modified));
+    }
+    fs::remove_file(&backup).map_err(|e| t!("originals.remove_failed", backup.display(), e))?;
+    if let Some(parent) = backup.parent().filter(|parent| is_originals_dir(parent)) {
+        // 空になった`.originals`は片付ける（残っていれば失敗するだけ）
+        let _ = fs::remove_dir(parent);
+    }
+
+    let info = image::image_info(&source)?;
+    let taken_at = exif_info::taken_at(&source).unwrap_or(info.modified);
+    LibraryIndex::open(app_handle)?.update_file(path, info.size, info.modified, taken_at)?;
+    event_bridge::emit(app_handle, "image-updated", Delivery::Batch, path);
+    tracing::info!("編集前のファイルに戻しました: {}", path);
+    Ok(RestoredOriginal { path: path.to_string(), backup: backup.to_string_lossy().to_string() })
+}
+
+/// 編集前に残しておいたファイルで画像を元に戻す
+#[tauri::command]
+pub async fn restore_original(app_handle: AppHandle, path: String) -> Result<RestoredOriginal, String> {
+    let result = restore(&app_handle, &path);
+    audit::complete(&app_handle, "restore_original", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_path_mirrors_original() {
+        let vault = Path::new("/data/originals");
+        assert_eq!(vault_path(vault, Path::new("/home/me/photos/a.jpg")), vault.join("home/me/photos/a.jpg"));
+        assert!(is_originals_dir(Path::new("/photos/.originals")));
+        assert!(!is_originals_dir(Path::new("/photos/originals")));
+    }
+}
@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::PoisonError;
+use tauri::{AppHandle, Manager};
+use crate::config::{self, ResourceConfig};
+use crate::i18n::t;
+use crate::launch::{LaunchState, LaunchTarget};
+use crate::settings::AppSettings;
+
+/// パスを正規化する（`..`とシンボリックリンクを解決する）
+pub fn canonicalize(path: &str) -> Result<PathBuf, String> {
+    fs::canonicalize(path).map_err(|_| t!("path.not_found", path))
+}
+
+/// 読み取りを許可するフォルダ（設定フォルダ・アプリデータ・起動時やドロップで開いた対象）
+fn allowed_roots(app_handle: &AppHandle) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = ResourceConfig::load(app_handle)
+        .map(|config| config.filters.include)
+        .unwrap_or_default()
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    roots.push(config::app_data_dir(app_handle));
+
+    if let Some(state) = app_handle.try_state::<LaunchState>() {
+        match &*state.0.lock().unwrap_or_else(PoisonError::into_inner) {
+            Some(LaunchTarget::Folder { path }) => roots.push(PathBuf::from(path)),
+            Some(LaunchTarget::Image { path }) => roots.extend(Path::new(path).parent().map(Path::to_path_buf)),
+            _ => {},
+        }
+    }
+
+    // 比較できるよう許可側も正規化する（存在しないフォルダは除く）
+    roots.into_iter().filter_map(|root| fs::canonicalize(root).ok()).collect()
+}
+
+/// 正規化済みのパスが許可されたフォルダ内にあるか
+fn is_within(path: &Path, roots: &[PathBuf]) -> bool {
+    roots.iter().any(|root| path.starts_with(root))
+}
+
+/// パスが正規化済みの`roots`内にあるか検証し、正規化したパスを返す
+pub(crate) fn guard_within(path: &str, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    let canonical = canonicalize(path)?;
+    if !is_within(&canonical, roots) {
+        tracing::warn!("許可されていない場所へのアクセスを拒否しました: {}", path);
+        return Err(t!("path.outside_library", path));
+    }
+    Ok(canonical)
+}
+
+/// `roots`内のフォルダか検証し、正規化したパスを返す
+fn guard_dir_within(path: &str, roots: Option<&[PathBuf]>) -> Result<PathBuf, String> {
+    let canonical = match roots {
+        Some(roots) => guard_within(path, roots)?,
+        None => canonicalize(path)?,
+    };
+    if !canonical.is_dir() {
+        return Err(t!("path.not_directory", path));
+    }
+    Ok(canonical)
+}
+
+/// 一覧を取得してよいフォルダか検証し、正規化したパスを返す（`guard`と同じ範囲に制限する）
+pub fn guard_dir(app_handle: &AppHandle, path: &str) -> Result<PathBuf, String> {
+    let settings = AppSettings::load(app_handle).unwrap_or_default().path_guard;
+    if settings.restrict_to_library {
+        guard_dir_within(path, Some(&allowed_roots(app_handle)))
+    } else {
+        guard_dir_within(path, None)
+    }
+}
+
+/// 読み取りを許可するパスか検証し、正規化したパスを返す
+///
+/// 設定で制限を無効にした場合も、正規化とパスの存在確認は行う
+pub fn guard(app_handle: &AppHandle, path: &str) -> Result<PathBuf, String> {
+    let settings = AppSettings::load(app_handle).unwrap_or_default().path_guard;
+    if settings.restrict_to_library {
+        guard_within(path, &allowed_roots(app_handle))
+    } else {
+        canonicalize(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traversal_is_resolved_before_check() {
+        let dir = std::env::temp_dir().join(format!("poir-path-guard-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("library")).unwrap();
+        fs::create_dir_all(dir.join("outside")).unwrap();
+        fs::write(dir.join("library/a.jpg"), b"a").unwrap();
+        fs::write(dir.join("outside/secret.txt"), b"s").unwrap();
+
+        let roots = vec![fs::canonicalize(dir.join("library")).unwrap()];
+        let inside = canonicalize(&dir.join("library/a.jpg").to_string_lossy()).unwrap();
+        let escaped = canonicalize(&dir.join("library/../outside/secret.txt").to_string_lossy()).unwrap();
+        assert!(is_within(&inside, &roots));
+        assert!(!is_within(&escaped, &roots));
+        assert!(canonicalize(&dir.join("library/missing.jpg").to_string_lossy()).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_guard_dir_rejects_folders_outside_library() {
+        let dir = std::env::temp_dir().join(format!("poir-path-guard-dir-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("library/2024")).unwrap();
+        fs::create_dir_all(dir.join("outside")).unwrap();
+        fs::write(dir.join("library/a.jpg"), b"a").unwrap();
+
+        let roots = vec![fs::canonicalize(dir.join("library")).unwrap()];
+        let path = |relative: &str| dir.join(relative).to_string_lossy().to_string();
+        assert!(guard_dir_within(&path("library/2024"), Some(&roots)).is_ok());
+        assert!(guard_dir_within(&path("outside"), Some(&roots)).is_err());
+        assert!(guard_dir_within(&path("library/../outside"), Some(&roots)).is_err());
+        assert!(guard_dir_within(&path("library/a.jpg"), Some(&roots)).is_err());
+        assert!(guard_dir_within(&path("outside"), None).is_ok());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+use ::image::codecs::jpeg::JpegEncoder;
+use ::image::DynamicImage;
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref};
+use crate::i18n::t;
+use crate::safe_write::{self, WriteOptions};
+
+/// 画像をJPEGで埋め込む際の品質
+const JPEG_QUALITY: u8 = 90;
+
+/// 画像を1枚ずつページにしたPDFを組み立てる
+///
+/// 画像はJPEGに変換して埋め込む（透過は失われる）
+pub struct PdfBuilder {
+    pdf: Pdf,
+    next_ref: i32,
+    pages: Vec<Ref>,
+}
+
+impl Default for PdfBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfBuilder {
+    const CATALOG: Ref = Ref::new(1);
+    const PAGE_TREE: Ref = Ref::new(2);
+
+    pub fn new() -> Self {
+        Self { pdf: Pdf::new(), next_ref: 3, pages: Vec::new() }
+    }
+
+    fn alloc(&mut self) -> Ref {
+        let id = Ref::new(self.next_ref);
+        self.next_ref += 1;
+        id
+    }
+
+    /// ページを追加し、画像を`rect`（ポイント単位、左下原点の`x, y, 幅, 高さ`）に置く
+    pub fn add_image_page(&mut self, image: &DynamicImage, page_size: (f32, f32), rect: [f32; 4]) -> Result<(), String> {
+        let rgb = image.to_rgb8();
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY)
+            .encode_image(&rgb)
+            .map_err(|e| t!("pdf.encode_failed", e))?;
+
+        let page_id = self.alloc();
+        let image_id = self.alloc();
+        let content_id = self.alloc();
+        let image_name = Name(b"Im0");
+
+        let mut xobject = self.pdf.image_xobject(image_id, &jpeg);
+        xobject.filter(Filter::DctDecode);
+        xobject.width(rgb.width() as i32);
+        xobject.height(rgb.height() as i32);
+        xobject.color_space().device_rgb();
+        xobject.bits_per_component(8);
+        xobject.finish();
+
+        let mut content = Content::new();
+        content.save_state();
+        content.transform([rect[2], 0.0, 0.0, rect[3], rect[0], rect[1]]);
+        content.x_object(image_name);
+        content.restore_state();
+        self.pdf.stream(content_id, &content.finish());
+
+        let mut page = self.pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, page_size.0, page_size.1));
+        page.parent(Self::PAGE_TREE);
+        page.contents(content_id);
+        page.resources().x_objects().pair(image_name, image_id);
+        page.finish();
+
+        self.pages.push(page_id);
+        Ok(())
+    }
+
+    /// PDFを書き出す
+    pub fn finish(mut self) -> Vec<u8> {
+        self.pdf.catalog(Self::CATALOG).pages(Self::PAGE_TREE);
+        let count = self.pages.len() as i32;
+        self.pdf.pages(Self::PAGE_TREE).kids(self.pages).count(count);
+        self.pdf.finish()
+    }
+
+    /// PDFをファイルに保存する
+    pub fn save(self, dest: &Path) -> Result<(), String> {
+        safe_write::write_bytes(dest, &self.finish(), WriteOptions::default())
+            .map_err(|e| t!("pdf.save_failed", dest.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builds_one_page_per_image() {
+        let image = DynamicImage::ImageRgb8(::image::RgbImage::from_pixel(4, 3, ::image::Rgb([200, 10, 10])));
+        let mut builder = PdfBuilder::new();
+        builder.add_image_page(&image, (400.0, 300.0), [0.0, 0.0, 400.0, 300.0]).unwrap();
+        builder.add_image_page(&image, (300.0, 400.0), [0.0, 87.5, 300.0, 225.0]).unwrap();
+
+        let bytes = builder.finish();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.starts_with("%PDF-"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/DCTDecode"));
+    }
+}
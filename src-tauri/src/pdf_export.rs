@@ -0,0 +1,213 @@
+use std::path::{Path, PathBuf};
+use ::image::{DynamicImage, GenericImageView};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::i18n::t;
+use crate::jobs::{self, JobHandle};
+use crate::path_guard;
+use crate::pdf::PdfBuilder;
+use crate::preview;
+
+/// 1ミリメートルあたりのポイント数
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+/// 用紙を画像に合わせる際の解像度（ピクセルをポイントに換算する）
+const IMAGE_PAGE_DPI: f32 = 150.0;
+
+/// 用紙の大きさ
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSize {
+    #[default]
+    A4,
+    Letter,
+    /// 画像ごとに画像と同じ縦横比の用紙にする
+    Image,
+}
+
+impl PageSize {
+    /// 縦向きの用紙の大きさ（ポイント）
+    fn points(self) -> Option<(f32, f32)> {
+        match self {
+            PageSize::A4 => Some((595.28, 841.89)),
+            PageSize::Letter => Some((612.0, 792.0)),
+            PageSize::Image => None,
+        }
+    }
+}
+
+/// 用紙への画像の合わせ方
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PageFit {
+    /// 画像全体が収まるように縮小する（余白ができる）
+    #[default]
+    Fit,
+    /// 余白なく埋まるように拡大し、はみ出した部分を切り落とす
+    Fill,
+}
+
+/// PDF書き出しの設定
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PdfExportOptions {
+    /// 保存先のPDFのパス
+    pub dest_path: String,
+    pub page_size: PageSize,
+    pub fit: PageFit,
+    /// 用紙の余白（ミリメートル）
+    pub margin_mm: f32,
+    /// 横長の画像は用紙を横向きにする
+    pub auto_orientation: bool,
+    /// 埋め込む画像の長辺の最大値（PDFが大きくなりすぎないよう縮小する）
+    pub max_image_size: u32,
+}
+
+impl Default for PdfExportOptions {
+    fn default() -> Self {
+        Self {
+            dest_path: String::new(),
+            page_size: PageSize::default(),
+            fit: PageFit::default(),
+            margin_mm: 10.0,
+            auto_orientation: true,
+            max_image_size: 3000,
+        }
+    }
+}
+
+/// PDF書き出しジョブの結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PdfExportResult {
+    pub path: String,
+    pub pages: usize,
+    /// 読み込めずに飛ばした画像
+    pub skipped: Vec<String>,
+}
+
+/// 画像を置く用紙の大きさ（ポイント）
+fn page_for(image: (u32, u32), options: &PdfExportOptions) -> (f32, f32) {
+    let Some((width, height)) = options.page_size.points() else {
+        let margin = options.margin_mm.max(0.0) * POINTS_PER_MM * 2.0;
+        let scale = 72.0 / IMAGE_PAGE_DPI;
+        return (image.0 as f32 * scale + margin, image.1 as f32 * scale + margin);
+    };
+    if options.auto_orientation && image.0 > image.1 {
+        (height, width)
+    } else {
+        (width, height)
+    }
+}
+
+/// 画像を置く範囲（左下原点の`x, y, 幅, 高さ`）と、切り落とす場合の画像内の範囲（`x, y, 幅, 高さ`）を求める
+fn place(image: (u32, u32), page: (f32, f32), margin: f32, fit: PageFit) -> ([f32; 4], Option<[u32; 4]>) {
+    let area = ((page.0 - margin * 2.0).max(1.0), (page.1 - margin * 2.0).max(1.0));
+    let (iw, ih) = (image.0.max(1) as f32, image.1.max(1) as f32);
+    match fit {
+        PageFit::Fit => {
+            let scale = (area.0 / iw).min(area.1 / ih);
+            let (w, h) = (iw * scale, ih * scale);
+            ([(page.0 - w) / 2.0, (page.1 - h) / 2.0, w, h], None)
+        },
+        PageFit::Fill => {
+            let scale = (area.0 / iw).max(area.1 / ih);
+            let crop_w = ((area.0 / scale).round() as u32).clamp(1, image.0.max(1));
+            let crop_h = ((area.1 / scale).round() as u32).clamp(1, image.1.max(1));
+            let crop = [(image.0 - crop_w) / 2, (image.1 - crop_h) / 2, crop_w, crop_h];
+            ([margin, margin, area.0, area.1], Some(crop))
+        },
+    }
+}
+
+fn add_page(pdf: &mut PdfBuilder, image: DynamicImage, options: &PdfExportOptions) -> Result<(), String> {
+    let max = options.max_image_size.max(1);
+    let image = if image.width().max(image.height()) > max { image.thumbnail(max, max) } else { image };
+    let page = page_for(image.dimensions(), options);
+    let (rect, crop) = place(image.dimensions(), page, options.margin_mm.max(0.0) * POINTS_PER_MM, options.fit);
+    let image = match crop {
+        Some([x, y, w, h]) => image.crop_imm(x, y, w, h),
+        None => image,
+    };
+    pdf.add_image_page(&image, page, rect)
+}
+
+fn run(app_handle: &AppHandle, job: &JobHandle, sources: &[PathBuf], options: &PdfExportOptions, dest: &Path) -> Result<PdfExportResult, String> {
+    let mut pdf = PdfBuilder::new();
+    let mut pages = 0;
+    let mut skipped = Vec::new();
+    for (index, source) in sources.iter().enumerate() {
+        if job.is_cancelled() {
+            return Err(t!("jobs.cancelled"));
+        }
+        let name = source.to_string_lossy().to_string();
+        job.progress(index, Some(&name));
+        let added = preview::open_oriented(app_handle, source).and_then(|(image, _)| add_page(&mut pdf, image, options));
+        match added {
+            Ok(()) => pages += 1,
+            Err(error) => {
+                tracing::warn!("PDFに追加できませんでした: {} - {}", name, error);
+                skipped.push(name);
+            },
+        }
+        job.progress(index + 1, None);
+    }
+    if pages == 0 {
+        return Err(t!("pdf_export.empty"));
+    }
+    pdf.save(dest)?;
+    tracing::info!("PDFを書き出しました: {} ({}ページ)", dest.display(), pages);
+    Ok(PdfExportResult { path: dest.to_string_lossy().to_string(), pages, skipped })
+}
+
+fn start(app_handle: &AppHandle, paths: Vec<String>, options: PdfExportOptions) -> Result<String, String> {
+    if paths.is_empty() {
+        return Err(t!("pdf_export.empty"));
+    }
+    let dest = PathBuf::from(&options.dest_path);
+    let is_pdf = dest.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if !is_pdf {
+        return Err(t!("pdf_export.invalid_dest", options.dest_path));
+    }
+    if dest.parent().is_some_and(|parent| !parent.as_os_str().is_empty() && !parent.is_dir()) {
+        return Err(t!("path.not_directory", dest.display()));
+    }
+    let sources = paths.iter()
+        .map(|path| path_guard::guard(app_handle, path))
+        .collect::<Result<Vec<PathBuf>, String>>()?;
+
+    let handle = app_handle.clone();
+    jobs::spawn(app_handle, "pdf", sources.len(), move |job| run(&handle, job, &sources, &options, &dest))
+}
+
+/// 選択した画像を1枚1ページにまとめたPDFを書き出すジョブを開始し、ジョブIDを返す
+///
+/// 進捗と結果は`job-progress`イベントで通知する。中止した場合はPDFを保存しない
+#[tauri::command]
+pub async fn export_selection_to_pdf(app_handle: AppHandle, paths: Vec<String>, options: PdfExportOptions) -> Result<String, String> {
+    let result = start(&app_handle, paths, options);
+    audit::complete(&app_handle, "export_selection_to_pdf", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place_fit_and_fill() {
+        // 横長の画像を正方形の用紙に収める
+        let (rect, crop) = place((200, 100), (120.0, 120.0), 10.0, PageFit::Fit);
+        assert_eq!(rect, [10.0, 35.0, 100.0, 50.0]);
+        assert!(crop.is_none());
+
+        // 余白なく埋める場合は左右を切り落とす
+        let (rect, crop) = place((200, 100), (120.0, 120.0), 10.0, PageFit::Fill);
+        assert_eq!(rect, [10.0, 10.0, 100.0, 100.0]);
+        assert_eq!(crop, Some([50, 0, 100, 100]));
+
+        let options = PdfExportOptions::default();
+        assert_eq!(page_for((300, 200), &options), (841.89, 595.28));
+        assert_eq!(page_for((200, 300), &options), (595.28, 841.89));
+    }
+}
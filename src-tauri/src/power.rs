@@ -0,0 +1,223 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::event_bridge::{self, Delivery};
+use crate::jobs;
+use crate::settings::AppSettings;
+
+/// 電源の状態を確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 節電中に処理の区切りごとに待つ時間
+const THROTTLE_DELAY: Duration = Duration::from_millis(250);
+
+/// 一時停止中に再開を確認する間隔
+const PAUSE_POLL: Duration = Duration::from_secs(1);
+
+/// 現在の電源の状態とバックグラウンド処理の動作
+static STATUS: Mutex<Option<PowerStatus>> = Mutex::new(None);
+
+/// バッテリー駆動中のバックグラウンド処理（縮小画像の作成・監視・インデックス作成）の扱い
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerPolicy {
+    /// 電源に関係なく通常どおり実行する
+    Ignore,
+    /// 処理の間隔を空けて負荷を下げる
+    #[default]
+    Throttle,
+    /// 電源に接続されるまで止める
+    Pause,
+}
+
+/// バックグラウンド処理の動作
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BackgroundMode {
+    #[default]
+    Normal,
+    Throttled,
+    Paused,
+}
+
+/// 電源の状態
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct PowerState {
+    /// バッテリーで動作しているか（バッテリーがない・判定できない場合はfalse）
+    pub on_battery: bool,
+    /// バッテリー残量（%）
+    pub battery_percent: Option<u8>,
+}
+
+/// `power-state-changed`で通知する内容
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct PowerStatus {
+    pub state: PowerState,
+    pub policy: PowerPolicy,
+    pub mode: BackgroundMode,
+}
+
+/// 電源の状態と設定からバックグラウンド処理の動作を決める
+fn mode(state: &PowerState, policy: PowerPolicy) -> BackgroundMode {
+    match (state.on_battery, policy) {
+        (false, _) | (true, PowerPolicy::Ignore) => BackgroundMode::Normal,
+        (true, PowerPolicy::Throttle) => BackgroundMode::Throttled,
+        (true, PowerPolicy::Pause) => BackgroundMode::Paused,
+    }
+}
+
+/// Linuxの`/sys/class/power_supply`から電源の状態を読む
+fn read_sysfs(root: &Path) -> Option<PowerState> {
+    let read = |dir: &Path, name: &str| fs::read_to_string(dir.join(name)).map(|value| value.trim().to_string()).ok();
+    let mut mains_online = None;
+    let mut battery = None;
+    for entry in fs::read_dir(root).ok()?.flatten() {
+        let dir = entry.path();
+        match read(&dir, "type").as_deref() {
+            Some("Mains") | Some("USB") => {
+                let online = read(&dir, "online").as_deref() == Some("1");
+                mains_online = Some(mains_online.unwrap_or(false) || online);
+            },
+            Some("Battery") if battery.is_none() => {
+                let discharging = read(&dir, "status").as_deref() == Some("Discharging");
+                let percent = read(&dir, "capacity").and_then(|value| value.parse().ok());
+                battery = Some((discharging, percent));
+            },
+            _ => {},
+        }
+    }
+    let (discharging, battery_percent) = battery?;
+    // 電源アダプターの情報がない環境では放電中かどうかで判定する
+    Some(PowerState { on_battery: mains_online.map_or(discharging, |online| !online), battery_percent })
+}
+
+/// macOSの`pmset -g batt`の出力から電源の状態を読む
+fn parse_pmset(output: &str) -> Option<PowerState> {
+    let on_battery = output.lines().next()?.contains("'Battery Power'");
+    let battery_percent = output.split(|c: char| c.is_whitespace() || c == ';')
+        .find_map(|word| word.strip_suffix('%')?.parse().ok());
+    if battery_percent.is_none() && !on_battery {
+        return None;
+    }
+    Some(PowerState { on_battery, battery_percent })
+}
+
+/// Windowsの`Win32_Battery`の状態（BatteryStatus 1が放電中）と残量を読む
+fn parse_win32_battery(output: &str) -> Option<PowerState> {
+    let mut values = output.split_whitespace();
+    let status: u32 = values.next()?.parse().ok()?;
+    let battery_percent = values.next().and_then(|value| value.parse().ok());
+    Some(PowerState { on_battery: status == 1, battery_percent })
+}
+
+/// 現在の電源の状態を読む（判定できなければ電源に接続されているとみなす）
+fn read_state() -> PowerState {
+    let state = if cfg!(target_os = "linux") {
+        read_sysfs(Path::new("/sys/class/power_supply"))
+    } else if cfg!(target_os = "macos") {
+        Command::new("pmset").args(["-g", "batt"]).output().ok()
+            .and_then(|output| parse_pmset(&String::from_utf8_lossy(&output.stdout)))
+    } else if cfg!(windows) {
+        Command::new("powershell")
+            .args(["-NoProfile", "-Command"])
+            .arg("$b = Get-CimInstance Win32_Battery | Select-Object -First 1; if ($b) { \"$($b.BatteryStatus) $($b.EstimatedChargeRemaining)\" }")
+            .output().ok()
+            .and_then(|output| parse_win32_battery(&String::from_utf8_lossy(&output.stdout)))
+    } else {
+        None
+    };
+    state.unwrap_or_default()
+}
+
+/// 電源の状態を更新し、変わっていれば`power-state-changed`を通知する
+fn update(app_handle: &AppHandle, state: PowerState, policy: PowerPolicy) -> PowerStatus {
+    let status = PowerStatus { state, policy, mode: mode(&state, policy) };
+    let previous = STATUS.lock().unwrap_or_else(PoisonError::into_inner).replace(status);
+    if previous != Some(status) {
+        tracing::info!("電源の状態が変わりました: {:?}", status);
+        event_bridge::emit(app_handle, "power-state-changed", Delivery::Latest, status);
+    }
+    status
+}
+
+/// 電源の状態を監視するスレッドを起動する
+pub fn start_monitor(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        let policy = AppSettings::load(&app_handle).unwrap_or_default().power.policy;
+        update(&app_handle, read_state(), policy);
+        std::thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+/// 現在のバックグラウンド処理の動作（`pause_background_work`で一時停止中なら常にPaused）
+pub fn background_mode() -> BackgroundMode {
+    if jobs::is_paused() {
+        return BackgroundMode::Paused;
+    }
+    STATUS.lock().unwrap_or_else(PoisonError::into_inner).map(|status| status.mode).unwrap_or_default()
+}
+
+/// バックグラウンド処理の区切りで呼ぶ
+///
+/// 節電中は少し待ち、一時停止中は再開されるか`cancelled`がtrueを返すまで待つ
+pub fn checkpoint(cancelled: impl Fn() -> bool) {
+    loop {
+        match background_mode() {
+            BackgroundMode::Normal => return,
+            BackgroundMode::Throttled => {
+                std::thread::sleep(THROTTLE_DELAY);
+                return;
+            },
+            BackgroundMode::Paused if cancelled() => return,
+            BackgroundMode::Paused => std::thread::sleep(PAUSE_POLL),
+        }
+    }
+}
+
+/// 電源の状態とバックグラウンド処理の動作を取得する
+#[tauri::command]
+pub fn get_power_status(app_handle: AppHandle) -> PowerStatus {
+    let current = *STATUS.lock().unwrap_or_else(PoisonError::into_inner);
+    current.unwrap_or_else(|| {
+        let policy = AppSettings::load(&app_handle).unwrap_or_default().power.policy;
+        update(&app_handle, read_state(), policy)
+    })
+}
+
+/// バッテリー駆動中のバックグラウンド処理の扱いを設定する（すぐに反映する）
+#[tauri::command]
+pub fn set_power_policy(app_handle: AppHandle, policy: PowerPolicy) -> Result<PowerStatus, String> {
+    let result = AppSettings::load(&app_handle).and_then(|mut settings| {
+        settings.power.policy = policy;
+        settings.save(&app_handle)
+    });
+    audit::complete(&app_handle, "set_power_policy", &result);
+    result?;
+    let state = STATUS.lock().unwrap_or_else(PoisonError::into_inner).map(|status| status.state).unwrap_or_else(read_state);
+    Ok(update(&app_handle, state, policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_power_state() {
+        let pmset = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t85%; discharging; 4:12 remaining present: true\n";
+        assert_eq!(parse_pmset(pmset), Some(PowerState { on_battery: true, battery_percent: Some(85) }));
+        assert_eq!(parse_pmset("Now drawing from 'AC Power'\n"), None);
+        assert_eq!(parse_win32_battery("2 100\r\n"), Some(PowerState { on_battery: false, battery_percent: Some(100) }));
+        assert_eq!(parse_win32_battery(""), None);
+
+        let battery = PowerState { on_battery: true, battery_percent: None };
+        assert_eq!(mode(&battery, PowerPolicy::Throttle), BackgroundMode::Throttled);
+        assert_eq!(mode(&battery, PowerPolicy::Ignore), BackgroundMode::Normal);
+        assert_eq!(mode(&PowerState::default(), PowerPolicy::Pause), BackgroundMode::Normal);
+    }
+}
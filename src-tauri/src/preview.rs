@@ -0,0 +1,278 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use ::image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager};
+use crate::config;
+use crate::display::{self, HdrTransfer};
+use crate::exif_info;
+use crate::metrics;
+use crate::sensitive;
+use crate::settings::{AppSettings, DecodeLimits};
+use crate::tone_map;
+
+/// 生成した画像（プレビュー・比較結果等）のキャッシュ先を取得する
+pub fn get_cache_dir(app_handle: &AppHandle, kind: &str) -> PathBuf {
+    app_handle.path().app_cache_dir()
+        .unwrap_or_else(|_| config::app_data_dir(app_handle).join("cache"))
+        .join(kind)
+}
+
+/// 元ファイルのパス・サイズ・更新日時と生成条件からキャッシュのキーを作る
+///
+/// 元ファイルが更新されるとキーが変わるため、古いキャッシュは使われない
+pub fn cache_key(path: &Path, variant: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    variant.hash(&mut hasher);
+    if let Ok(metadata) = fs::metadata(path) {
+        metadata.len().hash(&mut hasher);
+        if let Ok(modified) = metadata.modified() {
+            modified.hash(&mut hasher);
+        }
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+/// 大きすぎて展開を拒否した際のエラー（JSON文字列として返す）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TooLargeError {
+    /// 常に`"too_large"`（画面側で表示できない旨を示すため）
+    pub kind: String,
+    pub width: u32,
+    pub height: u32,
+    /// 展開に必要なメモリ量の見積もり（バイト）
+    pub required_bytes: u64,
+    pub max_bytes: u64,
+    pub message: String,
+}
+
+/// RGBA 8bitの1画素あたりのバイト数（展開後の大きさの見積もりの下限）
+pub(crate) const RGBA8_BYTES_PER_PIXEL: u64 = 4;
+
+/// 展開に必要なメモリ量が上限内か確認する（上限0で無制限）
+///
+/// `bytes_per_pixel`は展開後の1画素あたりのバイト数（16bitの画像は8bitの倍になる）
+pub(crate) fn check_budget(path: &Path, width: u32, height: u32, bytes_per_pixel: u64, max_bytes: u64) -> Result<(), String> {
+    let required_bytes = width as u64 * height as u64 * bytes_per_pixel;
+    if max_bytes == 0 || required_bytes <= max_bytes {
+        return Ok(());
+    }
+    tracing::warn!("大きすぎる画像の展開を拒否しました: {} ({}x{})", path.display(), width, height);
+    let error = TooLargeError {
+        kind: "too_large".to_string(),
+        width,
+        height,
+        required_bytes,
+        max_bytes,
+        message: format!("画像が大きすぎるため表示できません。設定の展開の上限を引き上げてください: {} ({}x{})", path.display(), width, height),
+    };
+    Err(serde_json::to_string(&error).unwrap_or(error.message))
+}
+
+/// 画像ファイルを読み込む
+///
+/// 1枚の巨大な画像でメモリを使い果たさないよう、先に寸法と色の形式を読んで設定の上限を超えるものは展開しない。
+/// 16bitのPNG・TIFFは精度を落とさずに展開する（表示・保存の際に必要なら8bitにする）。
+/// 展開にかかった時間は`image_decode`として計測値に記録する
+pub fn open_image(app_handle: &AppHandle, path: &Path, limits: &DecodeLimits) -> Result<DynamicImage, String> {
+    let failed = |e: &dyn std::fmt::Display| format!("画像の読み込みに失敗: {} - {}", path.display(), e);
+    let mut reader = ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| failed(&e))?;
+    // 寸法の偽装に備え、デコーダー側の確保量にも同じ上限を設ける
+    if limits.max_decode_bytes > 0 {
+        let mut decoder_limits = ::image::Limits::default();
+        decoder_limits.max_alloc = Some(limits.max_decode_bytes);
+        reader.limits(decoder_limits);
+    } else {
+        reader.no_limits();
+    }
+    let decoder = reader.into_decoder().map_err(|e| failed(&e))?;
+    let (width, height) = decoder.dimensions();
+    let bytes_per_pixel = (decoder.color_type().bytes_per_pixel() as u64).max(RGBA8_BYTES_PER_PIXEL);
+    check_budget(path, width, height, bytes_per_pixel, limits.max_decode_bytes)?;
+    let started = Instant::now();
+    let image = DynamicImage::from_decoder(decoder).map_err(|e| failed(&e))?;
+    metrics::record_operation(app_handle, "image_decode", started.elapsed());
+    Ok(image)
+}
+
+/// 展開の上限を超えたことによるエラーか
+fn is_too_large(error: &str) -> bool {
+    serde_json::from_str::<TooLargeError>(error).is_ok()
+}
+
+/// 縮小画像の元にする画像を読み込む
+///
+/// 上限を超えて展開できない画像でも、EXIFに埋め込まれた縮小画像があればそれを使う
+/// （埋め込みの縮小画像は小さく、同じ上限で展開できる）
+fn open_for_thumbnail(app_handle: &AppHandle, path: &Path, limits: &DecodeLimits) -> Result<DynamicImage, String> {
+    let error = match open_image(app_handle, path, limits) {
+        Ok(image) => return Ok(image),
+        Err(error) if is_too_large(&error) => error,
+        Err(error) => return Err(error),
+    };
+    let Some(embedded) = exif_info::embedded_thumbnail(path) else { return Err(error) };
+    let mut reader = ImageReader::with_format(std::io::Cursor::new(embedded), ImageFormat::Jpeg);
+    if limits.max_decode_bytes > 0 {
+        let mut decoder_limits = ::image::Limits::default();
+        decoder_limits.max_alloc = Some(limits.max_decode_bytes);
+        reader.limits(decoder_limits);
+    }
+    tracing::info!("埋め込みの縮小画像を使います: {}", path.display());
+    reader.decode().map_err(|_| error)
+}
+
+/// 16bit・浮動小数点の画像を8bitにする（縮小画像など表示用の画像は8bitで十分なため）
+pub fn to_8bit(image: DynamicImage) -> DynamicImage {
+    match image {
+        DynamicImage::ImageLuma8(_) | DynamicImage::ImageLumaA8(_) | DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_) => image,
+        image if image.color().has_alpha() => DynamicImage::ImageRgba8(image.to_rgba8()),
+        image => DynamicImage::ImageRgb8(image.to_rgb8()),
+    }
+}
+
+/// 画像をPNGでキャッシュに保存する
+pub fn save_png(image: &DynamicImage, dest: &Path) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("ディレクトリの作成に失敗 ({}): {}", parent.display(), e))?;
+    }
+    image.save_with_format(dest, ImageFormat::Png)
+        .map_err(|e| format!("画像の保存に失敗: {} - {}", dest.display(), e))
+}
+
+/// 作成した縮小画像
+#[derive(Debug, Clone)]
+pub struct Thumbnail {
+    pub path: PathBuf,
+    /// 適用したEXIFの向き（適用していなければNone）
+    pub applied_orientation: Option<u32>,
+    /// 刺激の強い画像のためぼかしたか
+    pub blurred: bool,
+    /// 元の画像がHDRか（縮小画像は設定の方式でトーンマッピング済み）
+    pub hdr: Option<HdrTransfer>,
+}
+
+/// 刺激の強い画像の縮小画像に掛けるぼかしの強さ（縮小画像の長辺に対する割合）
+const SENSITIVE_BLUR_FRACTION: f32 = 0.05;
+
+/// EXIFの向き（1〜8）に従って画像を正立させる
+pub fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// 適用するEXIFの向きを取得する（設定で無効、または回転不要ならNone）
+pub fn orientation_to_apply(app_handle: &AppHandle, path: &Path) -> Option<u32> {
+    if !AppSettings::load(app_handle).unwrap_or_default().preview.auto_rotate {
+        return None;
+    }
+    exif_info::orientation(path).filter(|orientation| *orientation != 1)
+}
+
+/// 画像を読み込み、設定で有効ならEXIFの向きを適用する（適用した向きも返す）
+pub fn open_oriented(app_handle: &AppHandle, path: &Path) -> Result<(DynamicImage, Option<u32>), String> {
+    let limits = AppSettings::load(app_handle).unwrap_or_default().decode_limits;
+    let image = open_image(app_handle, path, &limits)?;
+    Ok(match orientation_to_apply(app_handle, path) {
+        Some(orientation) => (apply_orientation(image, orientation), Some(orientation)),
+        None => (image, None),
+    })
+}
+
+/// 縮小画像を作成してキャッシュし、そのパスを返す（作成済みならそのまま返す）
+///
+/// 横倒しで表示されないよう、設定で有効ならEXIFの向きを適用する。
+/// 刺激の強い画像は、表示を許可するまでぼかしたものを返す。
+/// 展開の上限を超える画像は、EXIFに埋め込まれた縮小画像がある場合に限り作成できる
+pub fn thumbnail(app_handle: &AppHandle, path: &Path, max_size: u32) -> Result<Thumbnail, String> {
+    let applied_orientation = orientation_to_apply(app_handle, path);
+    let blurred = sensitive::should_blur(app_handle, path);
+    let hdr = display::detect_hdr(path);
+    let settings = AppSettings::load(app_handle).unwrap_or_default();
+    // HDRの画像は設定の方式でトーンマッピングする（方式を変えたら作り直す）
+    let tone_mapping = hdr.map(|transfer| (transfer, settings.preview.tone_mapping));
+    let variant = format!(
+        "thumbnail-{}-o{}{}{}",
+        max_size, applied_orientation.unwrap_or(1), if blurred { "-blur" } else { "" },
+        tone_mapping.map(|(_, operator)| format!("-{:?}", operator)).unwrap_or_default(),
+    );
+    let dest = get_cache_dir(app_handle, "thumbnails")
+        .join(format!("{}.png", cache_key(path, &variant)));
+    let cached = dest.exists();
+    metrics::record_cache(app_handle, "thumbnail", cached);
+    if !cached {
+        let image = open_for_thumbnail(app_handle, path, &settings.decode_limits)?.thumbnail(max_size, max_size);
+        let image = match tone_mapping {
+            Some((transfer, operator)) => tone_map::tone_map(&image, transfer, operator),
+            None => to_8bit(image),
+        };
+        let image = match applied_orientation {
+            Some(orientation) => apply_orientation(image, orientation),
+            None => image,
+        };
+        let image = if blurred {
+            image.blur(max_size as f32 * SENSITIVE_BLUR_FRACTION)
+        } else {
+            image
+        };
+        save_png(&image, &dest)?;
+    }
+    Ok(Thumbnail { path: dest, applied_orientation, blurred, hdr })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_budget_rejects_oversized_images() {
+        let path = Path::new("/photos/huge.png");
+        assert!(check_budget(path, 1000, 1000, 4, 4_000_000).is_ok());
+        assert!(check_budget(path, 30000, 30000, 4, 0).is_ok());
+
+        let error: TooLargeError = serde_json::from_str(&check_budget(path, 30000, 30000, 4, 512 * 1024 * 1024).unwrap_err()).unwrap();
+        assert_eq!(error.kind, "too_large");
+        assert_eq!(error.required_bytes, 3_600_000_000);
+        assert!(is_too_large(&check_budget(path, 30000, 30000, 4, 1).unwrap_err()));
+        assert!(!is_too_large("画像の読み込みに失敗"));
+        // 16bitのRGBAは同じ寸法でも倍のメモリを使う
+        assert!(check_budget(path, 1000, 1000, 8, 4_000_000).is_err());
+    }
+
+    #[test]
+    fn test_to_8bit_keeps_tones() {
+        let image = DynamicImage::ImageRgb16(::image::ImageBuffer::from_pixel(1, 1, ::image::Rgb([u16::MAX, 32_896, 0])));
+        let converted = to_8bit(image);
+        assert_eq!(converted.color(), ::image::ColorType::Rgb8);
+        assert_eq!(converted.to_rgb8().get_pixel(0, 0).0, [255, 128, 0]);
+    }
+
+    #[test]
+    fn test_apply_orientation() {
+        // 左上が赤の2x1の画像
+        let mut image = ::image::RgbImage::new(2, 1);
+        image.put_pixel(0, 0, ::image::Rgb([255, 0, 0]));
+        let image = DynamicImage::ImageRgb8(image);
+
+        // 6: 時計回りに90度回転すると、赤は右上に移る
+        let rotated = apply_orientation(image.clone(), 6).to_rgb8();
+        assert_eq!(rotated.dimensions(), (1, 2));
+        assert_eq!(rotated.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(apply_orientation(image.clone(), 3).to_rgb8().get_pixel(1, 0).0, [255, 0, 0]);
+        assert_eq!(apply_orientation(image.clone(), 1).to_rgb8(), image.to_rgb8());
+    }
+}
@@ -0,0 +1,248 @@
+use std::process::Command;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::exif_info;
+use crate::i18n::t;
+use crate::image;
+use crate::path_guard;
+
+/// 用紙の向き
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    /// 画像の縦横比に合わせる
+    #[default]
+    Auto,
+    Portrait,
+    Landscape,
+}
+
+fn default_copies() -> u32 {
+    1
+}
+
+fn default_fit_to_page() -> bool {
+    true
+}
+
+/// 印刷の設定
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PrintOptions {
+    /// 用紙に合わせて拡大・縮小する
+    #[serde(default = "default_fit_to_page")]
+    pub fit_to_page: bool,
+    /// 用紙の向き
+    #[serde(default)]
+    pub orientation: Orientation,
+    /// 部数
+    #[serde(default = "default_copies")]
+    pub copies: u32,
+    /// プリンター名（未指定なら既定のプリンター）
+    #[serde(default)]
+    pub printer: Option<String>,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            fit_to_page: default_fit_to_page(),
+            orientation: Orientation::Auto,
+            copies: default_copies(),
+            printer: None,
+        }
+    }
+}
+
+/// 向きの指定を解決する（Autoは画像の縦横比から決める）
+fn resolve_orientation(orientation: Orientation, dimensions: Option<(u32, u32)>) -> Orientation {
+    match (orientation, dimensions) {
+        (Orientation::Auto, Some((width, height))) if width > height => Orientation::Landscape,
+        (Orientation::Auto, _) => Orientation::Portrait,
+        (other, _) => other,
+    }
+}
+
+/// CUPS（macOS・Linux）の`lp`コマンドの引数を組み立てる
+fn lp_args(path: &str, options: &PrintOptions, orientation: Orientation) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(printer) = &options.printer {
+        args.extend(["-d".to_string(), printer.clone()]);
+    }
+    args.extend(["-n".to_string(), options.copies.max(1).to_string()]);
+    if options.fit_to_page {
+        args.extend(["-o".to_string(), "fit-to-page".to_string()]);
+    }
+    // orientation-requested: 3=縦, 4=横
+    let orientation_value = if orientation == Orientation::Landscape { "4" } else { "3" };
+    args.extend(["-o".to_string(), format!("orientation-requested={}", orientation_value)]);
+    args.push("--".to_string());
+    args.push(path.to_string());
+    args
+}
+
+/// 印刷サイズを計算する解像度（DPI）
+const PRINT_DPIS: [u32; 4] = [150, 200, 300, 350];
+
+/// 判定に使う用紙（名前・短辺・長辺のミリメートル）
+const PAPER_SIZES: [(&str, f64, f64); 6] = [
+    ("L", 89.0, 127.0),
+    ("2L", 127.0, 178.0),
+    ("A4", 210.0, 297.0),
+    ("A3", 297.0, 420.0),
+    ("六切", 203.0, 254.0),
+    ("四切", 254.0, 305.0),
+];
+
+/// 写真の印刷で十分とされる解像度
+const RECOMMENDED_DPI: f64 = 300.0;
+
+/// 印刷したときの大きさ
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PrintSize {
+    pub dpi: f64,
+    pub width_mm: f64,
+    pub height_mm: f64,
+    pub width_inch: f64,
+    pub height_inch: f64,
+}
+
+/// 用紙いっぱいに印刷したときの解像度
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PaperFit {
+    pub name: String,
+    pub width_mm: f64,
+    pub height_mm: f64,
+    /// 用紙に収めたときの実効解像度（画像の向きに合わせて用紙を回転した場合）
+    pub effective_dpi: f64,
+    /// 推奨解像度（300DPI）以上で印刷できる
+    pub sufficient: bool,
+}
+
+/// 画像の印刷サイズの情報
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PrintInfo {
+    /// 向きを反映した画素数
+    pub width: u32,
+    pub height: u32,
+    /// ファイルに記録された解像度（横・縦のDPI）
+    pub dpi: Option<(f64, f64)>,
+    /// 記録された解像度で印刷したときの大きさ
+    pub native_size: Option<PrintSize>,
+    /// 代表的な解像度で印刷したときの大きさ
+    pub sizes: Vec<PrintSize>,
+    pub papers: Vec<PaperFit>,
+}
+
+fn print_size(width: u32, height: u32, dpi_x: f64, dpi_y: f64) -> PrintSize {
+    let (width_inch, height_inch) = (width as f64 / dpi_x, height as f64 / dpi_y);
+    PrintSize {
+        dpi: dpi_x,
+        width_mm: width_inch * 25.4,
+        height_mm: height_inch * 25.4,
+        width_inch,
+        height_inch,
+    }
+}
+
+/// 画素数と解像度から印刷サイズを計算する
+fn print_info(width: u32, height: u32, dpi: Option<(f64, f64)>) -> PrintInfo {
+    let (long, short) = (width.max(height) as f64, width.min(height) as f64);
+    let papers = PAPER_SIZES.iter().map(|(name, paper_short, paper_long)| {
+        // 用紙に収める（縦横比が違う場合は余白ができる）ため、小さい方の解像度で印刷される
+        let effective_dpi = (long / (paper_long / 25.4)).min(short / (paper_short / 25.4));
+        PaperFit {
+            name: name.to_string(),
+            width_mm: *paper_short,
+            height_mm: *paper_long,
+            effective_dpi,
+            sufficient: effective_dpi >= RECOMMENDED_DPI,
+        }
+    }).collect();
+
+    PrintInfo {
+        width,
+        height,
+        dpi,
+        native_size: dpi.map(|(x, y)| print_size(width, height, x, y)),
+        sizes: PRINT_DPIS.iter().map(|dpi| print_size(width, height, *dpi as f64, *dpi as f64)).collect(),
+        papers,
+    }
+}
+
+/// 画像の解像度と、代表的な解像度・用紙で印刷したときの大きさを取得する（印刷に十分な画素数かの確認用）
+#[tauri::command]
+pub async fn get_print_info(app_handle: AppHandle, path: String) -> Result<PrintInfo, String> {
+    let file = path_guard::guard(&app_handle, &path)?;
+    let (width, height) = ::image::image_dimensions(&file).map_err(|e| t!("print.read_failed", path, e))?;
+    // 90度回転して表示する画像は縦横を入れ替える
+    let (width, height) = match exif_info::orientation(&file) {
+        Some(5..=8) => (height, width),
+        _ => (width, height),
+    };
+    Ok(print_info(width, height, exif_info::dpi(&file)))
+}
+
+/// OSの印刷機能に画像を送る
+///
+/// macOS・LinuxはCUPSの`lp`で直接印刷し、Windowsは関連付けられたアプリの印刷機能を使う
+/// （Windowsでは向き・拡大縮小はアプリの印刷ダイアログで指定する）
+#[tauri::command]
+pub async fn print_image(app_handle: AppHandle, path: String, options: Option<PrintOptions>) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    let file = path_guard::guard(&app_handle, &path)?;
+    if !file.is_file() || !image::is_image_file(&file) {
+        return Err(t!("path.not_found", path));
+    }
+
+    let status = if cfg!(windows) {
+        let verb = match &options.printer {
+            Some(printer) => format!("-Verb PrintTo -ArgumentList '\"{}\"'", printer.replace('\'', "''")),
+            None => "-Verb Print".to_string(),
+        };
+        Command::new("powershell")
+            .args(["-NoProfile", "-Command"])
+            .arg(format!("Start-Process -FilePath '{}' {}", path.replace('\'', "''"), verb))
+            .status()
+    } else {
+        let dimensions = ::image::image_dimensions(&file).ok();
+        let orientation = resolve_orientation(options.orientation, dimensions);
+        Command::new("lp").args(lp_args(&path, &options, orientation)).status()
+    };
+
+    match status {
+        Ok(status) if status.success() => {
+            tracing::info!("印刷を開始しました: {}", path);
+            Ok(())
+        },
+        Ok(status) => Err(t!("print.failed", path, status)),
+        Err(e) => Err(t!("print.unavailable", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lp_args() {
+        let options = PrintOptions { copies: 2, printer: Some("Office".to_string()), ..Default::default() };
+        let orientation = resolve_orientation(options.orientation, Some((6000, 4000)));
+        assert_eq!(orientation, Orientation::Landscape);
+        assert_eq!(lp_args("/photos/a.jpg", &options, orientation), vec![
+            "-d", "Office", "-n", "2", "-o", "fit-to-page", "-o", "orientation-requested=4", "--", "/photos/a.jpg",
+        ]);
+        assert_eq!(resolve_orientation(Orientation::Auto, None), Orientation::Portrait);
+    }
+
+    #[test]
+    fn test_print_info() {
+        let info = print_info(3000, 2000, Some((72.0, 72.0)));
+        assert!((info.native_size.unwrap().width_inch - 41.67).abs() < 0.01);
+        assert_eq!(info.sizes[2].dpi, 300.0);
+        assert!((info.sizes[2].width_mm - 254.0).abs() < 0.01);
+        let a4 = info.papers.iter().find(|paper| paper.name == "A4").unwrap();
+        assert!((a4.effective_dpi - 2000.0 / (210.0 / 25.4)).abs() < 0.01);
+        assert!(!a4.sufficient);
+        assert!(info.papers.iter().find(|paper| paper.name == "L").unwrap().sufficient);
+    }
+}
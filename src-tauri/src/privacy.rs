@@ -0,0 +1,365 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use sha2::Sha256;
+use tauri::AppHandle;
+use crate::audit;
+use crate::config::{self, ResourceConfig};
+use crate::event_bridge::{self, Delivery};
+use crate::i18n::t;
+use crate::image::ImageInfo;
+
+/// プライバシーモードの状態（画面からの設定保存で解除されないよう、アプリ設定とは別に保存する）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct PrivacyFile {
+    /// プライバシーモード中か
+    enabled: bool,
+    /// 解除に必要なPINのハッシュ（`pbkdf2-sha256$反復回数$ソルト$ハッシュ`）
+    pin_hash: Option<String>,
+    /// PINの照合に続けて失敗した記録
+    attempts: PinAttempts,
+}
+
+/// PINのハッシュに使うPBKDF2-HMAC-SHA256の反復回数
+#[cfg(not(test))]
+const PIN_HASH_ITERATIONS: u32 = 600_000;
+#[cfg(test)]
+const PIN_HASH_ITERATIONS: u32 = 1_000;
+
+/// PINのハッシュの形式名
+const PIN_HASH_SCHEME: &str = "pbkdf2-sha256";
+
+/// 待たずに照合できる失敗回数
+const FREE_ATTEMPTS: u32 = 5;
+
+/// 失敗が続いた際に照合を受け付けない時間（回数が増えるごとに倍にする）
+const LOCKOUT_BASE_SECS: u64 = 30;
+
+/// 照合を受け付けない時間の上限
+const LOCKOUT_MAX_SECS: u64 = 15 * 60;
+
+/// PINの照合に続けて失敗した記録（再起動で回避されないよう、PINのハッシュと一緒に保存する）
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub(crate) struct PinAttempts {
+    /// 続けて失敗した回数
+    failures: u32,
+    /// 次に照合を受け付ける日時（Unix時間）
+    retry_after: u64,
+}
+
+impl PinAttempts {
+    /// 照合の結果を記録する（成功したら回数を戻し、失敗が続いたら次に受け付けるまでの時間を延ばす）
+    fn record(&mut self, success: bool, now: u64) {
+        if success {
+            *self = Self::default();
+            return;
+        }
+        self.failures += 1;
+        if self.failures >= FREE_ATTEMPTS {
+            let doublings = (self.failures - FREE_ATTEMPTS).min(16);
+            self.retry_after = now + (LOCKOUT_BASE_SECS << doublings).min(LOCKOUT_MAX_SECS);
+        }
+    }
+}
+
+/// プライバシーモードの状態
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PrivacyStatus {
+    pub enabled: bool,
+    /// 解除にPINが必要か
+    pub pin_set: bool,
+    /// 非表示にしているフォルダ
+    pub private_folders: Vec<String>,
+}
+
+/// 保存先のパスを取得する
+fn get_privacy_path(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("privacy.json")
+}
+
+fn load(app_handle: &AppHandle) -> PrivacyFile {
+    fs::read_to_string(get_privacy_path(app_handle))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(app_handle: &AppHandle, privacy: &PrivacyFile) -> Result<(), String> {
+    let path = get_privacy_path(app_handle);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(privacy)
+        .map_err(|e| t!("privacy.save_failed", e))?;
+    fs::write(&path, json).map_err(|e| t!("privacy.save_failed", e))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// PINをソルト付きでハッシュ化する（総当たりに時間が掛かるようPBKDF2で引き延ばす）
+pub(crate) fn hash_pin(pin: &str, salt: &str, iterations: u32) -> String {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(pin.as_bytes(), salt.as_bytes(), iterations, &mut key);
+    format!("{}${}${}${}", PIN_HASH_SCHEME, iterations, salt, to_hex(&key))
+}
+
+/// 新しいソルト（OSの乱数生成器で作る）でPINをハッシュ化する
+pub(crate) fn new_pin_hash(pin: &str) -> Result<String, String> {
+    let mut salt = [0u8; 16];
+    getrandom::getrandom(&mut salt).map_err(|e| t!("privacy.random_failed", e))?;
+    Ok(hash_pin(pin, &to_hex(&salt), PIN_HASH_ITERATIONS))
+}
+
+/// 長さが同じなら内容によらず同じ時間で比べる（一致した文字数を応答時間から推測されないようにする）
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// PINが保存されたハッシュと一致するか
+pub(crate) fn verify_pin(pin: &str, stored: &str) -> bool {
+    let parts: Vec<&str> = stored.split('$').collect();
+    match parts.as_slice() {
+        [scheme, iterations, salt, _] if *scheme == PIN_HASH_SCHEME => iterations.parse()
+            .map(|iterations| constant_time_eq(hash_pin(pin, salt, iterations).as_bytes(), stored.as_bytes()))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// 失敗回数を制限してPINを照合する（PINが未設定なら常に成功）
+///
+/// 失敗が続いた後はしばらく照合せずにエラーを返す
+pub(crate) fn verify_with_attempts(
+    pin_hash: Option<&str>,
+    attempts: &mut PinAttempts,
+    pin: Option<&str>,
+    now: u64,
+) -> Result<(), String> {
+    let Some(stored) = pin_hash else { return Ok(()) };
+    if now < attempts.retry_after {
+        return Err(t!("privacy.pin_locked_out", attempts.retry_after - now));
+    }
+    let matched = pin.filter(|pin| verify_pin(pin, stored));
+    attempts.record(matched.is_some(), now);
+    match matched {
+        Some(_) => Ok(()),
+        None => Err(t!("privacy.pin_mismatch")),
+    }
+}
+
+/// プライバシーモード中か
+pub fn is_enabled(app_handle: &AppHandle) -> bool {
+    load(app_handle).enabled
+}
+
+/// プライバシーモード中に隠すフォルダを取得する（モードが無効なら空）
+pub fn hidden_folders(app_handle: &AppHandle) -> Vec<String> {
+    if !load(app_handle).enabled {
+        return Vec::new();
+    }
+    ResourceConfig::load(app_handle)
+        .map(|config| private_folders(&config))
+        .unwrap_or_default()
+}
+
+fn private_folders(config: &ResourceConfig) -> Vec<String> {
+    config.filters.include.iter()
+        .filter(|path| config.folder_options(path).private)
+        .cloned()
+        .collect()
+}
+
+/// パスが隠すフォルダ内にあるか
+pub fn is_hidden(path: &str, hidden: &[String]) -> bool {
+    hidden.iter().any(|folder| Path::new(path).starts_with(folder))
+}
+
+/// プライバシーモード中に隠すフォルダの画像を取り除く
+pub fn filter_images(app_handle: &AppHandle, images: Vec<ImageInfo>) -> Vec<ImageInfo> {
+    let hidden = hidden_folders(app_handle);
+    if hidden.is_empty() {
+        return images;
+    }
+    images.into_iter().filter(|image| !is_hidden(&image.path, &hidden)).collect()
+}
+
+fn status(app_handle: &AppHandle) -> Result<PrivacyStatus, String> {
+    let privacy = load(app_handle);
+    Ok(PrivacyStatus {
+        enabled: privacy.enabled,
+        pin_set: privacy.pin_hash.is_some(),
+        private_folders: private_folders(&ResourceConfig::load(app_handle)?),
+    })
+}
+
+/// プライバシーモードの状態を取得する
+#[tauri::command]
+pub async fn get_privacy_mode(app_handle: AppHandle) -> Result<PrivacyStatus, String> {
+    status(&app_handle)
+}
+
+/// プライバシーモードを切り替える
+///
+/// 有効にする際に`pin`を指定するとPINを設定し、以後の解除にはそのPINが必要になる。
+/// 設定済みのPINを変更する場合は`current_pin`に現在のPINが必要
+#[tauri::command]
+pub async fn set_privacy_mode(
+    app_handle: AppHandle,
+    enabled: bool,
+    pin: Option<String>,
+    current_pin: Option<String>,
+) -> Result<PrivacyStatus, String> {
+    let result = set_mode(&app_handle, enabled, pin.as_deref(), current_pin.as_deref());
+    audit::complete(&app_handle, "set_privacy_mode", &result);
+    result?;
+
+    let status = status(&app_handle)?;
+    event_bridge::emit(&app_handle, "privacy-mode-changed", Delivery::Latest, &status);
+    Ok(status)
+}
+
+/// 設定済みのPINと照合する（PINが未設定なら常に一致とみなす。失敗回数は`privacy`に記録する）
+fn check_pin(privacy: &mut PrivacyFile, pin: Option<&str>, now: u64) -> Result<(), String> {
+    verify_with_attempts(privacy.pin_hash.as_deref(), &mut privacy.attempts, pin, now)
+}
+
+/// モードの切り替えとPINの設定を状態に反映する（PINの確認に失敗したら失敗回数以外は変えない）
+fn apply_mode(privacy: &mut PrivacyFile, enabled: bool, pin: Option<&str>, current_pin: Option<&str>, now: u64) -> Result<(), String> {
+    if enabled {
+        if let Some(pin) = pin.filter(|pin| !pin.is_empty()) {
+            // 現在のPINを知らなければ差し替えられないようにする（差し替えたPINで解除できてしまうため）
+            check_pin(privacy, current_pin, now)?;
+            privacy.pin_hash = Some(new_pin_hash(pin)?);
+        }
+    } else {
+        check_pin(privacy, pin, now)?;
+    }
+    privacy.enabled = enabled;
+    Ok(())
+}
+
+fn set_mode(app_handle: &AppHandle, enabled: bool, pin: Option<&str>, current_pin: Option<&str>) -> Result<(), String> {
+    let mut privacy = load(app_handle);
+    // 失敗回数も残すため、照合に失敗しても保存する
+    let result = apply_mode(&mut privacy, enabled, pin, current_pin, now());
+    save(app_handle, &privacy)?;
+    result?;
+    tracing::info!("プライバシーモードを{}にしました", if enabled { "有効" } else { "無効" });
+    Ok(())
+}
+
+/// 設定フォルダをプライバシーモードで隠す対象にするか設定する
+///
+/// プライバシーモード中に隠す対象から外すには、PINを設定していれば`pin`が必要
+#[tauri::command]
+pub async fn set_folder_private(app_handle: AppHandle, path: String, private: bool, pin: Option<String>) -> Result<(), String> {
+    let result = set_private(&app_handle, &path, private, pin.as_deref());
+    audit::complete(&app_handle, "set_folder_private", &result);
+    result
+}
+
+/// 隠す対象から外してよいか（モード中はPINが必要）
+fn check_unhide(privacy: &mut PrivacyFile, private: bool, pin: Option<&str>, now: u64) -> Result<(), String> {
+    if !private && privacy.enabled {
+        check_pin(privacy, pin, now)?;
+    }
+    Ok(())
+}
+
+fn set_private(app_handle: &AppHandle, path: &str, private: bool, pin: Option<&str>) -> Result<(), String> {
+    let mut privacy = load(app_handle);
+    let before = privacy.attempts.clone();
+    let result = check_unhide(&mut privacy, private, pin, now());
+    if privacy.attempts != before {
+        save(app_handle, &privacy)?;
+    }
+    result?;
+    let mut config = ResourceConfig::load(app_handle)?;
+    if !config.filters.include.iter().any(|include| include == path) {
+        return Err(t!("privacy.not_configured", path));
+    }
+    let mut options = config.folder_options(path);
+    options.private = private;
+    config.set_folder_options(path, options);
+    config.save(app_handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_hash_and_hidden_paths() {
+        let stored = new_pin_hash("1234").unwrap();
+        assert!(stored.starts_with("pbkdf2-sha256$"));
+        assert!(verify_pin("1234", &stored));
+        assert!(!verify_pin("4321", &stored));
+        assert_ne!(new_pin_hash("1234").unwrap(), stored);
+        assert!(!verify_pin("1234", "broken"));
+        assert!(!verify_pin("1234", "salt$0123"));
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+
+        let hidden = vec!["/photos/private".to_string()];
+        assert!(is_hidden("/photos/private/a.jpg", &hidden));
+        assert!(!is_hidden("/photos/private-not/a.jpg", &hidden));
+        assert!(!is_hidden("/photos/public/a.jpg", &hidden));
+    }
+
+    #[test]
+    fn test_pin_cannot_be_replaced_or_bypassed() {
+        let mut privacy = PrivacyFile::default();
+        apply_mode(&mut privacy, true, Some("1234"), None, 0).unwrap();
+
+        // 現在のPINなしに差し替えて、差し替えたPINで解除することはできない
+        assert!(apply_mode(&mut privacy, true, Some("0000"), None, 0).is_err());
+        assert!(apply_mode(&mut privacy, true, Some("0000"), Some("9999"), 0).is_err());
+        assert!(apply_mode(&mut privacy, false, Some("0000"), None, 0).is_err());
+        assert!(privacy.enabled);
+
+        // モード中に隠す対象から外すにはPINが必要
+        assert!(check_unhide(&mut privacy, false, None, 0).is_err());
+        assert!(check_unhide(&mut privacy, false, Some("1234"), 0).is_ok());
+        assert!(check_unhide(&mut privacy, true, None, 0).is_ok());
+
+        apply_mode(&mut privacy, true, Some("0000"), Some("1234"), 0).unwrap();
+        apply_mode(&mut privacy, false, Some("0000"), None, 0).unwrap();
+        assert!(!privacy.enabled);
+        assert!(check_unhide(&mut privacy, false, None, 0).is_ok());
+    }
+
+    #[test]
+    fn test_repeated_failures_lock_out() {
+        let pin_hash = new_pin_hash("1234").unwrap();
+        let pin_hash = Some(pin_hash.as_str());
+        let mut attempts = PinAttempts::default();
+        for _ in 0..FREE_ATTEMPTS {
+            assert!(verify_with_attempts(pin_hash, &mut attempts, Some("0000"), 100).is_err());
+        }
+        // 失敗が続いた後は正しいPINでも待つ必要がある
+        assert_eq!(attempts.retry_after, 100 + LOCKOUT_BASE_SECS);
+        assert!(verify_with_attempts(pin_hash, &mut attempts, Some("1234"), 110).is_err());
+        assert_eq!(attempts.failures, FREE_ATTEMPTS);
+
+        // 待った後も失敗すると待ち時間が延びる
+        assert!(verify_with_attempts(pin_hash, &mut attempts, Some("0000"), 200).is_err());
+        assert_eq!(attempts.retry_after, 200 + LOCKOUT_BASE_SECS * 2);
+
+        verify_with_attempts(pin_hash, &mut attempts, Some("1234"), 1000).unwrap();
+        assert_eq!(attempts, PinAttempts::default());
+    }
+}
@@ -0,0 +1,122 @@
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use crate::i18n::t;
+use crate::settings::AppSettings;
+
+/// ファイルの置き換え方
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WriteOptions {
+    /// 置き換える前のファイルの更新日時・アクセス日時を引き継ぐ
+    pub preserve_times: bool,
+}
+
+impl WriteOptions {
+    /// 元のファイルを書き換える際の設定を読み込む
+    pub fn for_edit(app_handle: &AppHandle) -> Self {
+        Self { preserve_times: AppSettings::load(app_handle).unwrap_or_default().file_writes.preserve_timestamps }
+    }
+}
+
+/// 書き込み先と同じフォルダの一時ファイル（同じボリュームでないと置き換えが不可分にならない）
+fn temp_path(dest: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(dest.file_name().unwrap_or_default());
+    name.push(format!(".{}.tmp", std::process::id()));
+    dest.with_file_name(name)
+}
+
+/// 書き込んだ内容をディスクまで書き出す
+fn sync_file(path: &Path) -> Result<(), String> {
+    File::options().write(true).open(path)
+        .and_then(|file| file.sync_all())
+        .map_err(|e| t!("safe_write.sync_failed", path.display(), e))
+}
+
+/// 置き換えたことをフォルダにも記録する（Windowsではフォルダを開けないため省く）
+fn sync_dir(dest: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = dest.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        if let Err(e) = File::open(parent).and_then(|dir| dir.sync_all()) {
+            tracing::warn!("フォルダの書き出しに失敗しました: {} - {}", parent.display(), e);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = dest;
+}
+
+/// 一時ファイルに書き出し、ディスクまで書き出してから書き込み先と置き換える
+///
+/// 途中で異常終了や電源断があっても、書き込み先は元の内容か新しい内容のどちらかになる。
+/// 一時ファイルへの書き込みは`write`に任せる（一時ファイルの拡張子は書き込み先と異なる）。
+/// 既存のファイルを置き換える場合は権限を引き継ぎ、`options`に応じて日時も引き継ぐ
+pub fn write_with(dest: &Path, options: WriteOptions, write: impl FnOnce(&Path) -> Result<(), String>) -> Result<(), String> {
+    let temp = temp_path(dest);
+    let existing = fs::metadata(dest).ok();
+    let result = write(&temp)
+        .and_then(|_| sync_file(&temp))
+        .and_then(|_| match &existing {
+            Some(metadata) => fs::set_permissions(&temp, metadata.permissions())
+                .map_err(|e| t!("safe_write.permissions_failed", dest.display(), e)),
+            None => Ok(()),
+        })
+        .and_then(|_| fs::rename(&temp, dest).map_err(|e| t!("safe_write.replace_failed", dest.display(), e)));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+        return result;
+    }
+    sync_dir(dest);
+
+    if let Some(metadata) = existing.filter(|_| options.preserve_times) {
+        let mut times = FileTimes::new();
+        if let Ok(accessed) = metadata.accessed() {
+            times = times.set_accessed(accessed);
+        }
+        if let Ok(modified) = metadata.modified() {
+            times = times.set_modified(modified);
+        }
+        File::options().write(true).open(dest)
+            .and_then(|file| file.set_times(times))
+            .map_err(|e| t!("safe_write.times_failed", dest.display(), e))?;
+    }
+    Ok(())
+}
+
+/// バイト列を`write_with`と同じ手順で書き込む
+pub fn write_bytes(dest: &Path, contents: &[u8], options: WriteOptions) -> Result<(), String> {
+    write_with(dest, options, |temp| fs::write(temp, contents).map_err(|e| t!("safe_write.write_failed", temp.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_write_replaces_and_preserves_times() {
+        let dir = std::env::temp_dir().join(format!("poir-safe-write-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dest = dir.join("photo.jpg");
+        fs::write(&dest, b"old").unwrap();
+        let past = SystemTime::now() - Duration::from_secs(86_400);
+        File::options().write(true).open(&dest).unwrap().set_modified(past).unwrap();
+
+        write_bytes(&dest, b"new", WriteOptions { preserve_times: true }).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"new");
+        assert_eq!(fs::metadata(&dest).unwrap().modified().unwrap(), past);
+
+        write_bytes(&dest, b"newer", WriteOptions::default()).unwrap();
+        assert!(fs::metadata(&dest).unwrap().modified().unwrap() > past);
+
+        // 書き込みに失敗したら元のファイルはそのままで、一時ファイルも残さない
+        let failed = write_with(&dest, WriteOptions::default(), |temp| {
+            fs::write(temp, b"partial").unwrap();
+            Err("failed".to_string())
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read(&dest).unwrap(), b"newer");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(desktop)]
+use ::image::imageops;
+use ::image::{DynamicImage, RgbaImage};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager};
+#[cfg(desktop)]
+use xcap::{Monitor, Window};
+use crate::audit;
+use crate::clipboard_watch;
+use crate::config::{self, AddPathOutcome, ResourceConfig};
+use crate::i18n::t;
+use crate::launch::{self, LaunchTarget};
+use crate::preview;
+use crate::settings::{AppSettings, ScreenshotSettings};
+
+/// キャプチャする範囲
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum CaptureMode {
+    /// メインのモニター全体
+    Full,
+    /// このアプリ以外で最前面にあるウィンドウ
+    Window,
+    /// 画面上の範囲（仮想画面の座標、画面側で選択する）
+    Region { x: i32, y: i32, width: u32, height: u32 },
+}
+
+/// キャプチャの結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Screenshot {
+    /// 保存した画像のパス
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// 保存先を設定フォルダに追加したか
+    pub folder_added: bool,
+}
+
+/// 保存先のフォルダ
+fn screenshots_dir(app_handle: &AppHandle, settings: &ScreenshotSettings) -> PathBuf {
+    settings.folder.as_ref()
+        .map(PathBuf::from)
+        .or_else(|| app_handle.path().picture_dir().ok().map(|dir| dir.join("poir-viewer")))
+        .unwrap_or_else(|| config::app_data_dir(app_handle).join("screenshots"))
+}
+
+/// モニター上の範囲を、そのモニターの画像内の範囲に直す（はみ出す部分は切り詰める）
+#[cfg(desktop)]
+fn crop_rect(monitor: (i32, i32, u32, u32), region: (i32, i32, u32, u32)) -> Option<(u32, u32, u32, u32)> {
+    let (mx, my, mw, mh) = monitor;
+    let (x, y, width, height) = region;
+    let left = x.max(mx);
+    let top = y.max(my);
+    let right = (x + width as i32).min(mx + mw as i32);
+    let bottom = (y + height as i32).min(my + mh as i32);
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(((left - mx) as u32, (top - my) as u32, (right - left) as u32, (bottom - top) as u32))
+}
+
+#[cfg(desktop)]
+fn capture_image(mode: CaptureMode) -> Result<RgbaImage, String> {
+    let failed = |e: xcap::XCapError| t!("screenshot.capture_failed", e);
+    match mode {
+        CaptureMode::Full => {
+            let monitors = Monitor::all().map_err(failed)?;
+            let monitor = monitors.iter().find(|monitor| monitor.is_primary())
+                .or(monitors.first())
+                .ok_or_else(|| t!("screenshot.no_monitor"))?;
+            monitor.capture_image().map_err(failed)
+        },
+        CaptureMode::Window => {
+            // 一覧は手前のウィンドウから並ぶ
+            let own = std::process::id();
+            let window = Window::all().map_err(failed)?
+                .into_iter()
+                .find(|window| window.pid() != own && !window.is_minimized() && window.width() > 0 && window.height() > 0)
+                .ok_or_else(|| t!("screenshot.no_window"))?;
+            window.capture_image().map_err(failed)
+        },
+        CaptureMode::Region { x, y, width, height } => {
+            let monitor = Monitor::from_point(x, y).map_err(|_| t!("screenshot.region_outside"))?;
+            let bounds = (monitor.x(), monitor.y(), monitor.width(), monitor.height());
+            let (left, top, width, height) = crop_rect(bounds, (x, y, width, height))
+                .ok_or_else(|| t!("screenshot.region_outside"))?;
+            let image = monitor.capture_image().map_err(failed)?;
+            // 高DPIのモニターでは画像がモニターの論理サイズより大きい
+            let scale = image.width() as f32 / bounds.2.max(1) as f32;
+            let scaled = |value: u32| (value as f32 * scale).round() as u32;
+            Ok(imageops::crop_imm(&image, scaled(left), scaled(top), scaled(width), scaled(height)).to_image())
+        },
+    }
+}
+
+/// モバイルでは画面をキャプチャできない
+#[cfg(mobile)]
+fn capture_image(_mode: CaptureMode) -> Result<RgbaImage, String> {
+    Err(t!("screenshot.unsupported"))
+}
+
+/// 保存先が設定フォルダに含まれていなければ追加する
+fn add_to_library(app_handle: &AppHandle, dir: &str) -> Result<bool, String> {
+    let mut config = ResourceConfig::load(app_handle)?;
+    if config.filters.include.iter().any(|include| std::path::Path::new(dir).starts_with(include)) {
+        return Ok(false);
+    }
+    let added = config.add_include_paths(&[dir.to_string()]).remove(0);
+    match added.outcome {
+        AddPathOutcome::Added => config.save(app_handle).map(|_| true),
+        AddPathOutcome::AlreadyAdded => Ok(false),
+        AddPathOutcome::Invalid => Err(added.message.unwrap_or_default()),
+    }
+}
+
+fn capture(app_handle: &AppHandle, mode: CaptureMode) -> Result<Screenshot, String> {
+    let settings = AppSettings::load(app_handle).unwrap_or_default().screenshot;
+    let image = capture_image(mode)?;
+    let (width, height) = image.dimensions();
+
+    let dir = screenshots_dir(app_handle, &settings);
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let dest = clipboard_watch::timestamped_path(&dir, "screenshot", timestamp);
+    preview::save_png(&DynamicImage::ImageRgba8(image), &dest)?;
+    tracing::info!("画面をキャプチャしました: {} ({}x{})", dest.display(), width, height);
+
+    let folder_added = settings.add_to_library && add_to_library(app_handle, &dir.to_string_lossy())?;
+    let path = dest.to_string_lossy().to_string();
+    launch::open_target(app_handle, LaunchTarget::Image { path: path.clone() });
+    Ok(Screenshot { path, width, height, folder_added })
+}
+
+/// 画面をキャプチャして設定のフォルダに保存し、そのままビューアで開く
+///
+/// 保存先が設定フォルダに含まれていなければ（設定で無効にしない限り）追加し、以後の一覧にも表示されるようにする
+#[tauri::command]
+pub async fn capture_screenshot(app_handle: AppHandle, mode: CaptureMode) -> Result<Screenshot, String> {
+    let result = capture(&app_handle, mode);
+    audit::complete(&app_handle, "capture_screenshot", &result);
+    result
+}
+
+#[cfg(all(test, desktop))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crop_rect_clamps_to_monitor() {
+        let monitor = (1920, 0, 1280, 1024);
+        assert_eq!(crop_rect(monitor, (2000, 100, 200, 100)), Some((80, 100, 200, 100)));
+        assert_eq!(crop_rect(monitor, (1800, -50, 300, 200)), Some((0, 0, 180, 150)));
+        assert_eq!(crop_rect(monitor, (0, 0, 100, 100)), None);
+    }
+}
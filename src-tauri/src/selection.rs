@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager, State};
+use crate::event_bridge::{self, Delivery};
+use crate::i18n::t;
+
+/// ID未指定時に使う共有の選択
+pub const DEFAULT_SELECTION: &str = "default";
+
+/// 選択中の画像（選択した順）
+#[derive(Debug, Default, Clone)]
+struct Selection {
+    paths: Vec<String>,
+    members: HashSet<String>,
+}
+
+impl Selection {
+    fn add(&mut self, paths: Vec<String>) {
+        for path in paths {
+            if self.members.insert(path.clone()) {
+                self.paths.push(path);
+            }
+        }
+    }
+
+    fn remove(&mut self, paths: &[String]) {
+        for path in paths {
+            self.members.remove(path);
+        }
+        self.paths.retain(|path| self.members.contains(path));
+    }
+}
+
+/// `selection-changed`で通知する内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SelectionChanged {
+    pub selection_id: String,
+    /// 選択中の画像数
+    pub count: usize,
+}
+
+/// 全ウィンドウで共有する選択を保持するステート
+#[derive(Default)]
+pub struct SelectionState(Mutex<HashMap<String, Selection>>);
+
+impl SelectionState {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, Selection>>, String> {
+        self.0.lock().map_err(|e| t!("common.lock_failed", t!("selection.name"), e))
+    }
+}
+
+fn selection_key(selection_id: Option<String>) -> String {
+    selection_id.unwrap_or_else(|| DEFAULT_SELECTION.to_string())
+}
+
+/// 選択の変更を全ウィンドウに通知する（短時間の変更は配列にまとめて届く）
+fn notify(app_handle: &AppHandle, selection_id: &str, count: usize) {
+    event_bridge::emit(app_handle, "selection-changed", Delivery::Batch, SelectionChanged {
+        selection_id: selection_id.to_string(),
+        count,
+    });
+}
+
+/// 一括処理のために選択中の画像のパスを取得する
+pub fn paths(app_handle: &AppHandle, selection_id: Option<String>) -> Result<Vec<String>, String> {
+    let key = selection_key(selection_id);
+    let state = app_handle.try_state::<SelectionState>().ok_or_else(|| t!("selection.empty", key))?;
+    let paths = state.lock()?.get(&key).map(|selection| selection.paths.clone()).unwrap_or_default();
+    if paths.is_empty() {
+        return Err(t!("selection.empty", key));
+    }
+    Ok(paths)
+}
+
+/// 一括処理で削除された画像を選択から外す
+pub fn forget(app_handle: &AppHandle, paths: &[String]) {
+    let Some(state) = app_handle.try_state::<SelectionState>() else { return };
+    let Ok(mut selections) = state.lock() else { return };
+    for (selection_id, selection) in selections.iter_mut() {
+        let before = selection.paths.len();
+        selection.remove(paths);
+        if selection.paths.len() != before {
+            notify(app_handle, selection_id, selection.paths.len());
+        }
+    }
+}
+
+/// 画像を選択に追加し、選択中の画像数を返す
+#[tauri::command]
+pub fn add_to_selection(
+    app_handle: AppHandle,
+    state: State<'_, SelectionState>,
+    selection_id: Option<String>,
+    paths: Vec<String>,
+) -> Result<usize, String> {
+    let key = selection_key(selection_id);
+    let mut selections = state.lock()?;
+    let selection = selections.entry(key.clone()).or_default();
+    selection.add(paths);
+    notify(&app_handle, &key, selection.paths.len());
+    Ok(selection.paths.len())
+}
+
+/// 画像を選択から外し、選択中の画像数を返す
+#[tauri::command]
+pub fn remove_from_selection(
+    app_handle: AppHandle,
+    state: State<'_, SelectionState>,
+    selection_id: Option<String>,
+    paths: Vec<String>,
+) -> Result<usize, String> {
+    let key = selection_key(selection_id);
+    let mut selections = state.lock()?;
+    let count = match selections.get_mut(&key) {
+        Some(selection) => {
+            selection.remove(&paths);
+            selection.paths.len()
+        },
+        None => 0,
+    };
+    notify(&app_handle, &key, count);
+    Ok(count)
+}
+
+/// 選択を解除する
+#[tauri::command]
+pub fn clear_selection(
+    app_handle: AppHandle,
+    state: State<'_, SelectionState>,
+    selection_id: Option<String>,
+) -> Result<(), String> {
+    let key = selection_key(selection_id);
+    state.lock()?.remove(&key);
+    notify(&app_handle, &key, 0);
+    Ok(())
+}
+
+/// 選択中の画像のパスを選択した順で取得する
+#[tauri::command]
+pub fn get_selection(state: State<'_, SelectionState>, selection_id: Option<String>) -> Result<Vec<String>, String> {
+    let key = selection_key(selection_id);
+    Ok(state.lock()?.get(&key).map(|selection| selection.paths.clone()).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_keeps_order_without_duplicates() {
+        let mut selection = Selection::default();
+        selection.add(vec!["/b.jpg".to_string(), "/a.jpg".to_string()]);
+        selection.add(vec!["/a.jpg".to_string(), "/c.jpg".to_string()]);
+        assert_eq!(selection.paths, vec!["/b.jpg", "/a.jpg", "/c.jpg"]);
+
+        selection.remove(&["/a.jpg".to_string()]);
+        assert_eq!(selection.paths, vec!["/b.jpg", "/c.jpg"]);
+        assert!(!selection.members.contains("/a.jpg"));
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::sync::atomic::{AtomicBool, Ordering};
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Manager, State};
+use crate::audit;
+use crate::i18n::t;
+use crate::image::ImageInfo;
+use crate::index::LibraryIndex;
+use crate::settings::{AppSettings, SensitiveSettings};
+
+/// モデルを指定しなかったときのファイル名（アプリデータの`models`内）
+#[cfg_attr(not(feature = "ml"), allow(dead_code))]
+const DEFAULT_MODEL: &str = "nsfw.onnx";
+
+/// 判定中か（スキャンのたびに重ねて開始しないため）
+static INDEXING: AtomicBool = AtomicBool::new(false);
+
+/// 表示を許可した画像（アプリを終了するまで有効）
+#[derive(Default)]
+pub struct SensitiveState {
+    revealed: Mutex<HashSet<String>>,
+}
+
+/// 判定結果の保存先（インデックスと同じSQLiteファイル）
+pub struct SensitiveStore {
+    conn: Connection,
+}
+
+impl SensitiveStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("sensitive.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する（`manual`は手動で指定した結果で、再判定で上書きしない）
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS sensitive_images (
+                path TEXT PRIMARY KEY,
+                score REAL NOT NULL,
+                sensitive INTEGER NOT NULL,
+                manual INTEGER NOT NULL DEFAULT 0
+            );"
+        ).map_err(|e| t!("sensitive.init_failed", e))
+    }
+
+    /// モデルの判定結果を保存する（手動で指定した画像はそのまま）
+    #[cfg_attr(not(feature = "ml"), allow(dead_code))]
+    pub fn set_score(&self, path: &str, score: f32, sensitive: bool) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT INTO sensitive_images (path, score, sensitive) VALUES (?1, ?2, ?3)
+             ON CONFLICT(path) DO UPDATE SET score = excluded.score, sensitive = excluded.sensitive WHERE manual = 0",
+            params![path, score as f64, sensitive],
+        ).map(|_| ()).map_err(|e| t!("sensitive.save_failed", path, e))
+    }
+
+    /// 手動で判定を指定する（誤判定の修正など）
+    pub fn set_manual(&self, path: &str, sensitive: bool) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT INTO sensitive_images (path, score, sensitive, manual) VALUES (?1, 0, ?2, 1)
+             ON CONFLICT(path) DO UPDATE SET sensitive = excluded.sensitive, manual = 1",
+            params![path, sensitive],
+        ).map(|_| ()).map_err(|e| t!("sensitive.save_failed", path, e))
+    }
+
+    fn query_paths(&self, sql: &str) -> Result<HashSet<String>, String> {
+        let mut stmt = self.conn.prepare(sql)
+            .map_err(|e| t!("sensitive.read_failed", e))?;
+        stmt.query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<HashSet<_>, _>>())
+            .map_err(|e| t!("sensitive.read_failed", e))
+    }
+
+    /// 刺激の強い画像のパス
+    pub fn flagged(&self) -> Result<HashSet<String>, String> {
+        self.query_paths("SELECT path FROM sensitive_images WHERE sensitive = 1")
+    }
+
+    /// 判定済みの画像のパス
+    #[cfg_attr(not(feature = "ml"), allow(dead_code))]
+    pub fn judged(&self) -> Result<HashSet<String>, String> {
+        self.query_paths("SELECT path FROM sensitive_images")
+    }
+}
+
+/// 判定用のラベルの確率の合計（刺激の強さ）
+#[cfg_attr(not(feature = "ml"), allow(dead_code))]
+fn sensitive_score(scores: &[f32], labels: &[String], sensitive_labels: &[String]) -> f32 {
+    crate::concepts::probabilities(scores)
+        .iter()
+        .zip(labels)
+        .filter(|(_, label)| sensitive_labels.iter().any(|s| s.eq_ignore_ascii_case(label)))
+        .map(|(probability, _)| probability)
+        .sum()
+}
+
+/// 刺激の強い画像のパス（読み込めなければ空）
+fn flagged_paths(app_handle: &AppHandle) -> HashSet<String> {
+    SensitiveStore::open(app_handle)
+        .and_then(|store| store.flagged())
+        .map_err(|e| tracing::warn!("判定結果を読み込めませんでした: {}", e))
+        .unwrap_or_default()
+}
+
+/// 一覧の画像に判定結果（`sensitive`）を設定する
+pub fn mark(app_handle: &AppHandle, images: Vec<ImageInfo>) -> Vec<ImageInfo> {
+    let flagged = flagged_paths(app_handle);
+    if flagged.is_empty() {
+        return images;
+    }
+    images.into_iter()
+        .map(|image| ImageInfo { sensitive: flagged.contains(&image.path), ..image })
+        .collect()
+}
+
+/// プレビューをぼかすべきか（刺激の強い画像で、まだ表示を許可していない）
+pub fn should_blur(app_handle: &AppHandle, path: &Path) -> bool {
+    if !AppSettings::load(app_handle).unwrap_or_default().sensitive.blur_previews {
+        return false;
+    }
+    let path = path.to_string_lossy().to_string();
+    let revealed = app_handle.try_state::<SensitiveState>()
+        .is_some_and(|state| state.revealed.lock().unwrap_or_else(PoisonError::into_inner).contains(&path));
+    !revealed && flagged_paths(app_handle).contains(&path)
+}
+
+/// インデックス内の未判定の画像を判定して保存する
+#[cfg(feature = "ml")]
+fn index_pending(app_handle: &AppHandle, settings: &SensitiveSettings) -> Result<usize, String> {
+    use crate::concepts::{self, model};
+
+    let (model_path, labels_path) = concepts::model_paths(
+        app_handle, settings.model_path.as_deref(), settings.labels_path.as_deref(), DEFAULT_MODEL,
+    );
+    if !model_path.is_file() {
+        return Err(t!("concepts.model_not_found", model_path.display()));
+    }
+    let labels = model::load_labels(&labels_path)?;
+    let classifier = model::Classifier::load(&model_path, settings.input_size)?;
+
+    let store = SensitiveStore::open(app_handle)?;
+    let judged = store.judged()?;
+    let pending: Vec<ImageInfo> = LibraryIndex::open(app_handle)?
+        .all_images()?
+        .into_iter()
+        .filter(|image| !judged.contains(&image.path))
+        .collect();
+
+    let limits = AppSettings::load(app_handle).unwrap_or_default().decode_limits;
+    let mut count = 0;
+    for image in pending {
+        let result = crate::preview::open_image(app_handle, Path::new(&image.path), &limits)
+            .and_then(|decoded| classifier.scores(&decoded));
+        match result {
+            Ok(scores) => {
+                let score = sensitive_score(&scores, &labels, &settings.sensitive_labels);
+                store.set_score(&image.path, score, score >= settings.threshold)?;
+                count += 1;
+            },
+            Err(e) => tracing::warn!("画像を判定できませんでした: {} - {}", image.path, e),
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(not(feature = "ml"))]
+fn index_pending(_app_handle: &AppHandle, _settings: &SensitiveSettings) -> Result<usize, String> {
+    Err(t!("concepts.unavailable"))
+}
+
+/// 設定で有効なら、未判定の画像の判定をバックグラウンドで開始する（スキャン後に呼ぶ）
+pub fn index_in_background(app_handle: &AppHandle) {
+    let settings = AppSettings::load(app_handle).unwrap_or_default().sensitive;
+    if !settings.enabled || INDEXING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let handle = app_handle.clone();
+    std::thread::spawn(move || {
+        match index_pending(&handle, &settings) {
+            Ok(count) => tracing::info!("刺激の強い画像の判定を行いました: {}枚", count),
+            Err(e) => tracing::warn!("刺激の強い画像の判定を行えませんでした: {}", e),
+        }
+        INDEXING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// 刺激の強い画像の表示を許可する（以後プレビューをぼかさない、アプリの終了まで有効）
+#[tauri::command]
+pub fn reveal_sensitive_image(state: State<'_, SensitiveState>, path: String) {
+    state.revealed.lock().unwrap_or_else(PoisonError::into_inner).insert(path);
+}
+
+/// 画像が刺激の強い画像かを手動で指定する（判定の誤りを直す）
+#[tauri::command]
+pub async fn set_image_sensitive(app_handle: AppHandle, path: String, sensitive: bool) -> Result<(), String> {
+    let result = SensitiveStore::open(&app_handle).and_then(|store| store.set_manual(&path, sensitive));
+    audit::complete(&app_handle, "set_image_sensitive", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_flag_survives_rescoring() {
+        let path = std::env::temp_dir().join(format!("poir-sensitive-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let store = SensitiveStore::open_at(&path).unwrap();
+
+        store.set_score("/a.jpg", 0.9, true).unwrap();
+        store.set_score("/b.jpg", 0.1, false).unwrap();
+        store.set_manual("/a.jpg", false).unwrap();
+        store.set_score("/a.jpg", 0.95, true).unwrap();
+        store.set_score("/b.jpg", 0.8, true).unwrap();
+
+        assert_eq!(store.flagged().unwrap(), HashSet::from(["/b.jpg".to_string()]));
+        assert_eq!(store.judged().unwrap().len(), 2);
+
+        let labels: Vec<String> = ["drawings", "hentai", "neutral", "porn", "sexy"].iter().map(|s| s.to_string()).collect();
+        let score = sensitive_score(&[0.1, 0.2, 0.3, 0.3, 0.1], &labels, &SensitiveSettings::default().sensitive_labels);
+        assert!((score - 0.6).abs() < 1e-6);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager, State};
+use crate::config;
+use crate::i18n::t;
+
+/// 保存するセッションの内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionFile {
+    /// 保存日時（Unix時間）
+    pub saved_at: u64,
+    /// フロントエンドのUI状態（フィルター・開いているフォルダ・スクロール位置・選択中の画像等）
+    pub state: serde_json::Value,
+}
+
+/// 未保存のUI状態を保持するステート（ウィンドウを閉じる際に保存する）
+#[derive(Default)]
+pub struct SessionState(Mutex<Option<serde_json::Value>>);
+
+/// 保存先のパスを取得する
+fn get_session_path(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("session.json")
+}
+
+/// UI状態をファイルに書き込む
+fn write_session(app_handle: &AppHandle, state: serde_json::Value) -> Result<(), String> {
+    let saved_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+
+    let path = get_session_path(app_handle);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&SessionFile { saved_at, state })
+        .map_err(|e| t!("session.serialize_failed", e))?;
+    fs::write(&path, json)
+        .map_err(|e| t!("session.save_failed", e))
+}
+
+/// 未保存のUI状態があれば保存する（ウィンドウを閉じる際に呼ばれる）
+pub fn autosave(app_handle: &AppHandle) {
+    let Some(session) = app_handle.try_state::<SessionState>() else { return };
+    let pending = session.0.lock().ok().and_then(|mut pending| pending.take());
+    if let Some(state) = pending {
+        match write_session(app_handle, state) {
+            Ok(()) => tracing::info!("セッションを自動保存しました"),
+            Err(e) => tracing::warn!("セッションの自動保存に失敗しました: {}", e),
+        }
+    }
+}
+
+/// UI状態をすぐに保存する
+#[tauri::command]
+pub async fn save_session(
+    app_handle: AppHandle,
+    session: State<'_, SessionState>,
+    state_json: serde_json::Value,
+) -> Result<(), String> {
+    write_session(&app_handle, state_json)?;
+    if let Ok(mut pending) = session.0.lock() {
+        *pending = None;
+    }
+    Ok(())
+}
+
+/// UI状態を更新する（ファイルにはウィンドウを閉じる際に保存する）
+///
+/// スクロール位置のように頻繁に変わる状態はこちらで通知する
+#[tauri::command]
+pub fn update_session(session: State<'_, SessionState>, state_json: serde_json::Value) -> Result<(), String> {
+    *session.0.lock().map_err(|e| t!("common.lock_failed", t!("session.name"), e))? = Some(state_json);
+    Ok(())
+}
+
+/// 保存されたUI状態を読み込む（未保存・読み込めない場合はNone）
+#[tauri::command]
+pub async fn load_session(app_handle: AppHandle) -> Result<Option<serde_json::Value>, String> {
+    let path = get_session_path(&app_handle);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| t!("session.read_failed", e))?;
+    match serde_json::from_str::<SessionFile>(&content) {
+        Ok(session) => Ok(Some(session.state)),
+        Err(e) => {
+            // 壊れたセッションでは起動を妨げず、新しい状態から始める
+            tracing::warn!("セッションを解析できませんでした: {}", e);
+            Ok(None)
+        },
+    }
+}
@@ -0,0 +1,589 @@
+use std::fs;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use crate::audit;
+use crate::collation::Collation;
+use crate::config;
+use crate::i18n::t;
+use crate::maintenance::MaintenanceTask;
+use crate::originals::OriginalsStore;
+use crate::power::PowerPolicy;
+use crate::tone_map::ToneMapOperator;
+use crate::watchdog::Operation;
+
+/// アプリ全体の設定（画像フォルダの設定はresources.jsonで別管理）
+///
+/// 項目を追加しても既存のファイルを読めるよう、すべて`#[serde(default)]`とする
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AppSettings {
+    /// 起動時に更新を確認する
+    pub check_updates_on_startup: bool,
+    /// 無操作時のスクリーンセーバー
+    pub screensaver: ScreensaverSettings,
+    /// グローバルショートカット
+    pub global_shortcut: GlobalShortcutSettings,
+    /// 一覧系コマンドの応答サイズの上限
+    pub list_limits: ListLimits,
+    /// パスを受け取るコマンドの読み取り範囲
+    pub path_guard: PathGuardSettings,
+    /// ファイルシステムを多く読むコマンドの時間制限
+    pub timeouts: CommandTimeouts,
+    /// 画面へ送るイベントのまとめ方
+    pub events: EventSettings,
+    /// 画像の展開に使うメモリの上限
+    pub decode_limits: DecodeLimits,
+    /// 書庫（zip・cbz）の展開の上限
+    pub archive_limits: ArchiveLimits,
+    /// 縮小画像・プレビューの作成
+    pub preview: PreviewSettings,
+    /// 画像内の文字の読み取り（OCR）
+    pub ocr: OcrSettings,
+    /// 端末内の画像分類による自動タグ付け
+    pub classifier: ClassifierSettings,
+    /// 刺激の強い画像の判定とプレビューのぼかし
+    pub sensitive: SensitiveSettings,
+    /// QRコード・バーコードの読み取り
+    pub barcode: BarcodeSettings,
+    /// 超解像モデルによる拡大
+    pub upscale: UpscaleSettings,
+    /// 動画からのフレームの取り出し
+    pub video: VideoSettings,
+    /// 定期的なメンテナンス
+    pub maintenance: MaintenanceSettings,
+    /// 共有フォルダを使ったタグ・レーティング・アルバムの同期
+    pub sync: SyncSettings,
+    /// バッテリー駆動中のバックグラウンド処理
+    pub power: PowerSettings,
+    /// クリップボードにコピーされた画像の保存
+    pub clipboard_capture: ClipboardCaptureSettings,
+    /// 画面のキャプチャ
+    pub screenshot: ScreenshotSettings,
+    /// 元の画像を書き換える際の動作
+    pub file_writes: FileWriteSettings,
+    /// ファイル名の並べ方
+    pub sorting: SortSettings,
+}
+
+/// 定期的なメンテナンス（操作がない間にバックグラウンドで行う）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MaintenanceSettings {
+    pub enabled: bool,
+    /// この時間（分）操作がなければ実行する（0でいつでも）
+    pub idle_minutes: u64,
+    pub tasks: Vec<ScheduledTask>,
+    /// キャッシュ全体のサイズの上限（バイト、超えた分は古いものから削除する）
+    pub max_cache_bytes: u64,
+    /// 事前に作成する縮小画像の大きさ
+    pub thumbnail_size: u32,
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        let task = |task, interval_hours, enabled| ScheduledTask { task, interval_hours, enabled };
+        Self {
+            enabled: true,
+            idle_minutes: 5,
+            tasks: vec![
+                task(MaintenanceTask::Rescan, 24, true),
+                task(MaintenanceTask::Thumbnails, 24, false),
+                task(MaintenanceTask::CacheEviction, 24, true),
+                task(MaintenanceTask::Vacuum, 24 * 7, true),
+            ],
+            max_cache_bytes: 2 * 1024 * 1024 * 1024,
+            thumbnail_size: 256,
+        }
+    }
+}
+
+/// メンテナンス作業ごとの実行間隔
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScheduledTask {
+    pub task: MaintenanceTask,
+    /// 実行間隔（時間）
+    pub interval_hours: u64,
+    pub enabled: bool,
+}
+
+/// バッテリー駆動中のバックグラウンド処理（縮小画像の作成・監視・インデックス作成・ジョブ）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PowerSettings {
+    pub policy: PowerPolicy,
+}
+
+/// クリップボードにコピーされた画像を保存する（スクリーンショット・切り抜きの収集用）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ClipboardCaptureSettings {
+    pub enabled: bool,
+    /// 保存先（省略するとアプリデータの`clips`）
+    pub folder: Option<String>,
+    /// クリップボードを確認する間隔（ミリ秒）
+    pub interval_ms: u64,
+}
+
+impl Default for ClipboardCaptureSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            folder: None,
+            interval_ms: 1000,
+        }
+    }
+}
+
+/// 画面のキャプチャの保存先
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScreenshotSettings {
+    /// 保存先（省略すると「ピクチャ」の`poir-viewer`）
+    pub folder: Option<String>,
+    /// 保存先が設定フォルダに含まれていなければ追加する
+    pub add_to_library: bool,
+}
+
+impl Default for ScreenshotSettings {
+    fn default() -> Self {
+        Self {
+            folder: None,
+            add_to_library: true,
+        }
+    }
+}
+
+/// 元の画像を書き換える際の動作（回転・切り抜き・EXIFの編集）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct FileWriteSettings {
+    /// 書き換えても更新日時を元のままにする（日付順の並びを変えないため）
+    pub preserve_timestamps: bool,
+    /// 書き換える前の元のファイルの残し先（`restore_original`で戻せる）
+    pub keep_originals: OriginalsStore,
+}
+
+/// ファイル名の並べ方（一覧・ナビゲーションで名前順を指定した場合）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SortSettings {
+    /// 文字の比べ方（日本語と英語の名前が混ざる場合は`japanese`か`romaji`）
+    pub collation: Collation,
+}
+
+/// 共有フォルダを使った同期（起動時に他の端末の変更を取り込む）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SyncSettings {
+    pub enabled: bool,
+    /// DropboxやSyncthingなどで同期されるフォルダ
+    pub folder: Option<String>,
+    /// 端末ごとにパスが異なる共有フォルダの対応
+    pub roots: Vec<SyncRoot>,
+}
+
+/// この端末でのパスと、端末間で共通の名前の対応（例: `Z:\photos`と`nas-photos`）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SyncRoot {
+    pub local: String,
+    pub shared: String,
+}
+
+/// 超解像モデルによる拡大（`ml`フィーチャーを有効にしてビルドした場合のみ動作する）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct UpscaleSettings {
+    /// ONNX形式の超解像モデル（ESRGAN系、省略するとアプリデータの`models/upscale.onnx`）
+    pub model_path: Option<String>,
+    /// モデルの拡大率
+    pub model_scale: u32,
+    /// 一度にモデルへ入力する区画の大きさ（大きいほど速いがメモリを使う）
+    pub tile_size: u32,
+    /// 区画の継ぎ目が目立たないよう周囲に重ねる幅
+    pub tile_overlap: u32,
+}
+
+impl Default for UpscaleSettings {
+    fn default() -> Self {
+        Self {
+            model_path: None,
+            model_scale: 4,
+            tile_size: 128,
+            tile_overlap: 8,
+        }
+    }
+}
+
+/// QRコード・バーコードの読み取り（ZBarの`zbarimg`コマンドを使う）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct BarcodeSettings {
+    /// `zbarimg`の実行ファイル（省略するとPATHから探す）
+    pub zbarimg_path: Option<String>,
+}
+
+/// 動画からのフレームの取り出し（FFmpegの`ffmpeg`コマンドを使う）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct VideoSettings {
+    /// `ffmpeg`の実行ファイル（省略するとPATHから探す）
+    pub ffmpeg_path: Option<String>,
+}
+
+/// 刺激の強い画像の判定（`ml`フィーチャーを有効にしてビルドした場合のみ判定する）とプレビューのぼかし
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SensitiveSettings {
+    /// スキャン後に未判定の画像を判定する
+    pub enabled: bool,
+    /// ONNX形式の判定モデル（省略するとアプリデータの`models/nsfw.onnx`）
+    pub model_path: Option<String>,
+    /// ラベルの一覧（省略するとモデルと同じ場所の`.labels.txt`）
+    pub labels_path: Option<String>,
+    /// モデルに入力する画像の大きさ
+    pub input_size: u32,
+    /// 刺激の強い画像として扱うラベル
+    pub sensitive_labels: Vec<String>,
+    /// `sensitive_labels`の確率の合計がこの値以上なら刺激の強い画像とする
+    pub threshold: f32,
+    /// 表示を許可するまでプレビューをぼかす
+    pub blur_previews: bool,
+}
+
+impl Default for SensitiveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model_path: None,
+            labels_path: None,
+            input_size: 224,
+            sensitive_labels: vec!["porn".to_string(), "hentai".to_string(), "sexy".to_string()],
+            threshold: 0.7,
+            blur_previews: true,
+        }
+    }
+}
+
+/// 端末内の画像分類による自動タグ付け（`ml`フィーチャーを有効にしてビルドした場合のみ動作する）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ClassifierSettings {
+    /// スキャン後に未分類の画像を分類する
+    pub enabled: bool,
+    /// ONNX形式の画像分類モデル（省略するとアプリデータの`models/classifier.onnx`）
+    pub model_path: Option<String>,
+    /// ラベルの一覧（1行に1クラス、`dog, puppy`のように別名をカンマで並べられる。省略するとモデルと同じ場所の`.labels.txt`）
+    pub labels_path: Option<String>,
+    /// モデルに入力する画像の大きさ
+    pub input_size: u32,
+    /// タグにする最低の確率
+    pub min_score: f32,
+    /// 1枚あたりのタグの最大数
+    pub max_tags: usize,
+}
+
+impl Default for ClassifierSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model_path: None,
+            labels_path: None,
+            input_size: 224,
+            min_score: 0.2,
+            max_tags: 5,
+        }
+    }
+}
+
+/// 画像内の文字の読み取り（OCR、Tesseractのコマンドを使う）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct OcrSettings {
+    /// Tesseractの実行ファイル（省略するとPATHから探す）
+    pub tesseract_path: Option<String>,
+    /// 言語を指定しなかったときに使う言語（Tesseractの言語コード、`jpn+eng`のように複数指定できる）
+    pub default_lang: String,
+    /// 読み取った文字をインデックスに保存して検索できるようにする
+    pub index_text: bool,
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            tesseract_path: None,
+            default_lang: "eng".to_string(),
+            index_text: true,
+        }
+    }
+}
+
+/// 縮小画像・プレビューの作成
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PreviewSettings {
+    /// EXIFの向きに従って正立させる
+    pub auto_rotate: bool,
+    /// HDRの画像の縮小画像・プレビューに使うトーンマッピングの方式
+    pub tone_mapping: ToneMapOperator,
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self { auto_rotate: true, tone_mapping: ToneMapOperator::default() }
+    }
+}
+
+/// 書庫（zip・cbz）の展開の上限（展開キャッシュでディスクを使い果たすのを防ぐ）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ArchiveLimits {
+    /// 書庫内のエントリ数
+    pub max_entries: usize,
+    /// 展開後の合計サイズ（バイト）
+    pub max_total_bytes: u64,
+    /// 1エントリの展開後のサイズ（バイト）
+    pub max_entry_bytes: u64,
+    /// 1エントリの圧縮率（展開後サイズ / 圧縮サイズ）
+    pub max_ratio: u64,
+    /// 書庫内のフォルダの深さ
+    pub max_depth: usize,
+    /// 展開キャッシュ全体のサイズ（バイト、超えた分は古いものから削除する）
+    pub max_cache_bytes: u64,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_total_bytes: 4 * 1024 * 1024 * 1024,
+            max_entry_bytes: 256 * 1024 * 1024,
+            max_ratio: 100,
+            max_depth: 8,
+            max_cache_bytes: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// 画像の展開に使うメモリの上限（巨大な画像でプロセスが落ちるのを防ぐ）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DecodeLimits {
+    /// 1枚の展開に使う最大バイト数（0で無制限）
+    pub max_decode_bytes: u64,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self { max_decode_bytes: 512 * 1024 * 1024 }
+    }
+}
+
+/// 画面へ送るイベントのまとめ方
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EventSettings {
+    /// 最初のイベントから送信までに待つ時間（ミリ秒、この間のイベントをまとめる）
+    pub flush_interval_ms: u64,
+    /// 1回に配列にまとめて送る最大件数
+    pub max_batch_size: usize,
+}
+
+impl Default for EventSettings {
+    fn default() -> Self {
+        Self { flush_interval_ms: 100, max_batch_size: 500 }
+    }
+}
+
+/// ファイルシステムを多く読むコマンドの時間制限（秒、0で無制限）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CommandTimeouts {
+    pub scan_secs: u64,
+    pub metadata_secs: u64,
+    pub checksum_secs: u64,
+    pub ocr_secs: u64,
+}
+
+impl Default for CommandTimeouts {
+    fn default() -> Self {
+        Self {
+            scan_secs: 120,
+            metadata_secs: 20,
+            checksum_secs: 120,
+            ocr_secs: 60,
+        }
+    }
+}
+
+impl CommandTimeouts {
+    /// 処理の種類ごとの時間制限を取得する
+    pub fn seconds(&self, operation: Operation) -> u64 {
+        match operation {
+            Operation::Scan => self.scan_secs,
+            Operation::Metadata => self.metadata_secs,
+            Operation::Checksum => self.checksum_secs,
+            Operation::Ocr => self.ocr_secs,
+        }
+    }
+}
+
+/// パスを受け取るコマンドの読み取り範囲
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PathGuardSettings {
+    /// 設定フォルダ・アプリデータ・起動時に開いた対象の外を拒否する
+    pub restrict_to_library: bool,
+}
+
+impl Default for PathGuardSettings {
+    fn default() -> Self {
+        Self { restrict_to_library: true }
+    }
+}
+
+/// 一覧系コマンドの応答サイズの上限（巨大なJSONでWebViewが固まるのを防ぐ）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ListLimits {
+    /// 1ページあたりの最大件数
+    pub max_items_per_page: usize,
+    /// 1回の応答に含める画像一覧の最大サイズ（JSONのバイト数）
+    pub max_payload_bytes: usize,
+}
+
+impl Default for ListLimits {
+    fn default() -> Self {
+        Self {
+            max_items_per_page: 1000,
+            max_payload_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// グローバルショートカットで行う操作
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    /// ビューアの表示・非表示を切り替える
+    #[default]
+    ToggleWindow,
+    /// スクリーンセーバーのスライドショーを開始する
+    Screensaver,
+}
+
+/// グローバルショートカットの設定
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct GlobalShortcutSettings {
+    /// キーの組み合わせ（例: `CommandOrControl+Shift+V`）。Noneは無効
+    pub accelerator: Option<String>,
+    /// 押されたときの操作
+    pub action: ShortcutAction,
+}
+
+/// スクリーンセーバーの設定
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScreensaverSettings {
+    /// 有効にする
+    pub enabled: bool,
+    /// 開始までの無操作時間（分）
+    pub idle_minutes: u64,
+    /// 表示するアルバム（未指定ならライブラリ全体）
+    pub album: Option<String>,
+    /// 切り替え間隔（ミリ秒）
+    pub interval_ms: u64,
+}
+
+impl Default for ScreensaverSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_minutes: 10,
+            album: None,
+            interval_ms: 10_000,
+        }
+    }
+}
+
+impl AppSettings {
+    /// 設定ファイルのパスを取得
+    pub fn get_settings_path(app_handle: &AppHandle) -> PathBuf {
+        config::app_data_dir(app_handle).join("settings.json")
+    }
+
+    /// 設定を読み込む（ファイルがなければ既定値）
+    pub fn load(app_handle: &AppHandle) -> Result<Self, String> {
+        let path = Self::get_settings_path(app_handle);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| t!("settings.read_failed", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| t!("settings.parse_failed", e))
+    }
+
+    /// 設定を保存する
+    pub fn save(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let path = Self::get_settings_path(app_handle);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| t!("settings.serialize_failed", e))?;
+        fs::write(&path, json)
+            .map_err(|e| t!("settings.save_failed", e))
+    }
+}
+
+/// アプリ設定を取得する
+#[tauri::command]
+pub fn load_app_settings(app_handle: AppHandle) -> Result<AppSettings, String> {
+    AppSettings::load(&app_handle)
+}
+
+/// 読み取り範囲の制限と外部コマンドの実行ファイルのうち、変更される項目
+fn protected_changes(current: &AppSettings, new: &AppSettings) -> Vec<String> {
+    let mut changes = Vec::new();
+    if new.path_guard.restrict_to_library != current.path_guard.restrict_to_library {
+        changes.push(t!("settings.protected_restrict_to_library", new.path_guard.restrict_to_library));
+    }
+    let executables = [
+        ("tesseract", &current.ocr.tesseract_path, &new.ocr.tesseract_path),
+        ("zbarimg", &current.barcode.zbarimg_path, &new.barcode.zbarimg_path),
+        ("ffmpeg", &current.video.ffmpeg_path, &new.video.ffmpeg_path),
+    ];
+    for (name, current, new) in executables {
+        if current != new {
+            changes.push(t!("settings.protected_executable", name, new.as_deref().unwrap_or("PATH")));
+        }
+    }
+    changes
+}
+
+/// アプリ設定を保存する
+///
+/// 読み取り範囲の制限や外部コマンドの実行ファイルを変更する場合は、OSの確認ダイアログで承認されたときだけ保存する
+#[tauri::command]
+pub async fn save_app_settings(app_handle: AppHandle, settings: AppSettings) -> Result<(), String> {
+    let changes = protected_changes(&AppSettings::load(&app_handle).unwrap_or_default(), &settings);
+    let confirmed = changes.is_empty() || app_handle.dialog()
+        .message(t!("settings.confirm_protected", changes.join("\n")))
+        .title(t!("settings.confirm_protected_title"))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancel)
+        .blocking_show();
+    let result = if confirmed {
+        settings.save(&app_handle)
+    } else {
+        Err(t!("settings.protected_declined"))
+    };
+    audit::complete(&app_handle, "save_app_settings", &result);
+    result
+}
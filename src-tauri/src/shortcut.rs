@@ -0,0 +1,130 @@
+use tauri::AppHandle;
+#[cfg(desktop)]
+use tauri::plugin::TauriPlugin;
+#[cfg(desktop)]
+use tauri::{Manager, Wry};
+#[cfg(desktop)]
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use crate::audit;
+use crate::i18n::t;
+#[cfg(desktop)]
+use crate::idle;
+#[cfg(desktop)]
+use crate::settings::AppSettings;
+use crate::settings::ShortcutAction;
+
+/// キーの組み合わせ文字列を解析する
+#[cfg(desktop)]
+fn parse_accelerator(accelerator: &str) -> Result<Shortcut, String> {
+    accelerator.parse::<Shortcut>()
+        .map_err(|e| t!("shortcut.invalid", accelerator, e))
+}
+
+/// メインウィンドウの表示・非表示を切り替える
+#[cfg(desktop)]
+fn toggle_main_window(app_handle: &AppHandle) {
+    let Some(window) = app_handle.get_webview_window("main") else { return };
+    let visible = window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+    if visible && window.is_focused().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// ショートカットが押されたときの処理
+#[cfg(desktop)]
+fn handle_pressed(app_handle: &AppHandle) {
+    let settings = AppSettings::load(app_handle).unwrap_or_default();
+    match settings.global_shortcut.action {
+        ShortcutAction::ToggleWindow => toggle_main_window(app_handle),
+        ShortcutAction::Screensaver => idle::start_screensaver(app_handle, &settings.screensaver),
+    }
+}
+
+/// グローバルショートカットのプラグインを作成する
+#[cfg(desktop)]
+pub fn init_plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app_handle, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                handle_pressed(app_handle);
+            }
+        })
+        .build()
+}
+
+/// 設定に保存されたショートカットを登録する
+#[cfg(desktop)]
+pub fn register_saved(app_handle: &AppHandle) {
+    let settings = AppSettings::load(app_handle).unwrap_or_default();
+    let Some(accelerator) = settings.global_shortcut.accelerator else { return };
+
+    let result = parse_accelerator(&accelerator)
+        .and_then(|shortcut| app_handle.global_shortcut().register(shortcut).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => tracing::info!("グローバルショートカットを登録しました: {}", accelerator),
+        Err(e) => tracing::warn!("グローバルショートカットを登録できませんでした: {}", e),
+    }
+}
+
+/// グローバルショートカットを設定する（acceleratorがNoneなら解除）
+#[cfg(desktop)]
+fn apply_shortcut(
+    app_handle: &AppHandle,
+    accelerator: Option<String>,
+    action: Option<ShortcutAction>,
+) -> Result<(), String> {
+    let shortcut = accelerator.as_deref().map(parse_accelerator).transpose()?;
+
+    let global_shortcut = app_handle.global_shortcut();
+    global_shortcut.unregister_all()
+        .map_err(|e| t!("shortcut.unregister_failed", e))?;
+    if let Some(shortcut) = shortcut {
+        global_shortcut.register(shortcut)
+            .map_err(|e| t!("shortcut.register_failed", e))?;
+    }
+
+    let mut settings = AppSettings::load(app_handle)?;
+    settings.global_shortcut.accelerator = accelerator;
+    if let Some(action) = action {
+        settings.global_shortcut.action = action;
+    }
+    settings.save(app_handle)
+}
+
+/// モバイルではグローバルショートカットを使えない
+#[cfg(mobile)]
+fn apply_shortcut(
+    _app_handle: &AppHandle,
+    _accelerator: Option<String>,
+    _action: Option<ShortcutAction>,
+) -> Result<(), String> {
+    Err(t!("shortcut.unsupported"))
+}
+
+/// グローバルショートカットを設定する
+#[tauri::command]
+pub fn set_global_shortcut(
+    app_handle: AppHandle,
+    accelerator: Option<String>,
+    action: Option<ShortcutAction>,
+) -> Result<(), String> {
+    let result = apply_shortcut(&app_handle, accelerator, action);
+    audit::complete(&app_handle, "set_global_shortcut", &result);
+    result
+}
+
+#[cfg(all(test, desktop))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accelerator() {
+        assert!(parse_accelerator("CommandOrControl+Shift+V").is_ok());
+        assert!(parse_accelerator("Ctrl+Alt+F12").is_ok());
+        assert!(parse_accelerator("NotAKey+").is_err());
+    }
+}
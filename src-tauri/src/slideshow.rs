@@ -0,0 +1,769 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Emitter, Manager, Monitor, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use crate::album::AlbumStore;
+use crate::i18n::t;
+use crate::image::ImageInfo;
+use crate::lock;
+use crate::navigation::{self, NavigationSource, NavigationState};
+
+/// モニターごとのスライドショーウィンドウのラベルの接頭辞
+pub const WINDOW_LABEL_PREFIX: &str = "slideshow-";
+
+/// 切り替え間隔の下限（ミリ秒）
+const MIN_INTERVAL_MS: u64 = 500;
+
+fn default_interval_ms() -> u64 {
+    5000
+}
+
+fn default_preload() -> usize {
+    2
+}
+
+/// 画像を切り替える際の効果（表示は画面側で行う）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SlideTransition {
+    #[default]
+    None,
+    Fade,
+    Slide,
+    Zoom,
+}
+
+/// 音楽に合わせた切り替え（曲の再生は画面側で行う）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MusicSync {
+    /// 再生する曲のパス
+    pub track: String,
+    /// 曲のテンポ（指定すると拍に合わせて切り替える）
+    #[serde(default)]
+    pub bpm: Option<f64>,
+    /// 1枚あたりの拍数
+    #[serde(default = "default_beats_per_slide")]
+    pub beats_per_slide: u32,
+}
+
+fn default_beats_per_slide() -> u32 {
+    8
+}
+
+impl MusicSync {
+    /// 拍に合わせた切り替え間隔（ミリ秒、テンポが未指定ならNone）
+    fn beat_interval_ms(&self) -> Option<u64> {
+        let bpm = self.bpm.filter(|bpm| bpm.is_finite() && *bpm > 0.0)?;
+        Some((60_000.0 / bpm * self.beats_per_slide.max(1) as f64).round() as u64)
+    }
+}
+
+/// アルバムごとに保存するスライドショーの設定（アルバムを開始すると`SlideshowOptions`より優先する）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AlbumSlideshowSettings {
+    /// 切り替え間隔（ミリ秒）
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    #[serde(default)]
+    pub transition: SlideTransition,
+    #[serde(default)]
+    pub shuffle: bool,
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+    #[serde(default)]
+    pub music: Option<MusicSync>,
+}
+
+/// スライドショーの設定
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SlideshowOptions {
+    /// 対象フォルダ（未指定ならライブラリ全体）
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// 対象アルバム（フォルダより優先）
+    #[serde(default)]
+    pub album: Option<String>,
+    /// 切り替え間隔（ミリ秒）
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    /// ランダムな順序で表示する
+    #[serde(default)]
+    pub shuffle: bool,
+    /// 最後まで表示したら最初に戻る
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+    /// 最初に表示する画像の位置
+    #[serde(default)]
+    pub start_index: usize,
+    /// 先読みする枚数
+    #[serde(default = "default_preload")]
+    pub preload: usize,
+    /// `set_navigation_set`で設定した並び順で表示する（フォルダ・アルバムより優先）
+    #[serde(default)]
+    pub use_navigation: bool,
+    /// 切り替え効果
+    #[serde(default)]
+    pub transition: SlideTransition,
+    /// 音楽に合わせた切り替え（テンポを指定すると`interval_ms`より優先する）
+    #[serde(default)]
+    pub music: Option<MusicSync>,
+    /// アルバムに保存した設定を使わず、この設定のまま表示する
+    #[serde(default)]
+    pub ignore_album_settings: bool,
+}
+
+/// `slideshow-tick`で通知する内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SlideshowTick {
+    /// 表示する画像
+    pub image: ImageInfo,
+    /// 表示順での位置
+    pub position: usize,
+    /// 総画像数
+    pub total: usize,
+    /// 次に表示する画像（先読み用）
+    pub upcoming: Vec<ImageInfo>,
+}
+
+/// スライドショーの状態
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SlideshowStatus {
+    /// 実行中か
+    pub running: bool,
+    /// 一時停止中か
+    pub paused: bool,
+    /// 表示順での位置
+    pub position: usize,
+    /// 総画像数
+    pub total: usize,
+    /// 表示中の画像（開いたばかりのウィンドウが最初の`slideshow-tick`を受け取れない場合に使う）
+    #[serde(default)]
+    pub image: Option<ImageInfo>,
+    /// 現在の設定
+    pub options: Option<SlideshowOptions>,
+}
+
+/// 実行中のスライドショー
+struct Slideshow {
+    images: Vec<ImageInfo>,
+    /// 表示順（imagesのインデックス）
+    order: Vec<usize>,
+    /// 次の周回の表示順（ループ時に周回をまたいで先読みできるよう、前もって決めておく）
+    next_order: Vec<usize>,
+    position: usize,
+    options: SlideshowOptions,
+    paused: bool,
+    /// 次に切り替える時刻
+    deadline: Instant,
+    /// 開始ごとに変わる番号（古いタイマースレッドの終了判定に使う）
+    generation: u64,
+}
+
+impl Slideshow {
+    fn new(images: Vec<ImageInfo>, options: SlideshowOptions, generation: u64) -> Self {
+        let mut order: Vec<usize> = (0..images.len()).collect();
+        if options.shuffle {
+            fastrand::shuffle(&mut order);
+        }
+        let next_order = next_round(&order, options.shuffle);
+        let position = options.start_index.min(images.len().saturating_sub(1));
+        Self {
+            images,
+            order,
+            next_order,
+            position,
+            deadline: Instant::now() + options.interval(),
+            options,
+            paused: false,
+            generation,
+        }
+    }
+
+    /// 次の画像へ進める（最後まで表示済みでループしない場合はfalse）
+    fn advance(&mut self) -> bool {
+        if self.position + 1 < self.order.len() {
+            self.position += 1;
+            return true;
+        }
+        if !self.options.repeat {
+            return false;
+        }
+        let next_order = next_round(&self.next_order, self.options.shuffle);
+        self.order = std::mem::replace(&mut self.next_order, next_order);
+        self.position = 0;
+        true
+    }
+
+    fn tick(&self) -> SlideshowTick {
+        let upcoming = (1..=self.options.preload.min(self.order.len().saturating_sub(1)))
+            .filter_map(|offset| {
+                let position = self.position + offset;
+                if position < self.order.len() {
+                    Some(self.order[position])
+                } else if self.options.repeat {
+                    // 周回をまたぐ分は次の周回の表示順で数える（シャッフル時は並びが変わるため）
+                    Some(self.next_order[position % self.order.len()])
+                } else {
+                    None
+                }
+            })
+            .map(|index| self.images[index].clone())
+            .collect();
+
+        SlideshowTick {
+            image: self.images[self.order[self.position]].clone(),
+            position: self.position,
+            total: self.order.len(),
+            upcoming,
+        }
+    }
+
+    fn status(&self) -> SlideshowStatus {
+        SlideshowStatus {
+            running: true,
+            paused: self.paused,
+            position: self.position,
+            total: self.order.len(),
+            image: Some(self.images[self.order[self.position]].clone()),
+            options: Some(self.options.clone()),
+        }
+    }
+}
+
+/// 次の周回の表示順を決める（シャッフルする場合は並べ直す）
+fn next_round(order: &[usize], shuffle: bool) -> Vec<usize> {
+    let mut next = order.to_vec();
+    if shuffle {
+        fastrand::shuffle(&mut next);
+    }
+    next
+}
+
+impl SlideshowOptions {
+    fn interval(&self) -> Duration {
+        let interval_ms = self.music.as_ref()
+            .and_then(MusicSync::beat_interval_ms)
+            .unwrap_or(self.interval_ms);
+        Duration::from_millis(interval_ms.max(MIN_INTERVAL_MS))
+    }
+
+    /// アルバムに保存した設定で置き換える
+    fn with_album_settings(self, settings: AlbumSlideshowSettings) -> Self {
+        Self {
+            interval_ms: settings.interval_ms,
+            transition: settings.transition,
+            shuffle: settings.shuffle,
+            repeat: settings.repeat,
+            music: settings.music,
+            ..self
+        }
+    }
+}
+
+#[derive(Default)]
+struct SlideshowInner {
+    /// 表示先のウィンドウのラベル
+    window: String,
+    current: Mutex<Option<Slideshow>>,
+    /// 状態が変わったときにタイマースレッドを起こす
+    wake: Condvar,
+    /// 次に開始するスライドショーの番号
+    next_generation: AtomicU64,
+}
+
+/// スライドショーを管理するステート
+#[derive(Clone)]
+pub struct SlideshowState(Arc<SlideshowInner>);
+
+impl Default for SlideshowState {
+    fn default() -> Self {
+        Self::for_window("main")
+    }
+}
+
+impl SlideshowState {
+    /// 指定したウィンドウに表示するスライドショーのステートを作る
+    fn for_window(label: &str) -> Self {
+        Self(Arc::new(SlideshowInner { window: label.to_string(), ..Default::default() }))
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Option<Slideshow>>, String> {
+        self.0.current.lock().map_err(|e| t!("common.lock_failed", t!("slideshow.name"), e))
+    }
+}
+
+/// モニターごとのスライドショー（ウィンドウのラベルごと）を管理するステート
+#[derive(Default)]
+pub struct MonitorSlideshows(Mutex<HashMap<String, SlideshowState>>);
+
+impl MonitorSlideshows {
+    fn lock(&self) -> Result<MutexGuard<'_, HashMap<String, SlideshowState>>, String> {
+        self.0.lock().map_err(|e| t!("common.lock_failed", t!("slideshow.name"), e))
+    }
+}
+
+/// モニターの情報
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MonitorInfo {
+    /// `start_monitor_slideshow`で指定する番号
+    pub index: usize,
+    pub name: Option<String>,
+    /// 表示領域（物理ピクセル）
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    /// OSの主モニターか
+    pub primary: bool,
+}
+
+/// モニターで開始したスライドショー
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MonitorSlideshow {
+    /// 表示先のウィンドウのラベル
+    pub label: String,
+    pub monitor: usize,
+    pub status: SlideshowStatus,
+}
+
+/// モニターごとのスライドショーのウィンドウか
+pub fn is_slideshow_window(label: &str) -> bool {
+    label.starts_with(WINDOW_LABEL_PREFIX)
+}
+
+/// 呼び出し元ウィンドウのスライドショーを求める（モニターごとのウィンドウ以外はメインウィンドウのもの）
+fn target(window: &WebviewWindow, main: &SlideshowState, monitors: &MonitorSlideshows) -> Result<SlideshowState, String> {
+    if !is_slideshow_window(window.label()) {
+        return Ok(main.clone());
+    }
+    monitor_state(monitors, window.label())
+}
+
+/// モニターごとのウィンドウのスライドショーを求める（なければ作る）
+fn monitor_state(monitors: &MonitorSlideshows, label: &str) -> Result<SlideshowState, String> {
+    Ok(monitors.lock()?.entry(label.to_string()).or_insert_with(|| SlideshowState::for_window(label)).clone())
+}
+
+/// 閉じられたスライドショーのウィンドウのスライドショーを終了する
+pub fn forget_window(app_handle: &AppHandle, label: &str) {
+    if !is_slideshow_window(label) {
+        return;
+    }
+    let Some(state) = app_handle.try_state::<MonitorSlideshows>() else { return };
+    let removed = state.0.lock().ok().and_then(|mut shows| shows.remove(label));
+    if let Some(show) = removed {
+        let _ = stop(&show);
+        tracing::info!("スライドショーのウィンドウを閉じました: {}", label);
+    }
+}
+
+/// 表示対象の画像一覧を取得する
+fn collect_images(app_handle: &AppHandle, options: &SlideshowOptions) -> Result<Vec<ImageInfo>, String> {
+    if options.use_navigation {
+        return app_handle.try_state::<NavigationState>()
+            .and_then(|state| navigation::current_images(&state))
+            .ok_or_else(|| t!("navigation.not_set"));
+    }
+    let source = match (&options.album, &options.folder) {
+        (Some(name), _) => NavigationSource::Album { name: name.clone() },
+        (None, Some(path)) => NavigationSource::Folder { path: path.clone() },
+        (None, None) => NavigationSource::Library,
+    };
+    navigation::load_source(app_handle, &source)
+}
+
+/// 先読み対象のファイルを読み込み、OSのファイルキャッシュに載せる
+fn preload(images: &[ImageInfo]) {
+    let paths: Vec<String> = images.iter().map(|image| image.path.clone()).collect();
+    if paths.is_empty() {
+        return;
+    }
+    std::thread::spawn(move || {
+        for path in paths {
+            let _ = fs::read(&path);
+        }
+    });
+}
+
+/// 画像の切り替えを表示先のウィンドウに通知する
+fn emit_tick(app_handle: &AppHandle, label: &str, tick: SlideshowTick) {
+    // ロック中は画像のパスを画面に送らない
+    if lock::suppresses(app_handle, "slideshow-tick") {
+        return;
+    }
+    preload(&tick.upcoming);
+    if let Some(window) = app_handle.get_webview_window(label) {
+        let _ = window.emit("slideshow-tick", tick);
+    }
+}
+
+/// 間隔ごとに画像を切り替えるスレッドを起動する
+fn spawn_timer(app_handle: AppHandle, inner: Arc<SlideshowInner>, generation: u64) {
+    std::thread::spawn(move || {
+        let Ok(mut guard) = inner.current.lock() else { return };
+        while let Some(show) = guard.as_mut().filter(|show| show.generation == generation) {
+            if show.paused {
+                guard = match inner.wake.wait(guard) {
+                    Ok(guard) => guard,
+                    Err(_) => break,
+                };
+                continue;
+            }
+
+            let now = Instant::now();
+            if now < show.deadline {
+                let remaining = show.deadline - now;
+                guard = match inner.wake.wait_timeout(guard, remaining) {
+                    Ok((guard, _)) => guard,
+                    Err(_) => break,
+                };
+                continue;
+            }
+
+            if show.advance() {
+                show.deadline = now + show.options.interval();
+                let tick = show.tick();
+                drop(guard);
+                emit_tick(&app_handle, &inner.window, tick);
+            } else {
+                *guard = None;
+                drop(guard);
+                if let Some(window) = app_handle.get_webview_window(&inner.window) {
+                    let _ = window.emit("slideshow-ended", ());
+                }
+                break;
+            }
+
+            guard = match inner.current.lock() {
+                Ok(guard) => guard,
+                Err(_) => break,
+            };
+        }
+    });
+}
+
+/// スライドショーを開始する（実行中のものは置き換える）
+pub fn start(app_handle: &AppHandle, state: &SlideshowState, options: SlideshowOptions) -> Result<SlideshowStatus, String> {
+    let images = collect_images(app_handle, &options)?;
+    let album_settings = match &options.album {
+        Some(name) if !options.use_navigation && !options.ignore_album_settings => {
+            AlbumStore::open(app_handle).and_then(|store| store.slideshow_settings(name))?
+        },
+        _ => None,
+    };
+    let options = match album_settings {
+        Some(settings) => options.with_album_settings(settings),
+        None => options,
+    };
+    if images.is_empty() {
+        return Err(t!("slideshow.no_images"));
+    }
+
+    let (status, tick, generation) = {
+        let mut current = state.lock()?;
+        let generation = state.0.next_generation.fetch_add(1, Ordering::Relaxed);
+        let show = Slideshow::new(images, options, generation);
+        let result = (show.status(), show.tick(), generation);
+        *current = Some(show);
+        result
+    };
+    state.0.wake.notify_all();
+
+    tracing::info!("スライドショーを開始しました: {}枚 ({})", status.total, state.0.window);
+    emit_tick(app_handle, &state.0.window, tick);
+    spawn_timer(app_handle.clone(), state.0.clone(), generation);
+    Ok(status)
+}
+
+/// スライドショーを終了する
+pub fn stop(state: &SlideshowState) -> Result<(), String> {
+    *state.lock()? = None;
+    state.0.wake.notify_all();
+    Ok(())
+}
+
+/// スライドショーを開始する（実行中のものは置き換える）
+#[tauri::command]
+pub async fn start_slideshow(
+    app_handle: AppHandle,
+    window: WebviewWindow,
+    state: State<'_, SlideshowState>,
+    monitors: State<'_, MonitorSlideshows>,
+    options: SlideshowOptions,
+) -> Result<SlideshowStatus, String> {
+    start(&app_handle, &target(&window, &state, &monitors)?, options)
+}
+
+/// スライドショーを一時停止する
+#[tauri::command]
+pub fn pause_slideshow(
+    window: WebviewWindow,
+    state: State<'_, SlideshowState>,
+    monitors: State<'_, MonitorSlideshows>,
+) -> Result<SlideshowStatus, String> {
+    let state = target(&window, &state, &monitors)?;
+    let mut current = state.lock()?;
+    let show = current.as_mut().ok_or_else(|| t!("slideshow.not_running"))?;
+    show.paused = true;
+    let status = show.status();
+    drop(current);
+    state.0.wake.notify_all();
+    Ok(status)
+}
+
+/// 一時停止したスライドショーを再開する（切り替え間隔は再開時点から数える）
+#[tauri::command]
+pub fn resume_slideshow(
+    window: WebviewWindow,
+    state: State<'_, SlideshowState>,
+    monitors: State<'_, MonitorSlideshows>,
+) -> Result<SlideshowStatus, String> {
+    let state = target(&window, &state, &monitors)?;
+    let mut current = state.lock()?;
+    let show = current.as_mut().ok_or_else(|| t!("slideshow.not_running"))?;
+    show.paused = false;
+    show.deadline = Instant::now() + show.options.interval();
+    let status = show.status();
+    drop(current);
+    state.0.wake.notify_all();
+    Ok(status)
+}
+
+/// 次の画像へ進める（切り替え間隔はこの時点から数え直す）
+#[tauri::command]
+pub fn next_slideshow(
+    app_handle: AppHandle,
+    window: WebviewWindow,
+    state: State<'_, SlideshowState>,
+    monitors: State<'_, MonitorSlideshows>,
+) -> Result<SlideshowStatus, String> {
+    let state = target(&window, &state, &monitors)?;
+    let mut current = state.lock()?;
+    let show = current.as_mut().ok_or_else(|| t!("slideshow.not_running"))?;
+    if !show.advance() {
+        return Ok(show.status());
+    }
+    show.deadline = Instant::now() + show.options.interval();
+    let (status, tick) = (show.status(), show.tick());
+    drop(current);
+    state.0.wake.notify_all();
+
+    emit_tick(&app_handle, &state.0.window, tick);
+    Ok(status)
+}
+
+/// スライドショーを終了する
+#[tauri::command]
+pub fn stop_slideshow(
+    window: WebviewWindow,
+    state: State<'_, SlideshowState>,
+    monitors: State<'_, MonitorSlideshows>,
+) -> Result<(), String> {
+    stop(&target(&window, &state, &monitors)?)
+}
+
+/// スライドショーの状態を取得する
+#[tauri::command]
+pub fn get_slideshow_status(
+    window: WebviewWindow,
+    state: State<'_, SlideshowState>,
+    monitors: State<'_, MonitorSlideshows>,
+) -> Result<SlideshowStatus, String> {
+    let state = target(&window, &state, &monitors)?;
+    let current = state.lock()?;
+    Ok(current.as_ref().map(|show| show.status()).unwrap_or(SlideshowStatus {
+        running: false,
+        paused: false,
+        position: 0,
+        total: 0,
+        image: None,
+        options: None,
+    }))
+}
+
+/// 接続されているモニターの一覧を取得する
+#[tauri::command]
+pub fn list_monitors(app_handle: AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    let primary = app_handle.primary_monitor().ok().flatten();
+    let monitors = app_handle.available_monitors().map_err(|e| t!("slideshow.monitors_failed", e))?;
+    Ok(monitors.iter().enumerate().map(|(index, monitor)| MonitorInfo {
+        index,
+        name: monitor.name().cloned(),
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+        scale_factor: monitor.scale_factor(),
+        primary: primary.as_ref().is_some_and(|primary| primary.name() == monitor.name() && primary.position() == monitor.position()),
+    }).collect())
+}
+
+/// モニターに全画面のウィンドウを開く（既に開いていればそのウィンドウを使う）
+fn open_window(app_handle: &AppHandle, label: &str, monitor: &Monitor) -> Result<WebviewWindow, String> {
+    let window = match app_handle.get_webview_window(label) {
+        Some(window) => window,
+        None => WebviewWindowBuilder::new(app_handle, label, WebviewUrl::default())
+            .title("poir-viewer - slideshow")
+            .decorations(false)
+            .visible(false)
+            .build()
+            .map_err(|e| t!("slideshow.window_failed", e))?,
+    };
+    // 全画面表示はウィンドウのあるモニターで行われるため、先に対象のモニターへ移動する
+    let placed = window.set_fullscreen(false)
+        .and_then(|_| window.set_position(*monitor.position()))
+        .and_then(|_| window.set_fullscreen(true))
+        .and_then(|_| window.show());
+    if let Err(e) = placed {
+        let _ = window.destroy();
+        return Err(t!("slideshow.window_failed", e));
+    }
+    Ok(window)
+}
+
+/// モニターのウィンドウでスライドショーを開始する
+fn start_on_monitor(
+    app_handle: &AppHandle,
+    monitors: &MonitorSlideshows,
+    index: usize,
+    monitor: &Monitor,
+    options: SlideshowOptions,
+) -> Result<MonitorSlideshow, String> {
+    let label = format!("{}{}", WINDOW_LABEL_PREFIX, index);
+    let window = open_window(app_handle, &label, monitor)?;
+    let state = monitor_state(monitors, &label)?;
+    match start(app_handle, &state, options) {
+        Ok(status) => Ok(MonitorSlideshow { label, monitor: index, status }),
+        Err(e) => {
+            let _ = window.destroy();
+            Err(e)
+        },
+    }
+}
+
+/// 指定したモニターに全画面のウィンドウを開いてスライドショーを開始する（展示・フォトフレーム用）
+///
+/// ウィンドウ側の操作（一時停止・次へ・終了）は通常のスライドショーのコマンドで行う
+#[tauri::command]
+pub async fn start_monitor_slideshow(
+    app_handle: AppHandle,
+    monitors: State<'_, MonitorSlideshows>,
+    monitor: usize,
+    options: SlideshowOptions,
+) -> Result<MonitorSlideshow, String> {
+    let available = app_handle.available_monitors().map_err(|e| t!("slideshow.monitors_failed", e))?;
+    let target = available.get(monitor).ok_or_else(|| t!("slideshow.monitor_not_found", monitor))?;
+    start_on_monitor(&app_handle, &monitors, monitor, target, options)
+}
+
+/// すべてのモニターでそれぞれ独立したスライドショーを開始する
+///
+/// `shuffle`を指定するとモニターごとに異なる順序になる
+#[tauri::command]
+pub async fn start_slideshow_per_monitor(
+    app_handle: AppHandle,
+    monitors: State<'_, MonitorSlideshows>,
+    options: SlideshowOptions,
+) -> Result<Vec<MonitorSlideshow>, String> {
+    let available = app_handle.available_monitors().map_err(|e| t!("slideshow.monitors_failed", e))?;
+    available.iter()
+        .enumerate()
+        .map(|(index, monitor)| start_on_monitor(&app_handle, &monitors, index, monitor, options.clone()))
+        .collect()
+}
+
+/// モニターごとのスライドショーをすべて終了し、ウィンドウを閉じる
+#[tauri::command]
+pub fn stop_monitor_slideshows(app_handle: AppHandle, monitors: State<'_, MonitorSlideshows>) -> Result<(), String> {
+    let labels: Vec<String> = monitors.lock()?.keys().cloned().collect();
+    for label in labels {
+        match app_handle.get_webview_window(&label) {
+            Some(window) => {
+                let _ = window.destroy();
+            },
+            None => forget_window(&app_handle, &label),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn images(count: usize) -> Vec<ImageInfo> {
+        (0..count).map(|i| ImageInfo {
+            path: format!("/photos/{}.jpg", i),
+            name: format!("{}.jpg", i),
+            size: 0,
+            modified: 0,
+            extension: "jpg".to_string(),
+            sensitive: false,
+            bit_depth: None,
+        }).collect()
+    }
+
+    fn options(repeat: bool) -> SlideshowOptions {
+        serde_json::from_value(serde_json::json!({ "loop": repeat, "preload": 2 })).unwrap()
+    }
+
+    #[test]
+    fn test_album_settings_and_beat_interval() {
+        let settings = AlbumSlideshowSettings {
+            interval_ms: 3000,
+            transition: SlideTransition::Fade,
+            shuffle: false,
+            repeat: true,
+            music: None,
+        };
+        let options = options(false).with_album_settings(settings.clone());
+        assert!(options.repeat);
+        assert_eq!(options.transition, SlideTransition::Fade);
+        assert_eq!(options.interval(), Duration::from_millis(3000));
+
+        // 120BPMで8拍ごとなら4秒
+        let music = MusicSync { track: "/music/theme.mp3".to_string(), bpm: Some(120.0), beats_per_slide: 8 };
+        let options = options.with_album_settings(AlbumSlideshowSettings { music: Some(music), ..settings });
+        assert_eq!(options.interval(), Duration::from_millis(4000));
+    }
+
+    #[test]
+    fn test_advance_stops_without_loop() {
+        let mut show = Slideshow::new(images(3), options(false), 0);
+        assert!(show.advance());
+        assert_eq!(show.tick().upcoming.len(), 1);
+        assert!(show.advance());
+        assert!(!show.advance());
+        assert_eq!(show.position, 2);
+    }
+
+    #[test]
+    fn test_advance_wraps_with_loop() {
+        let mut show = Slideshow::new(images(3), options(true), 0);
+        show.advance();
+        show.advance();
+        let tick = show.tick();
+        assert_eq!(tick.image.name, "2.jpg");
+        assert_eq!(tick.upcoming.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), vec!["0.jpg", "1.jpg"]);
+        assert!(show.advance());
+        assert_eq!(show.position, 0);
+    }
+
+    #[test]
+    fn test_upcoming_matches_reshuffled_order_after_wrap() {
+        let options = serde_json::from_value(serde_json::json!({ "loop": true, "shuffle": true, "preload": 2 })).unwrap();
+        let mut show = Slideshow::new(images(4), options, 0);
+        // 周回をまたいで何度か進め、先読みした画像が実際に表示される画像と一致することを確かめる
+        for _ in 0..12 {
+            let upcoming: Vec<String> = show.tick().upcoming.into_iter().map(|i| i.path).collect();
+            assert!(show.advance());
+            assert_eq!(show.tick().image.path, upcoming[0]);
+            assert!(show.advance());
+            assert_eq!(show.tick().image.path, upcoming[1]);
+        }
+    }
+}
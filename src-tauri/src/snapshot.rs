@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{params, Connection};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::i18n::t;
+use crate::index::{IndexEntry, LibraryIndex};
+
+/// スナップショットの概要
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SnapshotSummary {
+    pub name: String,
+    /// 作成日時（Unix時間）
+    pub created_at: u64,
+    /// 画像数
+    pub count: usize,
+}
+
+/// スナップショット間で変更された画像
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ModifiedImage {
+    pub path: String,
+    pub size_before: u64,
+    pub size_after: u64,
+    pub modified_before: u64,
+    pub modified_after: u64,
+}
+
+/// 2つのスナップショットの差分（`a`から`b`への変化）
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SnapshotDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<ModifiedImage>,
+    /// 変化のなかった画像の数
+    pub unchanged: usize,
+}
+
+/// 記録した画像1件（パス・サイズ・更新日時）
+type SnapshotImage = (String, u64, u64);
+
+/// スナップショットの保存先（インデックスと同じSQLiteファイル）
+pub struct SnapshotStore {
+    conn: Connection,
+}
+
+impl SnapshotStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("snapshot.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS snapshots (
+                name TEXT PRIMARY KEY,
+                created_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS snapshot_images (
+                snapshot TEXT NOT NULL,
+                path TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                modified INTEGER NOT NULL,
+                PRIMARY KEY (snapshot, path)
+            );"
+        ).map_err(|e| t!("snapshot.init_failed", e))
+    }
+
+    /// インデックスの記録をスナップショットとして保存する
+    pub fn create(&mut self, name: &str, entries: &[IndexEntry], created_at: u64) -> Result<SnapshotSummary, String> {
+        if name.trim().is_empty() {
+            return Err(t!("snapshot.empty_name"));
+        }
+        if self.exists(name)? {
+            return Err(t!("snapshot.already_exists", name));
+        }
+
+        let tx = self.conn.transaction()
+            .map_err(|e| t!("snapshot.create_failed", e))?;
+        tx.execute("INSERT INTO snapshots (name, created_at) VALUES (?1, ?2)", params![name, created_at as i64])
+            .map_err(|e| t!("snapshot.create_path_failed", name, e))?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO snapshot_images (snapshot, path, size, modified) VALUES (?1, ?2, ?3, ?4)"
+            ).map_err(|e| t!("snapshot.create_failed", e))?;
+            for entry in entries {
+                stmt.execute(params![name, entry.path, entry.size as i64, entry.modified as i64])
+                    .map_err(|e| t!("snapshot.create_path_failed", entry.path, e))?;
+            }
+        }
+        tx.commit().map_err(|e| t!("snapshot.create_failed", e))?;
+
+        Ok(SnapshotSummary { name: name.to_string(), created_at, count: self.images(name)?.len() })
+    }
+
+    /// スナップショットが存在するか
+    pub fn exists(&self, name: &str) -> Result<bool, String> {
+        self.conn.query_row("SELECT COUNT(*) FROM snapshots WHERE name = ?1", params![name], |row| row.get::<_, i64>(0))
+            .map(|count| count > 0)
+            .map_err(|e| t!("snapshot.read_failed", e))
+    }
+
+    /// スナップショットの一覧を新しい順に取得する
+    pub fn list(&self) -> Result<Vec<SnapshotSummary>, String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.name, s.created_at, COUNT(i.path) FROM snapshots s
+             LEFT JOIN snapshot_images i ON i.snapshot = s.name
+             GROUP BY s.name ORDER BY s.created_at DESC, s.name"
+        ).map_err(|e| t!("snapshot.read_failed", e))?;
+        stmt.query_map([], |row| {
+            Ok(SnapshotSummary {
+                name: row.get(0)?,
+                created_at: row.get::<_, i64>(1)? as u64,
+                count: row.get::<_, i64>(2)? as usize,
+            })
+        })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| t!("snapshot.read_failed", e))
+    }
+
+    /// スナップショットに記録した画像をパス順に取得する
+    fn images(&self, name: &str) -> Result<Vec<SnapshotImage>, String> {
+        if !self.exists(name)? {
+            return Err(t!("snapshot.not_found", name));
+        }
+        let mut stmt = self.conn.prepare(
+            "SELECT path, size, modified FROM snapshot_images WHERE snapshot = ?1 ORDER BY path"
+        ).map_err(|e| t!("snapshot.read_failed", e))?;
+        stmt.query_map(params![name], |row| {
+            Ok((row.get(0)?, row.get::<_, i64>(1)? as u64, row.get::<_, i64>(2)? as u64))
+        })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| t!("snapshot.read_name_failed", name, e))
+    }
+
+    /// 2つのスナップショットの差分を求める
+    pub fn diff(&self, a: &str, b: &str) -> Result<SnapshotDiff, String> {
+        Ok(diff_images(&self.images(a)?, &self.images(b)?))
+    }
+}
+
+/// パス順に並んだ2つの記録を比べる
+fn diff_images(before: &[SnapshotImage], after: &[SnapshotImage]) -> SnapshotDiff {
+    let before_by_path: HashMap<&str, &SnapshotImage> = before.iter().map(|image| (image.0.as_str(), image)).collect();
+    let after_by_path: HashMap<&str, &SnapshotImage> = after.iter().map(|image| (image.0.as_str(), image)).collect();
+
+    let mut diff = SnapshotDiff::default();
+    for (path, size_after, modified_after) in after {
+        match before_by_path.get(path.as_str()) {
+            None => diff.added.push(path.clone()),
+            Some((_, size_before, modified_before)) if size_before != size_after || modified_before != modified_after => {
+                diff.modified.push(ModifiedImage {
+                    path: path.clone(),
+                    size_before: *size_before,
+                    size_after: *size_after,
+                    modified_before: *modified_before,
+                    modified_after: *modified_after,
+                });
+            },
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+    diff.removed = before.iter()
+        .filter(|(path, _, _)| !after_by_path.contains_key(path.as_str()))
+        .map(|(path, _, _)| path.clone())
+        .collect();
+    diff
+}
+
+/// 現在のインデックスの状態をスナップショットとして保存する
+#[tauri::command]
+pub async fn create_snapshot(app_handle: AppHandle, name: String) -> Result<SnapshotSummary, String> {
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    let result = LibraryIndex::open(&app_handle)
+        .and_then(|index| index.entries())
+        .and_then(|entries| SnapshotStore::open(&app_handle)?.create(&name, &entries, created_at));
+    audit::complete(&app_handle, "create_snapshot", &result);
+    result
+}
+
+/// スナップショットの一覧を取得する
+#[tauri::command]
+pub async fn list_snapshots(app_handle: AppHandle) -> Result<Vec<SnapshotSummary>, String> {
+    SnapshotStore::open(&app_handle)?.list()
+}
+
+/// スナップショット`a`から`b`までに追加・削除・変更された画像を取得する
+#[tauri::command]
+pub async fn diff_snapshots(app_handle: AppHandle, a: String, b: String) -> Result<SnapshotDiff, String> {
+    SnapshotStore::open(&app_handle)?.diff(&a, &b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, size: u64, modified: u64) -> IndexEntry {
+        IndexEntry { path: path.to_string(), folder: "/photos".to_string(), size, modified }
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let path = std::env::temp_dir().join(format!("poir-snapshot-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut store = SnapshotStore::open_at(&path).unwrap();
+
+        store.create("前", &[entry("/photos/a.jpg", 10, 1), entry("/photos/b.jpg", 20, 1), entry("/photos/c.jpg", 30, 1)], 100).unwrap();
+        let summary = store.create("後", &[entry("/photos/a.jpg", 10, 1), entry("/photos/b.jpg", 25, 2), entry("/photos/d.jpg", 40, 2)], 200).unwrap();
+        assert_eq!(summary.count, 3);
+        assert!(store.create("前", &[], 300).is_err());
+        assert_eq!(store.list().unwrap().iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["後", "前"]);
+
+        let diff = store.diff("前", "後").unwrap();
+        assert_eq!(diff.added, vec!["/photos/d.jpg"]);
+        assert_eq!(diff.removed, vec!["/photos/c.jpg"]);
+        assert_eq!(diff.modified, vec![ModifiedImage {
+            path: "/photos/b.jpg".to_string(),
+            size_before: 20,
+            size_after: 25,
+            modified_before: 1,
+            modified_after: 2,
+        }]);
+        assert_eq!(diff.unchanged, 1);
+        assert!(store.diff("前", "なし").is_err());
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,464 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR_STR};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::album::AlbumStore;
+use crate::audit;
+use crate::config;
+use crate::event_bridge::{self, Delivery};
+use crate::i18n::t;
+use crate::metadata::MetadataStore;
+use crate::settings::{AppSettings, SyncRoot, SyncSettings};
+
+/// 同期ファイルの形式（互換性のない変更をしたら上げる）
+const SYNC_FORMAT: u32 = 1;
+
+/// 共有フォルダ内で同期ファイルを置くフォルダ
+const SYNC_DIR: &str = "poir-sync";
+
+/// タグ・アルバム・アルバム内の画像が「ある」ことを表す値
+const PRESENT: u8 = 1;
+
+/// 同時に1回だけ同期する
+static SYNCING: Mutex<()> = Mutex::new(());
+
+/// 同期する項目（パスは端末間で共通の形）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncItem {
+    Tag { path: String, tag: String },
+    Rating { path: String },
+    Album { name: String },
+    AlbumImage { album: String, path: String },
+}
+
+/// 項目の最後の変更（後に変更したものが優先される）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct Register {
+    /// レーティングの値、それ以外は`PRESENT`（削除したらNone）
+    value: Option<u8>,
+    /// 変更日時（Unix時間、ミリ秒）
+    at: u64,
+    /// 変更した端末
+    device: String,
+}
+
+impl Register {
+    /// 同じ日時なら端末IDで決めて、どの端末でも同じ結果になるようにする
+    fn is_newer_than(&self, other: &Register) -> bool {
+        (self.at, &self.device) > (other.at, &other.device)
+    }
+}
+
+/// 同期ファイルの1行
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct SyncEntry {
+    #[serde(flatten)]
+    item: SyncItem,
+    #[serde(flatten)]
+    register: Register,
+}
+
+/// 端末ごとの同期ファイル（各端末は自分のファイルだけを書くため、同期ツールで競合しない）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct SyncFile {
+    format: u32,
+    device: String,
+    /// 最後に書き込んだ日時（Unix時間、ミリ秒）
+    updated_at: u64,
+    entries: Vec<SyncEntry>,
+}
+
+/// 全項目の最後の変更
+type SyncState = BTreeMap<SyncItem, Register>;
+
+/// 現在のタグ・レーティング・アルバム（項目と値）
+type Organization = BTreeMap<SyncItem, u8>;
+
+/// 同期の結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SyncResult {
+    /// 取り込んだ他の端末の数
+    pub devices: usize,
+    /// 他の端末の変更で追加・更新した項目の数
+    pub updated: usize,
+    /// 他の端末の変更で削除した項目の数
+    pub removed: usize,
+    /// 同期した日時（Unix時間、ミリ秒）
+    pub synced_at: u64,
+}
+
+/// 同期ファイルを書き込んだ端末
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SyncDevice {
+    pub device: String,
+    pub updated_at: u64,
+    pub entries: usize,
+}
+
+/// 同期の設定と状態
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncStatus {
+    pub settings: SyncSettings,
+    /// この端末のID
+    pub device_id: String,
+    pub last_sync: Option<u64>,
+    /// 共有フォルダにある同期ファイル（この端末を含む）
+    pub devices: Vec<SyncDevice>,
+}
+
+/// この端末の同期の記録
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct DeviceRecord {
+    device_id: String,
+    last_sync: Option<u64>,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// 端末の記録の保存先（バックアップに含めないよう設定とは別にする）
+fn get_device_path(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("sync-device.json")
+}
+
+/// 端末の記録を読み込む（なければ端末IDを作成して保存する）
+fn load_device(app_handle: &AppHandle) -> Result<DeviceRecord, String> {
+    let path = get_device_path(app_handle);
+    let record: DeviceRecord = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    if !record.device_id.is_empty() {
+        return Ok(record);
+    }
+    let record = DeviceRecord { device_id: format!("{:016x}", fastrand::u64(..)), last_sync: None };
+    save_device(app_handle, &record)?;
+    Ok(record)
+}
+
+fn save_device(app_handle: &AppHandle, record: &DeviceRecord) -> Result<(), String> {
+    let path = get_device_path(app_handle);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(record).map_err(|e| t!("sync.write_failed", path.display(), e))?;
+    fs::write(&path, json).map_err(|e| t!("sync.write_failed", path.display(), e))
+}
+
+/// この端末のパスを端末間で共通の形にする（対応のないパスはそのまま）
+fn to_shared(path: &str, roots: &[SyncRoot]) -> String {
+    for root in roots {
+        let local = root.local.trim_end_matches(['/', '\\']);
+        if let Some(rest) = path.strip_prefix(local) {
+            if rest.is_empty() || rest.starts_with(['/', '\\']) {
+                return format!("{}{}", root.shared, rest.replace('\\', "/"));
+            }
+        }
+    }
+    path.to_string()
+}
+
+/// 端末間で共通の形のパスをこの端末のパスに戻す
+fn to_local(path: &str, roots: &[SyncRoot]) -> String {
+    for root in roots {
+        if let Some(rest) = path.strip_prefix(root.shared.as_str()) {
+            if rest.is_empty() || rest.starts_with('/') {
+                let local = root.local.trim_end_matches(['/', '\\']);
+                return format!("{}{}", local, rest.replace('/', MAIN_SEPARATOR_STR));
+            }
+        }
+    }
+    path.to_string()
+}
+
+/// 項目内のパスを変換する
+fn map_paths(item: &SyncItem, convert: impl Fn(&str) -> String) -> SyncItem {
+    match item {
+        SyncItem::Tag { path, tag } => SyncItem::Tag { path: convert(path), tag: tag.clone() },
+        SyncItem::Rating { path } => SyncItem::Rating { path: convert(path) },
+        SyncItem::Album { name } => SyncItem::Album { name: name.clone() },
+        SyncItem::AlbumImage { album, path } => SyncItem::AlbumImage { album: album.clone(), path: convert(path) },
+    }
+}
+
+/// 前回の同期以降のこの端末での変更を記録する
+fn observe(state: &mut SyncState, current: &Organization, at: u64, device: &str) {
+    let removed: Vec<SyncItem> = state.iter()
+        .filter(|(item, register)| register.value.is_some() && !current.contains_key(*item))
+        .map(|(item, _)| item.clone())
+        .collect();
+    for item in removed {
+        state.insert(item, Register { value: None, at, device: device.to_string() });
+    }
+    for (item, value) in current {
+        if state.get(item).and_then(|register| register.value) != Some(*value) {
+            state.insert(item.clone(), Register { value: Some(*value), at, device: device.to_string() });
+        }
+    }
+}
+
+/// 他の端末の記録を取り込む（項目ごとに後の変更を残す）
+fn merge(state: &mut SyncState, other: &SyncState) {
+    for (item, register) in other {
+        if state.get(item).is_none_or(|current| register.is_newer_than(current)) {
+            state.insert(item.clone(), register.clone());
+        }
+    }
+}
+
+/// 記録から本来あるべき内容を求める（削除されたアルバムの画像は除く）
+fn resolve(state: &SyncState) -> Organization {
+    let present = |item: &SyncItem| state.get(item).is_some_and(|register| register.value.is_some());
+    state.iter()
+        .filter_map(|(item, register)| register.value.map(|value| (item, value)))
+        .filter(|(item, _)| match item {
+            SyncItem::AlbumImage { album, .. } => present(&SyncItem::Album { name: album.clone() }),
+            _ => true,
+        })
+        .map(|(item, value)| (item.clone(), value))
+        .collect()
+}
+
+/// 現在のタグ・レーティング・アルバムを読み込む
+fn read_organization(metadata: &MetadataStore, albums: &AlbumStore, roots: &[SyncRoot]) -> Result<Organization, String> {
+    let shared = |path: &str| to_shared(path, roots);
+    let mut organization = Organization::new();
+    for (path, image) in metadata.all()? {
+        for tag in image.tags {
+            organization.insert(SyncItem::Tag { path: shared(&path), tag }, PRESENT);
+        }
+        if let Some(rating) = image.rating {
+            organization.insert(SyncItem::Rating { path: shared(&path) }, rating);
+        }
+    }
+    for album in albums.list()? {
+        for path in albums.paths(&album.name)? {
+            organization.insert(SyncItem::AlbumImage { album: album.name.clone(), path: shared(&path) }, PRESENT);
+        }
+        organization.insert(SyncItem::Album { name: album.name }, PRESENT);
+    }
+    Ok(organization)
+}
+
+/// 現在の内容をあるべき内容に合わせ、追加・更新した数と削除した数を返す
+fn apply(
+    metadata: &MetadataStore,
+    albums: &mut AlbumStore,
+    current: &Organization,
+    target: &Organization,
+    roots: &[SyncRoot],
+) -> Result<(usize, usize), String> {
+    let (mut updated, mut removed) = (0, 0);
+    // 項目の順（タグ・レーティング・アルバム・アルバム内の画像）で、画像より先にアルバムを作成する
+    for (item, value) in target {
+        if current.get(item) == Some(value) {
+            continue;
+        }
+        match map_paths(item, |path| to_local(path, roots)) {
+            SyncItem::Tag { path, tag } => metadata.add_tags(&path, &[tag])?,
+            SyncItem::Rating { path } => metadata.set_rating(&path, *value)?,
+            SyncItem::Album { name } => albums.create(&name)?,
+            SyncItem::AlbumImage { album, path } => albums.add_images(&album, &[path])?,
+        }
+        updated += 1;
+    }
+    for item in current.keys().filter(|item| !target.contains_key(*item)) {
+        match map_paths(item, |path| to_local(path, roots)) {
+            SyncItem::Tag { path, tag } => metadata.remove_tag(&path, &tag)?,
+            SyncItem::Rating { path } => metadata.clear_rating(&path)?,
+            SyncItem::Album { name } => albums.delete(&name)?,
+            SyncItem::AlbumImage { album, path } => albums.remove_images(&album, &[path])?,
+        }
+        removed += 1;
+    }
+    Ok((updated, removed))
+}
+
+fn read_sync_file(path: &Path) -> Result<SyncFile, String> {
+    let content = fs::read_to_string(path).map_err(|e| t!("file.read_failed", path.display(), e))?;
+    let file: SyncFile = serde_json::from_str(&content).map_err(|e| t!("sync.invalid_file", path.display(), e))?;
+    if file.format != SYNC_FORMAT {
+        return Err(t!("sync.unsupported_format", path.display(), file.format));
+    }
+    Ok(file)
+}
+
+/// 共有フォルダ内の同期ファイルを読み込む（同期途中などで読めないファイルは飛ばす）
+fn read_sync_files(dir: &Path) -> Vec<SyncFile> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut files: Vec<SyncFile> = entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| read_sync_file(&path).map_err(|e| tracing::warn!("{}", e)).ok())
+        .collect();
+    files.sort_by(|a, b| a.device.cmp(&b.device));
+    files
+}
+
+/// 同期ファイルを書き込む（一時ファイルに書いてから置き換える）
+fn write_sync_file(dir: &Path, device: &str, state: &SyncState, updated_at: u64) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| t!("common.dir_create_failed", dir.display(), e))?;
+    let file = SyncFile {
+        format: SYNC_FORMAT,
+        device: device.to_string(),
+        updated_at,
+        entries: state.iter()
+            .map(|(item, register)| SyncEntry { item: item.clone(), register: register.clone() })
+            .collect(),
+    };
+    let path = dir.join(format!("{}.json", device));
+    let temp = dir.join(format!(".{}.json.tmp", device));
+    let json = serde_json::to_string_pretty(&file).map_err(|e| t!("sync.write_failed", path.display(), e))?;
+    fs::write(&temp, json)
+        .and_then(|_| fs::rename(&temp, &path))
+        .map_err(|e| t!("sync.write_failed", path.display(), e))
+}
+
+fn to_state(file: &SyncFile) -> SyncState {
+    file.entries.iter().map(|entry| (entry.item.clone(), entry.register.clone())).collect()
+}
+
+/// この端末の変更を記録し、他の端末の変更を取り込んでから、自分の同期ファイルを書き込む
+fn sync_folder(
+    dir: &Path,
+    device: &str,
+    metadata: &MetadataStore,
+    albums: &mut AlbumStore,
+    roots: &[SyncRoot],
+    now: u64,
+) -> Result<SyncResult, String> {
+    let files = read_sync_files(dir);
+    let mut state = files.iter()
+        .find(|file| file.device == device)
+        .map(to_state)
+        .unwrap_or_default();
+
+    let current = read_organization(metadata, albums, roots)?;
+    observe(&mut state, &current, now, device);
+    let others: Vec<&SyncFile> = files.iter().filter(|file| file.device != device).collect();
+    for file in &others {
+        merge(&mut state, &to_state(file));
+    }
+
+    let (updated, removed) = apply(metadata, albums, &current, &resolve(&state), roots)?;
+    write_sync_file(dir, device, &state, now)?;
+    Ok(SyncResult { devices: others.len(), updated, removed, synced_at: now })
+}
+
+/// 設定された共有フォルダと同期する
+fn run(app_handle: &AppHandle) -> Result<SyncResult, String> {
+    let settings = AppSettings::load(app_handle)?.sync;
+    let Some(folder) = settings.folder.filter(|_| settings.enabled) else {
+        return Err(t!("sync.not_configured"));
+    };
+    let folder = PathBuf::from(folder);
+    if !folder.is_dir() {
+        return Err(t!("path.not_directory", folder.display()));
+    }
+    let _guard = SYNCING.lock().map_err(|e| t!("common.lock_failed", "sync", e))?;
+
+    let mut device = load_device(app_handle)?;
+    let metadata = MetadataStore::open(app_handle)?;
+    let mut albums = AlbumStore::open(app_handle)?;
+    let result = sync_folder(&folder.join(SYNC_DIR), &device.device_id, &metadata, &mut albums, &settings.roots, now_millis())?;
+
+    device.last_sync = Some(result.synced_at);
+    save_device(app_handle, &device)?;
+    tracing::info!(
+        "共有フォルダと同期しました: 他の端末{}台・追加{}件・削除{}件",
+        result.devices, result.updated, result.removed,
+    );
+    event_bridge::emit(app_handle, "metadata-synced", Delivery::Latest, &result);
+    Ok(result)
+}
+
+/// 同期が有効なら起動時に他の端末の変更を取り込む
+pub fn sync_on_startup(app_handle: &AppHandle) {
+    if !AppSettings::load(app_handle).unwrap_or_default().sync.enabled {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = run(&app_handle) {
+            tracing::warn!("起動時の同期に失敗しました: {}", e);
+        }
+    });
+}
+
+/// 同期の設定と、共有フォルダにある各端末の同期ファイルを取得する
+#[tauri::command]
+pub async fn get_sync_status(app_handle: AppHandle) -> Result<SyncStatus, String> {
+    let settings = AppSettings::load(&app_handle)?.sync;
+    let device = load_device(&app_handle)?;
+    let devices = settings.folder.as_ref()
+        .map(|folder| read_sync_files(&Path::new(folder).join(SYNC_DIR)))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|file| SyncDevice { device: file.device, updated_at: file.updated_at, entries: file.entries.len() })
+        .collect();
+    Ok(SyncStatus { settings, device_id: device.device_id, last_sync: device.last_sync, devices })
+}
+
+/// 今すぐ共有フォルダと同期する
+#[tauri::command]
+pub async fn sync_metadata_now(app_handle: AppHandle) -> Result<SyncResult, String> {
+    let result = run(&app_handle);
+    audit::complete(&app_handle, "sync_metadata_now", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_devices_converge() {
+        let root = std::env::temp_dir().join(format!("poir-sync-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let shared = root.join(SYNC_DIR);
+        let open = |name: &str| {
+            let path = root.join(format!("{}.db", name));
+            (MetadataStore::open_at(&path).unwrap(), AlbumStore::open_at(&path).unwrap())
+        };
+        let roots_a = [SyncRoot { local: "/mnt/nas/photos".to_string(), shared: "nas".to_string() }];
+        let roots_b = [SyncRoot { local: "/Volumes/photos/".to_string(), shared: "nas".to_string() }];
+        let (meta_a, mut albums_a) = open("a");
+        let (meta_b, mut albums_b) = open("b");
+
+        meta_a.add_tags("/mnt/nas/photos/1.jpg", &["旅行".to_string(), "海".to_string()]).unwrap();
+        meta_a.set_rating("/mnt/nas/photos/1.jpg", 4).unwrap();
+        albums_a.create("夏").unwrap();
+        albums_a.add_images("夏", &["/mnt/nas/photos/1.jpg".to_string()]).unwrap();
+        sync_folder(&shared, "a", &meta_a, &mut albums_a, &roots_a, 100).unwrap();
+
+        let result = sync_folder(&shared, "b", &meta_b, &mut albums_b, &roots_b, 200).unwrap();
+        assert_eq!((result.devices, result.updated, result.removed), (1, 5, 0));
+        assert_eq!(meta_b.get("/Volumes/photos/1.jpg").unwrap().rating, Some(4));
+        assert_eq!(albums_b.paths("夏").unwrap(), vec!["/Volumes/photos/1.jpg"]);
+
+        // Bでタグを外してレーティングを変え、Aでは後からアルバムを削除する
+        meta_b.remove_tag("/Volumes/photos/1.jpg", "海").unwrap();
+        meta_b.set_rating("/Volumes/photos/1.jpg", 2).unwrap();
+        sync_folder(&shared, "b", &meta_b, &mut albums_b, &roots_b, 300).unwrap();
+        albums_a.delete("夏").unwrap();
+        sync_folder(&shared, "a", &meta_a, &mut albums_a, &roots_a, 400).unwrap();
+        sync_folder(&shared, "b", &meta_b, &mut albums_b, &roots_b, 500).unwrap();
+
+        for (metadata, albums, path) in [(&meta_a, &albums_a, "/mnt/nas/photos/1.jpg"), (&meta_b, &albums_b, "/Volumes/photos/1.jpg")] {
+            let image = metadata.get(path).unwrap();
+            assert_eq!(image.tags, vec!["旅行"]);
+            assert_eq!(image.rating, Some(2));
+            assert!(albums.list().unwrap().is_empty());
+        }
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
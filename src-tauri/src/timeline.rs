@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::config::ResourceConfig;
+use crate::display::HdrTransfer;
+use crate::exif_info;
+use crate::hidden;
+use crate::image::{self, ImageInfo};
+use crate::index::LibraryIndex;
+use crate::preview;
+use crate::privacy;
+use crate::sensitive;
+use crate::settings::AppSettings;
+
+/// 1つの期間に含める代表画像の数
+const REPRESENTATIVE_COUNT: usize = 4;
+
+/// 代表画像の縮小サイズ
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// まとめる単位
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Year,
+    #[default]
+    Month,
+    Day,
+}
+
+/// 代表画像
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Representative {
+    pub image: ImageInfo,
+    /// 縮小画像のパス（作成できなければNone）
+    pub thumbnail: Option<String>,
+    /// 縮小画像に適用したEXIFの向き（適用していなければNone）
+    pub applied_orientation: Option<u32>,
+    /// 刺激の強い画像のため縮小画像をぼかしたか
+    pub blurred: bool,
+    /// 元の画像がHDRか
+    pub hdr: Option<HdrTransfer>,
+}
+
+/// 最近追加された画像
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentImage {
+    pub image: ImageInfo,
+    /// 初めてインデックスに加えた日時（Unix時間）
+    pub first_indexed_at: u64,
+}
+
+/// タイムラインの1期間
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimelineBucket {
+    /// 期間のキー（"2024"・"2024-05"・"2024-05-03"）
+    pub key: String,
+    pub year: i64,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+    /// 期間内の画像数
+    pub count: usize,
+    /// 期間内の代表画像（新しい順）
+    pub representatives: Vec<Representative>,
+}
+
+/// Unix時間を期間のキーに変換する
+fn bucket_key(timestamp: u64, granularity: Granularity) -> (String, i64, Option<u32>, Option<u32>) {
+    let (year, month, day) = exif_info::civil_from_days((timestamp / 86400) as i64);
+    match granularity {
+        Granularity::Year => (format!("{:04}", year), year, None, None),
+        Granularity::Month => (format!("{:04}-{:02}", year, month), year, Some(month), None),
+        Granularity::Day => (format!("{:04}-{:02}-{:02}", year, month, day), year, Some(month), Some(day)),
+    }
+}
+
+/// 新しい順に並んだ画像を期間ごとにまとめる
+fn group(images: Vec<(ImageInfo, u64)>, granularity: Granularity) -> Vec<(TimelineBucket, Vec<ImageInfo>)> {
+    let mut buckets: Vec<(TimelineBucket, Vec<ImageInfo>)> = Vec::new();
+    for (image, timestamp) in images {
+        let (key, year, month, day) = bucket_key(timestamp, granularity);
+        match buckets.last_mut() {
+            Some((bucket, representatives)) if bucket.key == key => {
+                bucket.count += 1;
+                if representatives.len() < REPRESENTATIVE_COUNT {
+                    representatives.push(image);
+                }
+            },
+            _ => buckets.push((
+                TimelineBucket { key, year, month, day, count: 1, representatives: Vec::new() },
+                vec![image],
+            )),
+        }
+    }
+    buckets
+}
+
+/// 撮影日時が未取得の画像のEXIFを読み、インデックスに記録する
+fn fill_taken_at(index: &mut LibraryIndex) -> Result<(), String> {
+    let paths = index.paths_without_taken_at()?;
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let entries: Vec<(String, u64)> = paths.into_iter()
+        .filter_map(|path| {
+            let file = Path::new(&path);
+            // EXIFがなければ更新日時を撮影日時として記録し、次回から読み直さない
+            let taken_at = exif_info::taken_at(file)
+                .or_else(|| crate::image::image_info(file).ok().map(|info| info.modified))?;
+            Some((path, taken_at))
+        })
+        .collect();
+    tracing::info!("撮影日時を記録しました: {}件", entries.len());
+    index.set_taken_at(&entries)
+}
+
+/// インデックス済みの画像を撮影日時（なければ更新日時）で年・月・日ごとにまとめて取得する
+///
+/// 期間は1ページの最大件数と応答サイズの上限に収まる分だけ返す（続きは受け取った件数を`offset`に足して取得する）
+#[tauri::command]
+pub async fn get_timeline(
+    app_handle: AppHandle,
+    granularity: Option<Granularity>,
+    offset: Option<usize>,
+) -> Result<Vec<TimelineBucket>, String> {
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().list_limits;
+    let mut index = LibraryIndex::open(&app_handle)?;
+    if let Err(e) = fill_taken_at(&mut index) {
+        tracing::warn!("撮影日時の記録中にエラー: {}", e);
+    }
+
+    let hidden_folders = privacy::hidden_folders(&app_handle);
+    let hidden_images = hidden::hidden_paths(&app_handle);
+    let roots = ResourceConfig::load(&app_handle)?.enabled_roots();
+    let mut images = index.dated_images()?;
+    images.retain(|(image, _)| {
+        image::is_in_roots(&image.path, &roots)
+            && !privacy::is_hidden(&image.path, &hidden_folders)
+            && !hidden_images.contains(&image.path)
+    });
+
+    let granularity = granularity.unwrap_or_default();
+    // 縮小画像は返す期間の分だけ作る
+    let buckets: Vec<TimelineBucket> = group(images, granularity)
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limits.max_items_per_page.max(1))
+        .map(|(mut bucket, images)| {
+            bucket.representatives = images.into_iter()
+                .map(|image| {
+                    let thumbnail = preview::thumbnail(&app_handle, Path::new(&image.path), THUMBNAIL_SIZE)
+                        .map_err(|e| tracing::warn!("縮小画像を作成できませんでした: {}", e))
+                        .ok();
+                    Representative {
+                        applied_orientation: thumbnail.as_ref().and_then(|thumbnail| thumbnail.applied_orientation),
+                        blurred: thumbnail.as_ref().is_some_and(|thumbnail| thumbnail.blurred),
+                        hdr: thumbnail.as_ref().and_then(|thumbnail| thumbnail.hdr),
+                        thumbnail: thumbnail.map(|thumbnail| thumbnail.path.to_string_lossy().to_string()),
+                        image,
+                    }
+                })
+                .collect();
+            bucket
+        })
+        .collect();
+    Ok(image::page_of(buckets, 0, None, &limits))
+}
+
+/// `since`（Unix時間）以降に監視フォルダに現れた画像を、インデックスに加えた日時の新しい順で取得する
+///
+/// ファイルの更新日時ではなく、初めてスキャンで見つけた日時で判定する（コピーで日時が古いままの画像も含む）。
+/// 1ページの最大件数と応答サイズの上限に収まる分だけ返す（続きは受け取った件数を`offset`に足して取得する）
+#[tauri::command]
+pub async fn get_recently_added(
+    app_handle: AppHandle,
+    since: u64,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<RecentImage>, String> {
+    let limits = AppSettings::load(&app_handle).unwrap_or_default().list_limits;
+    let recent = LibraryIndex::open(&app_handle)?.recently_added(since)?;
+    let added_at: HashMap<String, u64> = recent.iter().map(|(image, at)| (image.path.clone(), *at)).collect();
+    let images = image::filter_enabled_folders(&app_handle, recent.into_iter().map(|(image, _)| image).collect());
+    let images = sensitive::mark(&app_handle, hidden::filter_images(&app_handle, privacy::filter_images(&app_handle, images)));
+    let recent: Vec<RecentImage> = images.into_iter()
+        .map(|image| RecentImage { first_indexed_at: added_at.get(&image.path).copied().unwrap_or(0), image })
+        .collect();
+    Ok(image::page_of(recent, offset.unwrap_or(0), limit, &limits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(name: &str) -> ImageInfo {
+        ImageInfo {
+            path: format!("/photos/{}", name),
+            name: name.to_string(),
+            size: 1,
+            modified: 0,
+            extension: "jpg".to_string(),
+            sensitive: false,
+            bit_depth: None,
+        }
+    }
+
+    #[test]
+    fn test_group_by_month() {
+        // 2024-05-03, 2024-05-01, 2023-12-31
+        let images = vec![(image("a.jpg"), 1714739696), (image("b.jpg"), 1714521600), (image("c.jpg"), 1704020000)];
+        let buckets = group(images, Granularity::Month);
+
+        let keys: Vec<(&str, usize)> = buckets.iter().map(|(b, _)| (b.key.as_str(), b.count)).collect();
+        assert_eq!(keys, vec![("2024-05", 2), ("2023-12", 1)]);
+        assert_eq!(buckets[0].1.len(), 2);
+        assert_eq!(bucket_key(1714739696, Granularity::Day).0, "2024-05-03");
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::Path;
+use ::image::{DynamicImage, Rgba, RgbaImage};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::display::{self, HdrTransfer};
+use crate::i18n::t;
+use crate::path_guard;
+use crate::preview;
+use crate::settings::AppSettings;
+
+/// SDRの白とみなす明るさ（cd/m²、ITU-R BT.2408の基準白）
+const SDR_WHITE_NITS: f32 = 203.0;
+
+/// PQの最大の明るさ（cd/m²）
+const PQ_MAX_NITS: f32 = 10_000.0;
+
+/// HLGの想定するディスプレイの最大の明るさ（cd/m²）
+const HLG_PEAK_NITS: f32 = 1_000.0;
+
+/// HLGのシステムガンマ（最大1000cd/m²のとき）
+const HLG_SYSTEM_GAMMA: f32 = 1.2;
+
+/// Hableのトーンカーブで白とみなす明るさ（SDRの白に対する倍率）
+const HABLE_WHITE: f32 = 11.2;
+
+/// BT.2020の原色からsRGB（BT.709）の原色への変換（線形の値に掛ける）
+const BT2020_TO_BT709: [[f32; 3]; 3] = [
+    [1.6605, -0.5876, -0.0728],
+    [-0.1246, 1.1329, -0.0083],
+    [-0.0182, -0.1006, 1.1187],
+];
+
+/// HDRの画像をSDRで表示するためのトーンマッピングの方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ToneMapOperator {
+    /// トーンマッピングしない（HDRの値をそのまま切り詰める）
+    Off,
+    /// 明るい部分をなだらかに圧縮する（Reinhard）
+    Reinhard,
+    /// 映画風のカーブ（Hable / Uncharted 2）
+    #[default]
+    Hable,
+    /// ACESの近似カーブ（Narkowicz）
+    Aces,
+}
+
+/// PQの信号値を明るさ（cd/m²）に変換する（SMPTE ST 2084）
+fn pq_to_nits(value: f32) -> f32 {
+    const M1: f32 = 2610.0 / 16384.0;
+    const M2: f32 = 2523.0 / 4096.0 * 128.0;
+    const C1: f32 = 3424.0 / 4096.0;
+    const C2: f32 = 2413.0 / 4096.0 * 32.0;
+    const C3: f32 = 2392.0 / 4096.0 * 32.0;
+    let power = value.clamp(0.0, 1.0).powf(1.0 / M2);
+    PQ_MAX_NITS * ((power - C1).max(0.0) / (C2 - C3 * power)).powf(1.0 / M1)
+}
+
+/// HLGの信号値をシーンの線形の値（0〜1）に変換する（ARIB STD-B67）
+fn hlg_to_linear(value: f32) -> f32 {
+    const A: f32 = 0.178_832_77;
+    const B: f32 = 0.284_668_92;
+    const C: f32 = 0.559_910_7;
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.5 {
+        value * value / 3.0
+    } else {
+        (((value - C) / A).exp() + B) / 12.0
+    }
+}
+
+/// 線形の値をsRGBの信号値に変換する
+fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn hable_curve(x: f32) -> f32 {
+    const A: f32 = 0.15;
+    const B: f32 = 0.50;
+    const C: f32 = 0.10;
+    const D: f32 = 0.20;
+    const E: f32 = 0.02;
+    const F: f32 = 0.30;
+    ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F
+}
+
+impl ToneMapOperator {
+    /// SDRの白を1とする線形の値を0〜1に収める
+    fn apply(self, value: f32) -> f32 {
+        let value = value.max(0.0);
+        match self {
+            ToneMapOperator::Off => value.min(1.0),
+            ToneMapOperator::Reinhard => value / (1.0 + value),
+            ToneMapOperator::Hable => hable_curve(value * 2.0) / hable_curve(HABLE_WHITE),
+            ToneMapOperator::Aces => (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14),
+        }
+    }
+}
+
+/// 信号値をSDRの白を1とする線形の値に変換する
+fn to_relative_linear(transfer: HdrTransfer, value: f32) -> f32 {
+    match transfer {
+        HdrTransfer::Pq => pq_to_nits(value) / SDR_WHITE_NITS,
+        // 色成分ごとにシステムガンマを掛ける簡易的なOOTF
+        HdrTransfer::Hlg => HLG_PEAK_NITS * hlg_to_linear(value).powf(HLG_SYSTEM_GAMMA) / SDR_WHITE_NITS,
+    }
+}
+
+/// HDRの画像をトーンマッピングして8bitのsRGBにする
+///
+/// 16bitや浮動小数点の画像は精度を保ったまま変換する（BT.2020の原色を前提とする）
+pub fn tone_map(image: &DynamicImage, transfer: HdrTransfer, operator: ToneMapOperator) -> DynamicImage {
+    let source = image.to_rgba32f();
+    let mapped = RgbaImage::from_fn(source.width(), source.height(), |x, y| {
+        let pixel = source.get_pixel(x, y).0;
+        let linear = [0, 1, 2].map(|channel| to_relative_linear(transfer, pixel[channel]));
+        let rgb = BT2020_TO_BT709.map(|row| row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2]);
+        let [r, g, b] = rgb.map(|value| (linear_to_srgb(operator.apply(value)) * 255.0).round() as u8);
+        Rgba([r, g, b, (pixel[3].clamp(0.0, 1.0) * 255.0).round() as u8])
+    });
+    DynamicImage::ImageRgba8(mapped)
+}
+
+/// HDRの画像をトーンマッピングしたプレビューを作成し、そのパスを返す（元ファイルは変更しない）
+///
+/// `operator`を省略すると設定の方式を使う。HDRでない画像はエラー
+#[tauri::command]
+pub async fn get_tone_mapped_preview(
+    app_handle: AppHandle,
+    path: String,
+    max_size: Option<u32>,
+    operator: Option<ToneMapOperator>,
+) -> Result<String, String> {
+    let source = path_guard::guard(&app_handle, &path)?;
+    let transfer = display::detect_hdr(&source).ok_or_else(|| t!("tone_map.not_hdr", path))?;
+    let operator = operator.unwrap_or_else(|| AppSettings::load(&app_handle).unwrap_or_default().preview.tone_mapping);
+    let max_size = max_size.unwrap_or(2048);
+
+    let (image, applied_orientation) = preview::open_oriented(&app_handle, &source)?;
+    let variant = format!("tone-map-{:?}-{}-o{}", operator, max_size, applied_orientation.unwrap_or(1));
+    let dest = preview::get_cache_dir(&app_handle, "tone_mapped")
+        .join(format!("{}.png", preview::cache_key(Path::new(&source), &variant)));
+    if !dest.exists() {
+        preview::save_png(&tone_map(&image.thumbnail(max_size, max_size), transfer, operator), &dest)?;
+    }
+    Ok(dest.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::{ImageBuffer, Rgb};
+
+    #[test]
+    fn test_transfer_functions() {
+        assert!(pq_to_nits(0.0) < 0.001);
+        assert!((pq_to_nits(1.0) - PQ_MAX_NITS).abs() < 1.0);
+        // PQの0.58付近がSDRの基準白（203cd/m²）
+        assert!((pq_to_nits(0.58) / SDR_WHITE_NITS - 1.0).abs() < 0.05);
+        assert!((hlg_to_linear(0.5) - 1.0 / 12.0).abs() < 1e-6);
+        assert!((hlg_to_linear(1.0) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_tone_map_keeps_highlights_in_range() {
+        // 暗い灰色と、PQで最大の明るさの白
+        let image = DynamicImage::ImageRgb16(ImageBuffer::from_fn(2, 1, |x, _| {
+            if x == 0 { Rgb([20_000u16; 3]) } else { Rgb([u16::MAX; 3]) }
+        }));
+        for operator in [ToneMapOperator::Reinhard, ToneMapOperator::Hable, ToneMapOperator::Aces] {
+            let mapped = tone_map(&image, HdrTransfer::Pq, operator).to_rgba8();
+            let (dark, bright) = (mapped.get_pixel(0, 0), mapped.get_pixel(1, 0));
+            assert!(dark[0] < bright[0], "{:?}", operator);
+            assert!(bright[0] >= 240 && bright[3] == 255, "{:?}", operator);
+        }
+        let clipped = tone_map(&image, HdrTransfer::Pq, ToneMapOperator::Off).to_rgba8();
+        assert_eq!(clipped.get_pixel(1, 0)[0], 255);
+    }
+}
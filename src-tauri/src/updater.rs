@@ -0,0 +1,123 @@
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::i18n::t;
+use crate::settings::AppSettings;
+
+/// 最新リリースの取得先（GitHub Releases）
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/koach-noir/poir-viewer/releases/latest";
+
+/// 更新確認のタイムアウト
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// GitHub Releases APIの応答（必要な項目のみ）
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+    body: Option<String>,
+    published_at: Option<String>,
+}
+
+/// 更新確認の結果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateInfo {
+    /// 実行中のバージョン
+    pub current_version: String,
+    /// 公開されている最新バージョン
+    pub latest_version: String,
+    /// 更新があるか
+    pub update_available: bool,
+    /// リリースノート
+    pub release_notes: Option<String>,
+    /// リリースページのURL
+    pub release_url: String,
+    /// 公開日時（ISO 8601）
+    pub published_at: Option<String>,
+}
+
+/// `v1.2.3`のようなタグをバージョンとして解釈する
+fn parse_version(tag: &str) -> Option<semver::Version> {
+    semver::Version::parse(tag.trim().trim_start_matches(['v', 'V'])).ok()
+}
+
+/// `latest`が`current`より新しいか判定する（解釈できない場合は更新なしとする）
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// 最新リリースを取得して現在のバージョンと比較する
+async fn fetch_update_info(current_version: &str) -> Result<UpdateInfo, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("poir-viewer/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| t!("updater.client_failed", e))?;
+
+    let release: GithubRelease = client.get(LATEST_RELEASE_URL)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| t!("updater.fetch_failed", e))?
+        .json()
+        .await
+        .map_err(|e| t!("updater.parse_failed", e))?;
+
+    let latest_version = release.tag_name.trim_start_matches(['v', 'V']).to_string();
+    Ok(UpdateInfo {
+        current_version: current_version.to_string(),
+        update_available: is_newer(&latest_version, current_version),
+        latest_version,
+        release_notes: release.body,
+        release_url: release.html_url,
+        published_at: release.published_at,
+    })
+}
+
+/// 起動時の更新確認（設定で有効な場合のみ。更新があれば`update-available`を通知する）
+pub fn check_on_startup(app_handle: &AppHandle) {
+    let enabled = AppSettings::load(app_handle)
+        .map(|settings| settings.check_updates_on_startup)
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let current_version = app_handle.package_info().version.to_string();
+        match fetch_update_info(&current_version).await {
+            Ok(info) if info.update_available => {
+                tracing::info!("新しいバージョンがあります: {} -> {}", info.current_version, info.latest_version);
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.emit("update-available", info);
+                }
+            },
+            Ok(_) => tracing::info!("最新バージョンを使用しています"),
+            Err(e) => tracing::warn!("更新の確認に失敗しました: {}", e),
+        }
+    });
+}
+
+/// 更新を確認する
+#[tauri::command]
+pub async fn check_for_updates(app_handle: AppHandle) -> Result<UpdateInfo, String> {
+    fetch_update_info(&app_handle.package_info().version.to_string()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.2.0", "0.1.0"));
+        assert!(is_newer("0.1.1", "0.1.0"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.1.0-beta.1", "0.1.0"));
+        assert!(!is_newer("nightly", "0.1.0"));
+    }
+}
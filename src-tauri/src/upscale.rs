@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+use ::image::{DynamicImage, GenericImageView, RgbImage};
+use ::image::imageops::{self, FilterType};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::crop;
+use crate::i18n::t;
+use crate::jobs::{self, JobHandle};
+use crate::path_guard;
+use crate::preview;
+use crate::safe_write::{self, WriteOptions};
+use crate::settings::{AppSettings, UpscaleSettings};
+
+/// モデルを指定しなかったときのファイル名（アプリデータの`models`内）
+const DEFAULT_MODEL: &str = "upscale.onnx";
+
+/// 拡大の結果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UpscaleResult {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 画像を区画に分ける（`(x, y, 幅, 高さ)`、右端・下端の区画は小さくなる）
+fn tile_grid(width: u32, height: u32, tile: u32) -> Vec<(u32, u32, u32, u32)> {
+    let tile = tile.max(1);
+    (0..height).step_by(tile as usize)
+        .flat_map(|y| (0..width).step_by(tile as usize).map(move |x| (x, y, tile.min(width - x), tile.min(height - y))))
+        .collect()
+}
+
+/// 区画ごとに`infer`で拡大してつなぎ合わせる
+///
+/// 各区画は周囲に`overlap`ずつ広げ、画像の外は端の画素を繰り返して常に同じ大きさで入力する。
+/// 出力からは広げた部分を除いて中央だけを使うため、継ぎ目が目立たない
+#[cfg_attr(not(feature = "ml"), allow(dead_code))]
+fn upscale_tiled<F>(
+    image: &RgbImage,
+    scale: u32,
+    tile: u32,
+    overlap: u32,
+    job: Option<&JobHandle>,
+    mut infer: F,
+) -> Result<RgbImage, String>
+where
+    F: FnMut(&RgbImage) -> Result<RgbImage, String>,
+{
+    let (width, height) = image.dimensions();
+    let side = tile + overlap * 2;
+    let grid = tile_grid(width, height, tile);
+    let mut output = RgbImage::new(width * scale, height * scale);
+    for (index, (x, y, w, h)) in grid.into_iter().enumerate() {
+        if job.is_some_and(|job| job.is_cancelled()) {
+            return Err(t!("jobs.cancelled"));
+        }
+        let input = RgbImage::from_fn(side, side, |dx, dy| {
+            let sx = (x as i64 + dx as i64 - overlap as i64).clamp(0, width as i64 - 1) as u32;
+            let sy = (y as i64 + dy as i64 - overlap as i64).clamp(0, height as i64 - 1) as u32;
+            *image.get_pixel(sx, sy)
+        });
+        let upscaled = infer(&input)?;
+        if upscaled.dimensions() != (side * scale, side * scale) {
+            return Err(t!("upscale.unexpected_output", format!("{}x{}", upscaled.width(), upscaled.height())));
+        }
+        let core = imageops::crop_imm(&upscaled, overlap * scale, overlap * scale, w * scale, h * scale).to_image();
+        imageops::replace(&mut output, &core, (x * scale) as i64, (y * scale) as i64);
+        if let Some(job) = job {
+            job.progress(index + 1, None);
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(feature = "ml")]
+mod model {
+    use std::path::Path;
+    use ::image::RgbImage;
+    use tract_onnx::prelude::*;
+    use crate::i18n::t;
+
+    /// 0〜1のRGBを入力し、拡大したRGBを出力するモデル
+    pub struct Upscaler {
+        model: TypedRunnableModel<TypedModel>,
+        side: usize,
+    }
+
+    impl Upscaler {
+        pub fn load(path: &Path, side: u32) -> Result<Self, String> {
+            let side = side as usize;
+            let model = tract_onnx::onnx()
+                .model_for_path(path)
+                .and_then(|model| model.with_input_fact(0, f32::fact([1, 3, side, side]).into()))
+                .and_then(|model| model.into_optimized())
+                .and_then(|model| model.into_runnable())
+                .map_err(|e| t!("upscale.model_failed", path.display(), e))?;
+            Ok(Self { model, side })
+        }
+
+        pub fn run(&self, tile: &RgbImage) -> Result<RgbImage, String> {
+            let tensor: Tensor = tract_ndarray::Array4::from_shape_fn((1, 3, self.side, self.side), |(_, c, y, x)| {
+                tile.get_pixel(x as u32, y as u32)[c] as f32 / 255.0
+            }).into();
+            let outputs = self.model.run(tvec!(tensor.into())).map_err(|e| t!("upscale.failed", e))?;
+            let view = outputs[0].to_array_view::<f32>().map_err(|e| t!("upscale.failed", e))?;
+            let shape = view.shape();
+            if shape.len() != 4 || shape[1] != 3 {
+                return Err(t!("upscale.unexpected_output", format!("{:?}", shape)));
+            }
+            let (height, width) = (shape[2], shape[3]);
+            Ok(RgbImage::from_fn(width as u32, height as u32, |x, y| {
+                let value = |c: usize| (view[[0, c, y as usize, x as usize]].clamp(0.0, 1.0) * 255.0).round() as u8;
+                ::image::Rgb([value(0), value(1), value(2)])
+            }))
+        }
+    }
+}
+
+#[cfg(feature = "ml")]
+fn run(source: &Path, image: DynamicImage, model_path: &Path, settings: &UpscaleSettings, job: &JobHandle) -> Result<RgbImage, String> {
+    let upscaler = model::Upscaler::load(model_path, settings.tile_size + settings.tile_overlap * 2)?;
+    tracing::info!("超解像モデルで拡大します: {}", source.display());
+    upscale_tiled(&image.to_rgb8(), settings.model_scale, settings.tile_size, settings.tile_overlap, Some(job), |tile| upscaler.run(tile))
+}
+
+#[cfg(not(feature = "ml"))]
+fn run(_source: &Path, _image: DynamicImage, _model_path: &Path, _settings: &UpscaleSettings, _job: &JobHandle) -> Result<RgbImage, String> {
+    Err(t!("upscale.unavailable"))
+}
+
+fn start(app_handle: &AppHandle, path: &str, factor: u32, dest: &str) -> Result<String, String> {
+    let settings = AppSettings::load(app_handle).unwrap_or_default();
+    let upscale = settings.upscale;
+    if !(2..=upscale.model_scale).contains(&factor) {
+        return Err(t!("upscale.invalid_factor", factor, upscale.model_scale));
+    }
+    let source = path_guard::guard(app_handle, path)?;
+    let dest_path = PathBuf::from(dest);
+    if dest_path.parent().is_some_and(|parent| !parent.as_os_str().is_empty() && !parent.is_dir()) {
+        return Err(t!("path.not_directory", dest_path.display()));
+    }
+    // 別名保存のみとし、元ファイルは上書きしない
+    if path_guard::canonicalize(dest).is_ok_and(|existing| existing == source) {
+        return Err(t!("crop.overwrite_source", dest));
+    }
+    let format_name = dest_path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_string();
+    let format = crop::parse_format(&format_name).ok_or_else(|| t!("crop.unsupported_format", format_name))?;
+    let (model_path, _) = crate::concepts::model_paths(app_handle, upscale.model_path.as_deref(), None, DEFAULT_MODEL);
+    if !cfg!(feature = "ml") {
+        return Err(t!("upscale.unavailable"));
+    }
+    if !model_path.is_file() {
+        return Err(t!("upscale.model_not_found", model_path.display()));
+    }
+
+    let (image, _) = preview::open_oriented(app_handle, &source)?;
+    let (width, height) = image.dimensions();
+    // モデルの出力と保存する画像の大きさが展開の上限内か先に確かめる
+    preview::check_budget(&source, width * upscale.model_scale, height * upscale.model_scale, preview::RGBA8_BYTES_PER_PIXEL, settings.decode_limits.max_decode_bytes)?;
+
+    let tiles = tile_grid(width, height, upscale.tile_size).len();
+    jobs::spawn(app_handle, "upscale", tiles, move |job| {
+        let upscaled = run(&source, image, &model_path, &upscale, job)?;
+        let (target_width, target_height) = (width * factor, height * factor);
+        let output = if factor == upscale.model_scale {
+            DynamicImage::ImageRgb8(upscaled)
+        } else {
+            DynamicImage::ImageRgb8(imageops::resize(&upscaled, target_width, target_height, FilterType::Lanczos3))
+        };
+        safe_write::write_with(&dest_path, WriteOptions::default(), |temp| crop::save_as(&output, temp, format))?;
+        tracing::info!("画像を拡大して保存しました: {} -> {} ({}倍)", source.display(), dest_path.display(), factor);
+        Ok(UpscaleResult { path: dest_path.to_string_lossy().to_string(), width: target_width, height: target_height })
+    })
+}
+
+/// 端末内の超解像モデル（ESRGAN系のONNX）で画像を`factor`倍に拡大して別名で保存するジョブを開始し、ジョブIDを返す
+///
+/// `factor`はモデルの拡大率以下で、モデルより小さい倍率は拡大後に縮小する。透過は保持しない。
+/// `ml`フィーチャーを有効にしてビルドした場合のみ動作し、進捗と結果は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn upscale_image(app_handle: AppHandle, path: String, factor: u32, dest: String) -> Result<String, String> {
+    let result = start(&app_handle, &path, factor, &dest);
+    audit::complete(&app_handle, "upscale_image", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiled_upscale_matches_whole_image() {
+        assert_eq!(tile_grid(5, 3, 2), vec![(0, 0, 2, 2), (2, 0, 2, 2), (4, 0, 1, 2), (0, 2, 2, 1), (2, 2, 2, 1), (4, 2, 1, 1)]);
+
+        let image = RgbImage::from_fn(7, 5, |x, y| ::image::Rgb([(x * 30) as u8, (y * 50) as u8, ((x + y) * 10) as u8]));
+        let nearest = |tile: &RgbImage| Ok(imageops::resize(tile, tile.width() * 2, tile.height() * 2, FilterType::Nearest));
+        let tiled = upscale_tiled(&image, 2, 3, 1, None, nearest).unwrap();
+        assert_eq!(tiled, imageops::resize(&image, 14, 10, FilterType::Nearest));
+
+        let wrong_size = |tile: &RgbImage| Ok(tile.clone());
+        assert!(upscale_tiled(&image, 2, 3, 1, None, wrong_size).is_err());
+    }
+}
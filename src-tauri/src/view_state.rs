@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::i18n::t;
+use crate::index::LibraryIndex;
+
+/// 記録しておく画像数の上限（古いものから削除する）
+const MAX_VIEW_STATES: usize = 5000;
+
+/// 画像ごとの表示状態
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ViewState {
+    /// 拡大率（1.0で等倍）
+    pub zoom: f64,
+    /// 表示位置（画像の中心からのずれ、画像の大きさに対する割合）
+    pub pan_x: f64,
+    pub pan_y: f64,
+    /// 一時的な回転（度、時計回り）
+    pub rotation: i32,
+}
+
+impl Default for ViewState {
+    fn default() -> Self {
+        Self { zoom: 1.0, pan_x: 0.0, pan_y: 0.0, rotation: 0 }
+    }
+}
+
+/// 表示状態の保存先（インデックスと同じSQLiteファイル）
+pub struct ViewStateStore {
+    conn: Connection,
+}
+
+impl ViewStateStore {
+    /// アプリデータ内のストアを開く
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        Self::open_at(&LibraryIndex::get_index_path(app_handle))
+    }
+
+    /// 指定されたパスのストアを開く
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)
+                .map_err(|e| t!("common.dir_create_failed", parent_dir.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| t!("view_state.open_failed", path.display(), e))?;
+
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// テーブルを作成する
+    fn migrate(&self) -> Result<(), String> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS image_view_states (
+                path TEXT PRIMARY KEY,
+                zoom REAL NOT NULL,
+                pan_x REAL NOT NULL,
+                pan_y REAL NOT NULL,
+                rotation INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );"
+        ).map_err(|e| t!("view_state.init_failed", e))
+    }
+
+    /// 表示状態を保存し、上限を超えた古い記録を削除する
+    pub fn save(&self, path: &str, state: &ViewState, updated_at: u64) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO image_view_states (path, zoom, pan_x, pan_y, rotation, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![path, state.zoom, state.pan_x, state.pan_y, state.rotation.rem_euclid(360), updated_at as i64],
+        ).map_err(|e| t!("view_state.save_path_failed", path, e))?;
+
+        self.conn.execute(
+            "DELETE FROM image_view_states WHERE path NOT IN (
+                SELECT path FROM image_view_states ORDER BY updated_at DESC, rowid DESC LIMIT ?1
+            )",
+            params![MAX_VIEW_STATES as i64],
+        ).map(|_| ()).map_err(|e| t!("view_state.save_failed", e))
+    }
+
+    /// 表示状態を取得する
+    pub fn get(&self, path: &str) -> Result<Option<ViewState>, String> {
+        self.conn.query_row(
+            "SELECT zoom, pan_x, pan_y, rotation FROM image_view_states WHERE path = ?1",
+            params![path],
+            |row| Ok(ViewState {
+                zoom: row.get(0)?,
+                pan_x: row.get(1)?,
+                pan_y: row.get(2)?,
+                rotation: row.get(3)?,
+            }),
+        ).optional().map_err(|e| t!("view_state.read_failed", path, e))
+    }
+}
+
+/// 画像の拡大率・表示位置・回転を保存する
+#[tauri::command]
+pub async fn save_view_state(app_handle: AppHandle, path: String, state: ViewState) -> Result<(), String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    ViewStateStore::open(&app_handle)?.save(&path, &state, now)
+}
+
+/// 保存された画像の表示状態を取得する（未保存ならNone）
+#[tauri::command]
+pub async fn get_view_state(app_handle: AppHandle, path: String) -> Result<Option<ViewState>, String> {
+    ViewStateStore::open(&app_handle)?.get(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_normalizes_rotation_and_round_trips() {
+        let path = std::env::temp_dir().join(format!("poir-view-state-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let store = ViewStateStore::open_at(&path).unwrap();
+
+        let state = ViewState { zoom: 2.5, pan_x: 0.25, pan_y: -0.1, rotation: -90 };
+        store.save("/maps/large.png", &state, 1).unwrap();
+        assert_eq!(store.get("/maps/large.png").unwrap(), Some(ViewState { rotation: 270, ..state }));
+        assert_eq!(store.get("/maps/other.png").unwrap(), None);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use crate::i18n::t;
+use crate::image::ImageInfo;
+use crate::launch::{self, LaunchTarget, LaunchView};
+use crate::window_state;
+
+/// 追加ビューアウィンドウのラベルの接頭辞
+pub const VIEWER_LABEL_PREFIX: &str = "viewer-";
+
+/// 追加ビューアウィンドウごとの表示状態
+#[derive(Default)]
+pub struct ViewerWindows {
+    views: Mutex<HashMap<String, LaunchView>>,
+    next_id: AtomicU64,
+}
+
+impl ViewerWindows {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, LaunchView>>, String> {
+        self.views.lock().map_err(|e| t!("common.lock_failed", t!("viewer.name"), e))
+    }
+}
+
+/// 現在位置から`delta`だけ移動した位置を求める（端では反対側へ回り込む）
+fn step_index(current: Option<usize>, len: usize, delta: i64) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let current = current.unwrap_or(0) as i64;
+    Some((current + delta).rem_euclid(len as i64) as usize)
+}
+
+/// ウィンドウのタイトルを表示対象から作る
+fn window_title(target: &LaunchTarget) -> String {
+    match target {
+        LaunchTarget::Folder { path } | LaunchTarget::Image { path } => Path::new(path)
+            .file_name()
+            .map(|name| format!("poir-viewer - {}", name.to_string_lossy()))
+            .unwrap_or_else(|| "poir-viewer".to_string()),
+        LaunchTarget::Album { name } => format!("poir-viewer - {}", name),
+    }
+}
+
+/// 閉じられたウィンドウの状態を破棄する
+pub fn forget_window(app_handle: &AppHandle, label: &str) {
+    if !label.starts_with(VIEWER_LABEL_PREFIX) {
+        return;
+    }
+    if let Some(state) = app_handle.try_state::<ViewerWindows>() {
+        if let Ok(mut views) = state.views.lock() {
+            views.remove(label);
+        }
+    }
+}
+
+/// フォルダ・画像・アルバムを独立したビューアウィンドウで開き、ウィンドウのラベルを返す
+#[tauri::command]
+pub async fn open_viewer_window(
+    app_handle: AppHandle,
+    state: State<'_, ViewerWindows>,
+    target: LaunchTarget,
+) -> Result<String, String> {
+    let view = launch::resolve(&app_handle, &target)?;
+    let label = format!("{}{}", VIEWER_LABEL_PREFIX, state.next_id.fetch_add(1, Ordering::Relaxed));
+
+    // ウィンドウ側は`get_viewer_state`で自分のラベルから表示状態を取得する
+    state.lock()?.insert(label.clone(), view);
+    let built = WebviewWindowBuilder::new(&app_handle, &label, WebviewUrl::default())
+        .title(window_title(&target))
+        .inner_size(1024.0, 768.0)
+        .build();
+
+    match built {
+        Ok(window) => window_state::restore(&window.as_ref().window()),
+        Err(e) => {
+            state.lock()?.remove(&label);
+            return Err(t!("viewer.create_failed", e));
+        },
+    }
+
+    tracing::info!("ビューアウィンドウを開きました: {} ({:?})", label, target);
+    Ok(label)
+}
+
+/// 呼び出し元ウィンドウの表示状態を取得する（メインウィンドウではNone）
+#[tauri::command]
+pub fn get_viewer_state(
+    window: WebviewWindow,
+    state: State<'_, ViewerWindows>,
+) -> Result<Option<LaunchView>, String> {
+    Ok(state.lock()?.get(window.label()).cloned())
+}
+
+/// 呼び出し元ウィンドウの表示位置を移動し、表示する画像を返す
+#[tauri::command]
+pub fn navigate_viewer(
+    window: WebviewWindow,
+    state: State<'_, ViewerWindows>,
+    delta: i64,
+) -> Result<Option<ImageInfo>, String> {
+    let mut views = state.lock()?;
+    let view = views.get_mut(window.label())
+        .ok_or_else(|| t!("viewer.not_found", window.label()))?;
+
+    view.current_index = step_index(view.current_index, view.images.len(), delta);
+    Ok(view.current_index.map(|index| view.images[index].clone()))
+}
+
+/// 開いているビューアウィンドウのラベル一覧を取得する
+#[tauri::command]
+pub fn list_viewer_windows(state: State<'_, ViewerWindows>) -> Result<Vec<String>, String> {
+    let mut labels: Vec<String> = state.lock()?.keys().cloned().collect();
+    labels.sort();
+    Ok(labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_index_wraps() {
+        assert_eq!(step_index(Some(0), 3, 1), Some(1));
+        assert_eq!(step_index(Some(2), 3, 1), Some(0));
+        assert_eq!(step_index(Some(0), 3, -1), Some(2));
+        assert_eq!(step_index(None, 3, 0), Some(0));
+        assert_eq!(step_index(Some(0), 0, 1), None);
+    }
+}
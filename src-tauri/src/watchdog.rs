@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::i18n::t;
+use crate::settings::AppSettings;
+
+/// 時間制限を設ける処理の種類
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    /// フォルダのスキャン
+    Scan,
+    /// タグ・レーティングの読み込み・取り込み
+    Metadata,
+    /// 内容のハッシュ計算
+    Checksum,
+    /// 画像内の文字の読み取り
+    Ocr,
+}
+
+/// 時間切れの際に返すエラー（JSON文字列として返す）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeoutError {
+    /// 常に`"timeout"`（画面側で通常のエラーと区別するため）
+    pub kind: String,
+    pub operation: Operation,
+    pub seconds: u64,
+    pub message: String,
+}
+
+fn timeout_error(operation: Operation, seconds: u64) -> String {
+    let error = TimeoutError {
+        kind: "timeout".to_string(),
+        operation,
+        seconds,
+        message: t!("watchdog.timed_out", seconds),
+    };
+    serde_json::to_string(&error).unwrap_or(error.message)
+}
+
+/// 処理をバックグラウンドで実行し、設定の時間内に終わらなければ時間切れのエラーを返す
+///
+/// 応答しないネットワークドライブで呼び出しが返らなくなるのを防ぐためのもので、
+/// 時間切れ後も処理は止めずに続け（インデックスの更新などは反映される）、結果は捨てる
+pub async fn run<T, F>(app_handle: &AppHandle, operation: Operation, task: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    let seconds = AppSettings::load(app_handle).unwrap_or_default().timeouts.seconds(operation);
+    let started = Instant::now();
+    let handle = tauri::async_runtime::spawn_blocking(move || {
+        let result = task();
+        if started.elapsed().as_secs() >= seconds && seconds > 0 {
+            tracing::info!("時間切れになった処理が終了しました ({:?}, {:?})", operation, started.elapsed());
+        }
+        result
+    });
+
+    if seconds == 0 {
+        return handle.await.map_err(|e| t!("watchdog.task_failed", e))?;
+    }
+    match tokio::time::timeout(Duration::from_secs(seconds), handle).await {
+        Ok(joined) => joined.map_err(|e| t!("watchdog.task_failed", e))?,
+        Err(_) => {
+            tracing::warn!("処理が{}秒以内に終わりませんでした: {:?}", seconds, operation);
+            Err(timeout_error(operation, seconds))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_error_is_structured() {
+        let error: serde_json::Value = serde_json::from_str(&timeout_error(Operation::Scan, 30)).unwrap();
+        assert_eq!(error["kind"], "timeout");
+        assert_eq!(error["operation"], "scan");
+        assert_eq!(error["seconds"], 30);
+    }
+}
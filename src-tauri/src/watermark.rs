@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use ::image::{imageops, DynamicImage, ImageFormat, Rgba, RgbaImage};
+use ab_glyph::FontVec;
+use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+use crate::audit;
+use crate::crop;
+use crate::font;
+use crate::i18n::t;
+use crate::jobs::{self, JobHandle};
+use crate::path_guard;
+use crate::preview;
+use crate::safe_write::{self, WriteOptions};
+
+/// 文字を描画する際の大きさ（描画後に透かしの幅に合わせて縮小する）
+const TEXT_RENDER_PX: f32 = 96.0;
+
+/// 画像の端からの余白（短辺に対する割合）
+const MARGIN_FRACTION: f32 = 0.02;
+
+/// 透かしを置く位置
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+/// 透かしの内容（画像か文字のどちらか）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Watermark {
+    /// 透かしに使う画像（ロゴなど）
+    pub image: Option<String>,
+    /// 透かしに使う文字（`image`がなければ使う）
+    pub text: Option<String>,
+    /// 文字に使うフォント（省略するとシステムのフォントを探す）
+    pub font_path: Option<String>,
+    /// 透かしの幅（画像の幅に対する割合、既定は0.25）
+    pub scale: Option<f32>,
+}
+
+/// 透かしを入れられなかった画像
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WatermarkFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// 透かしジョブの結果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WatermarkResult {
+    /// 保存したファイルのパス
+    pub written: Vec<String>,
+    pub failed: Vec<WatermarkFailure>,
+}
+
+/// 1行の文字を白で描画する（読みやすいよう右下に影を付ける）
+fn render_text(font: &FontVec, text: &str) -> RgbaImage {
+    let shadow = (TEXT_RENDER_PX / 24.0).ceil() as i32;
+    let (width, height) = font::measure(font, text, TEXT_RENDER_PX);
+    let mut canvas = RgbaImage::new(width.max(1) + shadow as u32, height.max(1) + shadow as u32);
+    font::draw_text(&mut canvas, font, text, (shadow, shadow), TEXT_RENDER_PX, Rgba([0, 0, 0, 160]));
+    font::draw_text(&mut canvas, font, text, (0, 0), TEXT_RENDER_PX, Rgba([255, 255, 255, 255]));
+    canvas
+}
+
+/// 透かしを置く左上の座標
+fn placement(base: (u32, u32), mark: (u32, u32), position: WatermarkPosition) -> (i64, i64) {
+    let margin = (base.0.min(base.1) as f32 * MARGIN_FRACTION).round() as i64;
+    let (bw, bh) = (base.0 as i64, base.1 as i64);
+    let (mw, mh) = (mark.0 as i64, mark.1 as i64);
+    match position {
+        WatermarkPosition::TopLeft => (margin, margin),
+        WatermarkPosition::TopRight => (bw - mw - margin, margin),
+        WatermarkPosition::BottomLeft => (margin, bh - mh - margin),
+        WatermarkPosition::BottomRight => (bw - mw - margin, bh - mh - margin),
+        WatermarkPosition::Center => ((bw - mw) / 2, (bh - mh) / 2),
+    }
+}
+
+/// 画像に透かしを重ねる（透かしは画像の幅に対する`scale`の割合に縮小する）
+fn apply(base: &DynamicImage, mark: &RgbaImage, position: WatermarkPosition, opacity: f32, scale: f32) -> RgbaImage {
+    let mut canvas = base.to_rgba8();
+    let target_width = ((canvas.width() as f32 * scale).round() as u32).clamp(1, canvas.width().max(1));
+    let target_height = ((mark.height() as f32 * target_width as f32 / mark.width().max(1) as f32).round() as u32).max(1);
+    let mark = imageops::resize(mark, target_width, target_height, imageops::FilterType::Triangle);
+
+    let (left, top) = placement(canvas.dimensions(), mark.dimensions(), position);
+    for (x, y, over) in mark.enumerate_pixels() {
+        let (px, py) = (left + x as i64, top + y as i64);
+        if px < 0 || py < 0 || px >= canvas.width() as i64 || py >= canvas.height() as i64 {
+            continue;
+        }
+        let pixel = canvas.get_pixel_mut(px as u32, py as u32);
+        *pixel = font::blend_pixel(*pixel, *over, opacity);
+    }
+    canvas
+}
+
+/// 保存先のパスと形式（元の形式で保存できなければPNGにする）
+fn output_path(source: &Path, dest_dir: &Path) -> (PathBuf, ImageFormat) {
+    let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = source.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    match crop::parse_format(extension) {
+        Some(format) => (dest_dir.join(format!("{}.{}", stem, extension)), format),
+        None => (dest_dir.join(format!("{}.png", stem)), ImageFormat::Png),
+    }
+}
+
+/// 描画済みの透かしと重ね方
+struct Stamp {
+    mark: RgbaImage,
+    position: WatermarkPosition,
+    opacity: f32,
+    scale: f32,
+}
+
+fn run(app_handle: &AppHandle, job: &JobHandle, sources: &[PathBuf], stamp: &Stamp, dest_dir: &Path) -> Result<WatermarkResult, String> {
+    let mut result = WatermarkResult::default();
+    for (index, source) in sources.iter().enumerate() {
+        if job.is_cancelled() {
+            break;
+        }
+        let name = source.to_string_lossy().to_string();
+        job.progress(index, Some(&name));
+
+        let (dest, format) = output_path(source, dest_dir);
+        let written = preview::open_oriented(app_handle, source).and_then(|(image, _)| {
+            let marked = apply(&image, &stamp.mark, stamp.position, stamp.opacity, stamp.scale);
+            let marked = DynamicImage::ImageRgba8(marked);
+            safe_write::write_with(&dest, WriteOptions::default(), |temp| crop::save_as(&marked, temp, format))
+        });
+        match written {
+            Ok(()) => result.written.push(dest.to_string_lossy().to_string()),
+            Err(error) => {
+                tracing::warn!("透かしを入れられませんでした: {} - {}", name, error);
+                result.failed.push(WatermarkFailure { path: name, error });
+            },
+        }
+        job.progress(index + 1, None);
+    }
+    Ok(result)
+}
+
+fn start(
+    app_handle: &AppHandle,
+    paths: Vec<String>,
+    watermark: Watermark,
+    position: WatermarkPosition,
+    opacity: f32,
+    dest_dir: &str,
+) -> Result<String, String> {
+    let sources = paths.iter()
+        .map(|path| path_guard::guard(app_handle, path))
+        .collect::<Result<Vec<PathBuf>, String>>()?;
+
+    let dest_dir = PathBuf::from(dest_dir);
+    fs::create_dir_all(&dest_dir).map_err(|e| t!("common.dir_create_failed", dest_dir.display(), e))?;
+    let dest_dir = path_guard::canonicalize(&dest_dir.to_string_lossy())?;
+    // 元の画像を上書きしないよう、元と同じフォルダへの保存は断る
+    if let Some(source) = sources.iter().find(|source| source.parent() == Some(dest_dir.as_path())) {
+        return Err(t!("watermark.overwrite_source", source.display()));
+    }
+
+    let mark = match (&watermark.image, &watermark.text) {
+        (Some(image), _) => {
+            let image = path_guard::guard(app_handle, image)?;
+            preview::open_oriented(app_handle, &image)?.0.to_rgba8()
+        },
+        (None, Some(text)) if !text.trim().is_empty() => {
+            render_text(&font::load_font(watermark.font_path.as_deref())?, text.trim())
+        },
+        _ => return Err(t!("watermark.empty")),
+    };
+    let stamp = Stamp {
+        mark,
+        position,
+        opacity: opacity.clamp(0.0, 1.0),
+        scale: watermark.scale.unwrap_or(0.25).clamp(0.01, 1.0),
+    };
+
+    let handle = app_handle.clone();
+    jobs::spawn(app_handle, "watermark", sources.len(), move |job| {
+        run(&handle, job, &sources, &stamp, &dest_dir)
+    })
+}
+
+/// 画像に透かし（ロゴ画像または文字）を入れて別フォルダに保存するジョブを開始し、ジョブIDを返す
+///
+/// 元の画像は変更しない。進捗と結果は`job-progress`イベントで通知する
+#[tauri::command]
+pub async fn apply_watermark(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    watermark: Watermark,
+    position: Option<WatermarkPosition>,
+    opacity: Option<f32>,
+    dest_dir: String,
+) -> Result<String, String> {
+    let result = start(&app_handle, paths, watermark, position.unwrap_or_default(), opacity.unwrap_or(0.5), &dest_dir);
+    audit::complete(&app_handle, "apply_watermark", &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_blends_mark_at_position() {
+        let base = DynamicImage::ImageRgba8(RgbaImage::from_pixel(100, 50, Rgba([0, 0, 0, 255])));
+        let mark = RgbaImage::from_pixel(10, 10, Rgba([255, 255, 255, 255]));
+
+        assert_eq!(placement((100, 50), (10, 10), WatermarkPosition::BottomRight), (89, 39));
+        assert_eq!(placement((100, 50), (10, 10), WatermarkPosition::Center), (45, 20));
+
+        let marked = apply(&base, &mark, WatermarkPosition::BottomRight, 0.5, 0.1);
+        assert_eq!(marked.get_pixel(95, 45), &Rgba([128, 128, 128, 255]));
+        assert_eq!(marked.get_pixel(10, 10), &Rgba([0, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Runtime, Window};
+use crate::config;
+use crate::i18n::t;
+
+/// ウィンドウの位置・大きさ・表示状態
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WindowGeometry {
+    /// 位置（物理ピクセル）
+    pub x: i32,
+    pub y: i32,
+    /// 内側の大きさ（物理ピクセル）
+    pub width: u32,
+    pub height: u32,
+    /// 最大化されているか
+    pub maximized: bool,
+    /// 全画面表示か
+    pub fullscreen: bool,
+    /// 表示していたモニター名
+    pub monitor: Option<String>,
+}
+
+/// モニターの表示領域（物理ピクセル）
+#[derive(Debug, Clone, Copy)]
+struct MonitorArea {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+/// ウィンドウのラベルごとの状態を保持するステート
+#[derive(Default)]
+pub struct WindowStateStore(Mutex<HashMap<String, WindowGeometry>>);
+
+/// 保存先のパスを取得する
+fn get_state_path(app_handle: &AppHandle) -> PathBuf {
+    config::app_data_dir(app_handle).join("window-state.json")
+}
+
+/// 保存済みの状態を読み込む（なければ空）
+pub fn load(app_handle: &AppHandle) -> WindowStateStore {
+    let states = fs::read_to_string(get_state_path(app_handle))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    WindowStateStore(Mutex::new(states))
+}
+
+/// 現在の状態をファイルに保存する
+pub fn save(app_handle: &AppHandle) -> Result<(), String> {
+    let Some(store) = app_handle.try_state::<WindowStateStore>() else { return Ok(()) };
+    let states = store.0.lock()
+        .map_err(|e| t!("common.lock_failed", t!("window_state.name"), e))?
+        .clone();
+
+    let path = get_state_path(app_handle);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| t!("common.dir_create_failed", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&states)
+        .map_err(|e| t!("window_state.serialize_failed", e))?;
+    fs::write(&path, json)
+        .map_err(|e| t!("window_state.save_failed", e))
+}
+
+/// ウィンドウの左上付近がいずれかのモニター内にあるか（外れたモニターへの復元を防ぐ）
+fn is_visible(geometry: &WindowGeometry, monitors: &[MonitorArea]) -> bool {
+    // タイトルバーをつかめるよう、左上から少し内側の点で判定する
+    let (x, y) = (geometry.x + 50, geometry.y + 20);
+    monitors.iter().any(|m| {
+        x >= m.x && y >= m.y && x < m.x + m.width as i32 && y < m.y + m.height as i32
+    })
+}
+
+/// ウィンドウの現在の状態を記録する
+///
+/// 最大化・全画面中は通常時の位置と大きさを残し、フラグのみ更新する
+pub fn track<R: Runtime>(window: &Window<R>) {
+    let Some(store) = window.app_handle().try_state::<WindowStateStore>() else { return };
+    let maximized = window.is_maximized().unwrap_or(false);
+    let fullscreen = window.is_fullscreen().unwrap_or(false);
+    let minimized = window.is_minimized().unwrap_or(false);
+    if minimized {
+        return;
+    }
+
+    let Ok(mut states) = store.0.lock() else { return };
+    let previous = states.get(window.label()).cloned();
+
+    let geometry = match (maximized || fullscreen, previous) {
+        (true, Some(previous)) => WindowGeometry { maximized, fullscreen, ..previous },
+        _ => {
+            let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else { return };
+            WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                maximized,
+                fullscreen,
+                monitor: window.current_monitor().ok().flatten().and_then(|m| m.name().cloned()),
+            }
+        },
+    };
+    states.insert(window.label().to_string(), geometry);
+}
+
+/// 保存済みの状態をウィンドウに反映する
+pub fn restore<R: Runtime>(window: &Window<R>) {
+    let Some(store) = window.app_handle().try_state::<WindowStateStore>() else { return };
+    let Some(geometry) = store.0.lock().ok().and_then(|states| states.get(window.label()).cloned()) else {
+        return;
+    };
+
+    let monitors: Vec<MonitorArea> = window.available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| MonitorArea {
+            x: m.position().x,
+            y: m.position().y,
+            width: m.size().width,
+            height: m.size().height,
+        })
+        .collect();
+
+    if geometry.width > 0 && geometry.height > 0 {
+        let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+    }
+    if is_visible(&geometry, &monitors) {
+        let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+    } else {
+        tracing::info!("保存されたウィンドウ位置が画面外のため復元しません: {}", window.label());
+    }
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+    if geometry.fullscreen {
+        let _ = window.set_fullscreen(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geometry(x: i32, y: i32) -> WindowGeometry {
+        WindowGeometry { x, y, width: 800, height: 600, maximized: false, fullscreen: false, monitor: None }
+    }
+
+    #[test]
+    fn test_is_visible() {
+        let monitors = [
+            MonitorArea { x: 0, y: 0, width: 1920, height: 1080 },
+            MonitorArea { x: 1920, y: 0, width: 2560, height: 1440 },
+        ];
+        assert!(is_visible(&geometry(100, 100), &monitors));
+        assert!(is_visible(&geometry(3000, 200), &monitors));
+        assert!(!is_visible(&geometry(5000, 200), &monitors));
+        assert!(!is_visible(&geometry(-900, 100), &monitors));
+    }
+}
@@ -18,6 +18,23 @@ interface ResourceConfig {
   };
 }
 
+// 設定フォルダごとの検証結果
+interface PathStatus {
+  path: string;
+  valid: boolean;
+  reason: string | null;
+}
+
+// "config-status"で通知される設定の状態
+interface ConfigStatus {
+  valid: boolean;
+  total: number;
+  valid_count: number;
+  invalid_count: number;
+  paths: PathStatus[];
+  reason: string | null;
+}
+
 function App() {
   const [greetMsg, setGreetMsg] = useState("");
   const [name, setName] = useState("");
@@ -26,6 +43,7 @@ function App() {
   const [showResourceConfig, setShowResourceConfig] = useState(false);
   const [resourceConfig, setResourceConfig] = useState<ResourceConfig | null>(null);
   const [configValid, setConfigValid] = useState<boolean>(false);
+  const [configStatus, setConfigStatus] = useState<ConfigStatus | null>(null);
   const [showImageViewer, setShowImageViewer] = useState<boolean>(false);
   const [resourcesJsonPath, setResourcesJsonPath] = useState<string>("");
   const [isLoadingPath, setIsLoadingPath] = useState<boolean>(true);
@@ -55,8 +73,9 @@ function App() {
     });
     
     // Rust側からのイベントリスナーを設定
-    const unlisten1 = listen<boolean>("config-status", (event) => {
-      setConfigValid(event.payload);
+    const unlisten1 = listen<ConfigStatus>("config-status", (event) => {
+      setConfigStatus(event.payload);
+      setConfigValid(event.payload.valid);
     });
     
     const unlisten2 = listen<boolean>("config-required", (event) => {
@@ -211,6 +230,17 @@ function App() {
         <button onClick={toggleResourceConfig} disabled={isLoadingPath}>
           {showResourceConfig ? "設定を閉じる" : "設定を開く"}
         </button>
+        {/* 使えない設定フォルダとその理由 */}
+        {!isLoadingPath && configStatus && !configStatus.valid && (
+          <ul className="config-issues">
+            {configStatus.reason && <li>{configStatus.reason}</li>}
+            {configStatus.paths.filter((status) => !status.valid).map((status) => (
+              <li key={status.path}>
+                <code>{status.path}</code>: {status.reason}
+              </li>
+            ))}
+          </ul>
+        )}
       </div>
       
       {/* 設定ファイルパス情報の表示 */}